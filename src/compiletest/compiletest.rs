@@ -231,7 +231,9 @@ pub fn run_tests(config: &config) {
 
 pub fn test_opts(config: &config) -> test::TestOpts {
     test::TestOpts {
-        filter: config.filter.clone(),
+        filters: config.filter.clone().move_iter().collect(),
+        filter_exact: false,
+        skip: ~[],
         run_ignored: config.run_ignored,
         logfile: config.logfile.clone(),
         run_tests: true,
@@ -239,7 +241,13 @@ pub fn test_opts(config: &config) -> test::TestOpts {
         ratchet_metrics: config.ratchet_metrics.clone(),
         ratchet_noise_percent: config.ratchet_noise_percent.clone(),
         save_metrics: config.save_metrics.clone(),
-        test_shard: config.test_shard.clone()
+        bench_baseline: None,
+        test_shard: config.test_shard.clone(),
+        format: test::FmtPretty,
+        report_time: None,
+        nocapture: false,
+        test_timeout: None,
+        list: false
     }
 }
 
@@ -293,7 +301,9 @@ pub fn make_test(config: &config, testfile: &Path,
         desc: test::TestDesc {
             name: make_test_name(config, testfile),
             ignore: header::is_test_ignored(config, testfile),
-            should_fail: false
+            should_fail: false,
+            expected_failure: None,
+            timeout: None
         },
         testfn: f(),
     }
@@ -0,0 +1,323 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * A Bloom filter: a space-efficient probabilistic set.
+ *
+ * A Bloom filter answers membership queries with no false negatives but
+ * a tunable rate of false positives. Items are hashed with keyed SipHash
+ * (see `std::hash`) and the resulting bit positions are stored in a
+ * `Bitv`. Two filters built with the same parameters and keys can be
+ * combined with `union` and `intersect`.
+ *
+ * # Example
+ *
+ * ```rust
+ * use extra::bloom::BloomFilter;
+ *
+ * let mut seen = BloomFilter::new(1000, 0.01);
+ * seen.insert(&~"GET /index.html");
+ * assert!(seen.contains(&~"GET /index.html"));
+ * ```
+ */
+
+use std::cmp;
+use std::rand;
+use std::rand::Rng;
+
+use bitv::Bitv;
+
+/// A Bloom filter over values of type `T`
+pub struct BloomFilter<T> {
+    priv bits: Bitv,
+    priv num_bits: uint,
+    priv num_hashes: uint,
+    priv k0: u64,
+    priv k1: u64,
+    priv num_items: uint
+}
+
+impl<T: IterBytes> BloomFilter<T> {
+    /**
+     * Create a filter sized to hold `expected_items` items with a false
+     * positive rate of at most `fp_rate` once that many have been inserted.
+     *
+     * The hash keys are chosen at random, so filters created this way
+     * cannot be combined with each other; use `with_rate_and_keys` for that.
+     */
+    pub fn new(expected_items: uint, fp_rate: f64) -> BloomFilter<T> {
+        let mut rng = rand::task_rng();
+        BloomFilter::with_rate_and_keys(expected_items, fp_rate,
+                                        rng.gen(), rng.gen())
+    }
+
+    /**
+     * Like `new`, but with explicit hash keys. Filters created with the
+     * same arguments can be merged with `union` and `intersect`.
+     */
+    pub fn with_rate_and_keys(expected_items: uint, fp_rate: f64,
+                              k0: u64, k1: u64) -> BloomFilter<T> {
+        assert!(fp_rate > 0.0 && fp_rate < 1.0);
+        let n = cmp::max(expected_items, 1) as f64;
+        let ln2 = 2.0f64.ln();
+        let m = (-n * fp_rate.ln() / (ln2 * ln2)).ceil();
+        let k = (m / n * ln2).round();
+        BloomFilter::with_keys(m as uint, k as uint, k0, k1)
+    }
+
+    /**
+     * Create a filter with `num_bits` bits and `num_hashes` hash functions
+     * using the SipHash keys `k0` and `k1`.
+     */
+    pub fn with_keys(num_bits: uint, num_hashes: uint,
+                     k0: u64, k1: u64) -> BloomFilter<T> {
+        let num_bits = cmp::max(num_bits, 1);
+        BloomFilter {
+            bits: Bitv::new(num_bits, false),
+            num_bits: num_bits,
+            num_hashes: cmp::max(num_hashes, 1),
+            k0: k0,
+            k1: k1,
+            num_items: 0
+        }
+    }
+
+    /// The number of bits in the filter
+    pub fn num_bits(&self) -> uint { self.num_bits }
+
+    /// The number of bit positions set for each inserted item
+    pub fn num_hashes(&self) -> uint { self.num_hashes }
+
+    /// The number of calls to `insert` that changed the filter
+    pub fn num_items(&self) -> uint { self.num_items }
+
+    /// Calls `f` with each bit position that `item` maps to.
+    fn each_index(&self, item: &T, f: &fn(uint) -> bool) -> bool {
+        // Kirsch-Mitzenmacher double hashing: two independent SipHash
+        // values are enough to simulate `num_hashes` hash functions.
+        let h1 = item.hash_keyed(self.k0, self.k1);
+        let h2 = item.hash_keyed(self.k1, self.k0) | 1;
+        let m = self.num_bits as u64;
+        for i in range(0u, self.num_hashes) {
+            let h = h1 + (i as u64) * h2;
+            if !f((h % m) as uint) { return false; }
+        }
+        true
+    }
+
+    /**
+     * Add `item` to the filter.
+     *
+     * Returns `false` if every bit for `item` was already set, in which
+     * case the item (or a colliding one) was probably present already.
+     */
+    pub fn insert(&mut self, item: &T) -> bool {
+        let mut idxs = ~[];
+        do self.each_index(item) |i| { idxs.push(i); true };
+
+        let mut changed = false;
+        for &i in idxs.iter() {
+            if !self.bits.get(i) {
+                self.bits.set(i, true);
+                changed = true;
+            }
+        }
+        if changed { self.num_items += 1; }
+        changed
+    }
+
+    /**
+     * Test whether `item` may be in the filter.
+     *
+     * `false` is always correct; `true` is wrong with a probability
+     * given by `false_positive_rate`.
+     */
+    pub fn contains(&self, item: &T) -> bool {
+        self.each_index(item, |i| self.bits.get(i))
+    }
+
+    /// Estimate the current probability that `contains` returns a false
+    /// positive, based on the fraction of bits that are set.
+    pub fn false_positive_rate(&self) -> f64 {
+        let set = self.bits.iter().count(|b| b) as f64;
+        let fraction = set / (self.num_bits as f64);
+        fraction.pow(&(self.num_hashes as f64))
+    }
+
+    /// Remove every item from the filter
+    pub fn clear(&mut self) {
+        self.bits.clear();
+        self.num_items = 0;
+    }
+
+    fn assert_compatible(&self, other: &BloomFilter<T>) {
+        if self.num_hashes != other.num_hashes ||
+           self.k0 != other.k0 || self.k1 != other.k1 ||
+           self.num_bits != other.num_bits {
+            fail!("Tried to combine Bloom filters with different parameters");
+        }
+    }
+
+    /**
+     * Add every item of `other` to `self`.
+     *
+     * Both filters must have the same size, number of hashes and keys.
+     * Returns `true` if `self` changed.
+     */
+    pub fn union(&mut self, other: &BloomFilter<T>) -> bool {
+        self.assert_compatible(other);
+        self.num_items += other.num_items;
+        self.bits.union(&other.bits)
+    }
+
+    /**
+     * Keep only the bits set in both `self` and `other`.
+     *
+     * The result may report a higher false positive rate than a filter
+     * built from the true intersection of the two sets. Both filters must
+     * have the same size, number of hashes and keys. Returns `true` if
+     * `self` changed.
+     */
+    pub fn intersect(&mut self, other: &BloomFilter<T>) -> bool {
+        self.assert_compatible(other);
+        self.num_items = cmp::min(self.num_items, other.num_items);
+        self.bits.intersect(&other.bits)
+    }
+}
+
+impl<T> Clone for BloomFilter<T> {
+    fn clone(&self) -> BloomFilter<T> {
+        BloomFilter {
+            bits: self.bits.clone(),
+            num_bits: self.num_bits,
+            num_hashes: self.num_hashes,
+            k0: self.k0,
+            k1: self.k1,
+            num_items: self.num_items
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use extra::test::BenchHarness;
+
+    #[test]
+    fn test_insert_contains() {
+        let mut b = BloomFilter::new(100, 0.01);
+        assert!(!b.contains(&1));
+        assert!(b.insert(&1));
+        assert!(b.contains(&1));
+        assert!(!b.insert(&1));
+        assert_eq!(b.num_items(), 1);
+    }
+
+    #[test]
+    fn test_no_false_negatives() {
+        let mut b = BloomFilter::new(1000, 0.01);
+        for i in range(0u, 1000) {
+            b.insert(&i);
+        }
+        for i in range(0u, 1000) {
+            assert!(b.contains(&i));
+        }
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let mut b = BloomFilter::with_rate_and_keys(1000, 0.01, 1, 2);
+        for i in range(0u, 1000) {
+            b.insert(&i);
+        }
+        let fp = range(1000u, 11000).count(|i| b.contains(&i));
+        // Allow plenty of slack over the requested 1%.
+        assert!(fp < 300);
+        assert!(b.false_positive_rate() < 0.03);
+    }
+
+    #[test]
+    fn test_strings() {
+        let mut b = BloomFilter::new(10, 0.001);
+        b.insert(&~"foo");
+        b.insert(&~"bar");
+        assert!(b.contains(&~"foo"));
+        assert!(b.contains(&~"bar"));
+        assert!(!b.contains(&~"baz"));
+    }
+
+    #[test]
+    fn test_sizing() {
+        let b: BloomFilter<uint> = BloomFilter::with_rate_and_keys(1000, 0.01, 0, 0);
+        // m = -n ln p / (ln 2)^2, k = m/n ln 2
+        assert_eq!(b.num_bits(), 9586);
+        assert_eq!(b.num_hashes(), 7);
+    }
+
+    #[test]
+    fn test_union_intersect() {
+        let mut a = BloomFilter::with_rate_and_keys(100, 0.001, 3, 4);
+        let mut b = BloomFilter::with_rate_and_keys(100, 0.001, 3, 4);
+        a.insert(&1);
+        a.insert(&2);
+        b.insert(&2);
+        b.insert(&3);
+
+        let mut u = a.clone();
+        assert!(u.union(&b));
+        assert!(u.contains(&1) && u.contains(&2) && u.contains(&3));
+
+        let mut i = a.clone();
+        assert!(i.intersect(&b));
+        assert!(i.contains(&2));
+        assert!(!i.contains(&1));
+        assert!(!i.contains(&3));
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_union_incompatible() {
+        let mut a = BloomFilter::with_rate_and_keys(100, 0.01, 1, 2);
+        let b = BloomFilter::with_rate_and_keys(100, 0.01, 2, 1);
+        a.union(&b);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut b = BloomFilter::new(10, 0.01);
+        b.insert(&5);
+        b.clear();
+        assert!(!b.contains(&5));
+        assert_eq!(b.num_items(), 0);
+    }
+
+    #[bench]
+    fn bench_insert(bh: &mut BenchHarness) {
+        let mut b = BloomFilter::new(10000, 0.01);
+        let mut i = 0u;
+        do bh.iter {
+            b.insert(&i);
+            i += 1;
+        }
+    }
+
+    #[bench]
+    fn bench_contains(bh: &mut BenchHarness) {
+        let mut b = BloomFilter::new(10000, 0.01);
+        for i in range(0u, 10000) {
+            b.insert(&i);
+        }
+        let mut i = 0u;
+        do bh.iter {
+            b.contains(&i);
+            i += 1;
+        }
+    }
+}
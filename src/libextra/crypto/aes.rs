@@ -0,0 +1,385 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * A software implementation of the AES block cipher (FIPS-197) with 128, 192 and 256 bit keys.
+ *
+ * The implementation works on bytes and avoids the large lookup tables of the usual optimized
+ * versions; the only secret dependent memory accesses left are into the 256 byte S-boxes, and
+ * the arithmetic in the MixColumns step has no secret dependent branches. That makes cache
+ * timing attacks harder, not impossible.
+ *
+ * The cipher only transforms single blocks; see `blockmodes` for encrypting messages.
+ */
+
+use std::vec;
+use std::vec::bytes::copy_memory;
+
+use symmetriccipher::{BlockEncryptor, BlockDecryptor};
+
+static BLOCK_SIZE: uint = 16;
+
+static SBOX: [u8, ..256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5,
+    0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0,
+    0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc,
+    0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a,
+    0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0,
+    0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b,
+    0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85,
+    0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5,
+    0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17,
+    0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88,
+    0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c,
+    0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9,
+    0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6,
+    0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e,
+    0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94,
+    0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68,
+    0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+static INV_SBOX: [u8, ..256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38,
+    0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87,
+    0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d,
+    0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2,
+    0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16,
+    0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda,
+    0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a,
+    0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02,
+    0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea,
+    0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85,
+    0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89,
+    0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20,
+    0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31,
+    0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d,
+    0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0,
+    0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26,
+    0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+static RCON: [u8, ..10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+// Multiplication by x in GF(2^8), without branching on the high bit.
+fn xtime(x: u8) -> u8 {
+    (x << 1) ^ ((x >> 7) * 0x1b)
+}
+
+// Multiplication in GF(2^8). The loop always runs eight times.
+fn gf_mul(a: u8, b: u8) -> u8 {
+    let mut a = a;
+    let mut b = b;
+    let mut p = 0u8;
+    for _ in range(0, 8) {
+        p ^= a * (b & 1);
+        a = xtime(a);
+        b >>= 1;
+    }
+    p
+}
+
+fn sub(x: u8) -> u8 {
+    SBOX[x as uint]
+}
+
+// Expands the key into (rounds + 1) round keys of 16 bytes each.
+fn expand_key(key: &[u8]) -> (uint, ~[u8]) {
+    let nk = match key.len() {
+        16 | 24 | 32 => key.len() / 4,
+        n => fail!("invalid AES key length: {} bytes", n)
+    };
+    let rounds = nk + 6;
+    let words = 4 * (rounds + 1);
+
+    let mut rk = vec::from_elem(4 * words, 0u8);
+    copy_memory(rk, key, key.len());
+    for i in range(nk, words) {
+        let mut t = [rk[4 * i - 4], rk[4 * i - 3], rk[4 * i - 2], rk[4 * i - 1]];
+        if i % nk == 0 {
+            // RotWord, SubWord and the round constant
+            t = [sub(t[1]) ^ RCON[i / nk - 1], sub(t[2]), sub(t[3]), sub(t[0])];
+        } else if nk > 6 && i % nk == 4 {
+            t = [sub(t[0]), sub(t[1]), sub(t[2]), sub(t[3])];
+        }
+        for j in range(0, 4) {
+            rk[4 * i + j] = rk[4 * (i - nk) + j] ^ t[j];
+        }
+    }
+    (rounds, rk)
+}
+
+fn add_round_key(state: &mut [u8, ..16], rk: &[u8]) {
+    for i in range(0, 16) {
+        state[i] ^= rk[i];
+    }
+}
+
+// The state is stored column by column, so byte (row r, column c) is state[4 * c + r].
+
+fn sub_bytes(state: &mut [u8, ..16], sbox: &[u8, ..256]) {
+    for i in range(0, 16) {
+        state[i] = sbox[state[i] as uint];
+    }
+}
+
+fn shift_rows(state: &mut [u8, ..16]) {
+    let s = *state;
+    for c in range(0, 4) {
+        for r in range(0, 4) {
+            state[4 * c + r] = s[4 * ((c + r) % 4) + r];
+        }
+    }
+}
+
+fn inv_shift_rows(state: &mut [u8, ..16]) {
+    let s = *state;
+    for c in range(0, 4) {
+        for r in range(0, 4) {
+            state[4 * ((c + r) % 4) + r] = s[4 * c + r];
+        }
+    }
+}
+
+fn mix_columns(state: &mut [u8, ..16]) {
+    for c in range(0, 4) {
+        let a = [state[4 * c], state[4 * c + 1], state[4 * c + 2], state[4 * c + 3]];
+        let t = a[0] ^ a[1] ^ a[2] ^ a[3];
+        for r in range(0, 4) {
+            state[4 * c + r] = a[r] ^ t ^ xtime(a[r] ^ a[(r + 1) % 4]);
+        }
+    }
+}
+
+fn inv_mix_columns(state: &mut [u8, ..16]) {
+    for c in range(0, 4) {
+        let a = [state[4 * c], state[4 * c + 1], state[4 * c + 2], state[4 * c + 3]];
+        for r in range(0, 4) {
+            state[4 * c + r] = gf_mul(a[r], 14) ^ gf_mul(a[(r + 1) % 4], 11) ^
+                gf_mul(a[(r + 2) % 4], 13) ^ gf_mul(a[(r + 3) % 4], 9);
+        }
+    }
+}
+
+/**
+ * AES in the encrypting direction. The key length selects AES-128, AES-192 or AES-256.
+ */
+pub struct AesEncryptor {
+    priv rounds: uint,
+    priv round_keys: ~[u8]
+}
+
+impl AesEncryptor {
+    /**
+     * Create a new encryptor.
+     *
+     * # Arguments
+     *
+     * * key - The key. Must be 16, 24 or 32 bytes long.
+     */
+    pub fn new(key: &[u8]) -> AesEncryptor {
+        let (rounds, round_keys) = expand_key(key);
+        AesEncryptor {
+            rounds: rounds,
+            round_keys: round_keys
+        }
+    }
+}
+
+impl BlockEncryptor for AesEncryptor {
+    fn block_size(&self) -> uint { BLOCK_SIZE }
+
+    fn encrypt_block(&self, input: &[u8], output: &mut [u8]) {
+        assert!(input.len() == BLOCK_SIZE && output.len() == BLOCK_SIZE);
+        let mut state = [0u8, ..16];
+        copy_memory(state, input, BLOCK_SIZE);
+
+        add_round_key(&mut state, self.round_keys.slice(0, 16));
+        for round in range(1, self.rounds + 1) {
+            sub_bytes(&mut state, &SBOX);
+            shift_rows(&mut state);
+            if round != self.rounds {
+                mix_columns(&mut state);
+            }
+            add_round_key(&mut state, self.round_keys.slice(16 * round, 16 * round + 16));
+        }
+
+        copy_memory(output, state, BLOCK_SIZE);
+    }
+}
+
+/**
+ * AES in the decrypting direction. The key length selects AES-128, AES-192 or AES-256.
+ */
+pub struct AesDecryptor {
+    priv rounds: uint,
+    priv round_keys: ~[u8]
+}
+
+impl AesDecryptor {
+    /**
+     * Create a new decryptor.
+     *
+     * # Arguments
+     *
+     * * key - The key. Must be 16, 24 or 32 bytes long.
+     */
+    pub fn new(key: &[u8]) -> AesDecryptor {
+        let (rounds, round_keys) = expand_key(key);
+        AesDecryptor {
+            rounds: rounds,
+            round_keys: round_keys
+        }
+    }
+}
+
+impl BlockDecryptor for AesDecryptor {
+    fn block_size(&self) -> uint { BLOCK_SIZE }
+
+    fn decrypt_block(&self, input: &[u8], output: &mut [u8]) {
+        assert!(input.len() == BLOCK_SIZE && output.len() == BLOCK_SIZE);
+        let mut state = [0u8, ..16];
+        copy_memory(state, input, BLOCK_SIZE);
+
+        let last = 16 * self.rounds;
+        add_round_key(&mut state, self.round_keys.slice(last, last + 16));
+        let mut round = self.rounds;
+        while round > 0 {
+            round -= 1;
+            inv_shift_rows(&mut state);
+            sub_bytes(&mut state, &INV_SBOX);
+            add_round_key(&mut state, self.round_keys.slice(16 * round, 16 * round + 16));
+            if round != 0 {
+                inv_mix_columns(&mut state);
+            }
+        }
+
+        copy_memory(output, state, BLOCK_SIZE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::{AesEncryptor, AesDecryptor};
+    use hex::FromHex;
+    use symmetriccipher::{BlockEncryptor, BlockDecryptor};
+
+    fn test_aes(key: &str, plaintext: &str, ciphertext: &str) {
+        let key = key.from_hex().unwrap();
+        let plaintext = plaintext.from_hex().unwrap();
+        let ciphertext = ciphertext.from_hex().unwrap();
+        let mut output = [0u8, ..16];
+
+        let enc = AesEncryptor::new(key);
+        assert_eq!(enc.block_size(), 16);
+        enc.encrypt_block(plaintext, output);
+        assert_eq!(output.to_owned(), ciphertext);
+
+        let dec = AesDecryptor::new(key);
+        dec.decrypt_block(ciphertext, output);
+        assert_eq!(output.to_owned(), plaintext);
+    }
+
+    // Test vectors from FIPS-197, appendix C
+
+    #[test]
+    fn test_aes_128() {
+        test_aes("000102030405060708090a0b0c0d0e0f",
+                 "00112233445566778899aabbccddeeff",
+                 "69c4e0d86a7b0430d8cdb78070b4c55a");
+    }
+
+    #[test]
+    fn test_aes_192() {
+        test_aes("000102030405060708090a0b0c0d0e0f1011121314151617",
+                 "00112233445566778899aabbccddeeff",
+                 "dda97ca4864cdfe06eaf70a0ec0d7191");
+    }
+
+    #[test]
+    fn test_aes_256() {
+        test_aes("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                 "00112233445566778899aabbccddeeff",
+                 "8ea2b7ca516745bfeafc49904b496089");
+    }
+
+    #[test]
+    fn test_aes_128_appendix_b() {
+        // FIPS-197, appendix B
+        test_aes("2b7e151628aed2a6abf7158809cf4f3c",
+                 "3243f6a8885a308d313198a2e0370734",
+                 "3925841d02dc09fbdc118597196a0b32");
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_aes_bad_key_length() {
+        AesEncryptor::new([0u8, ..20]);
+    }
+}
+
+#[cfg(test)]
+mod bench {
+    use aes::AesEncryptor;
+    use symmetriccipher::BlockEncryptor;
+    use test::BenchHarness;
+
+    #[bench]
+    pub fn aes_128_encrypt_block(bh: & mut BenchHarness) {
+        let enc = AesEncryptor::new([1u8, ..16]);
+        let input = [1u8, ..16];
+        let mut output = [0u8, ..16];
+        do bh.iter {
+            enc.encrypt_block(input, output);
+        }
+        bh.bytes = input.len() as u64;
+    }
+
+    #[bench]
+    pub fn aes_256_encrypt_block(bh: & mut BenchHarness) {
+        let enc = AesEncryptor::new([1u8, ..32]);
+        let input = [1u8, ..16];
+        let mut output = [0u8, ..16];
+        do bh.iter {
+            enc.encrypt_block(input, output);
+        }
+        bh.bytes = input.len() as u64;
+    }
+}
@@ -0,0 +1,414 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * An implementation of the BLAKE2 cryptographic hash functions (RFC 7693).
+ *
+ * `Blake2b` works on 64-bit words and produces digests of up to 64 bytes;
+ * `Blake2s` works on 32-bit words, for smaller platforms, and produces up
+ * to 32 bytes. Both support a keyed mode, which makes them a MAC without
+ * the overhead of HMAC.
+ */
+
+use std::vec::bytes::{MutableByteVector, copy_memory};
+
+use cryptoutil::{read_u64v_le, read_u32v_le, write_u64_le, write_u32_le};
+use digest::Digest;
+
+static SIGMA: [[uint, ..16], ..10] = [
+    [ 0,  1,  2,  3,  4,  5,  6,  7,  8,  9, 10, 11, 12, 13, 14, 15],
+    [14, 10,  4,  8,  9, 15, 13,  6,  1, 12,  0,  2, 11,  7,  5,  3],
+    [11,  8, 12,  0,  5,  2, 15, 13, 10, 14,  3,  6,  7,  1,  9,  4],
+    [ 7,  9,  3,  1, 13, 12, 11, 14,  2,  6,  5, 10,  4,  0, 15,  8],
+    [ 9,  0,  5,  7,  2,  4, 10, 15, 14,  1, 11, 12,  6,  8,  3, 13],
+    [ 2, 12,  6, 10,  0, 11,  8,  3,  4, 13,  7,  5, 15, 14,  1,  9],
+    [12,  5,  1, 15, 14, 13,  4, 10,  0,  7,  6,  3,  9,  2,  8, 11],
+    [13, 11,  7, 14, 12,  1,  3,  9,  5,  0, 15,  4,  8,  6,  2, 10],
+    [ 6, 15, 14,  9, 11,  3,  0,  8, 12,  2, 13,  7,  1,  4, 10,  5],
+    [10,  2,  8,  4,  7,  6,  1,  5, 15, 11,  9, 14,  3, 12, 13,  0],
+];
+
+static B_IV: [u64, ..8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+static S_IV: [u32, ..8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+    0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+fn rotr64(x: u64, n: uint) -> u64 { (x >> n) | (x << (64 - n)) }
+
+fn rotr32(x: u32, n: uint) -> u32 { (x >> n) | (x << (32 - n)) }
+
+/*
+ * Blake2b and Blake2s differ only in their word size, rotation constants,
+ * number of rounds and block size, so both are generated from one macro.
+ */
+macro_rules! impl_blake2(
+    ($name:ident, $word:ty, $bits:expr, $iv:expr, $rounds:expr,
+     $rotr:ident, $r1:expr, $r2:expr, $r3:expr, $r4:expr,
+     $read_words:ident, $write_word:ident) => (
+
+    impl $name {
+        /**
+         * Construct a new instance producing `output_bytes` bytes of
+         * digest, which must be between 1 and the maximum for this
+         * function.
+         */
+        pub fn new(output_bytes: uint) -> $name {
+            $name::new_keyed(output_bytes, [])
+        }
+
+        /**
+         * Construct a new keyed instance. The key may be up to as long as
+         * the maximum output and turns the hash into a MAC.
+         */
+        pub fn new_keyed(output_bytes: uint, key: &[u8]) -> $name {
+            assert!(output_bytes >= 1 && output_bytes <= $bits / 8);
+            assert!(key.len() <= $bits / 8);
+            let mut h = $name {
+                h: $iv,
+                t: [0, 0],
+                buffer: [0u8, ..$bits / 4],
+                buffer_idx: 0,
+                output_bytes: output_bytes,
+                key: key.to_owned(),
+                computed: false
+            };
+            h.reset();
+            h
+        }
+
+        // The mixing function, applied to four words of the working vector.
+        fn g(v: &mut [$word, ..16], a: uint, b: uint, c: uint, d: uint, x: $word, y: $word) {
+            v[a] = v[a] + v[b] + x;
+            v[d] = $rotr(v[d] ^ v[a], $r1);
+            v[c] = v[c] + v[d];
+            v[b] = $rotr(v[b] ^ v[c], $r2);
+            v[a] = v[a] + v[b] + y;
+            v[d] = $rotr(v[d] ^ v[a], $r3);
+            v[c] = v[c] + v[d];
+            v[b] = $rotr(v[b] ^ v[c], $r4);
+        }
+
+        fn compress(&mut self, last: bool) {
+            let mut m: [$word, ..16] = [0, ..16];
+            $read_words(m, self.buffer);
+
+            let mut v: [$word, ..16] = [0, ..16];
+            for i in range(0u, 8) {
+                v[i] = self.h[i];
+                v[i + 8] = $iv[i];
+            }
+            v[12] ^= self.t[0];
+            v[13] ^= self.t[1];
+            if last {
+                v[14] = !v[14];
+            }
+
+            for round in range(0u, $rounds) {
+                let s = &SIGMA[round % 10];
+                // Columns
+                $name::g(&mut v, 0, 4,  8, 12, m[s[0]], m[s[1]]);
+                $name::g(&mut v, 1, 5,  9, 13, m[s[2]], m[s[3]]);
+                $name::g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+                $name::g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+                // Diagonals
+                $name::g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+                $name::g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+                $name::g(&mut v, 2, 7,  8, 13, m[s[12]], m[s[13]]);
+                $name::g(&mut v, 3, 4,  9, 14, m[s[14]], m[s[15]]);
+            }
+
+            for i in range(0u, 8) {
+                self.h[i] ^= v[i] ^ v[i + 8];
+            }
+        }
+
+        fn increment_counter(&mut self, bytes: uint) {
+            let bytes = bytes as $word;
+            self.t[0] = self.t[0] + bytes;
+            if self.t[0] < bytes {
+                self.t[1] = self.t[1] + 1;
+            }
+        }
+    }
+
+    impl Digest for $name {
+        fn input(&mut self, input: &[u8]) {
+            assert!(!self.computed);
+            let block = self.buffer.len();
+            let mut i = 0;
+            while i < input.len() {
+                // The last block is compressed differently, so a full
+                // buffer is only compressed once more input arrives.
+                if self.buffer_idx == block {
+                    self.increment_counter(block);
+                    self.compress(false);
+                    self.buffer_idx = 0;
+                }
+                let n = ::std::cmp::min(block - self.buffer_idx, input.len() - i);
+                copy_memory(self.buffer.mut_slice(self.buffer_idx, self.buffer_idx + n),
+                            input.slice(i, i + n), n);
+                self.buffer_idx += n;
+                i += n;
+            }
+        }
+
+        fn result(&mut self, out: &mut [u8]) {
+            if !self.computed {
+                let idx = self.buffer_idx;
+                self.increment_counter(idx);
+                self.buffer.mut_slice_from(idx).set_memory(0);
+                self.compress(true);
+                self.computed = true;
+            }
+
+            let word_bytes = $bits / 64;
+            let mut buf = [0u8, ..$bits / 8];
+            for i in range(0u, 8) {
+                $write_word(buf.mut_slice(i * word_bytes, (i + 1) * word_bytes), self.h[i]);
+            }
+            copy_memory(out, buf, self.output_bytes);
+        }
+
+        fn reset(&mut self) {
+            self.h = $iv;
+            self.h[0] ^= 0x01010000 ^ ((self.key.len() as $word) << 8) ^
+                (self.output_bytes as $word);
+            self.t = [0, 0];
+            self.buffer_idx = 0;
+            self.computed = false;
+            if !self.key.is_empty() {
+                // The key is padded to a whole block and hashed first.
+                self.buffer.set_memory(0);
+                copy_memory(self.buffer, self.key, self.key.len());
+                self.buffer_idx = self.buffer.len();
+            }
+        }
+
+        fn output_bits(&self) -> uint { self.output_bytes * 8 }
+    }
+))
+
+/// The BLAKE2b hash algorithm, with digests of up to 512 bits
+pub struct Blake2b {
+    priv h: [u64, ..8],
+    priv t: [u64, ..2],
+    priv buffer: [u8, ..128],
+    priv buffer_idx: uint,
+    priv output_bytes: uint,
+    priv key: ~[u8],
+    priv computed: bool
+}
+
+impl_blake2!(Blake2b, u64, 512, B_IV, 12, rotr64, 32, 24, 16, 63, read_u64v_le, write_u64_le)
+
+/// The BLAKE2s hash algorithm, with digests of up to 256 bits
+pub struct Blake2s {
+    priv h: [u32, ..8],
+    priv t: [u32, ..2],
+    priv buffer: [u8, ..64],
+    priv buffer_idx: uint,
+    priv output_bytes: uint,
+    priv key: ~[u8],
+    priv computed: bool
+}
+
+impl_blake2!(Blake2s, u32, 256, S_IV, 10, rotr32, 16, 12, 8, 7, read_u32v_le, write_u32_le)
+
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use blake2::{Blake2b, Blake2s};
+    use digest::Digest;
+
+    struct Test {
+        input: ~str,
+        output_str: ~str,
+    }
+
+    fn test_hash<D: Digest>(sh: &mut D, tests: &[Test]) {
+        // Test that it works when accepting the message all at once
+        for t in tests.iter() {
+            sh.input_str(t.input);
+
+            let out_str = sh.result_str();
+            assert_eq!(out_str, t.output_str.clone());
+
+            sh.reset();
+        }
+
+        // Test that it works when accepting the message in pieces
+        for t in tests.iter() {
+            let len = t.input.len();
+            let mut left = len;
+            while left > 0u {
+                let take = (left + 1u) / 2u;
+                sh.input_str(t.input.slice(len - left, take + len - left));
+                left = left - take;
+            }
+
+            let out_str = sh.result_str();
+            assert_eq!(out_str, t.output_str.clone());
+
+            sh.reset();
+        }
+    }
+
+    fn kat_key(len: uint) -> ~[u8] {
+        vec::from_fn(len, |i| i as u8)
+    }
+
+    #[test]
+    fn test_blake2b() {
+        let tests = ~[
+            Test {
+                input: ~"",
+                output_str: ~"786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419" +
+                             "d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce"
+            },
+            Test {
+                input: ~"abc",
+                output_str: ~"ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1" +
+                             "7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+            },
+            Test {
+                input: ~"The quick brown fox jumps over the lazy dog",
+                output_str: ~"a8add4bdddfd93e4877d2746e62817b116364a1fa7bc148d95090bc7333b3673" +
+                             "f82401cf7aa2e4cb1ecd90296e3f14cb5413f8ed77be73045b13914cdcd6a918"
+            },
+        ];
+
+        let mut sh = ~Blake2b::new(64);
+
+        test_hash(sh, tests);
+    }
+
+    #[test]
+    fn test_blake2b_256() {
+        let tests = ~[
+            Test {
+                input: ~"",
+                output_str: ~"0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+            },
+        ];
+
+        let mut sh = ~Blake2b::new(32);
+
+        test_hash(sh, tests);
+    }
+
+    #[test]
+    fn test_blake2b_keyed() {
+        // From the reference implementation's known answer tests
+        let mut sh = Blake2b::new_keyed(64, kat_key(64));
+        assert_eq!(sh.result_str(),
+                   ~"10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786" +
+                   "b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568");
+        sh.reset();
+        sh.input([0u8]);
+        assert_eq!(sh.result_str(),
+                   ~"961f6dd1e4dd30f63901690c512e78e4b45e4742ed197c3c5e45c549fd25f2e4" +
+                   "187b0bc9fe30492b16b0d0bc4ef9b0f34c7003fac09a5ef1532e69430234cebd");
+    }
+
+    #[test]
+    fn test_blake2s() {
+        let tests = ~[
+            Test {
+                input: ~"",
+                output_str: ~"69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9"
+            },
+            Test {
+                input: ~"abc",
+                output_str: ~"508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"
+            },
+            Test {
+                input: ~"The quick brown fox jumps over the lazy dog",
+                output_str: ~"606beeec743ccbeff6cbcdf5d5302aa855c256c29b88c8ed331ea1a6bf3c8812"
+            },
+        ];
+
+        let mut sh = ~Blake2s::new(32);
+
+        test_hash(sh, tests);
+    }
+
+    #[test]
+    fn test_blake2s_keyed() {
+        // From the reference implementation's known answer tests
+        let mut sh = Blake2s::new_keyed(32, kat_key(32));
+        assert_eq!(sh.result_str(),
+                   ~"48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49");
+        sh.reset();
+        sh.input([0u8]);
+        assert_eq!(sh.result_str(),
+                   ~"40d15fee7c328830166ac3f918650f807e7e01e177258cdc0a39b11f598066f1");
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_blake2s_output_too_long() {
+        Blake2s::new(33);
+    }
+}
+
+#[cfg(test)]
+mod bench {
+
+    use blake2::{Blake2b, Blake2s};
+    use digest::Digest;
+    use test::BenchHarness;
+
+    #[bench]
+    pub fn blake2b_10(bh: & mut BenchHarness) {
+        let mut sh = Blake2b::new(64);
+        let bytes = [1u8, ..10];
+        do bh.iter {
+            sh.input(bytes);
+        }
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake2b_1k(bh: & mut BenchHarness) {
+        let mut sh = Blake2b::new(64);
+        let bytes = [1u8, ..1024];
+        do bh.iter {
+            sh.input(bytes);
+        }
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake2b_64k(bh: & mut BenchHarness) {
+        let mut sh = Blake2b::new(64);
+        let bytes = [1u8, ..65536];
+        do bh.iter {
+            sh.input(bytes);
+        }
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake2s_1k(bh: & mut BenchHarness) {
+        let mut sh = Blake2s::new(32);
+        let bytes = [1u8, ..1024];
+        do bh.iter {
+            sh.input(bytes);
+        }
+        bh.bytes = bytes.len() as u64;
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Modes of operation that turn a block cipher into a cipher for messages of any length.
+ *
+ * CTR mode turns the block cipher into a `SynchronousStreamCipher`. CBC mode pads the message
+ * to a whole number of blocks with PKCS #7 padding. Neither mode authenticates the message;
+ * combine them with a `Mac` when the ciphertext may be tampered with.
+ *
+ * # Example
+ *
+ * ```rust
+ * use extra::aes::AesEncryptor;
+ * use extra::blockmodes::CtrMode;
+ * use extra::symmetriccipher::CipherWriter;
+ *
+ * let ctr = CtrMode::new(AesEncryptor::new(key), nonce);
+ * let mut writer = CipherWriter::new(ctr, file);
+ * writer.write(data);
+ * ```
+ */
+
+use std::vec;
+use std::vec::bytes::copy_memory;
+
+use symmetriccipher::{BlockEncryptor, BlockDecryptor, SynchronousStreamCipher};
+
+/**
+ * A block cipher in counter mode. The initial counter block is incremented as a big-endian
+ * integer for every block of keystream. The same counter block must never be used twice with
+ * the same key.
+ */
+pub struct CtrMode<E> {
+    priv cipher: E,
+    priv counter: ~[u8],
+    priv keystream: ~[u8],
+    priv pos: uint
+}
+
+impl<E: BlockEncryptor> CtrMode<E> {
+    /**
+     * Create a new CTR mode cipher.
+     *
+     * # Arguments
+     *
+     * * cipher - The block cipher
+     * * iv - The initial counter block. Must be as long as the cipher's blocks.
+     */
+    pub fn new(cipher: E, iv: &[u8]) -> CtrMode<E> {
+        let bs = cipher.block_size();
+        assert!(iv.len() == bs);
+        CtrMode {
+            cipher: cipher,
+            counter: iv.to_owned(),
+            keystream: vec::from_elem(bs, 0u8),
+            pos: bs
+        }
+    }
+}
+
+// Adds one to the counter, treated as a big-endian integer, wrapping around on overflow.
+fn increment_counter(counter: &mut [u8]) {
+    let mut i = counter.len();
+    while i > 0 {
+        i -= 1;
+        counter[i] += 1;
+        if counter[i] != 0 {
+            break;
+        }
+    }
+}
+
+impl<E: BlockEncryptor> SynchronousStreamCipher for CtrMode<E> {
+    fn process(&mut self, input: &[u8], output: &mut [u8]) {
+        assert!(input.len() == output.len());
+        for i in range(0, input.len()) {
+            if self.pos == self.keystream.len() {
+                self.cipher.encrypt_block(self.counter, self.keystream);
+                increment_counter(self.counter);
+                self.pos = 0;
+            }
+            output[i] = input[i] ^ self.keystream[self.pos];
+            self.pos += 1;
+        }
+    }
+}
+
+/**
+ * Encrypt a message in CBC mode, padding it with PKCS #7 padding. The output is always longer
+ * than the input, by between one and a whole block of bytes.
+ *
+ * # Arguments
+ *
+ * * cipher - The block cipher
+ * * iv - The initialization vector. Must be as long as the cipher's blocks and should be
+ *        unpredictable.
+ * * input - The message
+ */
+pub fn cbc_encrypt<E: BlockEncryptor>(cipher: &E, iv: &[u8], input: &[u8]) -> ~[u8] {
+    let bs = cipher.block_size();
+    assert!(iv.len() == bs);
+
+    let pad = bs - input.len() % bs;
+    let mut data = vec::with_capacity(input.len() + pad);
+    data.push_all(input);
+    data.grow(pad, &(pad as u8));
+
+    let mut output = vec::from_elem(data.len(), 0u8);
+    let mut prev = iv.to_owned();
+    let mut block = vec::from_elem(bs, 0u8);
+    for (i, chunk) in data.chunk_iter(bs).enumerate() {
+        for j in range(0, bs) {
+            block[j] = chunk[j] ^ prev[j];
+        }
+        cipher.encrypt_block(block, prev);
+        copy_memory(output.mut_slice(i * bs, (i + 1) * bs), prev, bs);
+    }
+    output
+}
+
+/**
+ * Decrypt a message encrypted with `cbc_encrypt` and strip its padding. Returns `None` if the
+ * input is not a whole number of blocks or the padding is malformed.
+ *
+ * Note that telling an attacker whether the padding was valid allows them to decrypt the
+ * message; authenticate the ciphertext before decrypting it.
+ *
+ * # Arguments
+ *
+ * * cipher - The block cipher
+ * * iv - The initialization vector used to encrypt the message
+ * * input - The ciphertext
+ */
+pub fn cbc_decrypt<D: BlockDecryptor>(cipher: &D, iv: &[u8], input: &[u8]) -> Option<~[u8]> {
+    let bs = cipher.block_size();
+    assert!(iv.len() == bs);
+    if input.len() == 0 || input.len() % bs != 0 {
+        return None;
+    }
+
+    let mut output = vec::from_elem(input.len(), 0u8);
+    let mut prev = iv;
+    for (i, chunk) in input.chunk_iter(bs).enumerate() {
+        let block = output.mut_slice(i * bs, (i + 1) * bs);
+        cipher.decrypt_block(chunk, block);
+        for j in range(0, bs) {
+            block[j] ^= prev[j];
+        }
+        prev = chunk;
+    }
+
+    let pad = output[output.len() - 1] as uint;
+    if pad == 0 || pad > bs {
+        return None;
+    }
+    if !output.slice_from(output.len() - pad).iter().all(|b| *b as uint == pad) {
+        return None;
+    }
+    output.truncate(input.len() - pad);
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use aes::{AesEncryptor, AesDecryptor};
+    use blockmodes::{CtrMode, cbc_encrypt, cbc_decrypt};
+    use hex::FromHex;
+    use symmetriccipher::SynchronousStreamCipher;
+
+    // Test vectors from NIST SP 800-38A, appendix F
+
+    static PLAINTEXT: &'static str =
+        "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+         30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+
+    fn test_ctr(key: &str, ciphertext: &str) {
+        let key = key.from_hex().unwrap();
+        let iv = "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff".from_hex().unwrap();
+        let plaintext = PLAINTEXT.from_hex().unwrap();
+        let ciphertext = ciphertext.from_hex().unwrap();
+
+        let mut ctr = CtrMode::new(AesEncryptor::new(key), iv);
+        let mut output = [0u8, ..64];
+        ctr.process(plaintext, output);
+        assert_eq!(output.to_owned(), ciphertext);
+
+        // Decrypting in uneven pieces gives back the plaintext.
+        let mut ctr = CtrMode::new(AesEncryptor::new(key), iv);
+        for (i, chunk) in ciphertext.chunk_iter(7).enumerate() {
+            ctr.process(chunk, output.mut_slice(i * 7, i * 7 + chunk.len()));
+        }
+        assert_eq!(output.to_owned(), plaintext);
+    }
+
+    fn test_cbc(key: &str, ciphertext: &str) {
+        let key = key.from_hex().unwrap();
+        let iv = "000102030405060708090a0b0c0d0e0f".from_hex().unwrap();
+        let plaintext = PLAINTEXT.from_hex().unwrap();
+        let ciphertext = ciphertext.from_hex().unwrap();
+
+        // The NIST vectors are unpadded, so a whole block of padding follows them.
+        let output = cbc_encrypt(&AesEncryptor::new(key), iv, plaintext);
+        assert_eq!(output.len(), 80);
+        assert_eq!(output.slice_to(64).to_owned(), ciphertext);
+        assert_eq!(cbc_decrypt(&AesDecryptor::new(key), iv, output), Some(plaintext));
+    }
+
+    #[test]
+    fn test_ctr_aes_128() {
+        test_ctr("2b7e151628aed2a6abf7158809cf4f3c",
+                 "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+                  5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee");
+    }
+
+    #[test]
+    fn test_ctr_aes_192() {
+        test_ctr("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+                 "1abc932417521ca24f2b0459fe7e6e0b090339ec0aa6faefd5ccc2c6f4ce8e94\
+                  1e36b26bd1ebc670d1bd1d665620abf74f78a7f6d29809585a97daec58c6b050");
+    }
+
+    #[test]
+    fn test_ctr_aes_256() {
+        test_ctr("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+                 "601ec313775789a5b7a7f504bbf3d228f443e3ca4d62b59aca84e990cacaf5c5\
+                  2b0930daa23de94ce87017ba2d84988ddfc9c58db67aada613c2dd08457941a6");
+    }
+
+    #[test]
+    fn test_ctr_counter_wraps() {
+        let key = "2b7e151628aed2a6abf7158809cf4f3c".from_hex().unwrap();
+        let mut ctr = CtrMode::new(AesEncryptor::new(key), [0xffu8, ..16]);
+        let mut output = [0u8, ..32];
+        ctr.process([0u8, ..32], output);
+        assert_eq!(output.to_owned(),
+                   "8af2860142f786f409307c1a3f7eaaac7df76b0c1ab899b33e42f047b91b546f"
+                   .from_hex().unwrap());
+    }
+
+    #[test]
+    fn test_cbc_aes_128() {
+        test_cbc("2b7e151628aed2a6abf7158809cf4f3c",
+                 "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2\
+                  73bed6b8e3c1743b7116e69e222295163ff1caa1681fac09120eca307586e1a7");
+    }
+
+    #[test]
+    fn test_cbc_aes_192() {
+        test_cbc("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+                 "4f021db243bc633d7178183a9fa071e8b4d9ada9ad7dedf4e5e738763f69145a\
+                  571b242012fb7ae07fa9baac3df102e008b0e27988598881d920a9e64f5615cd");
+    }
+
+    #[test]
+    fn test_cbc_aes_256() {
+        test_cbc("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+                 "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d\
+                  39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b");
+    }
+
+    #[test]
+    fn test_cbc_padding() {
+        let key = [7u8, ..16];
+        let iv = [9u8, ..16];
+        let enc = AesEncryptor::new(key);
+        let dec = AesDecryptor::new(key);
+        for len in range(0u, 40) {
+            let msg = vec::from_elem(len, 0x5au8);
+            let ct = cbc_encrypt(&enc, iv, msg);
+            assert_eq!(ct.len(), (len / 16 + 1) * 16);
+            assert_eq!(cbc_decrypt(&dec, iv, ct), Some(msg));
+        }
+    }
+
+    #[test]
+    fn test_cbc_bad_input() {
+        let key = [7u8, ..16];
+        let iv = [9u8, ..16];
+        let dec = AesDecryptor::new(key);
+        assert_eq!(cbc_decrypt(&dec, iv, []), None);
+        assert_eq!(cbc_decrypt(&dec, iv, [0u8, ..15]), None);
+
+        // Decrypting only the first block of a longer message exposes its last bytes as padding.
+        let enc = AesEncryptor::new(key);
+        let mut msg = [0u8, ..16];
+        msg[14] = 3;
+        msg[15] = 2;
+        let ct = cbc_encrypt(&enc, iv, msg);
+        assert_eq!(cbc_decrypt(&dec, iv, ct.slice_to(16)), None);
+
+        msg[14] = 2;
+        let ct = cbc_encrypt(&enc, iv, msg);
+        assert_eq!(cbc_decrypt(&dec, iv, ct.slice_to(16)), Some(vec::from_elem(14, 0u8)));
+
+        msg[15] = 0;
+        let ct = cbc_encrypt(&enc, iv, msg);
+        assert_eq!(cbc_decrypt(&dec, iv, ct.slice_to(16)), None);
+    }
+}
+
+#[cfg(test)]
+mod bench {
+    use aes::AesEncryptor;
+    use blockmodes::CtrMode;
+    use symmetriccipher::SynchronousStreamCipher;
+    use test::BenchHarness;
+
+    #[bench]
+    pub fn ctr_aes_128_1k(bh: & mut BenchHarness) {
+        let mut ctr = CtrMode::new(AesEncryptor::new([1u8, ..16]), [0u8, ..16]);
+        let input = [1u8, ..1024];
+        let mut output = [0u8, ..1024];
+        do bh.iter {
+            ctr.process(input, output);
+        }
+        bh.bytes = input.len() as u64;
+    }
+}
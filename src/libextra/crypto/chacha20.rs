@@ -0,0 +1,247 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * An implementation of the ChaCha20 stream cipher.
+ *
+ * Both the original variant with a 64 bit nonce and a 64 bit block counter and the variant
+ * from RFC 7539 with a 96 bit nonce and a 32 bit block counter are supported; the length of
+ * the nonce selects between them. ChaCha20 needs no lookup tables and runs in constant time.
+ *
+ * # Example
+ *
+ * ```rust
+ * use extra::chacha20::ChaCha20;
+ * use extra::symmetriccipher::SynchronousStreamCipher;
+ *
+ * let mut cipher = ChaCha20::new(key, nonce);
+ * let mut ciphertext = vec::from_elem(message.len(), 0u8);
+ * cipher.process(message, ciphertext);
+ * ```
+ */
+
+use cryptoutil::{read_u32v_le, write_u32_le};
+use symmetriccipher::SynchronousStreamCipher;
+
+/**
+ * The ChaCha20 stream cipher. A nonce must never be used twice with the same key.
+ */
+pub struct ChaCha20 {
+    priv state: [u32, ..16],
+    priv output: [u8, ..64],
+    priv offset: uint,
+    priv wide_counter: bool,
+    priv exhausted: bool
+}
+
+fn rotl32(x: u32, n: uint) -> u32 {
+    (x << n) | (x >> (32 - n))
+}
+
+fn quarter_round(x: &mut [u32, ..16], a: uint, b: uint, c: uint, d: uint) {
+    x[a] += x[b]; x[d] = rotl32(x[d] ^ x[a], 16);
+    x[c] += x[d]; x[b] = rotl32(x[b] ^ x[c], 12);
+    x[a] += x[b]; x[d] = rotl32(x[d] ^ x[a], 8);
+    x[c] += x[d]; x[b] = rotl32(x[b] ^ x[c], 7);
+}
+
+impl ChaCha20 {
+    /**
+     * Create a new ChaCha20 instance, starting at block 0.
+     *
+     * # Arguments
+     *
+     * * key - The key. Must be 32 bytes long.
+     * * nonce - The nonce. Must be 8 or 12 bytes long.
+     */
+    pub fn new(key: &[u8], nonce: &[u8]) -> ChaCha20 {
+        ChaCha20::with_counter(key, nonce, 0)
+    }
+
+    /**
+     * Create a new ChaCha20 instance, starting at the given block of the keystream.
+     *
+     * # Arguments
+     *
+     * * key - The key. Must be 32 bytes long.
+     * * nonce - The nonce. Must be 8 or 12 bytes long.
+     * * counter - The index of the first 64 byte block of keystream to use
+     */
+    pub fn with_counter(key: &[u8], nonce: &[u8], counter: u32) -> ChaCha20 {
+        assert!(key.len() == 32);
+        assert!(nonce.len() == 8 || nonce.len() == 12);
+
+        // "expand 32-byte k"
+        let mut state = [0x61707865u32, 0x3320646e, 0x79622d32, 0x6b206574,
+                         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        read_u32v_le(state.mut_slice(4, 12), key);
+        state[12] = counter;
+        read_u32v_le(state.mut_slice(16 - nonce.len() / 4, 16), nonce);
+
+        ChaCha20 {
+            state: state,
+            output: [0u8, ..64],
+            offset: 64,
+            wide_counter: nonce.len() == 8,
+            exhausted: false
+        }
+    }
+
+    // Computes the next block of keystream and advances the counter.
+    fn update(&mut self) {
+        assert!(!self.exhausted, "ChaCha20 keystream exhausted");
+        let mut x = self.state;
+        for _ in range(0, 10) {
+            quarter_round(&mut x, 0, 4, 8, 12);
+            quarter_round(&mut x, 1, 5, 9, 13);
+            quarter_round(&mut x, 2, 6, 10, 14);
+            quarter_round(&mut x, 3, 7, 11, 15);
+            quarter_round(&mut x, 0, 5, 10, 15);
+            quarter_round(&mut x, 1, 6, 11, 12);
+            quarter_round(&mut x, 2, 7, 8, 13);
+            quarter_round(&mut x, 3, 4, 9, 14);
+        }
+        for i in range(0, 16) {
+            write_u32_le(self.output.mut_slice(4 * i, 4 * i + 4), x[i] + self.state[i]);
+        }
+
+        self.state[12] += 1;
+        if self.state[12] == 0 {
+            // The 96 bit nonce variant only has a 32 bit counter, and its keystream ends here.
+            if self.wide_counter {
+                self.state[13] += 1;
+            } else {
+                self.exhausted = true;
+            }
+        }
+        self.offset = 0;
+    }
+}
+
+impl SynchronousStreamCipher for ChaCha20 {
+    fn process(&mut self, input: &[u8], output: &mut [u8]) {
+        assert!(input.len() == output.len());
+        for i in range(0, input.len()) {
+            if self.offset == 64 {
+                self.update();
+            }
+            output[i] = input[i] ^ self.output[self.offset];
+            self.offset += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use chacha20::ChaCha20;
+    use hex::FromHex;
+    use symmetriccipher::SynchronousStreamCipher;
+
+    fn keystream(cipher: &mut ChaCha20, len: uint) -> ~[u8] {
+        let mut output = vec::from_elem(len, 0u8);
+        cipher.process(vec::from_elem(len, 0u8), output);
+        output
+    }
+
+    #[test]
+    fn test_chacha20_zero_key() {
+        let mut cipher = ChaCha20::new([0u8, ..32], [0u8, ..8]);
+        assert_eq!(keystream(&mut cipher, 64),
+                   "76b8e0ada0f13d90405d6ae55386bd28bdd219b8a08ded1aa836efcc8b770dc7\
+                    da41597c5157488d7724e03fb8d84a376a43b8f41518a11cc387b669b2ee6586"
+                   .from_hex().unwrap());
+
+        let mut cipher = ChaCha20::new([0u8, ..32], "0000000000000001".from_hex().unwrap());
+        assert_eq!(keystream(&mut cipher, 60),
+                   "de9cba7bf3d69ef5e786dc63973f653a0b49e015adbff7134fcb7df137821031\
+                    e85a050278a7084527214f73efc7fa5b5277062eb7a0433e445f41e3"
+                   .from_hex().unwrap());
+    }
+
+    #[test]
+    fn test_chacha20_block_function() {
+        // RFC 7539, section 2.3.2
+        let key = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+            .from_hex().unwrap();
+        let nonce = "000000090000004a00000000".from_hex().unwrap();
+        let mut cipher = ChaCha20::with_counter(key, nonce, 1);
+        assert_eq!(keystream(&mut cipher, 64),
+                   "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e\
+                    d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e"
+                   .from_hex().unwrap());
+    }
+
+    #[test]
+    fn test_chacha20_encrypt() {
+        // RFC 7539, section 2.4.2
+        let key = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+            .from_hex().unwrap();
+        let nonce = "000000000000004a00000000".from_hex().unwrap();
+        let plaintext = bytes!("Ladies and Gentlemen of the class of '99: If I could offer ",
+                               "you only one tip for the future, sunscreen would be it.");
+        let ciphertext =
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b\
+             f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8\
+             07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736\
+             5af90bbf74a35be6b40b8eedf2785e42874d".from_hex().unwrap();
+
+        let mut output = vec::from_elem(plaintext.len(), 0u8);
+        let mut cipher = ChaCha20::with_counter(key, nonce, 1);
+        cipher.process(plaintext, output);
+        assert_eq!(output, ciphertext.clone());
+
+        // Decrypting in uneven pieces gives back the plaintext.
+        let mut cipher = ChaCha20::with_counter(key, nonce, 1);
+        for (i, chunk) in ciphertext.chunk_iter(13).enumerate() {
+            cipher.process(chunk, output.mut_slice(i * 13, i * 13 + chunk.len()));
+        }
+        assert_eq!(output.as_slice(), plaintext);
+    }
+
+    #[test]
+    fn test_chacha20_counter_carry() {
+        // The 64 bit nonce variant carries the block counter into a second word.
+        let nonce = "0000000000000001".from_hex().unwrap();
+        let mut cipher = ChaCha20::with_counter([0u8, ..32], nonce, 0xffffffff);
+        let output = keystream(&mut cipher, 128);
+        assert_eq!(output.slice_from(64).to_owned(),
+                   "50a109d6eb0b5797bb745f205f1dfcfb6c183b43e3b5fb80dd65e48b6546a8e4\
+                    e16b1cac847db90b286bfcbc5b496dce94ed828ecf192db495e208953b906ff9"
+                   .from_hex().unwrap());
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_chacha20_counter_exhausted() {
+        let mut cipher = ChaCha20::with_counter([0u8, ..32], [0u8, ..12], 0xffffffff);
+        keystream(&mut cipher, 64);
+        keystream(&mut cipher, 1);
+    }
+}
+
+#[cfg(test)]
+mod bench {
+    use chacha20::ChaCha20;
+    use symmetriccipher::SynchronousStreamCipher;
+    use test::BenchHarness;
+
+    #[bench]
+    pub fn chacha20_1k(bh: & mut BenchHarness) {
+        let mut cipher = ChaCha20::new([1u8, ..32], [0u8, ..8]);
+        let input = [1u8, ..1024];
+        let mut output = [0u8, ..1024];
+        do bh.iter {
+            cipher.process(input, output);
+        }
+        bh.bytes = input.len() as u64;
+    }
+}
@@ -24,6 +24,18 @@ pub fn write_u64_be(dst: &mut[u8], input: u64) {
     }
 }
 
+/// Write a u64 into a vector, which must be 8 bytes long. The value is written in little-endian
+/// format.
+pub fn write_u64_le(dst: &mut[u8], input: u64) {
+    use std::cast::transmute;
+    use std::unstable::intrinsics::to_le64;
+    assert!(dst.len() == 8);
+    unsafe {
+        let x: *mut i64 = transmute(dst.unsafe_mut_ref(0));
+        *x = to_le64(input as i64);
+    }
+}
+
 /// Write a u32 into a vector, which must be 4 bytes long. The value is written in big-endian
 /// format.
 pub fn write_u32_be(dst: &mut[u8], input: u32) {
@@ -64,6 +76,22 @@ pub fn read_u64v_be(dst: &mut[u64], input: &[u8]) {
     }
 }
 
+/// Read a vector of bytes into a vector of u64s. The values are read in little-endian format.
+pub fn read_u64v_le(dst: &mut[u64], input: &[u8]) {
+    use std::cast::transmute;
+    use std::unstable::intrinsics::to_le64;
+    assert!(dst.len() * 8 == input.len());
+    unsafe {
+        let mut x: *mut i64 = transmute(dst.unsafe_mut_ref(0));
+        let mut y: *i64 = transmute(input.unsafe_ref(0));
+        do dst.len().times() {
+            *x = to_le64(*y);
+            x = x.offset(1);
+            y = y.offset(1);
+        }
+    }
+}
+
 /// Read a vector of bytes into a vector of u32s. The values are read in big-endian format.
 pub fn read_u32v_be(dst: &mut[u32], input: &[u8]) {
     use std::cast::transmute;
@@ -0,0 +1,151 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * An implementation of HKDF, the HMAC-based key derivation function
+ * (RFC 5869).
+ *
+ * HKDF turns input keying material that is not uniformly random, such as
+ * a Diffie-Hellman shared secret, into one or more strong keys. It is not
+ * suitable for passwords; use `pbkdf2` for those.
+ */
+
+use std::cmp;
+use std::vec::bytes::copy_memory;
+
+use hmac::{BlockDigest, Hmac};
+use mac::Mac;
+
+/**
+ * Extract a pseudorandom key from the input keying material.
+ *
+ * # Arguments
+ *
+ * * digest - The digest function to use
+ * * salt - An optional, non-secret random value. May be empty.
+ * * ikm - The input keying material
+ * * prk - The vector to hold the pseudorandom key. Must be as long as the
+ *         digest's output.
+ */
+pub fn hkdf_extract<D: BlockDigest>(digest: D, salt: &[u8], ikm: &[u8], prk: &mut [u8]) {
+    let mut mac = Hmac::new(digest, salt);
+    mac.input(ikm);
+    mac.result(prk);
+}
+
+/**
+ * Expand a pseudorandom key into output keying material.
+ *
+ * # Arguments
+ *
+ * * digest - The digest function to use
+ * * prk - A pseudorandom key, usually from `hkdf_extract`
+ * * info - Context specific information, binding the output to its use.
+ *          May be empty.
+ * * okm - The vector to fill with output keying material. At most 255
+ *         times the digest's output size.
+ */
+pub fn hkdf_expand<D: BlockDigest>(digest: D, prk: &[u8], info: &[u8], okm: &mut [u8]) {
+    let mut mac = Hmac::new(digest, prk);
+    let os = (mac.output_bits() + 7) / 8;
+    assert!(okm.len() <= 255 * os);
+
+    let mut t = ~[];
+    let mut pos = 0;
+    let mut n = 0u8;
+    while pos < okm.len() {
+        n += 1;
+        mac.reset();
+        mac.input(t);
+        mac.input(info);
+        mac.input([n]);
+        t = mac.result_bytes();
+
+        let len = cmp::min(os, okm.len() - pos);
+        copy_memory(okm.mut_slice_from(pos), t, len);
+        pos += len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use hex::FromHex;
+    use hkdf::{hkdf_extract, hkdf_expand};
+    use hmac::BlockDigest;
+    use sha1::Sha1;
+    use sha2::Sha256;
+
+    struct Test {
+        ikm: ~[u8],
+        salt: ~[u8],
+        info: ~[u8],
+        prk: ~[u8],
+        okm: ~[u8]
+    }
+
+    fn test_hkdf<D: BlockDigest>(digest: &fn() -> D, t: &Test) {
+        let mut prk = vec::from_elem(t.prk.len(), 0u8);
+        hkdf_extract(digest(), t.salt, t.ikm, prk);
+        assert_eq!(prk, t.prk.clone());
+
+        let mut okm = vec::from_elem(t.okm.len(), 0u8);
+        hkdf_expand(digest(), prk, t.info, okm);
+        assert_eq!(okm, t.okm.clone());
+    }
+
+    // Test vectors from RFC 5869, appendix A
+
+    #[test]
+    fn test_hkdf_sha256() {
+        test_hkdf(|| Sha256::new(), &Test {
+            ikm: vec::from_elem(22, 0x0bu8),
+            salt: "000102030405060708090a0b0c".from_hex().unwrap(),
+            info: "f0f1f2f3f4f5f6f7f8f9".from_hex().unwrap(),
+            prk: "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+                .from_hex().unwrap(),
+            okm: "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf\
+                  34007208d5b887185865".from_hex().unwrap()
+        });
+    }
+
+    #[test]
+    fn test_hkdf_sha256_empty_salt_info() {
+        test_hkdf(|| Sha256::new(), &Test {
+            ikm: vec::from_elem(22, 0x0bu8),
+            salt: ~[],
+            info: ~[],
+            prk: "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04"
+                .from_hex().unwrap(),
+            okm: "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
+                  9d201395faa4b61a96c8".from_hex().unwrap()
+        });
+    }
+
+    #[test]
+    fn test_hkdf_sha1() {
+        test_hkdf(|| Sha1::new(), &Test {
+            ikm: vec::from_elem(11, 0x0bu8),
+            salt: "000102030405060708090a0b0c".from_hex().unwrap(),
+            info: "f0f1f2f3f4f5f6f7f8f9".from_hex().unwrap(),
+            prk: "9b6c18c432a7bf8f0e71c8eb88f4b30baa2ba243".from_hex().unwrap(),
+            okm: "085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2\
+                  c22e422478d305f3f896".from_hex().unwrap()
+        });
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_hkdf_expand_too_long() {
+        let mut okm = vec::from_elem(255 * 20 + 1, 0u8);
+        hkdf_expand(Sha1::new(), [0u8, ..20], [], okm);
+    }
+}
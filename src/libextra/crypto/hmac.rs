@@ -0,0 +1,331 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * An implementation of the HMAC message authentication code (RFC 2104) on
+ * top of any `Digest` that implements `BlockDigest`.
+ *
+ * # Example
+ *
+ * ```rust
+ * use extra::hmac::Hmac;
+ * use extra::mac::Mac;
+ * use extra::sha2::Sha256;
+ *
+ * let mut hmac = Hmac::new(Sha256::new(), bytes!("secret"));
+ * hmac.input(payload);
+ * if !hmac.verify(signature) {
+ *     fail!("bad signature");
+ * }
+ * ```
+ */
+
+use std::vec;
+use std::vec::bytes::copy_memory;
+
+use digest::Digest;
+use mac::Mac;
+use blake2::{Blake2b, Blake2s};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Sha512, Sha384, Sha512Trunc256, Sha512Trunc224, Sha256, Sha224};
+use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256};
+
+/**
+ * A `Digest` that HMAC can be built on. HMAC pads its key to the size of
+ * the blocks the digest function processes, which `Digest` doesn't tell.
+ */
+pub trait BlockDigest: Digest {
+    /// Get the size in bytes of the blocks the digest function processes.
+    fn block_size(&self) -> uint;
+}
+
+impl BlockDigest for Md5 {
+    fn block_size(&self) -> uint { 64 }
+}
+
+impl BlockDigest for Sha1 {
+    fn block_size(&self) -> uint { 64 }
+}
+
+impl BlockDigest for Sha512 {
+    fn block_size(&self) -> uint { 128 }
+}
+
+impl BlockDigest for Sha384 {
+    fn block_size(&self) -> uint { 128 }
+}
+
+impl BlockDigest for Sha512Trunc256 {
+    fn block_size(&self) -> uint { 128 }
+}
+
+impl BlockDigest for Sha512Trunc224 {
+    fn block_size(&self) -> uint { 128 }
+}
+
+impl BlockDigest for Sha256 {
+    fn block_size(&self) -> uint { 64 }
+}
+
+impl BlockDigest for Sha224 {
+    fn block_size(&self) -> uint { 64 }
+}
+
+// The Keccak sponge absorbs its rate's worth of bytes at a time.
+impl BlockDigest for Sha3_224 {
+    fn block_size(&self) -> uint { 144 }
+}
+
+impl BlockDigest for Sha3_256 {
+    fn block_size(&self) -> uint { 136 }
+}
+
+impl BlockDigest for Sha3_384 {
+    fn block_size(&self) -> uint { 104 }
+}
+
+impl BlockDigest for Sha3_512 {
+    fn block_size(&self) -> uint { 72 }
+}
+
+impl BlockDigest for Shake128 {
+    fn block_size(&self) -> uint { 168 }
+}
+
+impl BlockDigest for Shake256 {
+    fn block_size(&self) -> uint { 136 }
+}
+
+impl BlockDigest for Blake2b {
+    fn block_size(&self) -> uint { 128 }
+}
+
+impl BlockDigest for Blake2s {
+    fn block_size(&self) -> uint { 64 }
+}
+
+/**
+ * The HMAC of a digest function `D`. The key is fixed when the Hmac is
+ * created; `reset` starts a new message with the same key.
+ */
+pub struct Hmac<D> {
+    priv digest: D,
+    priv i_key: ~[u8],
+    priv o_key: ~[u8],
+    priv finished: bool
+}
+
+// Pads the key to the digest's block size, hashing it first if it is longer.
+fn expand_key<D: BlockDigest>(digest: &mut D, key: &[u8]) -> ~[u8] {
+    let bs = digest.block_size();
+    let mut expanded_key = vec::from_elem(bs, 0u8);
+    if key.len() <= bs {
+        copy_memory(expanded_key, key, key.len());
+    } else {
+        let output_size = (digest.output_bits() + 7) / 8;
+        digest.input(key);
+        digest.result(expanded_key.mut_slice_to(output_size));
+        digest.reset();
+    }
+    expanded_key
+}
+
+impl<D: BlockDigest> Hmac<D> {
+    /**
+     * Create a new Hmac instance.
+     *
+     * # Arguments
+     *
+     * * digest - The Digest to use. Any input it has already seen is discarded.
+     * * key - The key to use. Keys longer than the digest's block size are
+     *         hashed first.
+     */
+    pub fn new(digest: D, key: &[u8]) -> Hmac<D> {
+        let mut digest = digest;
+        digest.reset();
+        let key = expand_key(&mut digest, key);
+        let i_key = key.map(|b| *b ^ 0x36);
+        let o_key = key.map(|b| *b ^ 0x5c);
+        digest.input(i_key);
+        Hmac {
+            digest: digest,
+            i_key: i_key,
+            o_key: o_key,
+            finished: false
+        }
+    }
+}
+
+impl<D: BlockDigest> Mac for Hmac<D> {
+    fn input(&mut self, data: &[u8]) {
+        assert!(!self.finished);
+        self.digest.input(data);
+    }
+
+    fn reset(&mut self) {
+        self.digest.reset();
+        self.digest.input(self.i_key);
+        self.finished = false;
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        if !self.finished {
+            let inner = self.digest.result_bytes();
+            self.digest.reset();
+            self.digest.input(self.o_key);
+            self.digest.input(inner);
+            self.finished = true;
+        }
+        self.digest.result(out);
+    }
+
+    fn output_bits(&self) -> uint { self.digest.output_bits() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use hex::FromHex;
+    use hmac::{BlockDigest, Hmac};
+    use mac::Mac;
+    use md5::Md5;
+    use sha1::Sha1;
+    use sha2::{Sha256, Sha512};
+
+    struct Test {
+        key: ~[u8],
+        data: ~[u8],
+        expected: &'static str
+    }
+
+    fn test_hmac<D: BlockDigest>(digest: &fn() -> D, tests: &[Test]) {
+        for t in tests.iter() {
+            let mut hmac = Hmac::new(digest(), t.key);
+            hmac.input(t.data);
+            assert_eq!(hmac.result_str().as_slice(), t.expected);
+            // The result can be read more than once.
+            assert_eq!(hmac.result_bytes(), t.expected.from_hex().unwrap());
+
+            // Feeding the input in pieces after a reset gives the same code.
+            hmac.reset();
+            for chunk in t.data.chunk_iter(3) {
+                hmac.input(chunk);
+            }
+            assert!(hmac.verify(t.expected.from_hex().unwrap()));
+        }
+    }
+
+    fn hi_there(key_len: uint) -> Test {
+        Test {
+            key: vec::from_elem(key_len, 0x0b),
+            data: "Hi There".as_bytes().to_owned(),
+            expected: ""
+        }
+    }
+
+    fn jefe() -> Test {
+        Test {
+            key: "Jefe".as_bytes().to_owned(),
+            data: "what do ya want for nothing?".as_bytes().to_owned(),
+            expected: ""
+        }
+    }
+
+    fn large_key(key_len: uint) -> Test {
+        Test {
+            key: vec::from_elem(key_len, 0xaa),
+            data: "Test Using Larger Than Block-Size Key - Hash Key First".as_bytes().to_owned(),
+            expected: ""
+        }
+    }
+
+    #[test]
+    fn test_hmac_md5() {
+        // Test vectors from RFC 2202
+        let tests = ~[
+            Test { expected: "9294727a3638bb1c13f48ef8158bfc9d", .. hi_there(16) },
+            Test { expected: "750c783e6ab0b503eaa86e310a5db738", .. jefe() },
+            Test {
+                key: vec::from_elem(16, 0xaa),
+                data: vec::from_elem(50, 0xdd),
+                expected: "56be34521d144c88dbb8c733f0e8b3f6"
+            },
+            Test { expected: "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd", .. large_key(80) },
+        ];
+        test_hmac(|| Md5::new(), tests);
+    }
+
+    #[test]
+    fn test_hmac_sha1() {
+        // Test vectors from RFC 2202
+        let tests = ~[
+            Test { expected: "b617318655057264e28bc0b6fb378c8ef146be00", .. hi_there(20) },
+            Test { expected: "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79", .. jefe() },
+            Test {
+                key: vec::from_elem(20, 0xaa),
+                data: vec::from_elem(50, 0xdd),
+                expected: "125d7342b9ac11cd91a39af48aa17b4f63f175d3"
+            },
+            Test { expected: "aa4ae5e15272d00e95705637ce8a3b55ed402112", .. large_key(80) },
+        ];
+        test_hmac(|| Sha1::new(), tests);
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // Test vectors from RFC 4231
+        let tests = ~[
+            Test {
+                expected: "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                .. hi_there(20)
+            },
+            Test {
+                expected: "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                .. jefe()
+            },
+            Test {
+                expected: "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                .. large_key(131)
+            },
+        ];
+        test_hmac(|| Sha256::new(), tests);
+    }
+
+    #[test]
+    fn test_hmac_sha512() {
+        // Test vectors from RFC 4231
+        let tests = ~[
+            Test {
+                expected: "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+                           daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+                .. hi_there(20)
+            },
+            Test {
+                expected: "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+                           9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+                .. jefe()
+            },
+        ];
+        test_hmac(|| Sha512::new(), tests);
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut hmac = Hmac::new(Sha1::new(), bytes!("key"));
+        hmac.input(bytes!("message"));
+        let mut code = hmac.result_bytes();
+        assert!(hmac.verify(code));
+        code[0] ^= 1;
+        assert!(!hmac.verify(code));
+        assert!(!hmac.verify(code.slice_to(10)));
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Common functionality related to message authentication codes
+
+use std::vec;
+
+use hex::ToHex;
+
+
+/**
+ * The Mac trait specifies an interface common to message authentication codes, such as HMAC.
+ * It mirrors `Digest`, but the result depends on a secret key as well as the input.
+ */
+pub trait Mac {
+    /**
+     * Provide message data.
+     *
+     * # Arguments
+     *
+     * * input - A vector of message data
+     */
+    fn input(&mut self, input: &[u8]);
+
+    /**
+     * Retrieve the authentication code. This method may be called multiple times.
+     *
+     * # Arguments
+     *
+     * * out - the vector to hold the result. Must be large enough to contain output_bits().
+     */
+    fn result(&mut self, out: &mut [u8]);
+
+    /**
+     * Reset the Mac, keeping its key. This method must be called after result() and before
+     * supplying more data.
+     */
+    fn reset(&mut self);
+
+    /**
+     * Get the output size in bits.
+     */
+    fn output_bits(&self) -> uint;
+
+    /**
+     * Convenience function that retrieves the authentication code as a
+     * newly allocated vec of bytes.
+     */
+    fn result_bytes(&mut self) -> ~[u8] {
+        let mut buf = vec::from_elem((self.output_bits()+7)/8, 0u8);
+        self.result(buf);
+        buf
+    }
+
+    /**
+     * Convenience function that retrieves the authentication code as a
+     * ~str in hexadecimal format.
+     */
+    fn result_str(&mut self) -> ~str {
+        self.result_bytes().to_hex()
+    }
+
+    /**
+     * Check the input seen so far against an authentication code received
+     * with it. The comparison takes the same time wherever the codes
+     * differ, so it does not leak how much of a forged code was right.
+     *
+     * # Arguments
+     *
+     * * code - The authentication code to check
+     */
+    fn verify(&mut self, code: &[u8]) -> bool {
+        fixed_time_eq(self.result_bytes(), code)
+    }
+}
+
+/**
+ * Compare two byte vectors in time that depends only on their length, not
+ * on their contents.
+ */
+pub fn fixed_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= *x ^ *y;
+    }
+    diff == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use mac::fixed_time_eq;
+
+    #[test]
+    fn test_fixed_time_eq() {
+        assert!(fixed_time_eq([], []));
+        assert!(fixed_time_eq([1, 2, 3], [1, 2, 3]));
+        assert!(!fixed_time_eq([1, 2, 3], [1, 2, 4]));
+        assert!(!fixed_time_eq([0, 2, 3], [1, 2, 3]));
+        assert!(!fixed_time_eq([1, 2], [1, 2, 3]));
+    }
+}
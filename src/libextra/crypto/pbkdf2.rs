@@ -0,0 +1,129 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * An implementation of PBKDF2, the password-based key derivation function
+ * from PKCS #5 (RFC 2898), using HMAC as its pseudorandom function.
+ *
+ * # Example
+ *
+ * ```rust
+ * use extra::pbkdf2::pbkdf2;
+ * use extra::sha2::Sha256;
+ *
+ * let mut key = [0u8, ..32];
+ * pbkdf2(Sha256::new(), password.as_bytes(), salt, 10000, key);
+ * ```
+ */
+
+use std::cmp;
+use std::vec;
+use std::vec::bytes::copy_memory;
+
+use cryptoutil::write_u32_be;
+use hmac::{BlockDigest, Hmac};
+use mac::Mac;
+
+/**
+ * Derive a key from a password.
+ *
+ * # Arguments
+ *
+ * * digest - The digest function to use with HMAC
+ * * password - The password
+ * * salt - A random salt, unique to this password
+ * * iterations - The number of rounds of HMAC per block of output. Must be
+ *                at least 1; larger values make guessing the password slower.
+ * * output - The vector to fill with the derived key
+ */
+pub fn pbkdf2<D: BlockDigest>(digest: D, password: &[u8], salt: &[u8], iterations: uint,
+                              output: &mut [u8]) {
+    assert!(iterations > 0);
+    let mut mac = Hmac::new(digest, password);
+    let os = (mac.output_bits() + 7) / 8;
+
+    let mut u = vec::from_elem(os, 0u8);
+    let mut t = vec::from_elem(os, 0u8);
+    let mut idx = [0u8, ..4];
+    let mut block = 1u32;
+    let mut pos = 0;
+    while pos < output.len() {
+        // U_1 = PRF(P, S || INT(i))
+        write_u32_be(idx, block);
+        mac.reset();
+        mac.input(salt);
+        mac.input(idx);
+        mac.result(u);
+        copy_memory(t, u, os);
+
+        // U_j = PRF(P, U_{j-1}); T_i = U_1 ^ U_2 ^ ... ^ U_c
+        for _ in range(1, iterations) {
+            mac.reset();
+            mac.input(u);
+            mac.result(u);
+            for (x, y) in t.mut_iter().zip(u.iter()) {
+                *x ^= *y;
+            }
+        }
+
+        let len = cmp::min(os, output.len() - pos);
+        copy_memory(output.mut_slice_from(pos), t, len);
+        pos += len;
+        block += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use hex::FromHex;
+    use pbkdf2::pbkdf2;
+    use sha1::Sha1;
+
+    fn test_pbkdf2_sha1(password: &[u8], salt: &[u8], iterations: uint, expected: &str) {
+        let expected = expected.from_hex().unwrap();
+        let mut output = vec::from_elem(expected.len(), 0u8);
+        pbkdf2(Sha1::new(), password, salt, iterations, output);
+        assert_eq!(output, expected);
+    }
+
+    // Test vectors from RFC 6070
+
+    #[test]
+    fn test_pbkdf2() {
+        test_pbkdf2_sha1(bytes!("password"), bytes!("salt"), 1,
+                         "0c60c80f961f0e71f3a9b524af6012062fe037a6");
+        test_pbkdf2_sha1(bytes!("password"), bytes!("salt"), 2,
+                         "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957");
+        test_pbkdf2_sha1(bytes!("password"), bytes!("salt"), 4096,
+                         "4b007901b765489abead49d926f721d065a429c1");
+    }
+
+    #[test]
+    fn test_pbkdf2_multiple_blocks() {
+        test_pbkdf2_sha1(bytes!("passwordPASSWORDpassword"),
+                         bytes!("saltSALTsaltSALTsaltSALTsaltSALTsalt"), 4096,
+                         "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038");
+    }
+
+    #[test]
+    fn test_pbkdf2_nul_bytes() {
+        test_pbkdf2_sha1(bytes!("pass", 0, "word"), bytes!("sa", 0, "lt"), 4096,
+                         "56fa6aa75548099dcc37d7f03425e0c3");
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_pbkdf2_zero_iterations() {
+        let mut output = [0u8, ..20];
+        pbkdf2(Sha1::new(), bytes!("password"), bytes!("salt"), 0, output);
+    }
+}
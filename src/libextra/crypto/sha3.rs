@@ -0,0 +1,530 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * An implementation of the SHA-3 cryptographic hash functions and the
+ * SHAKE extendable-output functions from FIPS 202, all built on the
+ * Keccak-f[1600] permutation.
+ *
+ * `Sha3_224`, `Sha3_256`, `Sha3_384` and `Sha3_512` are used like the
+ * other digests. `Shake128` and `Shake256` produce as much output as is
+ * asked of them: `result` returns the first `output_bits()` bits, and
+ * `squeeze` reads the output stream incrementally.
+ */
+
+use cryptoutil::read_u64v_le;
+use digest::Digest;
+
+static ROUNDS: uint = 24;
+
+static RC: [u64, ..ROUNDS] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+// Rotation offsets for the rho step, in the order lanes are visited by pi.
+static ROTC: [uint, ..24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44
+];
+
+// The lane each step of pi moves to.
+static PILN: [uint, ..24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1
+];
+
+// The largest rate, used by SHAKE128, in 64-bit lanes.
+static MAX_RATE_LANES: uint = 21;
+
+fn rotl(x: u64, n: uint) -> u64 { (x << n) | (x >> (64 - n)) }
+
+fn keccak_f(a: &mut [u64, ..25]) {
+    for round in range(0u, ROUNDS) {
+        // Theta
+        let mut c = [0u64, ..5];
+        for x in range(0u, 5) {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in range(0u, 5) {
+            let d = c[(x + 4) % 5] ^ rotl(c[(x + 1) % 5], 1);
+            for y in range(0u, 5) {
+                a[y * 5 + x] ^= d;
+            }
+        }
+
+        // Rho and pi
+        let mut t = a[1];
+        for i in range(0u, 24) {
+            let j = PILN[i];
+            let next = a[j];
+            a[j] = rotl(t, ROTC[i]);
+            t = next;
+        }
+
+        // Chi
+        for y in range(0u, 5) {
+            let mut row = [0u64, ..5];
+            for x in range(0u, 5) {
+                row[x] = a[y * 5 + x];
+            }
+            for x in range(0u, 5) {
+                a[y * 5 + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota
+        a[0] ^= RC[round];
+    }
+}
+
+// The sponge construction shared by all of the functions in this module.
+struct Keccak {
+    state: [u64, ..25],
+    // The number of bytes absorbed or squeezed per permutation.
+    rate: uint,
+    // The position within the current block.
+    pos: uint,
+    // The domain separation bits that start the padding.
+    suffix: u8,
+    squeezing: bool,
+}
+
+impl Keccak {
+    fn new(rate: uint, suffix: u8) -> Keccak {
+        Keccak {
+            state: [0u64, ..25],
+            rate: rate,
+            pos: 0,
+            suffix: suffix,
+            squeezing: false
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = [0u64, ..25];
+        self.pos = 0;
+        self.squeezing = false;
+    }
+
+    fn absorb(&mut self, input: &[u8]) {
+        assert!(!self.squeezing);
+        let lanes = self.rate / 8;
+        let mut i = 0;
+        while i < input.len() {
+            if self.pos == 0 && input.len() - i >= self.rate {
+                // A whole block can be xored in a lane at a time.
+                let mut block = [0u64, ..MAX_RATE_LANES];
+                read_u64v_le(block.mut_slice_to(lanes), input.slice(i, i + self.rate));
+                for j in range(0u, lanes) {
+                    self.state[j] ^= block[j];
+                }
+                keccak_f(&mut self.state);
+                i += self.rate;
+            } else {
+                self.state[self.pos / 8] ^= (input[i] as u64) << (8 * (self.pos % 8));
+                self.pos += 1;
+                i += 1;
+                if self.pos == self.rate {
+                    keccak_f(&mut self.state);
+                    self.pos = 0;
+                }
+            }
+        }
+    }
+
+    // Pads the input and switches to squeezing, if that hasn't happened yet.
+    fn finish(&mut self) {
+        if self.squeezing {
+            return;
+        }
+        let last = self.rate - 1;
+        self.state[self.pos / 8] ^= (self.suffix as u64) << (8 * (self.pos % 8));
+        self.state[last / 8] ^= 0x80u64 << (8 * (last % 8));
+        keccak_f(&mut self.state);
+        self.pos = 0;
+        self.squeezing = true;
+    }
+
+    fn squeeze(&mut self, out: &mut [u8]) {
+        self.finish();
+        for i in range(0u, out.len()) {
+            if self.pos == self.rate {
+                keccak_f(&mut self.state);
+                self.pos = 0;
+            }
+            out[i] = (self.state[self.pos / 8] >> (8 * (self.pos % 8))) as u8;
+            self.pos += 1;
+        }
+    }
+}
+
+macro_rules! impl_sha3( ($name:ident, $bits:expr) => (
+    impl $name {
+        /// Construct a new instance of the hash
+        pub fn new() -> $name {
+            $name { engine: Keccak::new(200 - 2 * $bits / 8, 0x06) }
+        }
+    }
+
+    impl Digest for $name {
+        fn input(&mut self, d: &[u8]) {
+            self.engine.absorb(d);
+        }
+
+        fn result(&mut self, out: &mut [u8]) {
+            // The digest is shorter than the rate, so it is always the
+            // start of the state and reading it doesn't change anything.
+            self.engine.finish();
+            let mut engine = self.engine;
+            engine.squeeze(out.mut_slice_to($bits / 8));
+        }
+
+        fn reset(&mut self) {
+            self.engine.reset();
+        }
+
+        fn output_bits(&self) -> uint { $bits }
+    }
+))
+
+/// The SHA3-224 hash algorithm
+#[allow(non_camel_case_types)]
+pub struct Sha3_224 {
+    priv engine: Keccak
+}
+
+impl_sha3!(Sha3_224, 224)
+
+/// The SHA3-256 hash algorithm
+#[allow(non_camel_case_types)]
+pub struct Sha3_256 {
+    priv engine: Keccak
+}
+
+impl_sha3!(Sha3_256, 256)
+
+/// The SHA3-384 hash algorithm
+#[allow(non_camel_case_types)]
+pub struct Sha3_384 {
+    priv engine: Keccak
+}
+
+impl_sha3!(Sha3_384, 384)
+
+/// The SHA3-512 hash algorithm
+#[allow(non_camel_case_types)]
+pub struct Sha3_512 {
+    priv engine: Keccak
+}
+
+impl_sha3!(Sha3_512, 512)
+
+macro_rules! impl_shake( ($name:ident, $security:expr) => (
+    impl $name {
+        /**
+         * Construct a new instance whose `result` is `output_bits` long.
+         * `squeeze` is not limited by this.
+         */
+        pub fn new(output_bits: uint) -> $name {
+            let engine = Keccak::new(200 - 2 * $security / 8, 0x1f);
+            $name {
+                engine: engine,
+                reader: engine,
+                output_bits: output_bits
+            }
+        }
+
+        /**
+         * Read the next `out.len()` bytes of output. Successive calls
+         * continue where the previous one stopped; `reset` must be called
+         * before supplying more input.
+         */
+        pub fn squeeze(&mut self, out: &mut [u8]) {
+            if !self.reader.squeezing {
+                self.engine.finish();
+                self.reader = self.engine;
+            }
+            self.reader.squeeze(out);
+        }
+    }
+
+    impl Digest for $name {
+        fn input(&mut self, d: &[u8]) {
+            self.engine.absorb(d);
+        }
+
+        fn result(&mut self, out: &mut [u8]) {
+            self.engine.finish();
+            let mut engine = self.engine;
+            engine.squeeze(out.mut_slice_to((self.output_bits + 7) / 8));
+        }
+
+        fn reset(&mut self) {
+            self.engine.reset();
+            self.reader.reset();
+        }
+
+        fn output_bits(&self) -> uint { self.output_bits }
+    }
+))
+
+/// The SHAKE128 extendable-output function
+pub struct Shake128 {
+    priv engine: Keccak,
+    priv reader: Keccak,
+    priv output_bits: uint
+}
+
+impl_shake!(Shake128, 128)
+
+/// The SHAKE256 extendable-output function
+pub struct Shake256 {
+    priv engine: Keccak,
+    priv reader: Keccak,
+    priv output_bits: uint
+}
+
+impl_shake!(Shake256, 256)
+
+
+#[cfg(test)]
+mod tests {
+    use cryptoutil::test::test_digest_1million_random;
+    use digest::Digest;
+    use hex::ToHex;
+    use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256};
+
+    struct Test {
+        input: ~str,
+        output_str: ~str,
+    }
+
+    fn test_hash<D: Digest>(sh: &mut D, tests: &[Test]) {
+        // Test that it works when accepting the message all at once
+        for t in tests.iter() {
+            sh.input_str(t.input);
+
+            let out_str = sh.result_str();
+            assert_eq!(out_str, t.output_str.clone());
+
+            sh.reset();
+        }
+
+        // Test that it works when accepting the message in pieces
+        for t in tests.iter() {
+            let len = t.input.len();
+            let mut left = len;
+            while left > 0u {
+                let take = (left + 1u) / 2u;
+                sh.input_str(t.input.slice(len - left, take + len - left));
+                left = left - take;
+            }
+
+            let out_str = sh.result_str();
+            assert_eq!(out_str, t.output_str.clone());
+
+            sh.reset();
+        }
+    }
+
+    #[test]
+    fn test_sha3_224() {
+        let tests = ~[
+            Test {
+                input: ~"",
+                output_str: ~"6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7"
+            },
+            Test {
+                input: ~"abc",
+                output_str: ~"e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf"
+            },
+        ];
+
+        let mut sh = ~Sha3_224::new();
+
+        test_hash(sh, tests);
+    }
+
+    #[test]
+    fn test_sha3_256() {
+        let tests = ~[
+            Test {
+                input: ~"",
+                output_str: ~"a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+            },
+            Test {
+                input: ~"abc",
+                output_str: ~"3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+            },
+            Test {
+                input: ~"The quick brown fox jumps over the lazy dog",
+                output_str: ~"69070dda01975c8c120c3aada1b282394e7f032fa9cf32f4cb2259a0897dfc04"
+            },
+        ];
+
+        let mut sh = ~Sha3_256::new();
+
+        test_hash(sh, tests);
+    }
+
+    #[test]
+    fn test_sha3_384() {
+        let tests = ~[
+            Test {
+                input: ~"",
+                output_str: ~"0c63a75b845e4f7d01107d852e4c2485c51a50aaaa94fc61995e71bbee983a2a" +
+                             "c3713831264adb47fb6bd1e058d5f004"
+            },
+            Test {
+                input: ~"abc",
+                output_str: ~"ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b2" +
+                             "98d88cea927ac7f539f1edf228376d25"
+            },
+        ];
+
+        let mut sh = ~Sha3_384::new();
+
+        test_hash(sh, tests);
+    }
+
+    #[test]
+    fn test_sha3_512() {
+        let tests = ~[
+            Test {
+                input: ~"",
+                output_str: ~"a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a6" +
+                             "15b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26"
+            },
+            Test {
+                input: ~"abc",
+                output_str: ~"b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e" +
+                             "10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+            },
+        ];
+
+        let mut sh = ~Sha3_512::new();
+
+        test_hash(sh, tests);
+    }
+
+    #[test]
+    fn test_shake128() {
+        let tests = ~[
+            Test {
+                input: ~"",
+                output_str: ~"7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+            },
+            Test {
+                input: ~"The quick brown fox jumps over the lazy dog",
+                output_str: ~"f4202e3c5852f9182a0430fd8144f0a74b95e7417ecae17db0f8cfeed0e3e66e"
+            },
+        ];
+
+        let mut sh = ~Shake128::new(256);
+
+        test_hash(sh, tests);
+    }
+
+    #[test]
+    fn test_shake256() {
+        let tests = ~[
+            Test {
+                input: ~"",
+                output_str: ~"46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f" +
+                             "d75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be"
+            },
+        ];
+
+        let mut sh = ~Shake256::new(512);
+
+        test_hash(sh, tests);
+    }
+
+    #[test]
+    fn test_shake_squeeze() {
+        // Reading the output in pieces, across the 168 byte rate, gives
+        // the same stream as reading it all at once.
+        let mut sh = Shake128::new(400 * 8);
+        sh.input_str("abc");
+        let expected = sh.result_bytes();
+
+        let mut out = ~[];
+        for &n in [1u, 7, 160, 32, 200].iter() {
+            let mut buf = ::std::vec::from_elem(n, 0u8);
+            sh.squeeze(buf);
+            out.push_all(buf);
+        }
+        assert_eq!(out.to_hex(), expected.to_hex());
+
+        sh.reset();
+        sh.input_str("abc");
+        assert_eq!(sh.result_bytes(), expected);
+    }
+
+    #[test]
+    fn test_1million_random_sha3_256() {
+        let mut sh = Sha3_256::new();
+        test_digest_1million_random(
+            &mut sh,
+            136,
+            "5c8875ae474a3634ba4fd55ec85bffd661f32aca75c6d699d0cdcb6c115891c1");
+    }
+}
+
+#[cfg(test)]
+mod bench {
+
+    use digest::Digest;
+    use sha3::{Sha3_256, Sha3_512};
+    use test::BenchHarness;
+
+    #[bench]
+    pub fn sha3_256_10(bh: & mut BenchHarness) {
+        let mut sh = Sha3_256::new();
+        let bytes = [1u8, ..10];
+        do bh.iter {
+            sh.input(bytes);
+        }
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn sha3_256_1k(bh: & mut BenchHarness) {
+        let mut sh = Sha3_256::new();
+        let bytes = [1u8, ..1024];
+        do bh.iter {
+            sh.input(bytes);
+        }
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn sha3_256_64k(bh: & mut BenchHarness) {
+        let mut sh = Sha3_256::new();
+        let bytes = [1u8, ..65536];
+        do bh.iter {
+            sh.input(bytes);
+        }
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn sha3_512_1k(bh: & mut BenchHarness) {
+        let mut sh = Sha3_512::new();
+        let bytes = [1u8, ..1024];
+        do bh.iter {
+            sh.input(bytes);
+        }
+        bh.bytes = bytes.len() as u64;
+    }
+}
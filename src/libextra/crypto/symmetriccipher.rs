@@ -0,0 +1,150 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Common functionality related to symmetric ciphers
+
+use std::rt::io::{Decorator, Writer};
+use std::vec;
+
+
+/**
+ * A block cipher in the encrypting direction, such as AES. On its own a block cipher only
+ * transforms single blocks; use one of the modes in `blockmodes` to encrypt messages.
+ */
+pub trait BlockEncryptor {
+    /**
+     * Get the size in bytes of the blocks the cipher processes.
+     */
+    fn block_size(&self) -> uint;
+
+    /**
+     * Encrypt a single block.
+     *
+     * # Arguments
+     *
+     * * input - The plaintext block. Must be exactly block_size() bytes long.
+     * * output - The vector to hold the ciphertext. Must be exactly block_size() bytes long.
+     */
+    fn encrypt_block(&self, input: &[u8], output: &mut [u8]);
+}
+
+/**
+ * A block cipher in the decrypting direction.
+ */
+pub trait BlockDecryptor {
+    /**
+     * Get the size in bytes of the blocks the cipher processes.
+     */
+    fn block_size(&self) -> uint;
+
+    /**
+     * Decrypt a single block.
+     *
+     * # Arguments
+     *
+     * * input - The ciphertext block. Must be exactly block_size() bytes long.
+     * * output - The vector to hold the plaintext. Must be exactly block_size() bytes long.
+     */
+    fn decrypt_block(&self, input: &[u8], output: &mut [u8]);
+}
+
+/**
+ * A cipher that XORs its input with a keystream, such as ChaCha20 or a block cipher in CTR
+ * mode. Encryption and decryption are the same operation, and the input may be fed in pieces
+ * of any size.
+ */
+pub trait SynchronousStreamCipher {
+    /**
+     * Encrypt or decrypt the next part of the message.
+     *
+     * # Arguments
+     *
+     * * input - The data to process
+     * * output - The vector to hold the result. Must be as long as the input.
+     */
+    fn process(&mut self, input: &[u8], output: &mut [u8]);
+}
+
+/**
+ * A Writer that passes everything written to it through a stream cipher before handing it to
+ * the inner Writer.
+ */
+pub struct CipherWriter<C, W> {
+    priv cipher: C,
+    priv inner: W,
+    priv buf: ~[u8]
+}
+
+impl<C: SynchronousStreamCipher, W: Writer> CipherWriter<C, W> {
+    /// Creates a new `CipherWriter` around `inner`
+    pub fn new(cipher: C, inner: W) -> CipherWriter<C, W> {
+        CipherWriter {
+            cipher: cipher,
+            inner: inner,
+            buf: ~[]
+        }
+    }
+}
+
+impl<C: SynchronousStreamCipher, W: Writer> Writer for CipherWriter<C, W> {
+    fn write(&mut self, buf: &[u8]) {
+        if self.buf.len() < buf.len() {
+            self.buf = vec::from_elem(buf.len(), 0u8);
+        }
+        let out = self.buf.mut_slice_to(buf.len());
+        self.cipher.process(buf, out);
+        self.inner.write(out);
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+    }
+}
+
+impl<C: SynchronousStreamCipher, W: Writer> Decorator<W> for CipherWriter<C, W> {
+    fn inner(self) -> W {
+        self.inner
+    }
+
+    fn inner_ref<'a>(&'a self) -> &'a W {
+        &self.inner
+    }
+
+    fn inner_mut_ref<'a>(&'a mut self) -> &'a mut W {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rt::io::{Decorator, Writer};
+    use std::rt::io::mem::MemWriter;
+
+    use chacha20::ChaCha20;
+    use symmetriccipher::{CipherWriter, SynchronousStreamCipher};
+
+    #[test]
+    fn test_cipher_writer() {
+        let key = [3u8, ..32];
+        let nonce = [5u8, ..8];
+        let data = [0x42u8, ..200];
+
+        let mut expected = [0u8, ..200];
+        let mut cipher = ChaCha20::new(key, nonce);
+        cipher.process(data, expected);
+
+        let mut writer = CipherWriter::new(ChaCha20::new(key, nonce), MemWriter::new());
+        for chunk in data.chunk_iter(33) {
+            writer.write(chunk);
+        }
+        writer.flush();
+        assert_eq!(writer.inner().inner(), expected.to_owned());
+    }
+}
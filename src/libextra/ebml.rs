@@ -10,11 +10,16 @@
 
 #[allow(missing_doc)];
 
+use std::hashmap::HashMap;
 use std::str;
 
 // Simple Extensible Binary Markup Language (ebml) reader and writer on a
 // cursor model. See the specification here:
 //     http://www.matroska.org/technical/specs/rfc/index.html
+//
+// Documents are read in place from a borrowed byte slice, so a reader can
+// work over any buffer the caller owns, including a mapped file or one
+// shared between tasks.
 
 // Common data structures
 struct EbmlTag {
@@ -29,18 +34,24 @@ struct EbmlState {
 }
 
 #[deriving(Clone)]
-pub struct Doc {
-    data: @~[u8],
+pub struct Doc<'self> {
+    data: &'self [u8],
     start: uint,
     end: uint,
 }
 
-impl Doc {
-    pub fn get(&self, tag: uint) -> Doc {
+impl<'self> Doc<'self> {
+    /// A document spanning all of `data`. `reader::Doc` does the same for
+    /// a vector, so that callers holding an `@~[u8]` can pass it directly.
+    pub fn new<'a>(data: &'a [u8]) -> Doc<'a> {
+        Doc { data: data, start: 0u, end: data.len() }
+    }
+
+    pub fn get(&self, tag: uint) -> Doc<'self> {
         reader::get_doc(*self, tag)
     }
 
-    pub fn as_str_slice<'a>(&'a self) -> &'a str {
+    pub fn as_str_slice(&self) -> &'self str {
         str::from_utf8_slice(self.data.slice(self.start, self.end))
     }
 
@@ -49,9 +60,9 @@ impl Doc {
     }
 }
 
-pub struct TaggedDoc {
+pub struct TaggedDoc<'self> {
     priv tag: uint,
-    doc: Doc,
+    doc: Doc<'self>,
 }
 
 pub enum EbmlEncoderTag {
@@ -86,6 +97,80 @@ pub enum EbmlEncoderTag {
 
     EsLabel, // Used only when debugging
 }
+
+/// Describes which tags may appear where in a document.
+///
+/// A checked `writer::Encoder` fails as soon as it is asked to write a tag
+/// the schema does not allow at that point, and `reader::dump_with` uses
+/// the schema to name tags and to tell nested elements from leaf data.
+#[deriving(Clone)]
+pub struct Schema {
+    priv roots: ~[uint],
+    priv children: HashMap<uint, ~[uint]>,
+    priv names: HashMap<uint, ~str>,
+}
+
+impl Schema {
+    /// An empty schema. Any tag may appear at the top level until one is
+    /// registered with `root`, and no tag may contain other tags until it
+    /// is given children with `allow`.
+    pub fn new() -> Schema {
+        Schema { roots: ~[], children: HashMap::new(), names: HashMap::new() }
+    }
+
+    /// Allows `tag` at the top level of a document.
+    pub fn root(&mut self, tag: uint) {
+        if !self.roots.contains(&tag) {
+            self.roots.push(tag);
+        }
+    }
+
+    /// Allows `child` directly inside `parent`.
+    pub fn allow(&mut self, parent: uint, child: uint) {
+        let kids = self.children.find_or_insert_with(parent, |_| ~[]);
+        if !kids.contains(&child) {
+            kids.push(child);
+        }
+    }
+
+    /// Gives `tag` a name for error messages and dumps.
+    pub fn name(&mut self, tag: uint, name: &str) {
+        self.names.insert(tag, name.to_owned());
+    }
+
+    /// Whether `child` may appear inside `parent`, or at the top level of
+    /// the document if `parent` is `None`.
+    pub fn allows(&self, parent: Option<uint>, child: uint) -> bool {
+        match parent {
+            None => self.roots.is_empty() || self.roots.contains(&child),
+            Some(parent) => match self.children.find(&parent) {
+                Some(kids) => kids.contains(&child),
+                None => false
+            }
+        }
+    }
+
+    /// `Some(true)` if `tag` contains other elements, `Some(false)` if the
+    /// schema knows it as leaf data, and `None` if it does not know it.
+    pub fn is_master(&self, tag: uint) -> Option<bool> {
+        if self.children.contains_key(&tag) {
+            Some(true)
+        } else if self.roots.contains(&tag) || self.names.contains_key(&tag) ||
+                  self.children.iter().any(|(_, kids)| kids.contains(&tag)) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// The tag's registered name, or its number.
+    pub fn describe(&self, tag: uint) -> ~str {
+        match self.names.find(&tag) {
+            Some(name) => format!("{} ({:#x})", *name, tag),
+            None => format!("{:#x}", tag)
+        }
+    }
+}
 // --------------------------------------
 
 pub mod reader {
@@ -98,6 +183,8 @@ pub mod reader {
     use std::int;
     use std::io;
     use std::option::{None, Option, Some};
+    use std::str;
+    use std::vec;
 
     #[cfg(target_arch = "x86")]
     #[cfg(target_arch = "x86_64")]
@@ -184,13 +271,31 @@ pub mod reader {
         vuint_at_slow(data, start)
     }
 
-    pub fn Doc(data: @~[u8]) -> Doc {
-        Doc { data: data, start: 0u, end: data.len() }
+    // Like `vuint_at`, but returns `None` for a truncated or invalid
+    // encoding instead of failing.
+    fn checked_vuint_at(data: &[u8], start: uint) -> Option<Res> {
+        if start >= data.len() {
+            return None;
+        }
+        let a = data[start];
+        let len = if a & 0x80u8 != 0u8 { 1u }
+                  else if a & 0x40u8 != 0u8 { 2u }
+                  else if a & 0x20u8 != 0u8 { 3u }
+                  else if a & 0x10u8 != 0u8 { 4u }
+                  else { return None };
+        if data.len() - start < len {
+            return None;
+        }
+        Some(vuint_at_slow(data, start))
     }
 
-    pub fn doc_at(data: @~[u8], start: uint) -> TaggedDoc {
-        let elt_tag = vuint_at(*data, start);
-        let elt_size = vuint_at(*data, elt_tag.next);
+    pub fn Doc<'a>(data: &'a ~[u8]) -> Doc<'a> {
+        Doc::new(*data)
+    }
+
+    pub fn doc_at<'a>(data: &'a [u8], start: uint) -> TaggedDoc<'a> {
+        let elt_tag = vuint_at(data, start);
+        let elt_size = vuint_at(data, elt_tag.next);
         let end = elt_size.next + elt_size.val;
         TaggedDoc {
             tag: elt_tag.val,
@@ -198,11 +303,34 @@ pub mod reader {
         }
     }
 
-    pub fn maybe_get_doc(d: Doc, tg: uint) -> Option<Doc> {
+    // The element starting at `start`, if it is well formed and ends no
+    // later than `end`.
+    fn checked_doc_at<'a>(data: &'a [u8], start: uint, end: uint)
+                          -> Option<TaggedDoc<'a>> {
+        let bounded = data.slice_to(end);
+        let elt_tag = match checked_vuint_at(bounded, start) {
+            Some(r) => r,
+            None => return None
+        };
+        let elt_size = match checked_vuint_at(bounded, elt_tag.next) {
+            Some(r) => r,
+            None => return None
+        };
+        if end - elt_size.next < elt_size.val {
+            return None;
+        }
+        Some(TaggedDoc {
+            tag: elt_tag.val,
+            doc: Doc { data: data, start: elt_size.next,
+                       end: elt_size.next + elt_size.val }
+        })
+    }
+
+    pub fn maybe_get_doc<'a>(d: Doc<'a>, tg: uint) -> Option<Doc<'a>> {
         let mut pos = d.start;
         while pos < d.end {
-            let elt_tag = vuint_at(*d.data, pos);
-            let elt_size = vuint_at(*d.data, elt_tag.next);
+            let elt_tag = vuint_at(d.data, pos);
+            let elt_size = vuint_at(d.data, elt_tag.next);
             pos = elt_size.next + elt_size.val;
             if elt_tag.val == tg {
                 return Some(Doc { data: d.data, start: elt_size.next,
@@ -212,7 +340,7 @@ pub mod reader {
         None
     }
 
-    pub fn get_doc(d: Doc, tg: uint) -> Doc {
+    pub fn get_doc<'a>(d: Doc<'a>, tg: uint) -> Doc<'a> {
         match maybe_get_doc(d, tg) {
             Some(d) => d,
             None => {
@@ -222,11 +350,11 @@ pub mod reader {
         }
     }
 
-    pub fn docs(d: Doc, it: &fn(uint, Doc) -> bool) -> bool {
+    pub fn docs<'a>(d: Doc<'a>, it: &fn(uint, Doc<'a>) -> bool) -> bool {
         let mut pos = d.start;
         while pos < d.end {
-            let elt_tag = vuint_at(*d.data, pos);
-            let elt_size = vuint_at(*d.data, elt_tag.next);
+            let elt_tag = vuint_at(d.data, pos);
+            let elt_size = vuint_at(d.data, elt_tag.next);
             pos = elt_size.next + elt_size.val;
             let doc = Doc { data: d.data, start: elt_size.next, end: pos };
             if !it(elt_tag.val, doc) {
@@ -236,11 +364,11 @@ pub mod reader {
         return true;
     }
 
-    pub fn tagged_docs(d: Doc, tg: uint, it: &fn(Doc) -> bool) -> bool {
+    pub fn tagged_docs<'a>(d: Doc<'a>, tg: uint, it: &fn(Doc<'a>) -> bool) -> bool {
         let mut pos = d.start;
         while pos < d.end {
-            let elt_tag = vuint_at(*d.data, pos);
-            let elt_size = vuint_at(*d.data, elt_tag.next);
+            let elt_tag = vuint_at(d.data, pos);
+            let elt_size = vuint_at(d.data, elt_tag.next);
             pos = elt_size.next + elt_size.val;
             if elt_tag.val == tg {
                 let doc = Doc { data: d.data, start: elt_size.next,
@@ -253,29 +381,28 @@ pub mod reader {
         return true;
     }
 
-    pub fn with_doc_data<T>(d: Doc, f: &fn(x: &[u8]) -> T) -> T {
+    pub fn with_doc_data<'a, T>(d: Doc<'a>, f: &fn(x: &'a [u8]) -> T) -> T {
         f(d.data.slice(d.start, d.end))
     }
 
-
     pub fn doc_as_u8(d: Doc) -> u8 {
         assert_eq!(d.end, d.start + 1u);
-        (*d.data)[d.start]
+        d.data[d.start]
     }
 
     pub fn doc_as_u16(d: Doc) -> u16 {
         assert_eq!(d.end, d.start + 2u);
-        io::u64_from_be_bytes(*d.data, d.start, 2u) as u16
+        io::u64_from_be_bytes(d.data, d.start, 2u) as u16
     }
 
     pub fn doc_as_u32(d: Doc) -> u32 {
         assert_eq!(d.end, d.start + 4u);
-        io::u64_from_be_bytes(*d.data, d.start, 4u) as u32
+        io::u64_from_be_bytes(d.data, d.start, 4u) as u32
     }
 
     pub fn doc_as_u64(d: Doc) -> u64 {
         assert_eq!(d.end, d.start + 8u);
-        io::u64_from_be_bytes(*d.data, d.start, 8u)
+        io::u64_from_be_bytes(d.data, d.start, 8u)
     }
 
     pub fn doc_as_i8(d: Doc) -> i8 { doc_as_u8(d) as i8 }
@@ -283,41 +410,101 @@ pub mod reader {
     pub fn doc_as_i32(d: Doc) -> i32 { doc_as_u32(d) as i32 }
     pub fn doc_as_i64(d: Doc) -> i64 { doc_as_u64(d) as i64 }
 
+    /// Decodes values from an EBML document.
+    ///
+    /// Malformed documents are reported on `serialize::decode_error`; the
+    /// path of the error names the struct fields and element indices
+    /// leading to the bad value.
+    ///
+    /// A `Decoder` keeps its own copy of the document it was made from,
+    /// so, unlike a `Doc`, it doesn't borrow the buffer and can be named in
+    /// a `Decodable<Decoder>` bound without a lifetime.
     pub struct Decoder {
-        priv parent: Doc,
+        priv parent: SharedDoc,
         priv pos: uint,
+        priv path: ~[~str],
     }
 
     pub fn Decoder(d: Doc) -> Decoder {
+        let data = @d.data.slice(d.start, d.end).to_owned();
         Decoder {
-            parent: d,
-            pos: d.start
+            parent: SharedDoc { data: data, start: 0, end: data.len() },
+            pos: 0,
+            path: ~[],
+        }
+    }
+
+    // A `Doc` over bytes the decoder owns a share of.
+    struct SharedDoc {
+        data: @~[u8],
+        start: uint,
+        end: uint,
+    }
+
+    impl SharedDoc {
+        fn borrow<'a>(&'a self) -> Doc<'a> {
+            Doc { data: self.data.as_slice(), start: self.start, end: self.end }
         }
     }
 
+    // A zeroed document of the right size for `tag`, decoded in place of
+    // a missing or malformed one after an error has been handled.
+    fn placeholder_doc(tag: EbmlEncoderTag) -> SharedDoc {
+        let size = match tag {
+            EsUint | EsU64 | EsInt | EsI64 | EsF64 => 8,
+            EsU32 | EsI32 | EsF32 | EsChar | EsEnumVid | EsVecLen | EsMapLen => 4,
+            EsU16 | EsI16 => 2,
+            EsU8 | EsI8 | EsBool => 1,
+            _ => 0
+        };
+        SharedDoc { data: @vec::from_elem(size, 0u8), start: 0, end: size }
+    }
+
     impl Decoder {
+        fn error(&self, kind: serialize::DecoderError) {
+            let mut path = self.path.concat();
+            if path.starts_with(".") {
+                path = path.slice_from(1).to_owned();
+            }
+            serialize::decode_error::cond.raise(serialize::DecodeError {
+                path: path,
+                kind: kind,
+            })
+        }
+
+        fn with_path<T>(&mut self, elem: ~str, f: &fn(&mut Decoder) -> T) -> T {
+            self.path.push(elem);
+            let value = f(self);
+            self.path.pop();
+            value
+        }
+
         fn _check_label(&mut self, lbl: &str) {
             if self.pos < self.parent.end {
-                let TaggedDoc { tag: r_tag, doc: r_doc } =
-                    doc_at(self.parent.data, self.pos);
+                let data = self.parent.data;
+                let TaggedDoc { tag: r_tag, doc: r_doc } = doc_at(*data, self.pos);
 
                 if r_tag == (EsLabel as uint) {
                     self.pos = r_doc.end;
                     let str = r_doc.as_str_slice();
                     if lbl != str {
-                        fail!("Expected label {} but found {}", lbl, str);
+                        self.error(serialize::ExpectedError(
+                            format!("label {}", lbl), format!("label {}", str)));
                     }
                 }
             }
         }
 
-        fn next_doc(&mut self, exp_tag: EbmlEncoderTag) -> Doc {
+        fn next_doc(&mut self, exp_tag: EbmlEncoderTag) -> SharedDoc {
             debug!(". next_doc(exp_tag={:?})", exp_tag);
             if self.pos >= self.parent.end {
-                fail!("no more documents in current node!");
+                self.error(serialize::ExpectedError(
+                    format!("EBML doc with tag {:?}", exp_tag),
+                    ~"the end of the current node"));
+                return placeholder_doc(exp_tag);
             }
-            let TaggedDoc { tag: r_tag, doc: r_doc } =
-                doc_at(self.parent.data, self.pos);
+            let data = self.parent.data;
+            let TaggedDoc { tag: r_tag, doc: r_doc } = doc_at(*data, self.pos);
             debug!("self.parent={}-{} self.pos={} r_tag={} r_doc={}-{}",
                    self.parent.start,
                    self.parent.end,
@@ -325,16 +512,33 @@ pub mod reader {
                    r_tag,
                    r_doc.start,
                    r_doc.end);
-            if r_tag != (exp_tag as uint) {
-                fail!("expected EBML doc with tag {:?} but found tag {:?}",
-                       exp_tag, r_tag);
-            }
             if r_doc.end > self.parent.end {
-                fail!("invalid EBML, child extends to {:#x}, parent to {:#x}",
-                      r_doc.end, self.parent.end);
+                self.error(serialize::SyntaxError(
+                    format!("invalid EBML, child extends to {:#x}, parent to {:#x}",
+                            r_doc.end, self.parent.end)));
+                self.pos = self.parent.end;
+                return placeholder_doc(exp_tag);
             }
             self.pos = r_doc.end;
-            r_doc
+            if r_tag != (exp_tag as uint) {
+                self.error(serialize::ExpectedError(
+                    format!("EBML doc with tag {:?}", exp_tag),
+                    format!("tag {} at {:#x}", r_tag, r_doc.start)));
+                return placeholder_doc(exp_tag);
+            }
+            SharedDoc { data: data, start: r_doc.start, end: r_doc.end }
+        }
+
+        // Checks a variant index read from the stream against the names
+        // the caller knows about.
+        fn check_variant(&self, names: &[&str], idx: uint) -> uint {
+            if idx < names.len() {
+                idx
+            } else {
+                self.error(serialize::UnknownVariantError(
+                    format!("variant index {}", idx)));
+                0
+            }
         }
 
         fn push_doc<T>(&mut self, exp_tag: EbmlEncoderTag,
@@ -351,7 +555,7 @@ pub mod reader {
         }
 
         fn _next_uint(&mut self, exp_tag: EbmlEncoderTag) -> uint {
-            let r = doc_as_u32(self.next_doc(exp_tag));
+            let r = doc_as_u32(self.next_doc(exp_tag).borrow());
             debug!("_next_uint exp_tag={:?} result={}", exp_tag, r);
             r as uint
         }
@@ -366,7 +570,7 @@ pub mod reader {
             self.parent = doc;
             self.pos = doc.start;
 
-            let result = op(self, doc);
+            let result = op(self, doc.borrow());
 
             self.parent = old_parent;
             self.pos = old_pos;
@@ -377,56 +581,68 @@ pub mod reader {
     impl serialize::Decoder for Decoder {
         fn read_nil(&mut self) -> () { () }
 
-        fn read_u64(&mut self) -> u64 { doc_as_u64(self.next_doc(EsU64)) }
-        fn read_u32(&mut self) -> u32 { doc_as_u32(self.next_doc(EsU32)) }
-        fn read_u16(&mut self) -> u16 { doc_as_u16(self.next_doc(EsU16)) }
-        fn read_u8 (&mut self) -> u8  { doc_as_u8 (self.next_doc(EsU8 )) }
+        fn read_u64(&mut self) -> u64 { doc_as_u64(self.next_doc(EsU64).borrow()) }
+        fn read_u32(&mut self) -> u32 { doc_as_u32(self.next_doc(EsU32).borrow()) }
+        fn read_u16(&mut self) -> u16 { doc_as_u16(self.next_doc(EsU16).borrow()) }
+        fn read_u8 (&mut self) -> u8  { doc_as_u8 (self.next_doc(EsU8 ).borrow()) }
         fn read_uint(&mut self) -> uint {
-            let v = doc_as_u64(self.next_doc(EsUint));
+            let v = doc_as_u64(self.next_doc(EsUint).borrow());
             if v > (::std::uint::max_value as u64) {
-                fail!("uint {} too large for this architecture", v);
+                self.error(serialize::SyntaxError(
+                    format!("uint {} too large for this architecture", v)));
+                return 0;
             }
             v as uint
         }
 
         fn read_i64(&mut self) -> i64 {
-            doc_as_u64(self.next_doc(EsI64)) as i64
+            doc_as_u64(self.next_doc(EsI64).borrow()) as i64
         }
         fn read_i32(&mut self) -> i32 {
-            doc_as_u32(self.next_doc(EsI32)) as i32
+            doc_as_u32(self.next_doc(EsI32).borrow()) as i32
         }
         fn read_i16(&mut self) -> i16 {
-            doc_as_u16(self.next_doc(EsI16)) as i16
+            doc_as_u16(self.next_doc(EsI16).borrow()) as i16
         }
         fn read_i8 (&mut self) -> i8 {
-            doc_as_u8(self.next_doc(EsI8 )) as i8
+            doc_as_u8(self.next_doc(EsI8 ).borrow()) as i8
         }
         fn read_int(&mut self) -> int {
-            let v = doc_as_u64(self.next_doc(EsInt)) as i64;
+            let v = doc_as_u64(self.next_doc(EsInt).borrow()) as i64;
             if v > (int::max_value as i64) || v < (int::min_value as i64) {
                 debug!("FIXME \\#6122: Removing this makes this function miscompile");
-                fail!("int {} out of range for this architecture", v);
+                self.error(serialize::SyntaxError(
+                    format!("int {} out of range for this architecture", v)));
+                return 0;
             }
             v as int
         }
 
         fn read_bool(&mut self) -> bool {
-            doc_as_u8(self.next_doc(EsBool)) != 0
+            doc_as_u8(self.next_doc(EsBool).borrow()) != 0
         }
 
         fn read_f64(&mut self) -> f64 {
-            let bits = doc_as_u64(self.next_doc(EsF64));
+            let bits = doc_as_u64(self.next_doc(EsF64).borrow());
             unsafe { transmute(bits) }
         }
         fn read_f32(&mut self) -> f32 {
-            let bits = doc_as_u32(self.next_doc(EsF32));
+            let bits = doc_as_u32(self.next_doc(EsF32).borrow());
             unsafe { transmute(bits) }
         }
         fn read_char(&mut self) -> char {
-            char::from_u32(doc_as_u32(self.next_doc(EsChar))).unwrap()
+            let v = doc_as_u32(self.next_doc(EsChar).borrow());
+            match char::from_u32(v) {
+                Some(c) => c,
+                None => {
+                    self.error(serialize::SyntaxError(
+                        format!("invalid char {:#x}", v)));
+                    '\x00'
+                }
+            }
         }
         fn read_str(&mut self) -> ~str {
-            self.next_doc(EsStr).as_str()
+            self.next_doc(EsStr).borrow().as_str()
         }
 
         // Compound types:
@@ -451,12 +667,13 @@ pub mod reader {
         }
 
         fn read_enum_variant<T>(&mut self,
-                                _: &[&str],
+                                names: &[&str],
                                 f: &fn(&mut Decoder, uint) -> T)
                                 -> T {
             debug!("read_enum_variant()");
             let idx = self._next_uint(EsEnumVid);
             debug!("  idx={}", idx);
+            let idx = self.check_variant(names, idx);
 
             let doc = self.next_doc(EsEnumBody);
 
@@ -475,16 +692,17 @@ pub mod reader {
                                     idx: uint,
                                     f: &fn(&mut Decoder) -> T) -> T {
             debug!("read_enum_variant_arg(idx={})", idx);
-            f(self)
+            self.with_path(format!("[{}]", idx), f)
         }
 
         fn read_enum_struct_variant<T>(&mut self,
-                                       _: &[&str],
+                                       names: &[&str],
                                        f: &fn(&mut Decoder, uint) -> T)
                                        -> T {
             debug!("read_enum_struct_variant()");
             let idx = self._next_uint(EsEnumVid);
             debug!("  idx={}", idx);
+            let idx = self.check_variant(names, idx);
 
             let doc = self.next_doc(EsEnumBody);
 
@@ -505,7 +723,7 @@ pub mod reader {
                                              f: &fn(&mut Decoder) -> T)
                                              -> T {
             debug!("read_enum_struct_variant_arg(name={}, idx={})", name, idx);
-            f(self)
+            self.with_path(format!(".{}", name), f)
         }
 
         fn read_struct<T>(&mut self,
@@ -524,7 +742,7 @@ pub mod reader {
                                 -> T {
             debug!("read_struct_field(name={}, idx={})", name, idx);
             self._check_label(name);
-            f(self)
+            self.with_path(format!(".{}", name), f)
         }
 
         fn read_tuple<T>(&mut self, f: &fn(&mut Decoder, uint) -> T) -> T {
@@ -560,8 +778,7 @@ pub mod reader {
                 do this.read_enum_variant(["None", "Some"]) |this, idx| {
                     match idx {
                         0 => f(this, false),
-                        1 => f(this, true),
-                        _ => fail!(),
+                        _ => f(this, true),
                     }
                 }
             }
@@ -579,7 +796,7 @@ pub mod reader {
         fn read_seq_elt<T>(&mut self, idx: uint, f: &fn(&mut Decoder) -> T)
                            -> T {
             debug!("read_seq_elt(idx={})", idx);
-            self.push_doc(EsVecElt, f)
+            do self.with_path(format!("[{}]", idx)) |d| { d.push_doc(EsVecElt, f) }
         }
 
         fn read_map<T>(&mut self, f: &fn(&mut Decoder, uint) -> T) -> T {
@@ -604,9 +821,88 @@ pub mod reader {
                                f: &fn(&mut Decoder) -> T)
                                -> T {
             debug!("read_map_elt_val(idx={})", idx);
-            self.push_doc(EsMapVal, f)
+            do self.with_path(format!("[{}]", idx)) |d| { d.push_doc(EsMapVal, f) }
         }
     }
+
+    /// Renders an EBML blob as an indented tree, one element per line.
+    ///
+    /// Elements whose contents parse as a sequence of elements are shown
+    /// with their children; anything else is shown as leaf data. Malformed
+    /// input is reported in the output rather than causing a failure.
+    pub fn dump(data: &[u8]) -> ~str {
+        dump_with(data, &Schema::new())
+    }
+
+    /// Like `dump`, but takes tag names and nesting from `schema` for the
+    /// tags it knows about.
+    pub fn dump_with(data: &[u8], schema: &Schema) -> ~str {
+        let mut out = ~"";
+        dump_elements(data, 0u, data.len(), 0u, schema, &mut out);
+        out
+    }
+
+    // Whether `start..end` holds nothing but well formed elements.
+    fn holds_elements(data: &[u8], start: uint, end: uint) -> bool {
+        let mut pos = start;
+        while pos < end {
+            match checked_doc_at(data, pos, end) {
+                Some(elt) => pos = elt.doc.end,
+                None => return false
+            }
+        }
+        start < end
+    }
+
+    fn dump_elements(data: &[u8], start: uint, end: uint, depth: uint,
+                     schema: &Schema, out: &mut ~str) {
+        let indent = "  ".repeat(depth);
+        let mut pos = start;
+        while pos < end {
+            let TaggedDoc { tag, doc } = match checked_doc_at(data, pos, end) {
+                Some(elt) => elt,
+                None => {
+                    out.push_str(format!("{}malformed element at {:#x}\n",
+                                         indent, pos));
+                    return;
+                }
+            };
+            let size = doc.end - doc.start;
+            out.push_str(format!("{}{} [{} byte{}]", indent, schema.describe(tag),
+                                 size, if size == 1 { "" } else { "s" }));
+            let master = match schema.is_master(tag) {
+                Some(master) => master,
+                None => holds_elements(data, doc.start, doc.end)
+            };
+            if master {
+                out.push_str("\n");
+                dump_elements(data, doc.start, doc.end, depth + 1, schema, out);
+            } else {
+                out.push_str(describe_bytes(data.slice(doc.start, doc.end)));
+                out.push_str("\n");
+            }
+            pos = doc.end;
+        }
+    }
+
+    // Printable ASCII is shown as a string, anything else as hex bytes.
+    fn describe_bytes(bytes: &[u8]) -> ~str {
+        static MAX_SHOWN: uint = 16;
+        if bytes.is_empty() {
+            return ~"";
+        }
+        if bytes.iter().all(|&b| b >= 0x20u8 && b < 0x7fu8) {
+            return format!(" {:?}", str::from_utf8_slice(bytes));
+        }
+        let mut s = ~"";
+        for &b in bytes.iter().take(MAX_SHOWN) {
+            s.push_str(format!(" {:02x}", b));
+        }
+        if bytes.len() > MAX_SHOWN {
+            s.push_str(" ...");
+        }
+        s
+    }
 }
 
 pub mod writer {
@@ -620,6 +916,10 @@ pub mod writer {
     pub struct Encoder {
         writer: @io::Writer,
         priv size_positions: ~[uint],
+        // The tags of the open elements; only kept when checking.
+        priv open_tags: ~[uint],
+        priv checked: bool,
+        priv schema: Option<Schema>,
     }
 
     impl Clone for Encoder {
@@ -627,10 +927,16 @@ pub mod writer {
             Encoder {
                 writer: self.writer,
                 size_positions: self.size_positions.clone(),
+                open_tags: self.open_tags.clone(),
+                checked: self.checked,
+                schema: self.schema.clone(),
             }
         }
     }
 
+    // The largest value a four-byte vuint can hold.
+    static MAX_VUINT: uint = 0x0fff_ffff;
+
     fn write_sized_vuint(w: @io::Writer, n: uint, size: uint) {
         match size {
             1u => w.write(&[0x80u8 | (n as u8)]),
@@ -655,14 +961,85 @@ pub mod writer {
         let size_positions: ~[uint] = ~[];
         Encoder {
             writer: w,
-            size_positions: size_positions
+            size_positions: size_positions,
+            open_tags: ~[],
+            checked: false,
+            schema: None,
         }
     }
 
     // FIXME (#2741): Provide a function to write the standard ebml header.
     impl Encoder {
+        /// Turns on checking of every write: tag ids and sizes must fit
+        /// their encodings, `end_tag` must match a `start_tag`, and nothing
+        /// may be written over an open element's size. Violations fail the
+        /// task with a description of the problem.
+        pub fn check_writes(&mut self) {
+            self.checked = true;
+        }
+
+        /// Like `check_writes`, but also requires the document to follow
+        /// `schema`.
+        pub fn check_schema(&mut self, schema: Schema) {
+            self.checked = true;
+            self.schema = Some(schema);
+        }
+
+        /// Checks that every element has been closed. Does nothing unless
+        /// checking is on.
+        pub fn finish(&self) {
+            if self.checked && !self.open_tags.is_empty() {
+                fail!("ebml: {} element(s) left open, innermost {}",
+                      self.open_tags.len(), self.describe(*self.open_tags.last()));
+            }
+        }
+
+        fn describe(&self, tag: uint) -> ~str {
+            match self.schema {
+                Some(ref schema) => schema.describe(tag),
+                None => format!("{:#x}", tag)
+            }
+        }
+
+        // Called before writing an element with the given tag.
+        fn check_child(&self, tag_id: uint) {
+            if !self.checked {
+                return;
+            }
+            if tag_id > MAX_VUINT {
+                fail!("ebml: tag id {:#x} does not fit in a vuint", tag_id);
+            }
+            match self.schema {
+                Some(ref schema) => {
+                    let parent = if self.open_tags.is_empty() {
+                        None
+                    } else {
+                        Some(*self.open_tags.last())
+                    };
+                    if !schema.allows(parent, tag_id) {
+                        match parent {
+                            Some(parent) => {
+                                fail!("ebml: {} is not allowed inside {}",
+                                      schema.describe(tag_id),
+                                      schema.describe(parent));
+                            }
+                            None => {
+                                fail!("ebml: {} is not allowed at the top level",
+                                      schema.describe(tag_id));
+                            }
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+
         pub fn start_tag(&mut self, tag_id: uint) {
             debug!("Start tag {}", tag_id);
+            self.check_child(tag_id);
+            if self.checked {
+                self.open_tags.push(tag_id);
+            }
 
             // Write the enum ID:
             write_vuint(self.writer, tag_id);
@@ -674,8 +1051,23 @@ pub mod writer {
         }
 
         pub fn end_tag(&mut self) {
+            if self.checked && self.size_positions.is_empty() {
+                fail!("ebml: end_tag without a matching start_tag");
+            }
             let last_size_pos = self.size_positions.pop();
             let cur_pos = self.writer.tell();
+            if self.checked {
+                let tag = self.open_tags.pop();
+                if cur_pos < last_size_pos + 4u {
+                    fail!("ebml: {} was closed at {:#x}, inside its own size \
+                           field at {:#x}", self.describe(tag), cur_pos,
+                          last_size_pos);
+                }
+                if cur_pos - last_size_pos - 4u > MAX_VUINT {
+                    fail!("ebml: {} is {} bytes, too large for its size field",
+                          self.describe(tag), cur_pos - last_size_pos - 4u);
+                }
+            }
             self.writer.seek(last_size_pos as int, io::SeekSet);
             let size = (cur_pos - last_size_pos - 4u);
             write_sized_vuint(self.writer, size, 4u);
@@ -691,6 +1083,11 @@ pub mod writer {
         }
 
         pub fn wr_tagged_bytes(&mut self, tag_id: uint, b: &[u8]) {
+            self.check_child(tag_id);
+            if self.checked && b.len() > MAX_VUINT {
+                fail!("ebml: {} is {} bytes, too large for its size field",
+                      self.describe(tag_id), b.len());
+            }
             write_vuint(self.writer, tag_id);
             write_vuint(self.writer, b.len());
             self.writer.write(b);
@@ -958,12 +1355,13 @@ pub mod writer {
 
 #[cfg(test)]
 mod tests {
-    use ebml::reader;
-    use ebml::writer;
-    use serialize::Encodable;
+    use arc::Arc;
+    use ebml::{reader, writer, Doc, Schema};
+    use serialize::{Encoder, Encodable};
     use serialize;
 
     use std::io;
+    use std::task;
     use std::option::{None, Option, Some};
 
     #[test]
@@ -985,4 +1383,139 @@ mod tests {
         test_v(None);
         test_v(Some(3));
     }
+
+    #[test]
+    fn test_unknown_variant() {
+        let bytes = do io::with_bytes_writer |wr| {
+            let mut ebml_w = writer::Encoder(wr);
+            do ebml_w.emit_enum("Option") |e| {
+                e.emit_enum_variant("Other", 2, 0, |_| ())
+            }
+        };
+        let mut errors = ~[];
+        let v: Option<int> = do serialize::decode_error::cond.trap(|e| {
+            errors.push(e)
+        }).inside {
+            let mut deser = reader::Decoder(reader::Doc(@bytes.clone()));
+            serialize::Decodable::decode(&mut deser)
+        };
+        assert_eq!(v, None);
+        assert_eq!(errors, ~[serialize::DecodeError {
+            path: ~"",
+            kind: serialize::UnknownVariantError(~"variant index 2")
+        }]);
+    }
+
+    #[test]
+    fn test_doc_from_slice() {
+        let bytes = do io::with_bytes_writer |wr| {
+            let mut ebml_w = writer::Encoder(wr);
+            (~"abc", 12u).encode(&mut ebml_w)
+        };
+        // Any buffer will do, including part of a larger one.
+        let mut buf = ~[0xffu8, 0xff];
+        buf.push_all(bytes);
+        let mut deser = reader::Decoder(Doc::new(buf.slice_from(2)));
+        let v: (~str, uint) = serialize::Decodable::decode(&mut deser);
+        assert_eq!(v, (~"abc", 12u));
+    }
+
+    #[test]
+    fn test_doc_in_other_task() {
+        let bytes = do io::with_bytes_writer |wr| {
+            write_sample(&mut writer::Encoder(wr))
+        };
+        let shared = Arc::new(bytes);
+        let (port, chan) = stream();
+        do task::spawn_with(shared.clone()) |shared| {
+            let doc = Doc::new(*shared.get());
+            chan.send(doc.get(1).get(2).as_str());
+        }
+        assert_eq!(port.recv(), ~"hi");
+        assert_eq!(reader::doc_as_u8(Doc::new(*shared.get()).get(1).get(3)), 7);
+    }
+
+    fn sample_schema() -> Schema {
+        let mut schema = Schema::new();
+        schema.root(1);
+        schema.allow(1, 2);
+        schema.allow(1, 3);
+        schema.name(1, "doc");
+        schema.name(2, "title");
+        schema.name(3, "count");
+        schema
+    }
+
+    fn write_sample(ebml_w: &mut writer::Encoder) {
+        ebml_w.start_tag(1);
+        ebml_w.wr_tagged_str(2, "hi");
+        ebml_w.wr_tagged_u8(3, 7);
+        ebml_w.end_tag();
+    }
+
+    #[test]
+    fn test_dump() {
+        let bytes = do io::with_bytes_writer |wr| {
+            write_sample(&mut writer::Encoder(wr))
+        };
+        assert_eq!(reader::dump(bytes),
+                   ~"0x1 [7 bytes]\n  0x2 [2 bytes] \"hi\"\n  0x3 [1 byte] 07\n");
+        let expected = ~"doc (0x1) [7 bytes]\n" +
+                       "  title (0x2) [2 bytes] \"hi\"\n" +
+                       "  count (0x3) [1 byte] 07\n";
+        assert_eq!(reader::dump_with(bytes, &sample_schema()), expected);
+    }
+
+    #[test]
+    fn test_dump_malformed() {
+        // The second element claims more bytes than are left.
+        assert_eq!(reader::dump(&[0x81u8, 0x81, 0x00, 0x82, 0x85, 0x01]),
+                   ~"0x1 [1 byte] 00\nmalformed element at 0x3\n");
+        assert_eq!(reader::dump(&[0x01u8]), ~"malformed element at 0x0\n");
+    }
+
+    #[test]
+    fn test_checked_writer() {
+        let bytes = do io::with_bytes_writer |wr| {
+            let mut ebml_w = writer::Encoder(wr);
+            ebml_w.check_schema(sample_schema());
+            write_sample(&mut ebml_w);
+            ebml_w.finish();
+        };
+        let doc = reader::Doc(&bytes);
+        assert_eq!(doc.get(1).get(2).as_str(), ~"hi");
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_checked_writer_unbalanced() {
+        do io::with_bytes_writer |wr| {
+            let mut ebml_w = writer::Encoder(wr);
+            ebml_w.check_writes();
+            ebml_w.end_tag();
+        };
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_checked_writer_left_open() {
+        do io::with_bytes_writer |wr| {
+            let mut ebml_w = writer::Encoder(wr);
+            ebml_w.check_writes();
+            ebml_w.start_tag(1);
+            ebml_w.finish();
+        };
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_checked_writer_bad_nesting() {
+        do io::with_bytes_writer |wr| {
+            let mut ebml_w = writer::Encoder(wr);
+            ebml_w.check_schema(sample_schema());
+            ebml_w.start_tag(1);
+            ebml_w.start_tag(2);
+            ebml_w.wr_tagged_u8(3, 7);
+        };
+    }
 }
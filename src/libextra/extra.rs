@@ -69,6 +69,10 @@ pub mod sort;
 pub mod dlist;
 pub mod treemap;
 
+pub mod bloom;
+pub mod hyperloglog;
+pub mod rope;
+
 // Crypto
 #[path="crypto/cryptoutil.rs"]
 mod cryptoutil;
@@ -80,6 +84,26 @@ pub mod md5;
 pub mod sha1;
 #[path="crypto/sha2.rs"]
 pub mod sha2;
+#[path="crypto/sha3.rs"]
+pub mod sha3;
+#[path="crypto/blake2.rs"]
+pub mod blake2;
+#[path="crypto/mac.rs"]
+pub mod mac;
+#[path="crypto/hmac.rs"]
+pub mod hmac;
+#[path="crypto/hkdf.rs"]
+pub mod hkdf;
+#[path="crypto/pbkdf2.rs"]
+pub mod pbkdf2;
+#[path="crypto/symmetriccipher.rs"]
+pub mod symmetriccipher;
+#[path="crypto/aes.rs"]
+pub mod aes;
+#[path="crypto/blockmodes.rs"]
+pub mod blockmodes;
+#[path="crypto/chacha20.rs"]
+pub mod chacha20;
 
 // And ... other stuff
 
@@ -102,6 +126,8 @@ pub mod bigint;
 pub mod rational;
 #[path="num/complex.rs"]
 pub mod complex;
+#[path="num/decimal.rs"]
+pub mod decimal;
 pub mod stats;
 pub mod semver;
 pub mod fileinput;
@@ -119,6 +145,7 @@ pub mod terminfo;
 // Compiler support modules
 
 pub mod test;
+pub mod quickcheck;
 pub mod serialize;
 
 // A curious inner-module that's not exported that contains the binding
@@ -128,6 +155,7 @@ pub mod serialize;
 pub mod extra {
     pub use serialize;
     pub use test;
+    pub use quickcheck;
 
     // For bootstrapping.
     pub use std::clone;
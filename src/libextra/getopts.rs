@@ -76,8 +76,15 @@
 //!     do_work(input, output);
 //! }
 //! ~~~
+//!
+//! The `groups` module describes options along with their descriptions so
+//! that usage text can be derived from them. Its `Command` type adds named
+//! positional arguments and subcommands, each with their own options, and
+//! options there can fall back to a default value or an environment
+//! variable. Use `opt_parse` to convert an option's argument with `from_str`.
 
 use std::cmp::Eq;
+use std::from_str::FromStr;
 use std::result::{Err, Ok};
 use std::result;
 use std::option::{Some, None};
@@ -138,7 +145,11 @@ pub struct Matches {
     /// Values of the Options that matched
     priv vals: ~[~[Optval]],
     /// Free string fragments
-    free: ~[~str]
+    free: ~[~str],
+    /// Values of the named positional arguments
+    priv positionals: ~[(~str, ~[~str])],
+    /// The subcommand that was given, along with its matches
+    priv subcommand: Option<(~str, ~Matches)>
 }
 
 /// The type returned when the command line does not conform to the
@@ -151,6 +162,9 @@ pub enum Fail_ {
     OptionMissing(~str),
     OptionDuplicated(~str),
     UnexpectedArgument(~str),
+    ArgumentInvalid(~str, ~str),
+    PositionalMissing(~str),
+    UnexpectedPositional(~str),
 }
 
 /// The type of failure that occured.
@@ -162,6 +176,9 @@ pub enum FailType {
     OptionMissing_,
     OptionDuplicated_,
     UnexpectedArgument_,
+    ArgumentInvalid_,
+    PositionalMissing_,
+    UnexpectedPositional_,
 }
 
 /// The result of parsing a command line with a set of options.
@@ -266,6 +283,55 @@ impl Matches {
     }
 
 
+    /// Parses the argument supplied to a matching option with `from_str`.
+    ///
+    /// Returns `Ok(None)` if the option was not present or had no argument,
+    /// and `ArgumentInvalid` if the argument could not be parsed.
+    pub fn opt_parse<T: FromStr>(&self, nm: &str) -> result::Result<Option<T>, Fail_> {
+        match self.opt_str(nm) {
+            None => Ok(None),
+            Some(s) => match from_str(s) {
+                Some(v) => Ok(Some(v)),
+                None => Err(ArgumentInvalid(nm.to_owned(), s))
+            }
+        }
+    }
+
+    /// Parses the arguments supplied to all matches of an option that
+    /// accepts multiple values, failing on the first that cannot be parsed.
+    pub fn opt_parses<T: FromStr>(&self, nm: &str) -> result::Result<~[T], Fail_> {
+        let mut acc = ~[];
+        for s in self.opt_strs(nm).move_iter() {
+            match from_str(s) {
+                Some(v) => acc.push(v),
+                None => return Err(ArgumentInvalid(nm.to_owned(), s))
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Returns the value given for a named positional argument, or `None` if
+    /// it was not given.
+    pub fn pos_str(&self, nm: &str) -> Option<~str> {
+        self.pos_strs(nm).move_iter().next()
+    }
+
+    /// Returns all the values given for a named positional argument.
+    pub fn pos_strs(&self, nm: &str) -> ~[~str] {
+        match self.positionals.iter().find(|&&(ref name, _)| nm == *name) {
+            Some(&(_, ref vals)) => vals.clone(),
+            None => fail!("No positional argument '{}' defined", nm)
+        }
+    }
+
+    /// Returns the name and matches of the subcommand that was given, if any.
+    pub fn subcommand<'a>(&'a self) -> Option<(&'a str, &'a Matches)> {
+        match self.subcommand {
+            Some((ref name, ref m)) => Some((name.as_slice(), &**m)),
+            None => None
+        }
+    }
+
     /// Returns the matching string, a default, or none.
     ///
     /// Returns none if the option was not present, `def` if the option was
@@ -384,6 +450,15 @@ impl Fail_ {
             UnexpectedArgument(ref nm) => {
                 format!("Option '{}' does not take an argument.", *nm)
             }
+            ArgumentInvalid(ref nm, ref val) => {
+                format!("Invalid argument '{}' to option '{}'.", *val, *nm)
+            }
+            PositionalMissing(ref nm) => {
+                format!("Required argument '{}' missing.", *nm)
+            }
+            UnexpectedPositional(ref arg) => {
+                format!("Unexpected argument: '{}'.", *arg)
+            }
         }
     }
 }
@@ -394,6 +469,17 @@ impl Fail_ {
 /// `opt_str`, etc. to interrogate results.  Returns `Err(Fail_)` on failure.
 /// Use `to_err_msg` to get an error message.
 pub fn getopts(args: &[~str], opts: &[Opt]) -> Result {
+    match getopts_until(args, opts, |_| false) {
+        Ok((m, _)) => Ok(m),
+        Err(f) => Err(f)
+    }
+}
+
+// Parses arguments up to the first free argument for which `stop` returns
+// true, returning the matches so far along with the index of that argument
+// (or the number of arguments if there was none).
+fn getopts_until(args: &[~str], opts: &[Opt], stop: &fn(&str) -> bool)
+                 -> result::Result<(Matches, uint), Fail_> {
     let n_opts = opts.len();
 
     fn f(_x: uint) -> ~[Optval] { return ~[]; }
@@ -401,11 +487,16 @@ pub fn getopts(args: &[~str], opts: &[Opt]) -> Result {
     let mut vals = vec::from_fn(n_opts, f);
     let mut free: ~[~str] = ~[];
     let l = args.len();
+    let mut stopped = l;
     let mut i = 0;
     while i < l {
         let cur = args[i].clone();
         let curlen = cur.len();
         if !is_arg(cur) {
+            if stop(cur) {
+                stopped = i;
+                break;
+            }
             free.push(cur);
         } else if cur == ~"--" {
             let mut j = i + 1;
@@ -512,18 +603,23 @@ pub fn getopts(args: &[~str], opts: &[Opt]) -> Result {
         }
         i += 1;
     }
-    Ok(Matches {
+    Ok((Matches {
         opts: opts.to_owned(),
         vals: vals,
-        free: free
-    })
+        free: free,
+        positionals: ~[],
+        subcommand: None
+    }, stopped))
 }
 
 /// A module which provides a way to specify descriptions and
 /// groups of short and long option names, together.
 pub mod groups {
     use getopts::{HasArg, Long, Maybe, Multi, No, Occur, Opt, Optional, Req};
-    use getopts::{Short, Yes};
+    use getopts::{Short, Yes, Fail_, Given, Optval, Val, getopts_until};
+    use getopts::{OptionMissing, PositionalMissing, UnexpectedPositional};
+
+    use std::os;
 
     /// One group of options, e.g., both -h and --help, along with
     /// their shared description and properties.
@@ -540,10 +636,66 @@ pub mod groups {
         /// Whether it has an argument
         hasarg: HasArg,
         /// How often it can occur
+        occur: Occur,
+        /// The argument to use when the option is not given
+        default: Option<~str>,
+        /// The environment variable to read when the option is not given
+        env: Option<~str>
+    }
+
+    /// A named positional argument, along with its description and how many
+    /// values it takes.
+    #[deriving(Clone, Eq)]
+    pub struct PosGroup {
+        /// Name of the argument
+        name: ~str,
+        /// Description
+        desc: ~str,
+        /// How many values it takes: exactly one, at most one, or any number
         occur: Occur
     }
 
+    /// A command with its own options, positional arguments and
+    /// subcommands, as in `rustpkg build [OPTIONS] PKG`.
+    #[deriving(Clone, Eq)]
+    pub struct Command {
+        /// Name of the command, used to select it as a subcommand
+        name: ~str,
+        /// Description
+        desc: ~str,
+        /// Options accepted by the command
+        opts: ~[OptGroup],
+        /// Positional arguments, in order
+        positionals: ~[PosGroup],
+        /// Subcommands
+        subcommands: ~[Command]
+    }
+
     impl OptGroup {
+        /// Use `value` as the argument when the option is not given.
+        pub fn with_default(self, value: &str) -> OptGroup {
+            assert!(self.hasarg != No);
+            OptGroup { default: Some(value.to_owned()), .. self }
+        }
+
+        /// Read the argument from the environment variable `var` when the
+        /// option is not given. This takes precedence over any default, and
+        /// a flag counts as given if the variable is set and not empty.
+        pub fn with_env(self, var: &str) -> OptGroup {
+            OptGroup { env: Some(var.to_owned()), .. self }
+        }
+
+        // The value to use when the option is not on the command line.
+        fn fallback(&self) -> Option<Optval> {
+            let env = self.env.as_ref().and_then(|var| os::getenv(*var));
+            match (self.hasarg, env) {
+                (No, Some(ref v)) if v.is_empty() => None,
+                (No, Some(_)) => Some(Given),
+                (_, Some(v)) => Some(Val(v)),
+                (_, None) => self.default.clone().map(|v| Val(v))
+            }
+        }
+
         /// Translate OptGroup into Opt.
         /// (Both short and long names correspond to different Opts).
         pub fn long_to_short(&self) -> Opt {
@@ -597,7 +749,9 @@ pub mod groups {
             hint: hint.to_owned(),
             desc: desc.to_owned(),
             hasarg: Yes,
-            occur: Req
+            occur: Req,
+            default: None,
+            env: None
         }
     }
 
@@ -611,7 +765,9 @@ pub mod groups {
             hint: hint.to_owned(),
             desc: desc.to_owned(),
             hasarg: Yes,
-            occur: Optional
+            occur: Optional,
+            default: None,
+            env: None
         }
     }
 
@@ -625,7 +781,9 @@ pub mod groups {
             hint: ~"",
             desc: desc.to_owned(),
             hasarg: No,
-            occur: Optional
+            occur: Optional,
+            default: None,
+            env: None
         }
     }
 
@@ -640,7 +798,9 @@ pub mod groups {
             hint: ~"",
             desc: desc.to_owned(),
             hasarg: No,
-            occur: Multi
+            occur: Multi,
+            default: None,
+            env: None
         }
     }
 
@@ -654,7 +814,9 @@ pub mod groups {
             hint: hint.to_owned(),
             desc: desc.to_owned(),
             hasarg: Maybe,
-            occur: Optional
+            occur: Optional,
+            default: None,
+            env: None
         }
     }
 
@@ -669,26 +831,206 @@ pub mod groups {
             hint: hint.to_owned(),
             desc: desc.to_owned(),
             hasarg: Yes,
-            occur: Multi
+            occur: Multi,
+            default: None,
+            env: None
+        }
+    }
+
+    /// Create a positional argument that must be given exactly once.
+    pub fn reqpos(name: &str, desc: &str) -> PosGroup {
+        PosGroup { name: name.to_owned(), desc: desc.to_owned(), occur: Req }
+    }
+
+    /// Create a positional argument that may be left out.
+    pub fn optpos(name: &str, desc: &str) -> PosGroup {
+        PosGroup { name: name.to_owned(), desc: desc.to_owned(), occur: Optional }
+    }
+
+    /// Create a positional argument that takes any number of values.
+    pub fn multipos(name: &str, desc: &str) -> PosGroup {
+        PosGroup { name: name.to_owned(), desc: desc.to_owned(), occur: Multi }
+    }
+
+    impl Command {
+        /// Create a command with no options, arguments or subcommands.
+        pub fn new(name: &str, desc: &str) -> Command {
+            Command {
+                name: name.to_owned(),
+                desc: desc.to_owned(),
+                opts: ~[],
+                positionals: ~[],
+                subcommands: ~[]
+            }
+        }
+
+        /// Add an option to the command.
+        pub fn opt(mut self, opt: OptGroup) -> Command {
+            self.opts.push(opt);
+            self
+        }
+
+        /// Add a positional argument, following any added before it.
+        pub fn positional(mut self, pos: PosGroup) -> Command {
+            self.positionals.push(pos);
+            self
+        }
+
+        /// Add a subcommand.
+        pub fn subcommand(mut self, cmd: Command) -> Command {
+            self.subcommands.push(cmd);
+            self
+        }
+
+        /// Find a subcommand by name.
+        pub fn find_subcommand<'a>(&'a self, name: &str) -> Option<&'a Command> {
+            self.subcommands.iter().find(|cmd| name == cmd.name.as_slice())
+        }
+
+        /// Parse command line args, not including the command name itself.
+        ///
+        /// Options must come before the name of a subcommand, and everything
+        /// after it is parsed by the subcommand; use `Matches::subcommand` to
+        /// get at the result. Free arguments are assigned to the positional
+        /// arguments in order, with the ones taking any number of values
+        /// leaving enough behind for the required ones that follow. When the
+        /// command has positional arguments or subcommands, leftover free
+        /// arguments are an error.
+        pub fn getopts(&self, args: &[~str]) -> ::getopts::Result {
+            parse(args, self.opts, self.positionals, self.subcommands)
+        }
+
+        /// Derive a usage message for the command, which was invoked as
+        /// `program` (e.g. `rustpkg build`).
+        pub fn usage(&self, program: &str) -> ~str {
+            let mut brief = format!("Usage: {}", program);
+            if !self.opts.is_empty() {
+                brief.push_str(" [OPTIONS]");
+            }
+            for pos in self.positionals.iter() {
+                brief.push_str(match pos.occur {
+                    Req => format!(" {}", pos.name),
+                    Optional => format!(" [{}]", pos.name),
+                    Multi => format!(" [{}...]", pos.name)
+                });
+            }
+            if !self.subcommands.is_empty() {
+                brief.push_str(" COMMAND");
+            }
+            if !self.desc.is_empty() {
+                brief.push_str("\n\n");
+                brief.push_str(self.desc);
+            }
+
+            let mut message = if self.opts.is_empty() {
+                brief + "\n"
+            } else {
+                usage(brief, self.opts)
+            };
+            if !self.positionals.is_empty() {
+                let rows = self.positionals.map(|pos| describe(" ".repeat(4) + pos.name, pos.desc));
+                message.push_str(format!("\nArguments:\n{}\n", rows.connect("\n")));
+            }
+            if !self.subcommands.is_empty() {
+                let rows = self.subcommands.map(|cmd| describe(" ".repeat(4) + cmd.name, cmd.desc));
+                message.push_str(format!("\nCommands:\n{}\n", rows.connect("\n")));
+            }
+            message
         }
     }
 
     /// Parse command line args with the provided long format options.
     pub fn getopts(args: &[~str], opts: &[OptGroup]) -> ::getopts::Result {
-        ::getopts::getopts(args, opts.map(|x| x.long_to_short()))
+        parse(args, opts, [], [])
+    }
+
+    // Parses args against the options, positional arguments and subcommands
+    // of a command.
+    fn parse(args: &[~str],
+             opts: &[OptGroup],
+             positionals: &[PosGroup],
+             subcommands: &[Command]) -> ::getopts::Result {
+        // Required options with a fallback are only missing if the fallback
+        // is too, which is checked once parsing is done.
+        let fallbacks = opts.map(|group| group.fallback());
+        let short_opts = opts.iter().zip(fallbacks.iter()).map(|(group, fallback)| {
+            let mut opt = group.long_to_short();
+            if fallback.is_some() {
+                opt.occur = match opt.occur { Req => Optional, occur => occur };
+            }
+            opt
+        }).to_owned_vec();
+
+        let is_command = |arg: &str| subcommands.iter().any(|cmd| arg == cmd.name.as_slice());
+        let (mut m, stopped) = match getopts_until(args, short_opts, is_command) {
+            Ok(r) => r,
+            Err(f) => return Err(f)
+        };
+
+        for (i, fallback) in fallbacks.move_iter().enumerate() {
+            if m.vals[i].is_empty() {
+                match fallback {
+                    Some(v) => m.vals[i].push(v),
+                    None if opts[i].occur == Req => {
+                        return Err(OptionMissing(short_opts[i].name.to_str()));
+                    }
+                    None => ()
+                }
+            }
+        }
+
+        if !positionals.is_empty() || !subcommands.is_empty() {
+            let assigned = assign_positionals(m.free, positionals);
+            match assigned {
+                Ok(vals) => m.positionals = vals,
+                Err(f) => return Err(f)
+            }
+        }
+
+        if stopped < args.len() {
+            let cmd = subcommands.iter().find(|cmd| args[stopped] == cmd.name).unwrap();
+            match cmd.getopts(args.slice_from(stopped + 1)) {
+                Ok(sub) => m.subcommand = Some((cmd.name.clone(), ~sub)),
+                Err(f) => return Err(f)
+            }
+        }
+        Ok(m)
+    }
+
+    fn assign_positionals(free: &[~str], positionals: &[PosGroup])
+                          -> Result<~[(~str, ~[~str])], Fail_> {
+        let mut vals = ~[];
+        let mut next = 0;
+        for (i, pos) in positionals.iter().enumerate() {
+            // leave a value for each required argument still to come
+            let required = positionals.slice_from(i + 1).iter().count(|p| p.occur == Req);
+            let left = free.len() - next;
+            let available = if left > required { left - required } else { 0 };
+            let n = match pos.occur {
+                Req if next == free.len() => return Err(PositionalMissing(pos.name.clone())),
+                Req => 1,
+                Optional => available.min(&1),
+                Multi => available
+            };
+            vals.push((pos.name.clone(), free.slice(next, next + n).to_owned()));
+            next += n;
+        }
+        if next < free.len() {
+            return Err(UnexpectedPositional(free[next].clone()));
+        }
+        Ok(vals)
     }
 
     /// Derive a usage message from a set of long options.
     pub fn usage(brief: &str, opts: &[OptGroup]) -> ~str {
-
-        let desc_sep = "\n" + " ".repeat(24);
-
         let mut rows = opts.iter().map(|optref| {
             let OptGroup{short_name: short_name,
                          long_name: long_name,
                          hint: hint,
                          desc: desc,
                          hasarg: hasarg,
+                         default: default,
+                         env: env,
                          _} = (*optref).clone();
 
             let mut row = " ".repeat(4);
@@ -725,39 +1067,57 @@ pub mod groups {
                 }
             }
 
-            // FIXME: #5516 should be graphemes not codepoints
-            // here we just need to indent the start of the description
-            let rowlen = row.char_len();
-            if rowlen < 24 {
-                do (24 - rowlen).times {
-                    row.push_char(' ')
-                }
-            } else {
-                row.push_str(desc_sep)
+            // fallbacks
+            let mut desc = desc;
+            for var in env.iter() {
+                desc.push_str(format!(" [env: {}]", *var));
             }
+            for value in default.iter() {
+                desc.push_str(format!(" [default: {}]", *value));
+            }
+
+            describe(row, desc)
+        });
 
-            // Normalize desc to contain words separated by one space character
-            let mut desc_normalized_whitespace = ~"";
-            for word in desc.word_iter() {
-                desc_normalized_whitespace.push_str(word);
-                desc_normalized_whitespace.push_char(' ');
+        format!("{}\n\nOptions:\n{}\n", brief, rows.collect::<~[~str]>().connect("\n"))
+    }
+
+    // Pads `row` out to the description column and appends the wrapped
+    // description.
+    fn describe(row: ~str, desc: &str) -> ~str {
+        let desc_sep = "\n" + " ".repeat(24);
+        let mut row = row;
+
+        // FIXME: #5516 should be graphemes not codepoints
+        // here we just need to indent the start of the description
+        let rowlen = row.char_len();
+        if rowlen < 24 {
+            do (24 - rowlen).times {
+                row.push_char(' ')
             }
+        } else {
+            row.push_str(desc_sep)
+        }
 
-            // FIXME: #5516 should be graphemes not codepoints
-            let mut desc_rows = ~[];
-            do each_split_within(desc_normalized_whitespace, 54) |substr| {
-                desc_rows.push(substr.to_owned());
-                true
-            };
+        // Normalize desc to contain words separated by one space character
+        let mut desc_normalized_whitespace = ~"";
+        for word in desc.word_iter() {
+            desc_normalized_whitespace.push_str(word);
+            desc_normalized_whitespace.push_char(' ');
+        }
 
-            // FIXME: #5516 should be graphemes not codepoints
-            // wrapped description
-            row.push_str(desc_rows.connect(desc_sep));
+        // FIXME: #5516 should be graphemes not codepoints
+        let mut desc_rows = ~[];
+        do each_split_within(desc_normalized_whitespace, 54) |substr| {
+            desc_rows.push(substr.to_owned());
+            true
+        };
 
-            row
-        });
+        // FIXME: #5516 should be graphemes not codepoints
+        // wrapped description
+        row.push_str(desc_rows.connect(desc_sep));
 
-        format!("{}\n\nOptions:\n{}\n", brief, rows.collect::<~[~str]>().connect("\n"))
+        row
     }
 
     /// Splits a string into substrings with possibly internal whitespace,
@@ -863,6 +1223,7 @@ mod tests {
     use getopts::groups::OptGroup;
     use getopts::*;
 
+    use std::os;
     use std::result::{Err, Ok};
     use std::result;
 
@@ -872,7 +1233,10 @@ mod tests {
           UnrecognizedOption(_) => assert!(ft == UnrecognizedOption_),
           OptionMissing(_) => assert!(ft == OptionMissing_),
           OptionDuplicated(_) => assert!(ft == OptionDuplicated_),
-          UnexpectedArgument(_) => assert!(ft == UnexpectedArgument_)
+          UnexpectedArgument(_) => assert!(ft == UnexpectedArgument_),
+          ArgumentInvalid(_, _) => assert!(ft == ArgumentInvalid_),
+          PositionalMissing(_) => assert!(ft == PositionalMissing_),
+          UnexpectedPositional(_) => assert!(ft == UnexpectedPositional_)
         }
     }
 
@@ -1639,4 +2003,152 @@ Options:
         debug!("generated: <<{}>>", usage);
         assert!(usage == expected)
     }
+
+    #[test]
+    fn test_opt_parse() {
+        let args = ~[~"-n", ~"12", ~"-l", ~"1", ~"-l", ~"x", ~"-m", ~"2", ~"-m", ~"3"];
+        let opts = ~[optopt("n"), optopt("o"), optmulti("l"), optmulti("m")];
+        let m = getopts(args, opts).unwrap();
+        assert_eq!(m.opt_parse::<uint>("n"), Ok(Some(12)));
+        assert_eq!(m.opt_parse::<uint>("o"), Ok(None));
+        assert_eq!(m.opt_parses::<uint>("m"), Ok(~[2, 3]));
+        match m.opt_parses::<uint>("l") {
+            Err(f) => {
+                check_fail_type(f.clone(), ArgumentInvalid_);
+                assert_eq!(f.to_err_msg(), ~"Invalid argument 'x' to option 'l'.");
+            }
+            _ => fail!()
+        }
+    }
+
+    #[test]
+    fn test_groups_fallbacks() {
+        os::unsetenv("GETOPTS_TEST_TARGET");
+        os::unsetenv("GETOPTS_TEST_VERBOSE");
+        let opts = ~[
+            groups::optopt("j", "jobs", "Jobs", "N").with_default("4"),
+            groups::reqopt("", "target", "Target", "TRIPLE").with_env("GETOPTS_TEST_TARGET"),
+            groups::optflag("v", "verbose", "Verbose").with_env("GETOPTS_TEST_VERBOSE")
+        ];
+
+        match groups::getopts([], opts) {
+            Err(f) => check_fail_type(f, OptionMissing_),
+            _ => fail!()
+        }
+
+        os::setenv("GETOPTS_TEST_TARGET", "x86_64");
+        os::setenv("GETOPTS_TEST_VERBOSE", "");
+        let m = groups::getopts([], opts).unwrap();
+        assert_eq!(m.opt_parse::<uint>("jobs"), Ok(Some(4)));
+        assert_eq!(m.opt_str("target"), Some(~"x86_64"));
+        assert!(!m.opt_present("verbose"));
+
+        // the command line takes precedence
+        os::setenv("GETOPTS_TEST_VERBOSE", "1");
+        let m = groups::getopts([~"--target=arm", ~"-j", ~"2"], opts).unwrap();
+        assert_eq!(m.opt_str("j"), Some(~"2"));
+        assert_eq!(m.opt_str("target"), Some(~"arm"));
+        assert!(m.opt_present("v"));
+    }
+
+    #[test]
+    fn test_groups_positionals() {
+        let cmd = groups::Command::new("cp", "Copy files")
+            .opt(groups::optflag("r", "recursive", "Copy directories"))
+            .positional(groups::multipos("SOURCE", "Files to copy"))
+            .positional(groups::reqpos("DEST", "Where to copy them"));
+
+        let m = cmd.getopts([~"a", ~"-r", ~"b", ~"c"]).unwrap();
+        assert!(m.opt_present("r"));
+        assert_eq!(m.pos_strs("SOURCE"), ~[~"a", ~"b"]);
+        assert_eq!(m.pos_str("DEST"), Some(~"c"));
+
+        let m = cmd.getopts([~"c"]).unwrap();
+        assert!(m.pos_strs("SOURCE").is_empty());
+        assert_eq!(m.pos_str("DEST"), Some(~"c"));
+
+        match cmd.getopts([]) {
+            Err(f) => check_fail_type(f, PositionalMissing_),
+            _ => fail!()
+        }
+
+        let cmd = groups::Command::new("x", "")
+            .positional(groups::optpos("A", ""))
+            .positional(groups::optpos("B", ""));
+        let m = cmd.getopts([~"1"]).unwrap();
+        assert_eq!(m.pos_str("A"), Some(~"1"));
+        assert_eq!(m.pos_str("B"), None);
+        match cmd.getopts([~"1", ~"2", ~"3"]) {
+            Err(f) => assert_eq!(f, UnexpectedPositional(~"3")),
+            _ => fail!()
+        }
+    }
+
+    #[test]
+    fn test_groups_subcommands() {
+        let cmd = groups::Command::new("rustpkg", "")
+            .opt(groups::optflag("v", "verbose", "Verbose"))
+            .opt(groups::optopt("c", "config", "Config", "NAME"))
+            .subcommand(groups::Command::new("build", "Build a package")
+                        .opt(groups::optopt("j", "jobs", "Jobs", "N"))
+                        .positional(groups::reqpos("PKG", "Package")))
+            .subcommand(groups::Command::new("clean", "Clean"));
+
+        let m = cmd.getopts([~"-v", ~"build", ~"-j", ~"2", ~"foo"]).unwrap();
+        assert!(m.opt_present("v"));
+        let (name, sub) = m.subcommand().unwrap();
+        assert_eq!(name, "build");
+        assert_eq!(sub.opt_str("j"), Some(~"2"));
+        assert_eq!(sub.pos_str("PKG"), Some(~"foo"));
+
+        // an option's argument is never taken for a subcommand
+        let m = cmd.getopts([~"-c", ~"build", ~"clean"]).unwrap();
+        assert_eq!(m.opt_str("c"), Some(~"build"));
+        let (name, _) = m.subcommand().unwrap();
+        assert_eq!(name, "clean");
+
+        // options after the subcommand name belong to it
+        match cmd.getopts([~"build", ~"-v", ~"foo"]) {
+            Err(f) => check_fail_type(f, UnrecognizedOption_),
+            _ => fail!()
+        }
+        match cmd.getopts([~"frob"]) {
+            Err(f) => assert_eq!(f, UnexpectedPositional(~"frob")),
+            _ => fail!()
+        }
+        assert!(cmd.getopts([~"-v"]).unwrap().subcommand().is_none());
+    }
+
+    #[test]
+    fn test_command_usage() {
+        let cmd = groups::Command::new("rustpkg", "Build and install packages")
+            .opt(groups::optflag("v", "verbose", "Print more"))
+            .opt(groups::optopt("j", "jobs", "Parallel jobs", "N").with_default("1")
+                                                                  .with_env("RUSTPKG_JOBS"))
+            .positional(groups::optpos("DIR", "Workspace to use"))
+            .subcommand(groups::Command::new("build", "Build a package"))
+            .subcommand(groups::Command::new("install", "Install a package"));
+
+        let expected =
+~"Usage: rustpkg [OPTIONS] [DIR] COMMAND
+
+Build and install packages
+
+Options:
+    -v --verbose        Print more
+    -j --jobs N         Parallel jobs [env: RUSTPKG_JOBS] [default: 1]
+
+Arguments:
+    DIR                 Workspace to use
+
+Commands:
+    build               Build a package
+    install             Install a package
+";
+
+        let usage = cmd.usage("rustpkg");
+        debug!("expected: <<{}>>", expected);
+        debug!("generated: <<{}>>", usage);
+        assert_eq!(usage, expected);
+    }
 }
@@ -23,7 +23,7 @@
  * `glob`/`fnmatch` functions.
  */
 
-use std::{os, path};
+use std::{os, path, str};
 use std::path::is_sep;
 
 use sort;
@@ -34,9 +34,10 @@ use sort;
  */
 pub struct GlobIterator {
     priv root: Path,
-    priv dir_patterns: ~[Pattern],
+    priv dir_patterns: ~[~[Pattern]],
     priv options: MatchOptions,
-    priv todo: ~[(Path,uint)]
+    priv excludes: ~[Pattern],
+    priv todo: ~[(Path, ~[(uint, uint)])]
 }
 
 ///
@@ -69,17 +70,21 @@ pub fn glob(pattern: &str) -> GlobIterator {
     glob_with(pattern, MatchOptions::new())
 }
 
-/**
- * Return an iterator that produces all the Paths that match the given pattern,
- * which may be absolute or relative to the current working directory.
- *
- * This function accepts Unix shell style patterns as described by `Pattern::new(..)`.
- * The options given are passed through unchanged to `Pattern::matches_with(..)` with
- * the exception that `require_literal_separator` is always set to `true` regardless of the
- * value passed to this function.
- *
- * Paths are yielded in alphabetical order, as absolute paths.
- */
+/// Return an iterator that produces all the Paths that match the given pattern,
+/// which may be absolute or relative to the current working directory.
+///
+/// This function accepts Unix shell style patterns as described by `Pattern::new(..)`.
+/// The options given are passed through unchanged to `Pattern::matches_with(..)` with
+/// the exception that `require_literal_separator` is always set to `true` regardless of the
+/// value passed to this function.
+///
+/// A `**` path component matches any number of directories, so `src/**/*.rs` finds every
+/// `.rs` file below `src`, and a trailing `**` matches everything below a directory. The
+/// directory tree is only walked as far as the pattern requires, one directory at a time as
+/// the iterator is advanced.
+///
+/// Paths are yielded in alphabetical order, as absolute paths. A directory is yielded before
+/// anything inside it.
 pub fn glob_with(pattern: &str, options: MatchOptions) -> GlobIterator {
     #[cfg(windows)]
     fn check_windows_verbatim(p: &Path) -> bool { path::windows::is_verbatim(p) }
@@ -93,58 +98,138 @@ pub fn glob_with(pattern: &str, options: MatchOptions) -> GlobIterator {
         if check_windows_verbatim(pat_root.get_ref()) {
             // XXX: How do we want to handle verbatim paths? I'm inclined to return nothing,
             // since we can't very well find all UNC shares with a 1-letter server name.
-            return GlobIterator { root: root, dir_patterns: ~[], options: options, todo: ~[] };
+            return GlobIterator { root: root, dir_patterns: ~[], options: options,
+                                  excludes: ~[], todo: ~[] };
         }
         root.push(pat_root.get_ref());
     }
 
+    // each alternative of a `{a,b}` group gets its own list of per-directory patterns
     let root_len = pat_root.map_default(0u, |p| p.as_vec().len());
-    let dir_patterns = pattern.slice_from(root_len.min(&pattern.len()))
+    let mut dir_patterns = ~[];
+    for chars in expand_braces(pattern.iter().to_owned_vec()).move_iter() {
+        let alternative = str::from_chars(chars);
+        let patterns = alternative.slice_from(root_len.min(&alternative.len()))
                        .split_terminator_iter(is_sep).map(|s| Pattern::new(s)).to_owned_vec();
+        if !patterns.is_empty() && !dir_patterns.contains(&patterns) {
+            dir_patterns.push(patterns);
+        }
+    }
 
-    let todo = list_dir_sorted(&root).move_iter().map(|x|(x,0u)).to_owned_vec();
+    let mut states = ~[];
+    for (alt, patterns) in dir_patterns.iter().enumerate() {
+        add_state(&mut states, *patterns, alt, 0);
+    }
+    let todo = list_dir_sorted(&root).move_iter().map(|x|(x,states.clone())).to_owned_vec();
 
     GlobIterator {
         root: root,
         dir_patterns: dir_patterns,
         options: options,
+        excludes: ~[],
         todo: todo,
     }
 }
 
+impl GlobIterator {
+    /// Skip any path that matches the given pattern, along with everything inside it.
+    ///
+    /// The pattern may be absolute or relative to the current working directory, and is
+    /// matched against whole paths with `require_literal_separator` set, so e.g.
+    /// `glob("src/**/*.rs").exclude("src/test").exclude("**/tmp")` leaves out the `src/test`
+    /// directory and any directory called `tmp`.
+    pub fn exclude(mut self, pattern: &str) -> GlobIterator {
+        let pattern = if Path::new(pattern).is_absolute() {
+            Pattern::new(pattern)
+        } else {
+            // FIXME (#9639): This needs to handle non-utf8 paths
+            let cwd = os::getcwd();
+            let mut prefix = Pattern::escape(cwd.as_str().unwrap_or(""));
+            if !prefix.rev_iter().next().map_default(false, |c| is_sep(c)) {
+                prefix.push_char('/');
+            }
+            Pattern::new(prefix + pattern)
+        };
+        self.excludes.push(pattern);
+        self
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        let options = MatchOptions { require_literal_separator: true, .. self.options };
+        self.excludes.iter().any(|pattern| pattern.matches_path_with(path, options))
+    }
+
+    // Matches a file name against each of the (alternative, component) positions its
+    // directory was reached with. Returns whether the file matches the whole pattern, and
+    // the positions to carry on from in its children.
+    fn advance(&self, name: &str, states: &[(uint, uint)]) -> (bool, ~[(uint, uint)]) {
+        let mut matched = false;
+        let mut next = ~[];
+        for &(alt, idx) in states.iter() {
+            let patterns = &self.dir_patterns[alt];
+            let last = idx == patterns.len() - 1;
+            if patterns[idx].is_recursive() {
+                // `**` matches this file, and may go on to match its children too
+                if !self.options.require_literal_leading_dot || !name.starts_with(".") {
+                    matched = matched || last;
+                    add_state(&mut next, *patterns, alt, idx);
+                }
+            } else if patterns[idx].matches_with(name, self.options) {
+                if last {
+                    matched = true;
+                } else {
+                    add_state(&mut next, *patterns, alt, idx + 1);
+                }
+            }
+        }
+        (matched, next)
+    }
+}
+
 impl Iterator<Path> for GlobIterator {
 
     fn next(&mut self) -> Option<Path> {
         loop {
-            if self.dir_patterns.is_empty() || self.todo.is_empty() {
+            if self.todo.is_empty() {
                 return None;
             }
 
-            let (path,idx) = self.todo.pop();
-            let ref pattern = self.dir_patterns[idx];
+            let (path, states) = self.todo.pop();
+            if self.is_excluded(&path) {
+                continue;
+            }
 
-            if pattern.matches_with(match path.filename_str() {
-                // this ugly match needs to go here to avoid a borrowck error
-                None => {
-                    // FIXME (#9639): How do we handle non-utf8 filenames? Ignore them for now
-                    // Ideally we'd still match them against a *
-                    continue;
-                }
-                Some(x) => x
-            }, self.options) {
-                if idx == self.dir_patterns.len() - 1 {
-                    // it is not possible for a pattern to match a directory *AND* its children
-                    // so we don't need to check the children
-                    return Some(path);
-                } else {
-                    self.todo.extend(&mut list_dir_sorted(&path).move_iter().map(|x|(x,idx+1)));
-                }
+            let (matched, next_states) = match path.filename_str() {
+                // FIXME (#9639): How do we handle non-utf8 filenames? Ignore them for now
+                // Ideally we'd still match them against a *
+                None => continue,
+                Some(name) => self.advance(name, states)
+            };
+
+            if !next_states.is_empty() && os::path_is_dir(&path) {
+                self.todo.extend(&mut list_dir_sorted(&path).move_iter()
+                                 .map(|x| (x, next_states.clone())));
+            }
+            if matched {
+                return Some(path);
             }
         }
     }
 
 }
 
+// Records that a directory's children should be matched against component `idx` of
+// alternative `alt`. A `**` component may match no directories at all, so the component
+// after it is recorded as well.
+fn add_state(states: &mut ~[(uint, uint)], patterns: &[Pattern], alt: uint, idx: uint) {
+    if !states.contains(&(alt, idx)) {
+        states.push((alt, idx));
+        if patterns[idx].is_recursive() && idx + 1 < patterns.len() {
+            add_state(states, patterns, alt, idx + 1);
+        }
+    }
+}
+
 fn list_dir_sorted(path: &Path) -> ~[Path] {
     let mut children = os::list_dir_path(path);
     sort::quick_sort(children, |p1, p2| p2.filename().unwrap() <= p1.filename().unwrap());
@@ -156,7 +241,7 @@ fn list_dir_sorted(path: &Path) -> ~[Path] {
  */
 #[deriving(Clone, Eq, TotalEq, Ord, TotalOrd, IterBytes, Default)]
 pub struct Pattern {
-    priv tokens: ~[PatternToken]
+    priv branches: ~[~[PatternToken]]
 }
 
 #[deriving(Clone, Eq, TotalEq, Ord, TotalOrd, IterBytes)]
@@ -164,6 +249,7 @@ enum PatternToken {
     Char(char),
     AnyChar,
     AnySequence,
+    AnyRecursiveSequence,
     AnyWithin(~[CharSpecifier]),
     AnyExcept(~[CharSpecifier])
 }
@@ -183,79 +269,33 @@ enum MatchResult {
 
 impl Pattern {
 
-    /**
-     * This function compiles Unix shell style patterns: `?` matches any single character,
-     * `*` matches any (possibly empty) sequence of characters and `[...]` matches any character
-     * inside the brackets, unless the first character is `!` in which case it matches any
-     * character except those between the `!` and the `]`. Character sequences can also specify
-     * ranges of characters, as ordered by Unicode, so e.g. `[0-9]` specifies any character
-     * between 0 and 9 inclusive.
-     *
-     * The metacharacters `?`, `*`, `[`, `]` can be matched by using brackets (e.g. `[?]`).
-     * When a `]` occurs immediately following `[` or `[!` then it is interpreted as
-     * being part of, rather then ending, the character set, so `]` and NOT `]` can be
-     * matched by `[]]` and `[!]]` respectively. The `-` character can be specified inside a
-     * character sequence pattern by placing it at the start or the end, e.g. `[abc-]`.
-     *
-     * When a `[` does not have a closing `]` before the end of the string then the `[` will
-     * be treated literally.
-     */
+    /// This function compiles Unix shell style patterns: `?` matches any single character,
+    /// `*` matches any (possibly empty) sequence of characters and `[...]` matches any character
+    /// inside the brackets, unless the first character is `!` in which case it matches any
+    /// character except those between the `!` and the `]`. Character sequences can also specify
+    /// ranges of characters, as ordered by Unicode, so e.g. `[0-9]` specifies any character
+    /// between 0 and 9 inclusive.
+    ///
+    /// A `**` that makes up a whole path component matches any number of path components
+    /// (including none), so `a/**/b` matches `a/b` and `a/x/y/b`, and a trailing `a/**` matches
+    /// everything below `a`. Anywhere else `**` is equivalent to `*`.
+    ///
+    /// `{a,b}` matches either of the comma-separated alternatives, which may themselves
+    /// contain patterns and nested braces, so `*.{rs,t{xt,oml}}` matches files ending in `.rs`,
+    /// `.txt` or `.toml`. Braces without a comma inside them are treated literally.
+    ///
+    /// The metacharacters `?`, `*`, `[`, `]`, `{`, `}` can be matched by using brackets (e.g.
+    /// `[?]`). When a `]` occurs immediately following `[` or `[!` then it is interpreted as
+    /// being part of, rather then ending, the character set, so `]` and NOT `]` can be
+    /// matched by `[]]` and `[!]]` respectively. The `-` character can be specified inside a
+    /// character sequence pattern by placing it at the start or the end, e.g. `[abc-]`.
+    ///
+    /// When a `[` does not have a closing `]` before the end of the string then the `[` will
+    /// be treated literally.
     pub fn new(pattern: &str) -> Pattern {
-
         let chars = pattern.iter().to_owned_vec();
-        let mut tokens = ~[];
-        let mut i = 0;
-
-        while i < chars.len() {
-            match chars[i] {
-                '?' => {
-                    tokens.push(AnyChar);
-                    i += 1;
-                }
-                '*' => {
-                    // *, **, ***, ****, ... are all equivalent
-                    while i < chars.len() && chars[i] == '*' {
-                        i += 1;
-                    }
-                    tokens.push(AnySequence);
-                }
-                '[' => {
-
-                    if i <= chars.len() - 4 && chars[i + 1] == '!' {
-                        match chars.slice_from(i + 3).position_elem(&']') {
-                            None => (),
-                            Some(j) => {
-                                let cs = parse_char_specifiers(chars.slice(i + 2, i + 3 + j));
-                                tokens.push(AnyExcept(cs));
-                                i += j + 4;
-                                continue;
-                            }
-                        }
-                    }
-                    else if i <= chars.len() - 3 && chars[i + 1] != '!' {
-                        match chars.slice_from(i + 2).position_elem(&']') {
-                            None => (),
-                            Some(j) => {
-                                let cs = parse_char_specifiers(chars.slice(i + 1, i + 2 + j));
-                                tokens.push(AnyWithin(cs));
-                                i += j + 3;
-                                continue;
-                            }
-                        }
-                    }
-
-                    // if we get here then this is not a valid range pattern
-                    tokens.push(Char('['));
-                    i += 1;
-                }
-                c => {
-                    tokens.push(Char(c));
-                    i += 1;
-                }
-            }
-        }
-
-        Pattern { tokens: tokens }
+        let branches = expand_braces(chars).move_iter().map(|b| tokenize(b)).to_owned_vec();
+        Pattern { branches: branches }
     }
 
     /**
@@ -267,8 +307,9 @@ impl Pattern {
         let mut escaped = ~"";
         for c in s.iter() {
             match c {
-                // note that ! does not need escaping because it is only special inside brackets
-                '?' | '*' | '[' | ']' => {
+                // note that ! and , do not need escaping because they are only special
+                // inside brackets and braces respectively
+                '?' | '*' | '[' | ']' | '{' | '}' => {
                     escaped.push_char('[');
                     escaped.push_char(c);
                     escaped.push_char(']');
@@ -281,18 +322,17 @@ impl Pattern {
         escaped
     }
 
-    /**
-     * Return if the given `str` matches this `Pattern` using the default
-     * match options (i.e. `MatchOptions::new()`).
-     *
-     * # Example
-     *
-     * ```rust
-     * assert!(Pattern::new("c?t").matches("cat"));
-     * assert!(Pattern::new("k[!e]tteh").matches("kitteh"));
-     * assert!(Pattern::new("d*g").matches("doog"));
-     * ```
-     */
+    /// Return if the given `str` matches this `Pattern` using the default
+    /// match options (i.e. `MatchOptions::new()`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// assert!(Pattern::new("c?t").matches("cat"));
+    /// assert!(Pattern::new("k[!e]tteh").matches("kitteh"));
+    /// assert!(Pattern::new("d*g").matches("doog"));
+    /// assert!(Pattern::new("src/**/*.{rs,rc}").matches("src/libextra/glob.rs"));
+    /// ```
     pub fn matches(&self, str: &str) -> bool {
         self.matches_with(str, MatchOptions::new())
     }
@@ -312,7 +352,9 @@ impl Pattern {
      * Return if the given `str` matches this `Pattern` using the specified match options.
      */
     pub fn matches_with(&self, str: &str, options: MatchOptions) -> bool {
-        self.matches_from(None, str, 0, options) == Match
+        self.branches.iter().any(|tokens| {
+            self.matches_from(*tokens, None, str, 0, options) == Match
+        })
     }
 
     /**
@@ -326,7 +368,13 @@ impl Pattern {
         }
     }
 
+    // Whether this is a lone `**`, which can match any number of path components.
+    fn is_recursive(&self) -> bool {
+        self.branches.len() == 1 && self.branches[0] == ~[AnyRecursiveSequence]
+    }
+
     fn matches_from(&self,
+                    tokens: &[PatternToken],
                     mut prev_char: Option<char>,
                     mut file: &str,
                     i: uint,
@@ -338,11 +386,11 @@ impl Pattern {
              && is_sep(prev_char.unwrap_or('/')))
         };
 
-        for (ti, token) in self.tokens.slice_from(i).iter().enumerate() {
+        for (ti, token) in tokens.slice_from(i).iter().enumerate() {
             match *token {
                 AnySequence => {
                     loop {
-                        match self.matches_from(prev_char, file, i + ti + 1, options) {
+                        match self.matches_from(tokens, prev_char, file, i + ti + 1, options) {
                             SubPatternDoesntMatch => (), // keep trying
                             m => return m,
                         }
@@ -359,6 +407,31 @@ impl Pattern {
                         file = next;
                     }
                 }
+                AnyRecursiveSequence => {
+                    let trailing = i + ti + 1 == tokens.len();
+                    loop {
+                        match self.matches_from(tokens, prev_char, file, i + ti + 1, options) {
+                            SubPatternDoesntMatch => (), // keep trying
+                            m => return m,
+                        }
+
+                        // skip a whole path component, along with the separator after it
+                        if file.is_empty() ||
+                           (options.require_literal_leading_dot && file.starts_with(".")) {
+                            return SubPatternDoesntMatch;
+                        }
+                        match file.find(is_sep) {
+                            Some(j) => {
+                                prev_char = Some(file.char_at(j));
+                                file = file.slice_from(j + 1);
+                            }
+                            None => {
+                                // a trailing `**` matches all of the last component
+                                return if trailing { Match } else { SubPatternDoesntMatch };
+                            }
+                        }
+                    }
+                }
                 _ => {
                     if file.is_empty() {
                         return EntirePatternDoesntMatch;
@@ -378,7 +451,7 @@ impl Pattern {
                         Char(c2) => {
                             chars_eq(c, c2, options.case_sensitive)
                         }
-                        AnySequence => {
+                        AnySequence | AnyRecursiveSequence => {
                             unreachable!()
                         }
                     };
@@ -400,6 +473,152 @@ impl Pattern {
 
 }
 
+fn tokenize(chars: &[char]) -> ~[PatternToken] {
+    let mut tokens = ~[];
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '?' => {
+                tokens.push(AnyChar);
+                i += 1;
+            }
+            '*' => {
+                let start = i;
+                while i < chars.len() && chars[i] == '*' {
+                    i += 1;
+                }
+                let whole_component = (start == 0 || is_sep(chars[start - 1])) &&
+                                      (i == chars.len() || is_sep(chars[i]));
+                if i - start == 2 && whole_component {
+                    // the separator following the `**` is matched along with it, so that
+                    // `a/**/b` can match `a/b`
+                    if i < chars.len() {
+                        i += 1;
+                    }
+                    tokens.push(AnyRecursiveSequence);
+                } else {
+                    // otherwise *, **, ***, ****, ... are all equivalent
+                    tokens.push(AnySequence);
+                }
+            }
+            '[' => {
+                match parse_brackets(chars, i) {
+                    Some((token, end)) => {
+                        tokens.push(token);
+                        i = end;
+                    }
+                    None => {
+                        // this is not a valid range pattern
+                        tokens.push(Char('['));
+                        i += 1;
+                    }
+                }
+            }
+            c => {
+                tokens.push(Char(c));
+                i += 1;
+            }
+        }
+    }
+
+    tokens
+}
+
+// Parses the `[...]` or `[!...]` starting at `chars[i]`, returning the token and the index
+// just past the closing `]`.
+fn parse_brackets(chars: &[char], i: uint) -> Option<(PatternToken, uint)> {
+    if i + 4 <= chars.len() && chars[i + 1] == '!' {
+        match chars.slice_from(i + 3).position_elem(&']') {
+            None => None,
+            Some(j) => {
+                let cs = parse_char_specifiers(chars.slice(i + 2, i + 3 + j));
+                Some((AnyExcept(cs), i + j + 4))
+            }
+        }
+    } else if i + 3 <= chars.len() && chars[i + 1] != '!' {
+        match chars.slice_from(i + 2).position_elem(&']') {
+            None => None,
+            Some(j) => {
+                let cs = parse_char_specifiers(chars.slice(i + 1, i + 2 + j));
+                Some((AnyWithin(cs), i + j + 3))
+            }
+        }
+    } else {
+        None
+    }
+}
+
+// Expands every `{a,b}` group, giving one pattern for each combination of alternatives.
+fn expand_braces(chars: &[char]) -> ~[~[char]] {
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '[' => {
+                match parse_brackets(chars, i) {
+                    Some((_, end)) => { i = end; continue; }
+                    None => ()
+                }
+            }
+            '{' => {
+                match parse_braces(chars, i) {
+                    Some((alternatives, end)) => {
+                        // braces later on, or nested in an alternative, are expanded when
+                        // recursing
+                        let mut expanded = ~[];
+                        for alternative in alternatives.iter() {
+                            let mut pattern = chars.slice_to(i).to_owned();
+                            pattern.push_all(*alternative);
+                            pattern.push_all(chars.slice_from(end));
+                            expanded.push_all_move(expand_braces(pattern));
+                        }
+                        return expanded;
+                    }
+                    None => ()
+                }
+            }
+            _ => ()
+        }
+        i += 1;
+    }
+    ~[chars.to_owned()]
+}
+
+// Splits the `{...}` starting at `chars[i]` at its top-level commas, returning the
+// alternatives and the index just past the closing `}`.
+fn parse_braces(chars: &[char], i: uint) -> Option<(~[~[char]], uint)> {
+    let mut alternatives = ~[];
+    let mut start = i + 1;
+    let mut depth = 0u;
+    let mut j = i + 1;
+    while j < chars.len() {
+        match chars[j] {
+            '[' => {
+                match parse_brackets(chars, j) {
+                    Some((_, end)) => { j = end; continue; }
+                    None => ()
+                }
+            }
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            '}' => {
+                if alternatives.is_empty() {
+                    return None;
+                }
+                alternatives.push(chars.slice(start, j).to_owned());
+                return Some((alternatives, j + 1));
+            }
+            ',' if depth == 0 => {
+                alternatives.push(chars.slice(start, j).to_owned());
+                start = j + 1;
+            }
+            _ => ()
+        }
+        j += 1;
+    }
+    None
+}
+
 fn parse_char_specifiers(s: &[char]) -> ~[CharSpecifier] {
     let mut cs = ~[];
     let mut i = 0;
@@ -525,8 +744,9 @@ impl MatchOptions {
 
 #[cfg(test)]
 mod test {
-    use std::os;
+    use std::{io, os};
     use super::*;
+    use tempfile::TempDir;
 
     #[test]
     fn test_absolute_pattern() {
@@ -645,6 +865,106 @@ mod test {
         let s = "_[_]_?_*_!_";
         assert_eq!(Pattern::escape(s), ~"_[[]_[]]_[?]_[*]_!_");
         assert!(Pattern::new(Pattern::escape(s)).matches(s));
+
+        let s = "{a,b}";
+        assert_eq!(Pattern::escape(s), ~"[{]a,b[}]");
+        assert!(Pattern::new(Pattern::escape(s)).matches(s));
+    }
+
+    #[test]
+    fn test_recursive_wildcard() {
+        let pat = Pattern::new("some/**/needle.txt");
+        assert!(pat.matches("some/needle.txt"));
+        assert!(pat.matches("some/one/needle.txt"));
+        assert!(pat.matches("some/one/two/needle.txt"));
+        assert!(!pat.matches("some/other/notthis.txt"));
+        assert!(!pat.matches("someneedle.txt"));
+
+        let pat = Pattern::new("**/*.rs");
+        assert!(pat.matches("lib.rs"));
+        assert!(pat.matches("src/lib.rs"));
+        assert!(pat.matches("src/libextra/glob.rs"));
+        assert!(!pat.matches("src/lib.rc"));
+
+        let pat = Pattern::new("src/**");
+        assert!(pat.matches("src/lib.rs"));
+        assert!(pat.matches("src/libextra/glob.rs"));
+        assert!(!pat.matches("src"));
+        assert!(!pat.matches("lib/src/lib.rs"));
+
+        // only a whole path component `**` can match separators
+        let options = MatchOptions {require_literal_separator: true, .. MatchOptions::new()};
+        assert!(!Pattern::new("a**/b").matches_with("a/x/b", options));
+        assert!(Pattern::new("a**/b").matches_with("axx/b", options));
+        assert!(Pattern::new("a/**/b").matches_with("a/x/y/b", options));
+        assert!(Pattern::new("**/*.rs").matches_with("a/x/y.rs", options));
+        assert!(!Pattern::new("*/*.rs").matches_with("a/x/y.rs", options));
+
+        let options = MatchOptions {require_literal_leading_dot: true, .. MatchOptions::new()};
+        assert!(!Pattern::new("a/**/b").matches_with("a/.git/b", options));
+        assert!(Pattern::new("a/**/b").matches_with("a/x/b", options));
+        assert!(Pattern::new("a/**/.b").matches_with("a/x/.b", options));
+        assert!(!Pattern::new("a/**").matches_with("a/x/.b", options));
+        assert!(Pattern::new("a/**").matches("a/x/.b"));
+    }
+
+    #[test]
+    fn test_brace_alternation() {
+        let pat = Pattern::new("*.{rs,rc}");
+        assert!(pat.matches("lib.rs"));
+        assert!(pat.matches("lib.rc"));
+        assert!(!pat.matches("lib.rt"));
+        assert!(!pat.matches("lib.{rs,rc}"));
+
+        let pat = Pattern::new("src/{lib{std,extra},test}/*.rs");
+        assert!(pat.matches("src/libstd/os.rs"));
+        assert!(pat.matches("src/libextra/glob.rs"));
+        assert!(pat.matches("src/test/run.rs"));
+        assert!(!pat.matches("src/lib/os.rs"));
+        assert!(!pat.matches("src/librustc/os.rs"));
+
+        let pat = Pattern::new("a{,b,[xy]?}c");
+        assert!(pat.matches("ac"));
+        assert!(pat.matches("abc"));
+        assert!(pat.matches("axzc"));
+        assert!(!pat.matches("azzc"));
+
+        // braces without a comma, unclosed braces and braces in brackets are literal
+        assert!(Pattern::new("a{b}c").matches("a{b}c"));
+        assert!(Pattern::new("a{}c").matches("a{}c"));
+        assert!(Pattern::new("a{bc").matches("a{bc"));
+        assert!(Pattern::new("a[{]b,c}").matches("a{b,c}"));
+    }
+
+    #[test]
+    fn test_glob_recursive() {
+        let dir = TempDir::new("glob-test").expect("couldn't create temp dir");
+        let root = dir.path();
+        let files = ["a/x.rs", "a/b/y.rs", "a/b/c/z.rs", "a/b/c/z.txt", "a/tmp/w.rs", "d.rs"];
+        for file in files.iter() {
+            let path = root.join(*file);
+            assert!(os::mkdir_recursive(&path.dir_path(), 0x1c0));
+            io::file_writer(&path, [io::Create]).unwrap();
+        }
+
+        // FIXME (#9639): This needs to handle non-utf8 paths
+        let prefix = Pattern::escape(root.as_str().unwrap()) + "/";
+        let found = |pattern: &str| glob(prefix + pattern).to_owned_vec();
+        let paths = |names: &[&str]| names.iter().map(|name| root.join(*name)).to_owned_vec();
+
+        assert_eq!(found("**/*.rs"),
+                   paths(["a/b/c/z.rs", "a/b/y.rs", "a/tmp/w.rs", "a/x.rs", "d.rs"]));
+        assert_eq!(found("a/**"),
+                   paths(["a/b", "a/b/c", "a/b/c/z.rs", "a/b/c/z.txt", "a/b/y.rs",
+                          "a/tmp", "a/tmp/w.rs", "a/x.rs"]));
+        assert_eq!(found("a/**/z.*"), paths(["a/b/c/z.rs", "a/b/c/z.txt"]));
+        assert_eq!(found("{a/b,a/tmp}/*.rs"), paths(["a/b/y.rs", "a/tmp/w.rs"]));
+        // a path matching several alternatives is only produced once
+        assert_eq!(found("a/{x,*}.rs"), paths(["a/x.rs"]));
+
+        let excluded = glob(prefix + "**/*.rs").exclude(prefix + "a/tmp")
+                                               .exclude(prefix + "**/c").to_owned_vec();
+        assert_eq!(excluded, paths(["a/b/y.rs", "a/x.rs", "d.rs"]));
     }
 
     #[test]
@@ -0,0 +1,257 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * HyperLogLog cardinality estimation.
+ *
+ * A `HyperLogLog` estimates the number of distinct items it has seen using
+ * `2^precision` one-byte registers, with a standard error of roughly
+ * `1.04 / sqrt(2^precision)`. Sketches built with the same precision and
+ * hash keys can be merged, which makes them convenient for counting
+ * distinct values across shards, and can be serialized with any
+ * `serialize::Encoder`.
+ *
+ * See: Flajolet et al., "HyperLogLog: the analysis of a near-optimal
+ * cardinality estimation algorithm" (2007).
+ */
+
+use std::rand;
+use std::rand::Rng;
+use std::vec;
+
+/// The smallest precision accepted by `HyperLogLog::new`
+pub static MIN_PRECISION: uint = 4;
+/// The largest precision accepted by `HyperLogLog::new`
+pub static MAX_PRECISION: uint = 16;
+
+/// A HyperLogLog cardinality estimator
+#[deriving(Clone, Eq, Encodable, Decodable)]
+pub struct HyperLogLog {
+    priv precision: uint,
+    priv k0: u64,
+    priv k1: u64,
+    priv registers: ~[u8]
+}
+
+impl HyperLogLog {
+    /**
+     * Create an empty sketch with `2^precision` registers and random hash
+     * keys. `precision` must be between `MIN_PRECISION` and
+     * `MAX_PRECISION`.
+     */
+    pub fn new(precision: uint) -> HyperLogLog {
+        let mut rng = rand::task_rng();
+        HyperLogLog::with_keys(precision, rng.gen(), rng.gen())
+    }
+
+    /**
+     * Create an empty sketch using the SipHash keys `k0` and `k1`. Sketches
+     * must share keys (and precision) to be merged.
+     */
+    pub fn with_keys(precision: uint, k0: u64, k1: u64) -> HyperLogLog {
+        assert!(precision >= MIN_PRECISION && precision <= MAX_PRECISION);
+        HyperLogLog {
+            precision: precision,
+            k0: k0,
+            k1: k1,
+            registers: vec::from_elem(1 << precision, 0u8)
+        }
+    }
+
+    /// The number of index bits used by this sketch
+    pub fn precision(&self) -> uint { self.precision }
+
+    /// Record an occurrence of `item`
+    pub fn insert<T: IterBytes>(&mut self, item: &T) {
+        let h = item.hash_keyed(self.k0, self.k1);
+        let p = self.precision;
+        let idx = (h >> (64 - p)) as uint;
+        // Rank of the first set bit in the remaining 64 - p bits. The
+        // sentinel bit bounds the rank when they are all zero.
+        let w = (h << p) | (1 << (p - 1));
+        let rank = w.leading_zeros() as u8 + 1;
+        if rank > self.registers[idx] {
+            self.registers[idx] = rank;
+        }
+    }
+
+    /// Estimate the number of distinct items inserted so far
+    pub fn cardinality(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m)
+        };
+
+        let mut sum = 0.0;
+        let mut zeros = 0u;
+        for &r in self.registers.iter() {
+            sum += 1.0 / ((1u64 << r) as f64);
+            if r == 0 { zeros += 1; }
+        }
+
+        let estimate = alpha * m * m / sum;
+        if estimate <= 2.5 * m && zeros != 0 {
+            // Small range correction: linear counting is more accurate
+            // while many registers are still empty.
+            m * (m / (zeros as f64)).ln()
+        } else {
+            estimate
+        }
+    }
+
+    /// Returns `true` if nothing has been inserted
+    pub fn is_empty(&self) -> bool {
+        self.registers.iter().all(|&r| r == 0)
+    }
+
+    /// Reset the sketch to its empty state
+    pub fn clear(&mut self) {
+        for r in self.registers.mut_iter() {
+            *r = 0;
+        }
+    }
+
+    /**
+     * Merge `other` into `self`, so that `self` estimates the cardinality
+     * of the union of both inputs. Both sketches must have the same
+     * precision and hash keys.
+     */
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        if self.precision != other.precision ||
+           self.k0 != other.k0 || self.k1 != other.k1 {
+            fail!("Tried to merge HyperLogLogs with different parameters");
+        }
+        for (a, &b) in self.registers.mut_iter().zip(other.registers.iter()) {
+            if b > *a {
+                *a = b;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use ebml;
+    use json;
+    use serialize::{Encodable, Decodable};
+    use extra::test::BenchHarness;
+
+    fn assert_close(estimate: f64, actual: uint, tolerance: f64) {
+        let err = (estimate - actual as f64).abs() / (actual as f64);
+        assert!(err < tolerance,
+                format!("estimate {} too far from {}", estimate, actual));
+    }
+
+    #[test]
+    fn test_empty() {
+        let h = HyperLogLog::new(10);
+        assert!(h.is_empty());
+        assert_eq!(h.cardinality(), 0.0);
+    }
+
+    #[test]
+    fn test_small() {
+        let mut h = HyperLogLog::new(12);
+        for i in range(0u, 100) {
+            h.insert(&i);
+            h.insert(&i);
+        }
+        assert_close(h.cardinality(), 100, 0.05);
+    }
+
+    #[test]
+    fn test_large() {
+        let mut h = HyperLogLog::with_keys(14, 7, 11);
+        for i in range(0u, 100000) {
+            h.insert(&i);
+        }
+        // Standard error at p = 14 is about 0.8%.
+        assert_close(h.cardinality(), 100000, 0.05);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = HyperLogLog::with_keys(12, 1, 2);
+        let mut b = HyperLogLog::with_keys(12, 1, 2);
+        for i in range(0u, 5000) {
+            a.insert(&i);
+        }
+        for i in range(2500u, 7500) {
+            b.insert(&i);
+        }
+        a.merge(&b);
+        assert_close(a.cardinality(), 7500, 0.1);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_merge_incompatible() {
+        let mut a = HyperLogLog::with_keys(12, 1, 2);
+        let b = HyperLogLog::with_keys(10, 1, 2);
+        a.merge(&b);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut h = HyperLogLog::new(4);
+        h.insert(&~"a");
+        assert!(!h.is_empty());
+        h.clear();
+        assert!(h.is_empty());
+    }
+
+    #[test]
+    fn test_ebml_round_trip() {
+        let mut h = HyperLogLog::new(8);
+        for i in range(0u, 300) {
+            h.insert(&i);
+        }
+
+        let bytes = do io::with_bytes_writer |wr| {
+            let mut encoder = ebml::writer::Encoder(wr);
+            h.encode(&mut encoder);
+        };
+        let mut decoder = ebml::reader::Decoder(ebml::reader::Doc(@bytes));
+        let h2: HyperLogLog = Decodable::decode(&mut decoder);
+        assert!(h == h2);
+        assert_eq!(h.cardinality(), h2.cardinality());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        // JSON numbers are doubles, so use keys that survive the trip.
+        let mut h = HyperLogLog::with_keys(4, 1, 2);
+        h.insert(&1);
+        h.insert(&2);
+
+        let s = do io::with_str_writer |wr| {
+            let mut encoder = json::Encoder(wr);
+            h.encode(&mut encoder);
+        };
+        let mut decoder = json::Decoder(json::from_str(s).unwrap());
+        let h2: HyperLogLog = Decodable::decode(&mut decoder);
+        assert!(h == h2);
+    }
+
+    #[bench]
+    fn bench_insert(bh: &mut BenchHarness) {
+        let mut h = HyperLogLog::new(14);
+        let mut i = 0u;
+        do bh.iter {
+            h.insert(&i);
+            i += 1;
+        }
+    }
+}
@@ -476,6 +476,239 @@ impl Json{
     pub fn to_pretty_str(&self) -> ~str {
         io::with_str_writer(|wr| self.to_pretty_writer(wr))
     }
+
+    /// If the Json value is an Object, returns the value associated with
+    /// the provided key. Otherwise, returns None.
+    pub fn find<'a>(&'a self, key: &str) -> Option<&'a Json> {
+        match *self {
+            Object(ref map) => map.find(&key.to_owned()),
+            _ => None
+        }
+    }
+
+    /// Like `find`, but returns a mutable reference.
+    pub fn find_mut<'a>(&'a mut self, key: &str) -> Option<&'a mut Json> {
+        match *self {
+            Object(ref mut map) => map.find_mut(&key.to_owned()),
+            _ => None
+        }
+    }
+
+    /// Follows a path of object keys from this value, e.g.
+    /// `json.find_path(["a", "b"])` is `json["a"]["b"]` in Javascript.
+    /// Returns None if any key along the path is missing.
+    pub fn find_path<'a>(&'a self, keys: &[&str]) -> Option<&'a Json> {
+        if keys.is_empty() { return Some(self); }
+        match self.find(keys[0]) {
+            Some(child) => child.find_path(keys.slice_from(1)),
+            None => None
+        }
+    }
+
+    /// Like `find_path`, but returns a mutable reference.
+    pub fn find_path_mut<'a>(&'a mut self, keys: &[&str]) -> Option<&'a mut Json> {
+        if keys.is_empty() { return Some(self); }
+        match self.find_mut(keys[0]) {
+            Some(child) => child.find_path_mut(keys.slice_from(1)),
+            None => None
+        }
+    }
+
+    /// Searches this value and everything nested within it, depth first,
+    /// for an object containing `key`, returning the first associated
+    /// value found.
+    pub fn search<'a>(&'a self, key: &str) -> Option<&'a Json> {
+        match *self {
+            Object(ref map) => {
+                match map.find(&key.to_owned()) {
+                    Some(v) => return Some(v),
+                    None => ()
+                }
+                for (_, v) in map.iter() {
+                    match v.search(key) {
+                        Some(found) => return Some(found),
+                        None => ()
+                    }
+                }
+                None
+            }
+            List(ref list) => {
+                for v in list.iter() {
+                    match v.search(key) {
+                        Some(found) => return Some(found),
+                        None => ()
+                    }
+                }
+                None
+            }
+            _ => None
+        }
+    }
+
+    /// Looks up a value by an RFC 6901 JSON Pointer, such as `/a/0/b`.
+    /// The empty pointer refers to the whole value. Returns None if the
+    /// pointer is malformed or does not refer to an existing value.
+    pub fn pointer<'a>(&'a self, pointer: &str) -> Option<&'a Json> {
+        match parse_pointer(pointer) {
+            Some(tokens) => self.pointer_tokens(tokens),
+            None => None
+        }
+    }
+
+    /// Like `pointer`, but returns a mutable reference.
+    pub fn pointer_mut<'a>(&'a mut self, pointer: &str) -> Option<&'a mut Json> {
+        match parse_pointer(pointer) {
+            Some(tokens) => self.pointer_tokens_mut(tokens),
+            None => None
+        }
+    }
+
+    fn pointer_tokens<'a>(&'a self, tokens: &[~str]) -> Option<&'a Json> {
+        if tokens.is_empty() { return Some(self); }
+        let child = match *self {
+            Object(ref map) => map.find(&tokens[0]),
+            List(ref list) => match pointer_index(tokens[0]) {
+                Some(i) if i < list.len() => Some(&list[i]),
+                _ => None
+            },
+            _ => None
+        };
+        match child {
+            Some(child) => child.pointer_tokens(tokens.slice_from(1)),
+            None => None
+        }
+    }
+
+    fn pointer_tokens_mut<'a>(&'a mut self, tokens: &[~str]) -> Option<&'a mut Json> {
+        if tokens.is_empty() { return Some(self); }
+        let child = match *self {
+            Object(ref mut map) => map.find_mut(&tokens[0]),
+            List(ref mut list) => match pointer_index(tokens[0]) {
+                Some(i) if i < list.len() => Some(&mut list[i]),
+                _ => None
+            },
+            _ => None
+        };
+        match child {
+            Some(child) => child.pointer_tokens_mut(tokens.slice_from(1)),
+            None => None
+        }
+    }
+
+    /// Returns true if the Json value is a Number.
+    pub fn is_number(&self) -> bool { self.as_f64().is_some() }
+
+    /// Returns true if the Json value is a String.
+    pub fn is_string(&self) -> bool { self.as_str().is_some() }
+
+    /// Returns true if the Json value is a Boolean.
+    pub fn is_boolean(&self) -> bool { self.as_bool().is_some() }
+
+    /// Returns true if the Json value is a List.
+    pub fn is_list(&self) -> bool { self.as_list().is_some() }
+
+    /// Returns true if the Json value is an Object.
+    pub fn is_object(&self) -> bool { self.as_object().is_some() }
+
+    /// Returns true if the Json value is Null.
+    pub fn is_null(&self) -> bool {
+        match *self { Null => true, _ => false }
+    }
+
+    /// If the Json value is a Number, returns it. Otherwise, returns None.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self { Number(n) => Some(n), _ => None }
+    }
+
+    /// If the Json value is a String, returns it. Otherwise, returns None.
+    pub fn as_str<'a>(&'a self) -> Option<&'a str> {
+        match *self { String(ref s) => Some(s.as_slice()), _ => None }
+    }
+
+    /// If the Json value is a Boolean, returns it. Otherwise, returns None.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self { Boolean(b) => Some(b), _ => None }
+    }
+
+    /// If the Json value is a List, returns it. Otherwise, returns None.
+    pub fn as_list<'a>(&'a self) -> Option<&'a List> {
+        match *self { List(ref list) => Some(list), _ => None }
+    }
+
+    /// Like `as_list`, but returns a mutable reference.
+    pub fn as_list_mut<'a>(&'a mut self) -> Option<&'a mut List> {
+        match *self { List(ref mut list) => Some(list), _ => None }
+    }
+
+    /// If the Json value is an Object, returns it. Otherwise, returns None.
+    pub fn as_object<'a>(&'a self) -> Option<&'a Object> {
+        match *self { Object(ref map) => Some(&**map), _ => None }
+    }
+
+    /// Like `as_object`, but returns a mutable reference.
+    pub fn as_object_mut<'a>(&'a mut self) -> Option<&'a mut Object> {
+        match *self { Object(ref mut map) => Some(&mut **map), _ => None }
+    }
+
+    /// Applies an RFC 7386 JSON merge patch to this value in place: keys
+    /// in an object patch are merged recursively, keys whose patch value
+    /// is Null are removed, and any other patch replaces the value.
+    pub fn merge_patch(&mut self, patch: &Json) {
+        match *patch {
+            Object(ref patch_map) => {
+                if !self.is_object() {
+                    *self = Object(~TreeMap::new());
+                }
+                let map = self.as_object_mut().unwrap();
+                for (key, value) in patch_map.iter() {
+                    if value.is_null() {
+                        map.pop(key);
+                    } else {
+                        if !map.contains_key(key) {
+                            map.insert(key.clone(), Null);
+                        }
+                        map.find_mut(key).unwrap().merge_patch(value);
+                    }
+                }
+            }
+            _ => *self = patch.clone()
+        }
+    }
+}
+
+/// Splits an RFC 6901 JSON Pointer into its unescaped reference tokens.
+fn parse_pointer(pointer: &str) -> Option<~[~str]> {
+    if pointer.is_empty() { return Some(~[]); }
+    if !pointer.starts_with("/") { return None; }
+
+    let mut tokens = ~[];
+    for token in pointer.slice_from(1).split_iter('/') {
+        let mut res = ~"";
+        let mut chars = token.iter();
+        loop {
+            match chars.next() {
+                None => break,
+                Some('~') => match chars.next() {
+                    Some('0') => res.push_char('~'),
+                    Some('1') => res.push_char('/'),
+                    _ => return None
+                },
+                Some(c) => res.push_char(c)
+            }
+        }
+        tokens.push(res);
+    }
+    Some(tokens)
+}
+
+/// Parses a JSON Pointer token used to index a list. Leading zeros are
+/// not allowed.
+fn pointer_index(token: &str) -> Option<uint> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with("0")) {
+        return None;
+    }
+    if !token.iter().all(|c| c.is_digit()) { return None; }
+    from_str(token)
 }
 
 pub struct Parser<T> {
@@ -799,91 +1032,926 @@ impl<T : Iterator<char>> Parser<T> {
               ']' => { self.bump(); return Ok(List(values)); }
               _ => return self.error(~"expected `,` or `]`")
             }
-        };
-    }
-
-    fn parse_object(&mut self) -> Result<Json, Error> {
-        self.bump();
-        self.parse_whitespace();
+        };
+    }
+
+    fn parse_object(&mut self) -> Result<Json, Error> {
+        self.bump();
+        self.parse_whitespace();
+
+        let mut values = ~TreeMap::new();
+
+        if self.ch == '}' {
+          self.bump();
+          return Ok(Object(values));
+        }
+
+        while !self.eof() {
+            self.parse_whitespace();
+
+            if self.ch != '"' {
+                return self.error(~"key must be a string");
+            }
+
+            let key = match self.parse_str() {
+              Ok(key) => key,
+              Err(e) => return Err(e)
+            };
+
+            self.parse_whitespace();
+
+            if self.ch != ':' {
+                if self.eof() { break; }
+                return self.error(~"expected `:`");
+            }
+            self.bump();
+
+            match self.parse_value() {
+              Ok(value) => { values.insert(key, value); }
+              Err(e) => return Err(e)
+            }
+            self.parse_whitespace();
+
+            match self.ch {
+              ',' => self.bump(),
+              '}' => { self.bump(); return Ok(Object(values)); }
+              _ => {
+                  if self.eof() { break; }
+                  return self.error(~"expected `,` or `}`");
+              }
+            }
+        }
+
+        return self.error(~"EOF while parsing object");
+    }
+}
+
+/// Decodes a json value from an @io::Reader
+pub fn from_reader(rdr: @io::Reader) -> Result<Json, Error> {
+    let s = str::from_utf8(rdr.read_whole_stream());
+    let mut parser = Parser(~s.iter());
+    parser.parse()
+}
+
+/// Decodes a json value from a string
+pub fn from_str(s: &str) -> Result<Json, Error> {
+    let mut parser = Parser(~s.iter());
+    parser.parse()
+}
+
+/// The events produced by a `StreamingParser`
+#[deriving(Eq)]
+pub enum JsonEvent {
+    /// The start of an object, `{`
+    ObjectStart,
+    /// The end of an object, `}`
+    ObjectEnd,
+    /// The start of a list, `[`
+    ListStart,
+    /// The end of a list, `]`
+    ListEnd,
+    /// An object key; the next event is its value
+    Key(~str),
+    /// A scalar value: a `Number`, `String`, `Boolean` or `Null`
+    Value(Json),
+}
+
+#[deriving(Eq)]
+enum ParserState {
+    // Expecting the top-level value.
+    ParseStart,
+    // Expecting a value, e.g. after an object key.
+    ParseValue,
+    // Just after `[`: expecting a value or `]`.
+    ParseListFirst,
+    // After a list element: expecting `,` or `]`.
+    ParseListNext,
+    // Just after `{`: expecting a key or `}`.
+    ParseObjectFirst,
+    // After an object value: expecting `,` or `}`.
+    ParseObjectNext,
+    // After the top-level value: expecting EOF.
+    ParseFinished,
+    // Everything has been returned, or an error occurred.
+    ParseDone,
+}
+
+#[deriving(Eq)]
+enum ContainerKind {
+    InList,
+    InObject,
+}
+
+/**
+ * A pull parser that yields a `JsonEvent` at a time instead of building a
+ * `Json` tree, so arbitrarily large documents can be processed in constant
+ * memory (apart from the nesting depth and the size of individual
+ * strings).
+ *
+ * The parser is an iterator over `Result<JsonEvent, Error>`. Iteration
+ * stops after the first error, which carries the line and column at which
+ * it occurred.
+ */
+pub struct StreamingParser<T> {
+    priv parser: Parser<T>,
+    priv state: ParserState,
+    priv stack: ~[ContainerKind],
+}
+
+/// Creates a streaming parser reading from an `Iterator<char>`
+pub fn StreamingParser<T : Iterator<char>>(rdr: ~T) -> StreamingParser<T> {
+    StreamingParser {
+        parser: Parser(rdr),
+        state: ParseStart,
+        stack: ~[],
+    }
+}
+
+impl<T : Iterator<char>> StreamingParser<T> {
+    /// The number of objects and lists that are currently open
+    pub fn depth(&self) -> uint { self.stack.len() }
+
+    fn error(&self, msg: ~str) -> Result<JsonEvent, Error> {
+        self.parser.error(msg)
+    }
+
+    fn after_value(&mut self) {
+        self.state = match self.stack.last_opt() {
+            None => ParseFinished,
+            Some(&InList) => ParseListNext,
+            Some(&InObject) => ParseObjectNext,
+        };
+    }
+
+    fn end_container(&mut self, event: JsonEvent) -> Result<JsonEvent, Error> {
+        self.parser.bump();
+        self.stack.pop();
+        self.after_value();
+        Ok(event)
+    }
+
+    fn parse_value_event(&mut self) -> Result<JsonEvent, Error> {
+        self.parser.parse_whitespace();
+        if self.parser.eof() { return self.error(~"EOF while parsing value"); }
+
+        match self.parser.ch {
+          '[' => {
+            self.parser.bump();
+            self.stack.push(InList);
+            self.state = ParseListFirst;
+            Ok(ListStart)
+          }
+          '{' => {
+            self.parser.bump();
+            self.stack.push(InObject);
+            self.state = ParseObjectFirst;
+            Ok(ObjectStart)
+          }
+          _ => {
+            // Everything else is a scalar, which the tree parser already
+            // knows how to read.
+            match self.parser.parse_value() {
+              Ok(value) => { self.after_value(); Ok(Value(value)) }
+              Err(e) => Err(e)
+            }
+          }
+        }
+    }
+
+    fn parse_key_event(&mut self) -> Result<JsonEvent, Error> {
+        self.parser.parse_whitespace();
+
+        if self.parser.ch != '"' {
+            if self.parser.eof() {
+                return self.error(~"EOF while parsing object");
+            }
+            return self.error(~"key must be a string");
+        }
+
+        let key = match self.parser.parse_str() {
+          Ok(key) => key,
+          Err(e) => return Err(e)
+        };
+
+        self.parser.parse_whitespace();
+        if self.parser.ch != ':' {
+            if self.parser.eof() {
+                return self.error(~"EOF while parsing object");
+            }
+            return self.error(~"expected `:`");
+        }
+        self.parser.bump();
+
+        self.state = ParseValue;
+        Ok(Key(key))
+    }
+}
+
+impl<T : Iterator<char>> Iterator<Result<JsonEvent, Error>> for StreamingParser<T> {
+    fn next(&mut self) -> Option<Result<JsonEvent, Error>> {
+        let result = match self.state {
+          ParseDone => return None,
+          ParseStart | ParseValue => self.parse_value_event(),
+          ParseListFirst => {
+            self.parser.parse_whitespace();
+            if self.parser.ch == ']' {
+                self.end_container(ListEnd)
+            } else {
+                self.parse_value_event()
+            }
+          }
+          ParseListNext => {
+            self.parser.parse_whitespace();
+            match self.parser.ch {
+              ',' => { self.parser.bump(); self.parse_value_event() }
+              ']' => self.end_container(ListEnd),
+              _ if self.parser.eof() => self.error(~"EOF while parsing list"),
+              _ => self.error(~"expected `,` or `]`")
+            }
+          }
+          ParseObjectFirst => {
+            self.parser.parse_whitespace();
+            if self.parser.ch == '}' {
+                self.end_container(ObjectEnd)
+            } else {
+                self.parse_key_event()
+            }
+          }
+          ParseObjectNext => {
+            self.parser.parse_whitespace();
+            match self.parser.ch {
+              ',' => { self.parser.bump(); self.parse_key_event() }
+              '}' => self.end_container(ObjectEnd),
+              _ if self.parser.eof() => self.error(~"EOF while parsing object"),
+              _ => self.error(~"expected `,` or `}`")
+            }
+          }
+          ParseFinished => {
+            self.parser.parse_whitespace();
+            if self.parser.eof() {
+                self.state = ParseDone;
+                return None;
+            }
+            self.error(~"trailing characters")
+          }
+        };
+
+        if result.is_err() {
+            self.state = ParseDone;
+        }
+        Some(result)
+    }
+}
+
+/// An `Iterator<char>` over the contents of an `@io::Reader`
+pub struct ReaderCharIterator {
+    priv rdr: @io::Reader,
+}
+
+impl Iterator<char> for ReaderCharIterator {
+    fn next(&mut self) -> Option<char> {
+        let ch = self.rdr.read_char();
+        // FIXME: #8971: unsound
+        if ch == unsafe { transmute(-1u32) } { None } else { Some(ch) }
+    }
+}
+
+/// Creates a streaming parser reading incrementally from an @io::Reader
+pub fn stream_from_reader(rdr: @io::Reader) -> StreamingParser<ReaderCharIterator> {
+    StreamingParser(~ReaderCharIterator { rdr: rdr })
+}
+
+struct StructFrame {
+    // Fields that were read ahead of the one being decoded, as events.
+    buffered: ~[(~str, ~[JsonEvent])],
+    // Whether the closing `}` has already been consumed.
+    ended: bool,
+}
+
+/**
+ * A structure to decode JSON to values in rust directly from a
+ * `StreamingParser`, without building an intermediate `Json` tree.
+ *
+ * Struct fields are matched by name, so they may appear in any order;
+ * fields that arrive before they are needed are held as events until
+ * they are read. Lists and maps must be buffered the same way because
+ * `Decodable` needs their length up front; use `each_elt` to process a
+ * top-level list one element at a time instead.
+ *
+ * Errors are raised on `serialize::decode_error` like `Decoder`. Once a
+ * value has the wrong shape, or the stream is malformed, the rest of that
+ * value is decoded from `null`s.
+ */
+pub struct StreamingDecoder<T> {
+    priv parser: StreamingParser<T>,
+    // Events to return before reading more from the parser, in reverse.
+    priv replay: ~[JsonEvent],
+    priv frames: ~[StructFrame],
+    priv path: ~[~str],
+    priv map_key: Option<~str>,
+    // Non-zero while decoding a value that could not be read from the
+    // stream; every read then sees `null`.
+    priv substituting: uint,
+    // Set once the parser has reported an error or run out of input.
+    priv failed: bool,
+}
+
+/// Creates a new decoder pulling events from the specified parser.
+pub fn StreamingDecoder<T : Iterator<char>>(parser: StreamingParser<T>)
+                                            -> StreamingDecoder<T> {
+    StreamingDecoder {
+        parser: parser,
+        replay: ~[],
+        frames: ~[],
+        path: ~[],
+        map_key: None,
+        substituting: 0,
+        failed: false,
+    }
+}
+
+/// A short description of a parser event, for error messages.
+fn event_kind(event: &JsonEvent) -> ~str {
+    match *event {
+        ObjectStart => ~"object",
+        ObjectEnd => ~"end of object",
+        ListStart => ~"list",
+        ListEnd => ~"end of list",
+        Key(ref k) => format!("key {}", escape_str(*k)),
+        Value(ref v) => json_kind(v),
+    }
+}
+
+impl<T : Iterator<char>> StreamingDecoder<T> {
+    fn next_event(&mut self) -> JsonEvent {
+        if self.substituting > 0 { return Value(Null); }
+        match self.replay.pop_opt() {
+            Some(event) => return event,
+            None => ()
+        }
+        if self.failed { return Value(Null); }
+        match self.parser.next() {
+            Some(Ok(event)) => event,
+            Some(Err(e)) => {
+                self.failed = true;
+                self.error(serialize::SyntaxError(e.to_str()));
+                Value(Null)
+            }
+            None => {
+                self.failed = true;
+                self.error(serialize::SyntaxError(~"unexpected end of JSON stream"));
+                Value(Null)
+            }
+        }
+    }
+
+    // Whether `next_event` can only return made-up `null`s from now on.
+    fn exhausted(&self) -> bool {
+        self.substituting > 0 || (self.failed && self.replay.is_empty())
+    }
+
+    fn unread(&mut self, event: JsonEvent) {
+        self.replay.push(event);
+    }
+
+    fn unread_all(&mut self, events: ~[JsonEvent]) {
+        for event in events.move_rev_iter() {
+            self.replay.push(event);
+        }
+    }
+
+    fn error(&self, kind: serialize::DecoderError) {
+        serialize::decode_error::cond.raise(serialize::DecodeError {
+            path: path_str(self.path),
+            kind: kind,
+        })
+    }
+
+    // Reports `event` as unexpected and skips over the rest of the value
+    // it starts, so that decoding stays in step with the stream.
+    fn mismatch(&mut self, expected: &str, event: JsonEvent) {
+        // Errors after a substitution or a syntax error are just noise.
+        if self.substituting == 0 && !self.failed {
+            self.error(serialize::ExpectedError(expected.to_owned(), event_kind(&event)));
+        }
+        match event {
+            ObjectStart | ListStart => {
+                self.unread(event);
+                self.capture_value();
+            }
+            _ => ()
+        }
+    }
+
+    fn expect(&mut self, expected: JsonEvent, desc: &str) -> bool {
+        let event = self.next_event();
+        if event == expected {
+            true
+        } else {
+            self.mismatch(desc, event);
+            false
+        }
+    }
+
+    // Consumes events up to and including `end`, which closes the
+    // container currently being read.
+    fn skip_to(&mut self, end: JsonEvent) {
+        while !self.exhausted() {
+            let event = self.next_event();
+            if event == end { return; }
+            match event {
+                ObjectStart | ListStart => {
+                    self.unread(event);
+                    self.capture_value();
+                }
+                _ => ()
+            }
+        }
+    }
+
+    // Runs `f` with every read returning `null`.
+    fn substitute<U>(&mut self, f: &fn(&mut StreamingDecoder<T>) -> U) -> U {
+        self.substituting += 1;
+        let value = f(self);
+        self.substituting -= 1;
+        value
+    }
+
+    fn with_path<U>(&mut self, elem: ~str, f: &fn(&mut StreamingDecoder<T>) -> U) -> U {
+        self.path.push(elem);
+        let value = f(self);
+        self.path.pop();
+        value
+    }
+
+    fn read_scalar(&mut self, expected: &str) -> Json {
+        match self.next_event() {
+            Value(value) => value,
+            event => { self.mismatch(expected, event); Null }
+        }
+    }
+
+    // Reads the events making up one complete value.
+    fn capture_value(&mut self) -> ~[JsonEvent] {
+        let mut events = ~[];
+        let mut depth = 0u;
+        loop {
+            if depth > 0 && self.exhausted() { return events; }
+            let event = self.next_event();
+            match event {
+                ObjectStart | ListStart => depth += 1,
+                ObjectEnd | ListEnd => depth -= 1,
+                _ => ()
+            }
+            let done = depth == 0 && match event { Key(_) => false, _ => true };
+            events.push(event);
+            if done { return events; }
+        }
+    }
+
+    /**
+     * Decodes each element of a list, which must be the next value in the
+     * stream, passing them to `f` one at a time. Unlike decoding a `~[T]`,
+     * the list is never held in memory. Stops early and returns `false` if
+     * `f` does.
+     */
+    pub fn each_elt<D: serialize::Decodable<StreamingDecoder<T>>>(
+            &mut self, f: &fn(D) -> bool) -> bool {
+        if !self.expect(ListStart, "a list") { return true; }
+        let mut idx = 0u;
+        while !self.exhausted() {
+            match self.next_event() {
+                ListEnd => break,
+                event => {
+                    self.unread(event);
+                    let value: D = do self.with_path(format!("[{}]", idx)) |d| {
+                        serialize::Decodable::decode(d)
+                    };
+                    if !f(value) { return false; }
+                    idx += 1;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl<T : Iterator<char>> serialize::Decoder for StreamingDecoder<T> {
+    fn read_nil(&mut self) -> () {
+        debug!("read_nil");
+        match self.read_scalar("null") {
+            Null => (),
+            value => self.mismatch("null", Value(value))
+        }
+    }
+
+    fn read_u64(&mut self)  -> u64  { self.read_f64() as u64 }
+    fn read_u32(&mut self)  -> u32  { self.read_f64() as u32 }
+    fn read_u16(&mut self)  -> u16  { self.read_f64() as u16 }
+    fn read_u8 (&mut self)  -> u8   { self.read_f64() as u8 }
+    fn read_uint(&mut self) -> uint { self.read_f64() as uint }
+
+    fn read_i64(&mut self) -> i64 { self.read_f64() as i64 }
+    fn read_i32(&mut self) -> i32 { self.read_f64() as i32 }
+    fn read_i16(&mut self) -> i16 { self.read_f64() as i16 }
+    fn read_i8 (&mut self) -> i8  { self.read_f64() as i8 }
+    fn read_int(&mut self) -> int { self.read_f64() as int }
+
+    fn read_bool(&mut self) -> bool {
+        debug!("read_bool");
+        match self.next_event() {
+            Value(Boolean(b)) => b,
+            event => { self.mismatch("a boolean", event); false }
+        }
+    }
+
+    fn read_f64(&mut self) -> f64 {
+        debug!("read_f64");
+        match self.next_event() {
+            Value(Number(f)) => f,
+            event => { self.mismatch("a number", event); 0.0 }
+        }
+    }
+    fn read_f32(&mut self) -> f32 { self.read_f64() as f32 }
+
+    fn read_char(&mut self) -> char {
+        match self.next_event() {
+            Value(String(s)) => {
+                if s.char_len() == 1 { return s.char_at(0); }
+                self.mismatch("a single character string", Value(String(s)));
+            }
+            event => self.mismatch("a single character string", event)
+        }
+        '\x00'
+    }
+
+    fn read_str(&mut self) -> ~str {
+        debug!("read_str");
+        match self.next_event() {
+            // Map keys are decoded as strings.
+            Key(s) | Value(String(s)) => s,
+            event => { self.mismatch("a string", event); ~"" }
+        }
+    }
+
+    fn read_enum<U>(&mut self, name: &str, f: &fn(&mut StreamingDecoder<T>) -> U) -> U {
+        debug!("read_enum({})", name);
+        f(self)
+    }
+
+    fn read_enum_variant<U>(&mut self,
+                            names: &[&str],
+                            f: &fn(&mut StreamingDecoder<T>, uint) -> U)
+                            -> U {
+        debug!("read_enum_variant(names={:?})", names);
+        // Variants with fields are encoded as
+        // {"variant": "Name", "fields": [...]}, in that order.
+        let (name, has_fields) = match self.next_event() {
+            Value(String(s)) => (s, false),
+            ObjectStart => {
+                if !self.expect(Key(~"variant"), "key \"variant\"") {
+                    self.skip_to(ObjectEnd);
+                    return self.substitute(|d| f(d, 0));
+                }
+                let name = self.read_str();
+                if !self.expect(Key(~"fields"), "key \"fields\"") ||
+                   !self.expect(ListStart, "a list of fields") {
+                    self.skip_to(ObjectEnd);
+                    return self.substitute(|d| f(d, 0));
+                }
+                (name, true)
+            }
+            event => {
+                self.mismatch("an enum variant", event);
+                return self.substitute(|d| f(d, 0));
+            }
+        };
+        let idx = match names.iter().position(|n| str::eq_slice(*n, name)) {
+            Some(idx) => idx,
+            None => {
+                self.error(serialize::UnknownVariantError(name));
+                if has_fields {
+                    self.skip_to(ListEnd);
+                    self.skip_to(ObjectEnd);
+                }
+                return self.substitute(|d| f(d, 0));
+            }
+        };
+        let value = f(self, idx);
+        if has_fields {
+            if !self.expect(ListEnd, "the end of the fields") {
+                self.skip_to(ListEnd);
+            }
+            if !self.expect(ObjectEnd, "the end of the variant") {
+                self.skip_to(ObjectEnd);
+            }
+        }
+        value
+    }
+
+    fn read_enum_variant_arg<U>(&mut self,
+                                idx: uint,
+                                f: &fn(&mut StreamingDecoder<T>) -> U)
+                                -> U {
+        debug!("read_enum_variant_arg(idx={})", idx);
+        self.with_path(format!("[{}]", idx), f)
+    }
+
+    fn read_enum_struct_variant<U>(&mut self,
+                                   names: &[&str],
+                                   f: &fn(&mut StreamingDecoder<T>, uint) -> U)
+                                   -> U {
+        debug!("read_enum_struct_variant(names={:?})", names);
+        self.read_enum_variant(names, f)
+    }
+
+    fn read_enum_struct_variant_field<U>(&mut self,
+                                         name: &str,
+                                         idx: uint,
+                                         f: &fn(&mut StreamingDecoder<T>) -> U)
+                                         -> U {
+        debug!("read_enum_struct_variant_field(name={}, idx={})", name, idx);
+        self.with_path(format!(".{}", name), f)
+    }
+
+    fn read_struct<U>(&mut self,
+                      name: &str,
+                      len: uint,
+                      f: &fn(&mut StreamingDecoder<T>) -> U)
+                      -> U {
+        debug!("read_struct(name={}, len={})", name, len);
+        match self.next_event() {
+            ObjectStart => (),
+            event => {
+                self.mismatch("an object", event);
+                return self.substitute(f);
+            }
+        }
+        self.frames.push(StructFrame { buffered: ~[], ended: false });
+        let value = f(self);
+        let frame = self.frames.pop();
+        if !frame.ended {
+            // Skip any fields the struct doesn't know about.
+            self.skip_to(ObjectEnd);
+        }
+        value
+    }
+
+    fn read_struct_field<U>(&mut self,
+                            name: &str,
+                            idx: uint,
+                            f: &fn(&mut StreamingDecoder<T>) -> U)
+                            -> U {
+        debug!("read_struct_field(name={}, idx={})", name, idx);
+        let elem = format!(".{}", name);
+        if self.substituting > 0 {
+            return self.with_path(elem, f);
+        }
+        let frame_idx = self.frames.len() - 1;
+
+        let pos = self.frames[frame_idx].buffered.iter().position(|&(ref k, _)| {
+            str::eq_slice(*k, name)
+        });
+        match pos {
+            Some(i) => {
+                let (_, events) = self.frames[frame_idx].buffered.swap_remove(i);
+                self.unread_all(events);
+                return self.with_path(elem, f);
+            }
+            None => ()
+        }
 
-        let mut values = ~TreeMap::new();
+        while !self.frames[frame_idx].ended && !self.exhausted() {
+            match self.next_event() {
+                Key(key) => {
+                    if str::eq_slice(key, name) {
+                        return self.with_path(elem, f);
+                    }
+                    let events = self.capture_value();
+                    self.frames[frame_idx].buffered.push((key, events));
+                }
+                ObjectEnd => self.frames[frame_idx].ended = true,
+                event => {
+                    self.mismatch("a key", event);
+                    self.skip_to(ObjectEnd);
+                    self.frames[frame_idx].ended = true;
+                }
+            }
+        }
 
-        if self.ch == '}' {
-          self.bump();
-          return Ok(Object(values));
+        if !self.failed {
+            self.error(serialize::MissingFieldError(name.to_owned()));
         }
+        do self.with_path(elem) |d| { d.substitute(f) }
+    }
 
-        while !self.eof() {
-            self.parse_whitespace();
+    fn read_tuple<U>(&mut self, f: &fn(&mut StreamingDecoder<T>, uint) -> U) -> U {
+        debug!("read_tuple()");
+        self.read_seq(f)
+    }
 
-            if self.ch != '"' {
-                return self.error(~"key must be a string");
-            }
+    fn read_tuple_arg<U>(&mut self,
+                         idx: uint,
+                         f: &fn(&mut StreamingDecoder<T>) -> U)
+                         -> U {
+        debug!("read_tuple_arg(idx={})", idx);
+        self.read_seq_elt(idx, f)
+    }
 
-            let key = match self.parse_str() {
-              Ok(key) => key,
-              Err(e) => return Err(e)
-            };
+    fn read_tuple_struct<U>(&mut self,
+                            name: &str,
+                            f: &fn(&mut StreamingDecoder<T>, uint) -> U)
+                            -> U {
+        debug!("read_tuple_struct(name={})", name);
+        self.read_tuple(f)
+    }
 
-            self.parse_whitespace();
+    fn read_tuple_struct_arg<U>(&mut self,
+                                idx: uint,
+                                f: &fn(&mut StreamingDecoder<T>) -> U)
+                                -> U {
+        debug!("read_tuple_struct_arg(idx={})", idx);
+        self.read_tuple_arg(idx, f)
+    }
 
-            if self.ch != ':' {
-                if self.eof() { break; }
-                return self.error(~"expected `:`");
-            }
-            self.bump();
+    fn read_option<U>(&mut self, f: &fn(&mut StreamingDecoder<T>, bool) -> U) -> U {
+        match self.next_event() {
+            Value(Null) => f(self, false),
+            event => { self.unread(event); f(self, true) }
+        }
+    }
 
-            match self.parse_value() {
-              Ok(value) => { values.insert(key, value); }
-              Err(e) => return Err(e)
+    fn read_seq<U>(&mut self, f: &fn(&mut StreamingDecoder<T>, uint) -> U) -> U {
+        debug!("read_seq()");
+        match self.next_event() {
+            ListStart => (),
+            event => {
+                self.mismatch("a list", event);
+                return self.substitute(|d| f(d, 0));
             }
-            self.parse_whitespace();
-
-            match self.ch {
-              ',' => self.bump(),
-              '}' => { self.bump(); return Ok(Object(values)); }
-              _ => {
-                  if self.eof() { break; }
-                  return self.error(~"expected `,` or `}`");
-              }
+        }
+        let mut events = ~[];
+        let mut len = 0u;
+        while !self.exhausted() {
+            match self.next_event() {
+                ListEnd => break,
+                event => {
+                    self.unread(event);
+                    events.push_all_move(self.capture_value());
+                    len += 1;
+                }
             }
         }
+        events.push(ListEnd);
+        self.unread_all(events);
+        let value = f(self, len);
+        self.skip_to(ListEnd);
+        value
+    }
 
-        return self.error(~"EOF while parsing object");
+    fn read_seq_elt<U>(&mut self, idx: uint, f: &fn(&mut StreamingDecoder<T>) -> U) -> U {
+        debug!("read_seq_elt(idx={})", idx);
+        self.with_path(format!("[{}]", idx), f)
     }
-}
 
-/// Decodes a json value from an @io::Reader
-pub fn from_reader(rdr: @io::Reader) -> Result<Json, Error> {
-    let s = str::from_utf8(rdr.read_whole_stream());
-    let mut parser = Parser(~s.iter());
-    parser.parse()
-}
+    fn read_map<U>(&mut self, f: &fn(&mut StreamingDecoder<T>, uint) -> U) -> U {
+        debug!("read_map()");
+        match self.next_event() {
+            ObjectStart => (),
+            event => {
+                self.mismatch("an object", event);
+                return self.substitute(|d| f(d, 0));
+            }
+        }
+        let mut events = ~[];
+        let mut len = 0u;
+        while !self.exhausted() {
+            match self.next_event() {
+                ObjectEnd => break,
+                Key(key) => {
+                    events.push(Key(key));
+                    events.push_all_move(self.capture_value());
+                    len += 1;
+                }
+                event => {
+                    self.mismatch("a key", event);
+                    self.skip_to(ObjectEnd);
+                    break;
+                }
+            }
+        }
+        events.push(ObjectEnd);
+        self.unread_all(events);
+        let value = f(self, len);
+        self.skip_to(ObjectEnd);
+        value
+    }
 
-/// Decodes a json value from a string
-pub fn from_str(s: &str) -> Result<Json, Error> {
-    let mut parser = Parser(~s.iter());
-    parser.parse()
+    fn read_map_elt_key<U>(&mut self,
+                           idx: uint,
+                           f: &fn(&mut StreamingDecoder<T>) -> U)
+                           -> U {
+        debug!("read_map_elt_key(idx={})", idx);
+        self.map_key = match self.replay.last_opt() {
+            Some(&Key(ref key)) if self.substituting == 0 => Some(key.clone()),
+            _ => None
+        };
+        f(self)
+    }
+
+    fn read_map_elt_val<U>(&mut self, idx: uint, f: &fn(&mut StreamingDecoder<T>) -> U)
+                           -> U {
+        debug!("read_map_elt_val(idx={})", idx);
+        let elem = match self.map_key.take() {
+            Some(key) => format!(".{}", key),
+            None => format!("[{}]", idx)
+        };
+        self.with_path(elem, f)
+    }
 }
 
 /// A structure to decode JSON to values in rust.
+///
+/// Type mismatches and missing fields are raised on
+/// `serialize::decode_error`, with the path to the offending value. If
+/// the handler returns, the value is decoded from `null` instead, without
+/// reporting further errors inside it.
 pub struct Decoder {
     priv stack: ~[Json],
+    priv path: ~[~str],
+    priv map_key: Option<~str>,
+    // Non-zero while decoding a stand-in for a value that was missing or
+    // had the wrong type.
+    priv substituting: uint,
 }
 
 /// Creates a new decoder instance for decoding the specified JSON value.
 pub fn Decoder(json: Json) -> Decoder {
     Decoder {
-        stack: ~[json]
+        stack: ~[json],
+        path: ~[],
+        map_key: None,
+        substituting: 0,
+    }
+}
+
+/// A short description of the type of a json value, for error messages.
+fn json_kind(json: &Json) -> ~str {
+    match *json {
+        Number(n) => format!("number {}", f64::to_str_digits(n, 6u)),
+        String(ref s) => escape_str(*s),
+        Boolean(b) => b.to_str(),
+        List(_) => ~"list",
+        Object(_) => ~"object",
+        Null => ~"null",
+    }
+}
+
+/// Formats a decoder path, e.g. `[".inner", "[0]", ".b"]` as `inner[0].b`.
+fn path_str(path: &[~str]) -> ~str {
+    let s = path.concat();
+    if s.starts_with(".") { s.slice_from(1).to_owned() } else { s }
+}
+
+impl Decoder {
+    fn pop(&mut self) -> Json {
+        // The stack only runs dry after an error has been handled.
+        self.stack.pop_opt().unwrap_or(Null)
+    }
+
+    fn error(&self, kind: serialize::DecoderError) {
+        if self.substituting > 0 { return; }
+        serialize::decode_error::cond.raise(serialize::DecodeError {
+            path: path_str(self.path),
+            kind: kind,
+        })
+    }
+
+    fn expected(&self, expected: &str, found: &Json) {
+        self.error(serialize::ExpectedError(expected.to_owned(), json_kind(found)))
+    }
+
+    fn with_path<T>(&mut self, elem: ~str, f: &fn(&mut Decoder) -> T) -> T {
+        self.path.push(elem);
+        let value = f(self);
+        self.path.pop();
+        value
+    }
+
+    fn substitute<T>(&mut self, f: &fn(&mut Decoder) -> T) -> T {
+        self.substituting += 1;
+        let value = f(self);
+        self.substituting -= 1;
+        value
     }
 }
 
 impl serialize::Decoder for Decoder {
     fn read_nil(&mut self) -> () {
         debug!("read_nil");
-        match self.stack.pop() {
+        match self.pop() {
             Null => (),
-            value => fail!("not a null: {:?}", value)
+            value => self.expected("null", &value)
         }
     }
 
@@ -901,35 +1969,37 @@ impl serialize::Decoder for Decoder {
 
     fn read_bool(&mut self) -> bool {
         debug!("read_bool");
-        match self.stack.pop() {
+        match self.pop() {
             Boolean(b) => b,
-            value => fail!("not a boolean: {:?}", value)
+            value => { self.expected("a boolean", &value); false }
         }
     }
 
     fn read_f64(&mut self) -> f64 {
         debug!("read_f64");
-        match self.stack.pop() {
+        match self.pop() {
             Number(f) => f,
-            value => fail!("not a number: {:?}", value)
+            value => { self.expected("a number", &value); 0.0 }
         }
     }
     fn read_f32(&mut self) -> f32 { self.read_f64() as f32 }
-    fn read_f32(&mut self) -> f32 { self.read_f64() as f32 }
 
     fn read_char(&mut self) -> char {
-        let mut v = ~[];
-        let s = self.read_str();
-        for c in s.iter() { v.push(c) }
-        if v.len() != 1 { fail!("string must have one character") }
-        v[0]
+        match self.pop() {
+            String(s) => {
+                if s.char_len() == 1 { return s.char_at(0); }
+                self.expected("a single character string", &String(s));
+            }
+            value => self.expected("a single character string", &value)
+        }
+        '\x00'
     }
 
     fn read_str(&mut self) -> ~str {
         debug!("read_str");
-        match self.stack.pop() {
+        match self.pop() {
             String(s) => s,
-            json => fail!("not a string: {:?}", json)
+            value => { self.expected("a string", &value); ~"" }
         }
     }
 
@@ -943,30 +2013,51 @@ impl serialize::Decoder for Decoder {
                             f: &fn(&mut Decoder, uint) -> T)
                             -> T {
         debug!("read_enum_variant(names={:?})", names);
-        let name = match self.stack.pop() {
-            String(s) => s,
+        let (name, fields) = match self.pop() {
+            String(s) => (s, ~[]),
             Object(o) => {
-                let n = match o.find(&~"variant").expect("invalidly encoded json") {
-                    &String(ref s) => s.clone(),
-                    _ => fail!("invalidly encoded json"),
+                let mut o = o;
+                let n = match o.pop(&~"variant") {
+                    Some(String(s)) => s,
+                    Some(value) => {
+                        self.expected("a variant name", &value);
+                        return self.substitute(|d| f(d, 0));
+                    }
+                    None => {
+                        self.error(serialize::MissingFieldError(~"variant"));
+                        return self.substitute(|d| f(d, 0));
+                    }
                 };
-                match o.find(&~"fields").expect("invalidly encoded json") {
-                    &List(ref l) => {
-                        for field in l.rev_iter() {
-                            self.stack.push(field.clone());
-                        }
-                    },
-                    _ => fail!("invalidly encoded json")
-                }
-                n
+                let fields = match o.pop(&~"fields") {
+                    Some(List(l)) => l,
+                    Some(value) => {
+                        self.expected("a list of fields", &value);
+                        return self.substitute(|d| f(d, 0));
+                    }
+                    None => {
+                        self.error(serialize::MissingFieldError(~"fields"));
+                        return self.substitute(|d| f(d, 0));
+                    }
+                };
+                (n, fields)
+            }
+            value => {
+                self.expected("an enum variant", &value);
+                return self.substitute(|d| f(d, 0));
             }
-            ref json => fail!("invalid variant: {:?}", *json),
-        };
-        let idx = match names.iter().position(|n| str::eq_slice(*n, name)) {
-            Some(idx) => idx,
-            None => fail!("Unknown variant name: {}", name),
         };
-        f(self, idx)
+        match names.iter().position(|n| str::eq_slice(*n, name)) {
+            Some(idx) => {
+                for field in fields.move_rev_iter() {
+                    self.stack.push(field);
+                }
+                f(self, idx)
+            }
+            None => {
+                self.error(serialize::UnknownVariantError(name));
+                self.substitute(|d| f(d, 0))
+            }
+        }
     }
 
     fn read_enum_variant_arg<T>(&mut self,
@@ -974,7 +2065,7 @@ impl serialize::Decoder for Decoder {
                                 f: &fn(&mut Decoder) -> T)
                                 -> T {
         debug!("read_enum_variant_arg(idx={})", idx);
-        f(self)
+        self.with_path(format!("[{}]", idx), f)
     }
 
     fn read_enum_struct_variant<T>(&mut self,
@@ -992,7 +2083,7 @@ impl serialize::Decoder for Decoder {
                                          f: &fn(&mut Decoder) -> T)
                                          -> T {
         debug!("read_enum_struct_variant_field(name={}, idx={})", name, idx);
-        self.read_enum_variant_arg(idx, f)
+        self.with_path(format!(".{}", name), f)
     }
 
     fn read_struct<T>(&mut self,
@@ -1001,8 +2092,18 @@ impl serialize::Decoder for Decoder {
                       f: &fn(&mut Decoder) -> T)
                       -> T {
         debug!("read_struct(name={}, len={})", name, len);
-        let value = f(self);
-        self.stack.pop();
+        let value = match self.pop() {
+            Object(obj) => {
+                self.stack.push(Object(obj));
+                f(self)
+            }
+            value => {
+                self.expected("an object", &value);
+                self.stack.push(Object(~TreeMap::new()));
+                self.substitute(f)
+            }
+        };
+        self.pop();
         value
     }
 
@@ -1012,21 +2113,27 @@ impl serialize::Decoder for Decoder {
                             f: &fn(&mut Decoder) -> T)
                             -> T {
         debug!("read_struct_field(name={}, idx={})", name, idx);
-        match self.stack.pop() {
-            Object(obj) => {
-                let mut obj = obj;
-                let value = match obj.pop(&name.to_owned()) {
-                    None => fail!("no such field: {}", name),
-                    Some(json) => {
-                        self.stack.push(json);
-                        f(self)
-                    }
-                };
-                self.stack.push(Object(obj));
-                value
+        let mut obj = match self.pop() {
+            Object(obj) => obj,
+            value => {
+                // `read_struct` makes sure this can't happen.
+                self.expected("an object", &value);
+                ~TreeMap::new()
             }
-            value => fail!("not an object: {:?}", value)
-        }
+        };
+        let value = match obj.pop(&name.to_owned()) {
+            None => {
+                self.error(serialize::MissingFieldError(name.to_owned()));
+                self.stack.push(Null);
+                do self.with_path(format!(".{}", name)) |d| { d.substitute(f) }
+            }
+            Some(json) => {
+                self.stack.push(json);
+                self.with_path(format!(".{}", name), f)
+            }
+        };
+        self.stack.push(Object(obj));
+        value
     }
 
     fn read_tuple<T>(&mut self, f: &fn(&mut Decoder, uint) -> T) -> T {
@@ -1059,7 +2166,7 @@ impl serialize::Decoder for Decoder {
     }
 
     fn read_option<T>(&mut self, f: &fn(&mut Decoder, bool) -> T) -> T {
-        match self.stack.pop() {
+        match self.pop() {
             Null => f(self, false),
             value => { self.stack.push(value); f(self, true) }
         }
@@ -1067,7 +2174,7 @@ impl serialize::Decoder for Decoder {
 
     fn read_seq<T>(&mut self, f: &fn(&mut Decoder, uint) -> T) -> T {
         debug!("read_seq()");
-        let len = match self.stack.pop() {
+        let len = match self.pop() {
             List(list) => {
                 let len = list.len();
                 for v in list.move_rev_iter() {
@@ -1075,19 +2182,19 @@ impl serialize::Decoder for Decoder {
                 }
                 len
             }
-            _ => fail!("not a list"),
+            value => { self.expected("a list", &value); 0 }
         };
         f(self, len)
     }
 
     fn read_seq_elt<T>(&mut self, idx: uint, f: &fn(&mut Decoder) -> T) -> T {
         debug!("read_seq_elt(idx={})", idx);
-        f(self)
+        self.with_path(format!("[{}]", idx), f)
     }
 
     fn read_map<T>(&mut self, f: &fn(&mut Decoder, uint) -> T) -> T {
         debug!("read_map()");
-        let len = match self.stack.pop() {
+        let len = match self.pop() {
             Object(obj) => {
                 let len = obj.len();
                 for (key, value) in obj.move_iter() {
@@ -1096,7 +2203,7 @@ impl serialize::Decoder for Decoder {
                 }
                 len
             }
-            json => fail!("not an object: {:?}", json),
+            value => { self.expected("an object", &value); 0 }
         };
         f(self, len)
     }
@@ -1106,13 +2213,21 @@ impl serialize::Decoder for Decoder {
                            f: &fn(&mut Decoder) -> T)
                            -> T {
         debug!("read_map_elt_key(idx={})", idx);
+        self.map_key = match self.stack.last_opt() {
+            Some(&String(ref key)) => Some(key.clone()),
+            _ => None
+        };
         f(self)
     }
 
     fn read_map_elt_val<T>(&mut self, idx: uint, f: &fn(&mut Decoder) -> T)
                            -> T {
         debug!("read_map_elt_val(idx={})", idx);
-        f(self)
+        let elem = match self.map_key.take() {
+            Some(key) => format!(".{}", key),
+            None => format!("[{}]", idx)
+        };
+        self.with_path(elem, f)
     }
 }
 
@@ -1323,7 +2438,8 @@ mod tests {
 
     use std::io;
 
-    use serialize::Decodable;
+    use serialize::{Decodable, DecodeError, try_decode, decode_error};
+    use serialize::{ExpectedError, MissingFieldError, UnknownVariantError, SyntaxError};
     use treemap::TreeMap;
 
     #[deriving(Eq, Encodable, Decodable)]
@@ -1944,4 +3060,346 @@ mod tests {
                 col: 8u,
                 msg: @~"EOF while parsing object"}));
     }
+
+    fn stream_events(s: &str) -> ~[Result<JsonEvent, Error>] {
+        StreamingParser(~s.iter()).collect()
+    }
+
+    #[test]
+    fn test_stream_scalars() {
+        assert_eq!(stream_events("null"), ~[Ok(Value(Null))]);
+        assert_eq!(stream_events(" 3.5 "), ~[Ok(Value(Number(3.5)))]);
+        assert_eq!(stream_events("\"foo\""), ~[Ok(Value(String(~"foo")))]);
+        assert_eq!(stream_events("true"), ~[Ok(Value(Boolean(true)))]);
+    }
+
+    #[test]
+    fn test_stream_nested() {
+        let events = stream_events("{\"a\": [1, true, null, []], \
+                                     \"b\": {\"c\": \"d\"}, \"e\": {}}");
+        assert_eq!(events, ~[
+            Ok(ObjectStart),
+            Ok(Key(~"a")),
+            Ok(ListStart),
+            Ok(Value(Number(1.0))),
+            Ok(Value(Boolean(true))),
+            Ok(Value(Null)),
+            Ok(ListStart),
+            Ok(ListEnd),
+            Ok(ListEnd),
+            Ok(Key(~"b")),
+            Ok(ObjectStart),
+            Ok(Key(~"c")),
+            Ok(Value(String(~"d"))),
+            Ok(ObjectEnd),
+            Ok(Key(~"e")),
+            Ok(ObjectStart),
+            Ok(ObjectEnd),
+            Ok(ObjectEnd),
+        ]);
+    }
+
+    #[test]
+    fn test_stream_depth() {
+        let mut parser = StreamingParser(~"[[1]]".iter());
+        parser.next();
+        parser.next();
+        assert_eq!(parser.depth(), 2);
+        parser.next();
+        parser.next();
+        assert_eq!(parser.depth(), 1);
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        fn last_error(s: &str) -> Error {
+            match stream_events(s).pop() {
+                Err(e) => e,
+                Ok(event) => fail!("expected an error, found {:?}", event)
+            }
+        }
+
+        assert_eq!(last_error(""), Error {
+            line: 1u, col: 1u, msg: @~"EOF while parsing value"});
+        assert_eq!(last_error("[1,"), Error {
+            line: 1u, col: 4u, msg: @~"EOF while parsing value"});
+        assert_eq!(last_error("[1 2]"), Error {
+            line: 1u, col: 4u, msg: @~"expected `,` or `]`"});
+        assert_eq!(last_error("{\"a\" 1}"), Error {
+            line: 1u, col: 6u, msg: @~"expected `:`"});
+        assert_eq!(last_error("{1: 1}"), Error {
+            line: 1u, col: 2u, msg: @~"key must be a string"});
+        assert_eq!(last_error("[1] x"), Error {
+            line: 1u, col: 5u, msg: @~"trailing characters"});
+        assert_eq!(last_error("{\n  \"foo\":\n \"bar\""), Error {
+            line: 3u, col: 8u, msg: @~"EOF while parsing object"});
+
+        // Iteration stops after the first error.
+        let events = stream_events("[tru]");
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn test_stream_matches_tree_parser() {
+        let s = "[{\"a\": [1.5, -2e3, \"x\\ny\"]}, false, {}]";
+        let mut list = ~[];
+        for event in StreamingParser(~s.iter()) {
+            match event.unwrap() {
+                Value(v) => list.push(v),
+                _ => ()
+            }
+        }
+        assert_eq!(list, ~[Number(1.5), Number(-2000.0), String(~"x\ny"), Boolean(false)]);
+    }
+
+    #[test]
+    fn test_stream_from_reader() {
+        let events = do io::with_str_reader("[1, \"two\"]") |rdr| {
+            stream_from_reader(rdr).collect::<~[Result<JsonEvent, Error>]>()
+        };
+        assert_eq!(events, ~[Ok(ListStart), Ok(Value(Number(1.0))),
+                             Ok(Value(String(~"two"))), Ok(ListEnd)]);
+    }
+
+    #[test]
+    fn test_streaming_decode_struct() {
+        let s = "{
+            \"inner\": [
+                { \"c\": [\"abc\", \"xyz\"], \"extra\": {\"x\": [1]}, \"b\": 2, \"a\": null }
+            ]
+        }";
+        let mut decoder = StreamingDecoder(StreamingParser(~s.iter()));
+        let v: Outer = Decodable::decode(&mut decoder);
+        assert_eq!(
+            v,
+            Outer {
+                inner: ~[
+                    Inner { a: (), b: 2, c: ~[~"abc", ~"xyz"] }
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_streaming_decode_enum_option_map() {
+        let s = "{\"a\": \"Dog\", \"b\": {\"variant\":\"Frog\",\"fields\":[\"Henry\", 349]}}";
+        let mut decoder = StreamingDecoder(StreamingParser(~s.iter()));
+        let mut map: TreeMap<~str, Animal> = Decodable::decode(&mut decoder);
+        assert_eq!(map.pop(&~"a"), Some(Dog));
+        assert_eq!(map.pop(&~"b"), Some(Frog(~"Henry", 349)));
+
+        let mut decoder = StreamingDecoder(StreamingParser(~"[null, \"x\"]".iter()));
+        let value: ~[Option<~str>] = Decodable::decode(&mut decoder);
+        assert_eq!(value, ~[None, Some(~"x")]);
+    }
+
+    #[test]
+    fn test_streaming_each_elt() {
+        let s = "[{\"inner\": []}, \
+                 {\"inner\": [{\"a\": null, \"b\": 1, \"c\": []}]}, \
+                 {\"inner\": []}]";
+        let mut decoder = StreamingDecoder(StreamingParser(~s.iter()));
+        let mut lens = ~[];
+        do decoder.each_elt |outer: Outer| {
+            lens.push(outer.inner.len());
+            true
+        };
+        assert_eq!(lens, ~[0, 1, 0]);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_streaming_decode_missing_field() {
+        let mut decoder = StreamingDecoder(StreamingParser(~"{\"a\": null, \"b\": 1}".iter()));
+        let _: Inner = Decodable::decode(&mut decoder);
+    }
+
+    #[test]
+    fn test_find_path() {
+        let json = from_str("{\"a\": {\"b\": [1, 2], \"c\": null}, \"d\": \"e\"}").unwrap();
+        assert_eq!(json.find("d"), Some(&String(~"e")));
+        assert_eq!(json.find("x"), None);
+        assert_eq!(json.find_path(["a", "c"]), Some(&Null));
+        assert_eq!(json.find_path(["a", "b"]).and_then(|b| b.as_list()).map(|l| l.len()),
+                   Some(2));
+        assert_eq!(json.find_path(["a", "x"]), None);
+        assert_eq!(json.find_path(["d", "e"]), None);
+        assert!(json.find_path([]) == Some(&json));
+    }
+
+    #[test]
+    fn test_search() {
+        let json = from_str("{\"a\": [{\"x\": 1}, {\"y\": {\"z\": 2}}], \
+                             \"b\": {\"z\": 3}}").unwrap();
+        assert_eq!(json.search("x"), Some(&Number(1.0)));
+        assert_eq!(json.search("z"), Some(&Number(2.0)));
+        assert_eq!(json.search("q"), None);
+    }
+
+    #[test]
+    fn test_pointer() {
+        // Examples from RFC 6901, section 5.
+        let json = from_str("{
+            \"foo\": [\"bar\", \"baz\"],
+            \"\": 0,
+            \"a/b\": 1,
+            \"c%d\": 2,
+            \"e^f\": 3,
+            \"g|h\": 4,
+            \"i\\\\j\": 5,
+            \"k\\\"l\": 6,
+            \" \": 7,
+            \"m~n\": 8
+        }").unwrap();
+        assert!(json.pointer("") == Some(&json));
+        assert_eq!(json.pointer("/foo").and_then(|v| v.as_list()).map(|l| l.len()), Some(2));
+        assert_eq!(json.pointer("/foo/0"), Some(&String(~"bar")));
+        assert_eq!(json.pointer("/"), Some(&Number(0.0)));
+        assert_eq!(json.pointer("/a~1b"), Some(&Number(1.0)));
+        assert_eq!(json.pointer("/c%d"), Some(&Number(2.0)));
+        assert_eq!(json.pointer("/e^f"), Some(&Number(3.0)));
+        assert_eq!(json.pointer("/g|h"), Some(&Number(4.0)));
+        assert_eq!(json.pointer("/i\\j"), Some(&Number(5.0)));
+        assert_eq!(json.pointer("/k\"l"), Some(&Number(6.0)));
+        assert_eq!(json.pointer("/ "), Some(&Number(7.0)));
+        assert_eq!(json.pointer("/m~0n"), Some(&Number(8.0)));
+
+        assert_eq!(json.pointer("foo"), None);
+        assert_eq!(json.pointer("/foo/2"), None);
+        assert_eq!(json.pointer("/foo/-"), None);
+        assert_eq!(json.pointer("/foo/01"), None);
+        assert_eq!(json.pointer("/m~2n"), None);
+        assert_eq!(json.pointer("/foo/0/x"), None);
+    }
+
+    #[test]
+    fn test_accessors() {
+        let json = from_str("[1.5, \"s\", true, null, [], {}]").unwrap();
+        let list = json.as_list().unwrap();
+        assert_eq!(list[0].as_f64(), Some(1.5));
+        assert_eq!(list[1].as_str(), Some("s"));
+        assert_eq!(list[2].as_bool(), Some(true));
+        assert!(list[3].is_null());
+        assert!(list[4].is_list() && list[4].as_list().unwrap().is_empty());
+        assert!(list[5].is_object() && list[5].as_object().unwrap().is_empty());
+        assert_eq!(list[1].as_f64(), None);
+        assert_eq!(list[0].as_str(), None);
+        assert!(!list[0].is_string() && list[0].is_number() && list[2].is_boolean());
+    }
+
+    #[test]
+    fn test_mut_accessors() {
+        let mut json = from_str("{\"a\": {\"b\": [1, 2]}}").unwrap();
+        *json.find_path_mut(["a", "b"]).unwrap() = Boolean(false);
+        assert_eq!(json.to_str(), ~"{\"a\":{\"b\":false}}");
+
+        *json.pointer_mut("/a").unwrap() = from_str("[0, 1]").unwrap();
+        *json.pointer_mut("/a/1").unwrap() = String(~"x");
+        json.find_mut("a").unwrap().as_list_mut().unwrap().push(Null);
+        json.as_object_mut().unwrap().insert(~"c", Number(3.0));
+        assert_eq!(json.to_str(), ~"{\"a\":[0,\"x\",null],\"c\":3}");
+        assert!(json.pointer_mut("/a/3").is_none());
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        // Examples from RFC 7386, appendix A.
+        fn check(target: &str, patch: &str, expected: &str) {
+            let mut target = from_str(target).unwrap();
+            target.merge_patch(&from_str(patch).unwrap());
+            assert_eq!(target, from_str(expected).unwrap());
+        }
+
+        check("{\"a\":\"b\"}", "{\"a\":\"c\"}", "{\"a\":\"c\"}");
+        check("{\"a\":\"b\"}", "{\"b\":\"c\"}", "{\"a\":\"b\",\"b\":\"c\"}");
+        check("{\"a\":\"b\"}", "{\"a\":null}", "{}");
+        check("{\"a\":\"b\",\"b\":\"c\"}", "{\"a\":null}", "{\"b\":\"c\"}");
+        check("{\"a\":[\"b\"]}", "{\"a\":\"c\"}", "{\"a\":\"c\"}");
+        check("{\"a\":\"c\"}", "{\"a\":[\"b\"]}", "{\"a\":[\"b\"]}");
+        check("{\"a\":{\"b\":\"c\"}}", "{\"a\":{\"b\":\"d\",\"c\":null}}", "{\"a\":{\"b\":\"d\"}}");
+        check("{\"a\":[{\"b\":\"c\"}]}", "{\"a\":[1]}", "{\"a\":[1]}");
+        check("[\"a\",\"b\"]", "[\"c\",\"d\"]", "[\"c\",\"d\"]");
+        check("{\"a\":\"b\"}", "[\"c\"]", "[\"c\"]");
+        check("{\"a\":\"foo\"}", "null", "null");
+        check("{\"a\":\"foo\"}", "\"bar\"", "\"bar\"");
+        check("{\"e\":null}", "{\"a\":1}", "{\"e\":null,\"a\":1}");
+        check("[1,2]", "{\"a\":\"b\",\"c\":null}", "{\"a\":\"b\"}");
+        check("{}", "{\"a\":{\"bb\":{\"ccc\":null}}}", "{\"a\":{\"bb\":{}}}");
+    }
+
+    fn decode_errors<T: Decodable<Decoder>>(s: &str) -> ~[DecodeError] {
+        let mut errors = ~[];
+        do decode_error::cond.trap(|e| errors.push(e)).inside {
+            let mut decoder = Decoder(from_str(s).unwrap());
+            let _: T = Decodable::decode(&mut decoder);
+        }
+        errors
+    }
+
+    #[test]
+    fn test_decode_error_path() {
+        let s = "{\"inner\": [{\"a\": null, \"b\": \"x\", \"c\": []}]}";
+        let mut decoder = Decoder(from_str(s).unwrap());
+        let result: Result<Outer, DecodeError> = try_decode(&mut decoder);
+        assert_eq!(result, Err(DecodeError {
+            path: ~"inner[0].b",
+            kind: ExpectedError(~"a number", ~"\"x\"")
+        }));
+
+        let mut decoder = Decoder(from_str("{\"a\": {}}").unwrap());
+        let result: Result<TreeMap<~str, Inner>, DecodeError> = try_decode(&mut decoder);
+        assert_eq!(result, Err(DecodeError {
+            path: ~"a.a",
+            kind: MissingFieldError(~"a")
+        }));
+
+        let mut decoder = Decoder(from_str("[\"Dog\", \"Cat\"]").unwrap());
+        let result: Result<~[Animal], DecodeError> = try_decode(&mut decoder);
+        assert_eq!(result, Err(DecodeError {
+            path: ~"[1]",
+            kind: UnknownVariantError(~"Cat")
+        }));
+    }
+
+    #[test]
+    fn test_decode_collect_errors() {
+        let errors = decode_errors::<Inner>("{\"a\": 1, \"c\": [\"x\", true]}");
+        assert_eq!(errors, ~[
+            DecodeError { path: ~"a", kind: ExpectedError(~"null", ~"number 1") },
+            DecodeError { path: ~"", kind: MissingFieldError(~"b") },
+            DecodeError { path: ~"c[1]", kind: ExpectedError(~"a string", ~"true") },
+        ]);
+        assert_eq!(errors[1].to_str(), ~"missing field `b`");
+        assert!(decode_errors::<Outer>("{\"inner\": []}").is_empty());
+    }
+
+    #[test]
+    fn test_streaming_decode_errors() {
+        let s = "{\"inner\": [{\"c\": {\"x\": [1]}, \"b\": 1, \"a\": null}]}";
+        let mut decoder = StreamingDecoder(StreamingParser(~s.iter()));
+        let result: Result<Outer, DecodeError> = try_decode(&mut decoder);
+        assert_eq!(result, Err(DecodeError {
+            path: ~"inner[0].c",
+            kind: ExpectedError(~"a list", ~"object")
+        }));
+
+        // The mismatched value is skipped, so later fields still decode.
+        let mut errors = ~[];
+        let value: ~[Inner] = do decode_error::cond.trap(|e| errors.push(e)).inside {
+            let s = "[{\"a\": null, \"b\": [2], \"c\": []}, {\"a\": null, \"b\": 3, \"c\": []}]";
+            let mut decoder = StreamingDecoder(StreamingParser(~s.iter()));
+            Decodable::decode(&mut decoder)
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, ~"[0].b");
+        assert_eq!(value, ~[Inner { a: (), b: 0, c: ~[] },
+                            Inner { a: (), b: 3, c: ~[] }]);
+
+        let mut decoder = StreamingDecoder(StreamingParser(~"[1, 2".iter()));
+        let result: Result<~[uint], DecodeError> = try_decode(&mut decoder);
+        match result {
+            Err(DecodeError { kind: SyntaxError(_), _ }) => (),
+            _ => fail!("expected a syntax error, found {:?}", result)
+        }
+    }
 }
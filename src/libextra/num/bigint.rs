@@ -269,6 +269,10 @@ impl Sub<BigUint, BigUint> for BigUint {
     }
 }
 
+// Below this many digits in the shorter operand, schoolbook multiplication is
+// faster than Karatsuba's.
+static KARATSUBA_THRESHOLD: uint = 32;
+
 impl Mul<BigUint, BigUint> for BigUint {
     fn mul(&self, other: &BigUint) -> BigUint {
         if self.is_zero() || other.is_zero() { return Zero::zero(); }
@@ -276,6 +280,9 @@ impl Mul<BigUint, BigUint> for BigUint {
         let (s_len, o_len) = (self.data.len(), other.data.len());
         if s_len == 1 { return mul_digit(other, self.data[0]);  }
         if o_len == 1 { return mul_digit(self,  other.data[0]); }
+        if num::min(s_len, o_len) < KARATSUBA_THRESHOLD {
+            return mul_schoolbook(self, other);
+        }
 
         // Using Karatsuba multiplication
         // (a1 * base + a0) * (b1 * base + b0)
@@ -317,6 +324,23 @@ impl Mul<BigUint, BigUint> for BigUint {
             return BigUint::new(prod);
         }
 
+        fn mul_schoolbook(a: &BigUint, b: &BigUint) -> BigUint {
+            let b_len = b.data.len();
+            let mut prod = vec::from_elem(a.data.len() + b_len, ZERO_BIG_DIGIT);
+            for (i, ai) in a.data.iter().enumerate() {
+                let mut carry = 0;
+                for (j, bj) in b.data.iter().enumerate() {
+                    let (hi, lo) = BigDigit::from_uint(
+                        (*ai as uint) * (*bj as uint) + (prod[i + j] as uint) + (carry as uint)
+                    );
+                    prod[i + j] = lo;
+                    carry = hi;
+                }
+                prod[i + b_len] = carry;
+            }
+            return BigUint::new(prod);
+        }
+
         #[inline]
         fn cut_at(a: &BigUint, n: uint) -> (BigUint, BigUint) {
             let mid = num::min(a.data.len(), n);
@@ -385,76 +409,16 @@ impl Integer for BigUint {
             Greater => {} // Do nothing
         }
 
-        let mut shift = 0;
-        let mut n = *other.data.last();
-        while n < (1 << BigDigit::bits - 2) {
-            n <<= 1;
-            shift += 1;
-        }
-        assert!(shift < BigDigit::bits);
-        let (d, m) = div_mod_floor_inner(self << shift, other << shift);
-        return (d, m >> shift);
-
-
-        fn div_mod_floor_inner(a: BigUint, b: BigUint) -> (BigUint, BigUint) {
-            let mut m = a;
-            let mut d: BigUint = Zero::zero();
-            let mut n = 1;
-            while m >= b {
-                let (d0, d_unit, b_unit) = div_estimate(&m, &b, n);
-                let mut d0 = d0;
-                let mut prod = b * d0;
-                while prod > m {
-                    // FIXME(#6050): overloaded operators force moves with generic types
-                    // d0 -= d_unit
-                    d0   = d0 - d_unit;
-                    // FIXME(#6050): overloaded operators force moves with generic types
-                    // prod = prod - b_unit;
-                    prod = prod - b_unit
-                }
-                if d0.is_zero() {
-                    n = 2;
-                    continue;
-                }
-                n = 1;
-                // FIXME(#6102): Assignment operator for BigInt causes ICE
-                // d += d0;
-                d = d + d0;
-                // FIXME(#6102): Assignment operator for BigInt causes ICE
-                // m -= prod;
-                m = m - prod;
-            }
-            return (d, m);
+        if other.data.len() == 1 {
+            let (d, m) = div_rem_digit(self, other.data[0]);
+            return (d, BigUint::from_slice([m]));
         }
 
-
-        fn div_estimate(a: &BigUint, b: &BigUint, n: uint)
-            -> (BigUint, BigUint, BigUint) {
-            if a.data.len() < n {
-                return (Zero::zero(), Zero::zero(), (*a).clone());
-            }
-
-            let an = a.data.slice(a.data.len() - n, a.data.len());
-            let bn = *b.data.last();
-            let mut d = ~[];
-            let mut carry = 0;
-            for elt in an.rev_iter() {
-                let ai = BigDigit::to_uint(carry, *elt);
-                let di = ai / (bn as uint);
-                assert!(di < BigDigit::base);
-                carry = (ai % (bn as uint)) as BigDigit;
-                d = ~[di as BigDigit] + d;
-            }
-
-            let shift = (a.data.len() - an.len()) - (b.data.len() - 1);
-            if shift == 0 {
-                return (BigUint::new(d), One::one(), (*b).clone());
-            }
-            let one: BigUint = One::one();
-            return (BigUint::from_slice(d).shl_unit(shift),
-                    one.shl_unit(shift),
-                    b.shl_unit(shift));
-        }
+        // Knuth's algorithm estimates quotient digits from the top digit of
+        // the divisor, which must have its high bit set for that to work
+        let shift = other.data.last().leading_zeros() as uint;
+        let (d, m) = div_rem_knuth(&(self << shift), &(other << shift));
+        return (d, m >> shift);
     }
 
     /**
@@ -501,6 +465,113 @@ impl Integer for BigUint {
     fn is_odd(&self) -> bool { !self.is_even() }
 }
 
+// Divides `a` by a single digit, returning the quotient and the remainder.
+fn div_rem_digit(a: &BigUint, b: BigDigit) -> (BigUint, BigDigit) {
+    let mut rem = 0;
+    let mut quot = vec::from_elem(a.data.len(), ZERO_BIG_DIGIT);
+    for i in range(0, a.data.len()).invert() {
+        let n = BigDigit::to_uint(rem, a.data[i]);
+        quot[i] = (n / (b as uint)) as BigDigit;
+        rem = (n % (b as uint)) as BigDigit;
+    }
+    return (BigUint::new(quot), rem);
+}
+
+// Divides `u` by `v` using Algorithm D from Knuth's TAOCP, 4.3.1. `v` must
+// have at least two digits, the top one with its high bit set, and must not
+// be greater than `u`.
+fn div_rem_knuth(u: &BigUint, v: &BigUint) -> (BigUint, BigUint) {
+    let v = v.data.as_slice();
+    let n = v.len();
+    let m = u.data.len() - n;
+    let mut u = u.data.clone();
+    u.push(0);
+    let mut q = vec::from_elem(m + 1, ZERO_BIG_DIGIT);
+    let (v_top, v_next) = (v[n - 1] as uint, v[n - 2] as uint);
+
+    for j in range(0, m + 1).invert() {
+        // Estimate the next quotient digit from the top two digits of the
+        // remainder, and correct the estimate with the next digit. This
+        // leaves it at most one too large.
+        let top = BigDigit::to_uint(u[j + n], u[j + n - 1]);
+        let mut q_hat = top / v_top;
+        let mut r_hat = top % v_top;
+        while q_hat >= BigDigit::base ||
+              q_hat * v_next > BigDigit::to_uint(r_hat as BigDigit, u[j + n - 2]) {
+            q_hat -= 1;
+            r_hat += v_top;
+            if r_hat >= BigDigit::base { break; }
+        }
+
+        // u[j .. j + n] -= q_hat * v
+        let mut carry = 0;
+        let mut borrow = 0;
+        for i in range(0, n) {
+            let (hi, lo) = BigDigit::from_uint(q_hat * (v[i] as uint) + carry);
+            carry = hi as uint;
+            let (no_borrow, diff) = BigDigit::from_uint(
+                BigDigit::base + (u[i + j] as uint) - (lo as uint) - borrow
+            );
+            u[i + j] = diff;
+            borrow = 1 - (no_borrow as uint);
+        }
+        let (no_borrow, diff) = BigDigit::from_uint(
+            BigDigit::base + (u[j + n] as uint) - carry - borrow
+        );
+        u[j + n] = diff;
+
+        if no_borrow == 0 {
+            // the estimate was one too large, so add v back
+            q_hat -= 1;
+            let mut carry = 0;
+            for i in range(0, n) {
+                let (hi, lo) = BigDigit::from_uint((u[i + j] as uint) + (v[i] as uint) + carry);
+                u[i + j] = lo;
+                carry = hi as uint;
+            }
+            u[j + n] += carry as BigDigit;
+        }
+        q[j] = q_hat as BigDigit;
+    }
+
+    u.truncate(n);
+    return (BigUint::new(q), BigUint::new(u));
+}
+
+// Calculates `BigDigit::base^(2 * k) / d` for a `d` of `k` digits whose top
+// digit has its high bit set, by Newton's iteration from the reciprocal of
+// the top half of `d`. Multiplying by it divides by `d`.
+fn reciprocal(d: &BigUint) -> BigUint {
+    let k = d.data.len();
+    let one: BigUint = One::one();
+    let p = one.shl_unit(2 * k);
+    // without Karatsuba, long division is no slower than Newton's iteration
+    if k < KARATSUBA_THRESHOLD {
+        return p / *d;
+    }
+
+    let h = (k + 1) / 2;
+    let mut x = reciprocal(&d.shr_unit(k - h)).shl_unit(k - h);
+    let dx = *d * x;
+    x = if dx <= p {
+        x + (x * (p - dx)).shr_unit(2 * k)
+    } else {
+        x - (x * (dx - p)).shr_unit(2 * k) - one
+    };
+
+    // the iteration leaves x within a few units of the reciprocal
+    let mut dx = *d * x;
+    while dx > p {
+        x = x - one;
+        dx = dx - *d;
+    }
+    while dx + *d <= p {
+        x = x + one;
+        dx = dx + *d;
+    }
+    return x;
+}
+
 impl ToPrimitive for BigUint {
     #[inline]
     fn to_i64(&self) -> Option<i64> {
@@ -647,6 +718,41 @@ impl_to_biguint!(u16,  FromPrimitive::from_u16)
 impl_to_biguint!(u32,  FromPrimitive::from_u32)
 impl_to_biguint!(u64,  FromPrimitive::from_u64)
 
+// Above this many digits, numbers are converted to strings by splitting them
+// in halves rather than peeling off one digit at a time.
+static TO_STR_THRESHOLD: uint = 32;
+
+// A power of a radix base, prepared for quick division: the power shifted so
+// its top bit is set, along with that shifted power's `reciprocal`.
+struct RadixPower {
+    power: BigUint,
+    shift: uint,
+    norm: BigUint,
+    recip: BigUint
+}
+
+impl RadixPower {
+    fn new(power: BigUint) -> RadixPower {
+        let shift = power.data.last().leading_zeros() as uint;
+        let norm = power << shift;
+        let recip = reciprocal(&norm);
+        RadixPower { power: power, shift: shift, norm: norm, recip: recip }
+    }
+
+    // Divides a number less than the square of the power by the power.
+    fn div_rem(&self, n: &BigUint) -> (BigUint, BigUint) {
+        let a = n << self.shift;
+        let mut q = (a * self.recip).shr_unit(2 * self.norm.data.len());
+        let mut r = a - q * self.norm;
+        // q falls at most two short of the quotient
+        while r >= self.norm {
+            r = r - self.norm;
+            q = q + One::one();
+        }
+        return (q, r >> self.shift);
+    }
+}
+
 impl ToStrRadix for BigUint {
     fn to_str_radix(&self, radix: uint) -> ~str {
         assert!(1 < radix && radix <= 16);
@@ -654,23 +760,51 @@ impl ToStrRadix for BigUint {
         if base == BigDigit::base {
             return fill_concat(self.data, radix, max_len)
         }
-        return fill_concat(convert_base((*self).clone(), base), radix, max_len);
+        if self.data.len() <= TO_STR_THRESHOLD {
+            return fill_concat(convert_base(self, base), radix, max_len);
+        }
+
+        // powers[i] is base^(2^i), up to the largest not above the number
+        let mut powers = ~[RadixPower::new(FromPrimitive::from_uint(base).unwrap())];
+        loop {
+            let square = powers.last().power * powers.last().power;
+            if square > *self { break; }
+            powers.push(RadixPower::new(square));
+        }
+        let mut digits = ~[];
+        convert_base_rec(self, powers, powers.len() - 1, false, base, &mut digits);
+        return fill_concat(digits, radix, max_len);
 
-        fn convert_base(n: BigUint, base: uint) -> ~[BigDigit] {
-            let divider    = FromPrimitive::from_uint(base).unwrap();
+        fn convert_base(n: &BigUint, base: uint) -> ~[BigDigit] {
             let mut result = ~[];
-            let mut m      = n;
-            while m > divider {
-                let (d, m0) = m.div_mod_floor(&divider);
-                result.push(m0.to_uint().unwrap() as BigDigit);
+            let mut m      = n.clone();
+            while !m.is_zero() {
+                let (d, m0) = div_rem_digit(&m, base as BigDigit);
+                result.push(m0);
                 m = d;
             }
-            if !m.is_zero() {
-                result.push(m.to_uint().unwrap() as BigDigit);
-            }
             return result;
         }
 
+        // Appends the digits of `n`, which must be less than the square of
+        // `powers[i]`, least significant first. If `exact` is set, they are
+        // padded with zeros to the full width of that bound.
+        fn convert_base_rec(n: &BigUint, powers: &[RadixPower], i: uint, exact: bool,
+                            base: uint, digits: &mut ~[BigDigit]) {
+            if i == 0 || n.data.len() <= TO_STR_THRESHOLD {
+                let converted = convert_base(n, base);
+                let width = converted.len();
+                digits.push_all_move(converted);
+                if exact {
+                    digits.grow((2 << i) - width, &ZERO_BIG_DIGIT);
+                }
+                return;
+            }
+            let (q, r) = powers[i].div_rem(n);
+            convert_base_rec(&r, powers, i - 1, true, base, digits);
+            convert_base_rec(&q, powers, i - 1, exact, base, digits);
+        }
+
         fn fill_concat(v: &[BigDigit], radix: uint, l: uint) -> ~str {
             if v.is_empty() { return ~"0" }
             let mut s = str::with_capacity(v.len() * l);
@@ -802,6 +936,58 @@ impl BigUint {
         let zeros = self.data.last().leading_zeros();
         return self.data.len()*BigDigit::bits - (zeros as uint);
     }
+
+    /// Raises the `BigUint` to the power of `exp`.
+    pub fn pow(&self, exp: uint) -> BigUint {
+        let mut result: BigUint = One::one();
+        let mut base = self.clone();
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 { result = result * base; }
+            exp >>= 1;
+            if exp > 0 { base = base * base; }
+        }
+        return result;
+    }
+
+    /// Calculates `self^exp mod modulus`. Fails when the modulus is zero.
+    pub fn modpow(&self, exp: &BigUint, modulus: &BigUint) -> BigUint {
+        assert!(!modulus.is_zero());
+        let one: BigUint = One::one();
+        let base = *self % *modulus;
+        let mut result = one % *modulus;
+        for i in range(0, exp.bits()).invert() {
+            result = (result * result) % *modulus;
+            if (exp.data[i / BigDigit::bits] >> (i % BigDigit::bits)) & 1 == 1 {
+                result = (result * base) % *modulus;
+            }
+        }
+        return result;
+    }
+
+    /// Calculates the `n`th root of the `BigUint`, rounded down. Fails when
+    /// `n` is zero.
+    pub fn nth_root(&self, n: uint) -> BigUint {
+        assert!(n > 0);
+        let one: BigUint = One::one();
+        if self.is_zero() || n == 1 { return self.clone(); }
+        if n >= self.bits() { return one; }
+
+        // Newton's iteration, which falls steadily to the root from any
+        // starting point above it
+        let n_big: BigUint = FromPrimitive::from_uint(n).unwrap();
+        let n_less: BigUint = FromPrimitive::from_uint(n - 1).unwrap();
+        let mut x = one << ((self.bits() + n - 1) / n);
+        loop {
+            let y = (x * n_less + *self / x.pow(n - 1)) / n_big;
+            if y >= x { return x; }
+            x = y;
+        }
+    }
+
+    /// Calculates the square root of the `BigUint`, rounded down.
+    #[inline]
+    pub fn sqrt(&self) -> BigUint { self.nth_root(2) }
 }
 
 #[cfg(target_word_size = "32")]
@@ -976,6 +1162,66 @@ impl Shr<uint, BigInt> for BigInt {
     }
 }
 
+// The two's complement digits of `n`, sign extended to `len` digits.
+fn twos_complement(n: &BigInt, len: uint) -> ~[BigDigit] {
+    let mut digits = n.data.data.clone();
+    digits.grow(len - digits.len(), &ZERO_BIG_DIGIT);
+    if n.sign == Minus {
+        negate_digits(&mut digits);
+    }
+    return digits;
+}
+
+// The `BigInt` with the given two's complement digits.
+fn from_twos_complement(digits: ~[BigDigit]) -> BigInt {
+    let mut digits = digits;
+    if digits.is_empty() || *digits.last() >> (BigDigit::bits - 1) == 0 {
+        return BigInt::new(Plus, digits);
+    }
+    negate_digits(&mut digits);
+    return BigInt::new(Minus, digits);
+}
+
+// Negates two's complement digits in place: inverts them and adds one.
+fn negate_digits(digits: &mut ~[BigDigit]) {
+    let mut carry = 1;
+    for d in digits.mut_iter() {
+        let (hi, lo) = BigDigit::from_uint(((!*d) as uint) + carry);
+        *d = lo;
+        carry = hi as uint;
+    }
+}
+
+impl BitAnd<BigInt, BigInt> for BigInt {
+    /// Bitwise and, treating the numbers as infinitely sign extended two's
+    /// complement.
+    fn bitand(&self, other: &BigInt) -> BigInt {
+        let len = num::max(self.data.data.len(), other.data.data.len()) + 1;
+        let (a, b) = (twos_complement(self, len), twos_complement(other, len));
+        from_twos_complement(do vec::from_fn(len) |i| { a[i] & b[i] })
+    }
+}
+
+impl BitOr<BigInt, BigInt> for BigInt {
+    /// Bitwise or, treating the numbers as infinitely sign extended two's
+    /// complement.
+    fn bitor(&self, other: &BigInt) -> BigInt {
+        let len = num::max(self.data.data.len(), other.data.data.len()) + 1;
+        let (a, b) = (twos_complement(self, len), twos_complement(other, len));
+        from_twos_complement(do vec::from_fn(len) |i| { a[i] | b[i] })
+    }
+}
+
+impl BitXor<BigInt, BigInt> for BigInt {
+    /// Bitwise exclusive or, treating the numbers as infinitely sign
+    /// extended two's complement.
+    fn bitxor(&self, other: &BigInt) -> BigInt {
+        let len = num::max(self.data.data.len(), other.data.data.len()) + 1;
+        let (a, b) = (twos_complement(self, len), twos_complement(other, len));
+        from_twos_complement(do vec::from_fn(len) |i| { a[i] ^ b[i] })
+    }
+}
+
 impl Zero for BigInt {
     #[inline]
     fn zero() -> BigInt {
@@ -1299,7 +1545,7 @@ impl FromStrRadix for BigInt {
     }
 }
 
-trait RandBigInt {
+pub trait RandBigInt {
     /// Generate a random `BigUint` of the given bit size.
     fn gen_biguint(&mut self, bit_size: uint) -> BigUint;
 
@@ -1429,6 +1675,33 @@ impl BigInt {
             Minus => None
         }
     }
+
+    /// Determines the fewest bits necessary to express the magnitude of the
+    /// `BigInt`.
+    #[inline]
+    pub fn bits(&self) -> uint { self.data.bits() }
+
+    /// Raises the `BigInt` to the power of `exp`.
+    pub fn pow(&self, exp: uint) -> BigInt {
+        let sign = if exp == 0 || (self.sign == Minus && exp % 2 == 0) {
+            Plus
+        } else {
+            self.sign
+        };
+        return BigInt::from_biguint(sign, self.data.pow(exp));
+    }
+
+    /// Calculates the `n`th root of the `BigInt`, rounded towards zero.
+    /// Fails when `n` is zero, or even and the `BigInt` is negative.
+    pub fn nth_root(&self, n: uint) -> BigInt {
+        assert!(self.sign != Minus || n % 2 == 1);
+        return BigInt::from_biguint(self.sign, self.data.nth_root(n));
+    }
+
+    /// Calculates the square root of the `BigInt`, rounded down. Fails when
+    /// the `BigInt` is negative.
+    #[inline]
+    pub fn sqrt(&self) -> BigInt { self.nth_root(2) }
 }
 
 #[cfg(test)]
@@ -2040,6 +2313,106 @@ mod biguint_tests {
         assert_eq!((one << 426).bits(), 427);
     }
 
+    #[test]
+    fn test_mul_div_large() {
+        let mut rng = task_rng();
+        do 10.times {
+            let a = rng.gen_biguint(6000);
+            let b = rng.gen_biguint(4000) + One::one();
+            let c = rng.gen_biguint(3000);
+            let r = c % b;
+
+            let ab = a * b;
+            assert_eq!((a + One::one()) * b, ab + b);
+            assert_eq!(a * (b * c), ab * c);
+            assert_eq!((ab + r).div_rem(&b), (a.clone(), r.clone()));
+        }
+    }
+
+    #[test]
+    fn test_to_str_radix_large() {
+        let ten: BigUint = FromPrimitive::from_uint(10).unwrap();
+        let one: BigUint = One::one();
+        assert_eq!(ten.pow(9).to_str(), ~"1000000000");
+        assert_eq!(ten.pow(2000).to_str(), ~"1" + "0".repeat(2000));
+        assert_eq!((ten.pow(2000) - one).to_str(), "9".repeat(2000));
+
+        let mut rng = task_rng();
+        for radix in range(2u, 17) {
+            let n = rng.gen_biguint(5000);
+            let parsed: Option<BigUint> = FromStrRadix::from_str_radix(n.to_str_radix(radix),
+                                                                        radix);
+            assert_eq!(parsed, Some(n));
+        }
+    }
+
+    #[test]
+    fn test_pow() {
+        let zero: BigUint = Zero::zero();
+        let one: BigUint = One::one();
+        let three: BigUint = FromPrimitive::from_uint(3).unwrap();
+        assert_eq!(zero.pow(0), one);
+        assert_eq!(zero.pow(3), zero);
+        assert_eq!(three.pow(0), one);
+        assert_eq!(three.pow(5), FromPrimitive::from_uint(243).unwrap());
+        assert_eq!(three.pow(100).to_str(),
+                   ~"515377520732011331036461129765621272702107522001");
+    }
+
+    #[test]
+    fn test_modpow() {
+        fn check(b: uint, e: uint, m: uint, ans: uint) {
+            let b: BigUint = FromPrimitive::from_uint(b).unwrap();
+            let e: BigUint = FromPrimitive::from_uint(e).unwrap();
+            let m: BigUint = FromPrimitive::from_uint(m).unwrap();
+            let ans: BigUint = FromPrimitive::from_uint(ans).unwrap();
+            assert_eq!(b.modpow(&e, &m), ans);
+        }
+
+        check(4, 13, 497, 445);
+        check(7, 0, 13, 1);
+        check(0, 0, 5, 1);
+        check(7, 5, 1, 0);
+        check(12345, 678, 91011, 85632);
+
+        // Fermat's little theorem for the Mersenne prime 2^127 - 1
+        let one: BigUint = One::one();
+        let p = (one << 127) - one;
+        let three: BigUint = FromPrimitive::from_uint(3).unwrap();
+        assert_eq!(three.modpow(&(p - one), &p), one);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_modpow_zero_modulus() {
+        let one: BigUint = One::one();
+        one.modpow(&one, &Zero::zero());
+    }
+
+    #[test]
+    fn test_nth_root() {
+        fn check(x: &BigUint, n: uint) {
+            let one: BigUint = One::one();
+            let root = x.nth_root(n);
+            assert!(root.pow(n) <= *x);
+            assert!((root + one).pow(n) > *x);
+        }
+
+        let zero: BigUint = Zero::zero();
+        let one: BigUint = One::one();
+        let ten: BigUint = FromPrimitive::from_uint(10).unwrap();
+        assert_eq!(zero.sqrt(), zero);
+        assert_eq!(one.nth_root(7), one);
+        assert_eq!(ten.pow(100).sqrt(), ten.pow(50));
+        assert_eq!((ten.pow(100) - one).sqrt(), ten.pow(50) - one);
+        assert_eq!((ten.pow(99) - one).nth_root(3), ten.pow(33) - one);
+
+        let mut rng = task_rng();
+        for n in range(1u, 10) {
+            check(&rng.gen_biguint(500), n);
+        }
+    }
+
     #[test]
     fn test_rand() {
         let mut rng = task_rng();
@@ -2532,6 +2905,64 @@ mod bigint_tests {
         assert_eq!(-zero, zero);
     }
 
+    #[test]
+    fn test_pow() {
+        fn check(b: int, e: uint, ans: int) {
+            let b: BigInt = FromPrimitive::from_int(b).unwrap();
+            let ans: BigInt = FromPrimitive::from_int(ans).unwrap();
+            assert_eq!(b.pow(e), ans);
+        }
+
+        check(0, 0, 1);
+        check(-3, 0, 1);
+        check(-3, 3, -27);
+        check(-3, 4, 81);
+        check(7, 2, 49);
+    }
+
+    #[test]
+    fn test_nth_root() {
+        fn check(x: int, n: uint, ans: int) {
+            let x: BigInt = FromPrimitive::from_int(x).unwrap();
+            let ans: BigInt = FromPrimitive::from_int(ans).unwrap();
+            assert_eq!(x.nth_root(n), ans);
+        }
+
+        check(0, 2, 0);
+        check(99, 2, 9);
+        check(-27, 3, -3);
+        check(-28, 3, -3);
+        check(-26, 3, -2);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_negative_sqrt() {
+        let n: BigInt = FromPrimitive::from_int(-4).unwrap();
+        n.sqrt();
+    }
+
+    #[test]
+    fn test_bitwise() {
+        fn check(a: &BigInt, b: &BigInt, and: &BigInt, or: &BigInt, xor: &BigInt) {
+            assert_eq!(*a & *b, *and);
+            assert_eq!(*b & *a, *and);
+            assert_eq!(*a | *b, *or);
+            assert_eq!(*b | *a, *or);
+            assert_eq!(*a ^ *b, *xor);
+            assert_eq!(*b ^ *a, *xor);
+        }
+        fn int(n: int) -> BigInt { FromPrimitive::from_int(n).unwrap() }
+
+        check(&int(0), &int(-1), &int(0), &int(-1), &int(-1));
+        check(&int(-12345), &int(6789), &int(2693), &int(-8249), &int(-10942));
+        check(&int(-12345), &int(-6789), &int(-15037), &int(-4097), &int(10940));
+
+        let one: BigInt = One::one();
+        let big = one << 100;
+        check(&-big, &(big + int(5)), &big, &(-big + int(5)), &(-(big << 1) + int(5)));
+    }
+
     #[test]
     fn test_rand() {
         let mut rng = task_rng();
@@ -2587,6 +3018,7 @@ mod bench {
     use super::*;
     use std::{iter, util};
     use std::num::{FromPrimitive, Zero, One};
+    use std::rand::task_rng;
     use extra::test::BenchHarness;
 
     fn factorial(n: uint) -> BigUint {
@@ -2624,4 +3056,46 @@ mod bench {
         do bh.iter { fac.to_str(); }
         do bh.iter { fib.to_str(); }
     }
+
+    #[bench]
+    fn to_str_large(bh: &mut BenchHarness) {
+        let n = task_rng().gen_biguint(1 << 15);
+        do bh.iter { n.to_str(); }
+    }
+
+    #[bench]
+    fn multiply_small(bh: &mut BenchHarness) {
+        let mut rng = task_rng();
+        let (a, b) = (rng.gen_biguint(1 << 10), rng.gen_biguint(1 << 10));
+        do bh.iter { a * b; }
+    }
+
+    #[bench]
+    fn multiply_large(bh: &mut BenchHarness) {
+        let mut rng = task_rng();
+        let (a, b) = (rng.gen_biguint(1 << 15), rng.gen_biguint(1 << 15));
+        do bh.iter { a * b; }
+    }
+
+    #[bench]
+    fn divide_large(bh: &mut BenchHarness) {
+        let mut rng = task_rng();
+        let (a, b) = (rng.gen_biguint(1 << 15), rng.gen_biguint(1 << 14));
+        do bh.iter { a / b; }
+    }
+
+    #[bench]
+    fn modpow_2048(bh: &mut BenchHarness) {
+        let mut rng = task_rng();
+        let base = rng.gen_biguint(2048);
+        let exp = rng.gen_biguint(2048);
+        let modulus = rng.gen_biguint(2048) | One::one();
+        do bh.iter { base.modpow(&exp, &modulus); }
+    }
+
+    #[bench]
+    fn sqrt_large(bh: &mut BenchHarness) {
+        let n = task_rng().gen_biguint(1 << 12);
+        do bh.iter { n.sqrt(); }
+    }
 }
@@ -0,0 +1,461 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Arbitrary precision decimal numbers
+//!
+//! A `BigDecimal` is exact wherever the result fits the scale it is given:
+//! addition, subtraction, multiplication and remainder never round, while
+//! division and `round` take the number of digits to keep after the point
+//! and a `RoundingMode`.
+
+use std::cmp;
+use std::from_str::FromStr;
+use std::num::{Zero, One, FromPrimitive};
+use super::bigint::{BigInt, BigUint, Plus, Minus};
+
+/// How to round away the digits a result has no room for.
+#[deriving(Clone, Eq)]
+pub enum RoundingMode {
+    /// Round to the nearest value, with ties going to an even last digit
+    HalfEven,
+    /// Round to the nearest value, with ties going away from zero
+    HalfUp,
+    /// Round towards negative infinity
+    Floor,
+    /// Round towards positive infinity
+    Ceil
+}
+
+/// The least number of digits after the point that the `/` operator keeps.
+pub static DIV_SCALE: uint = 32;
+
+/// A decimal number of arbitrary precision: an integer scaled down by a
+/// power of ten, so that `123.45` has the value 12345 and the scale 2.
+#[deriving(Clone)]
+pub struct BigDecimal {
+    priv value: BigInt,
+    priv scale: uint
+}
+
+impl BigDecimal {
+    /// Create the decimal `value / 10^scale`.
+    #[inline]
+    pub fn new(value: BigInt, scale: uint) -> BigDecimal {
+        BigDecimal { value: value, scale: scale }
+    }
+
+    /// Create a decimal representing the integer `n`.
+    #[inline]
+    pub fn from_bigint(n: BigInt) -> BigDecimal {
+        BigDecimal::new(n, 0)
+    }
+
+    /// Gets the unscaled value: the number times `10^scale`.
+    #[inline]
+    pub fn value<'a>(&'a self) -> &'a BigInt {
+        &self.value
+    }
+
+    /// Gets the number of digits after the decimal point.
+    #[inline]
+    pub fn scale(&self) -> uint {
+        self.scale
+    }
+
+    /// Returns the number with `scale` digits after the decimal point,
+    /// rounding with `mode` if that drops any.
+    pub fn round(&self, scale: uint, mode: RoundingMode) -> BigDecimal {
+        if scale >= self.scale {
+            return BigDecimal::new(self.value * ten_pow(scale - self.scale), scale);
+        }
+        let value = div_rounded(&self.value, &ten_pow(self.scale - scale), mode);
+        BigDecimal::new(value, scale)
+    }
+
+    /// Divides by `other`, keeping `scale` digits after the decimal point
+    /// and rounding with `mode`. Fails if `other` is zero.
+    pub fn div_round(&self, other: &BigDecimal, scale: uint,
+                     mode: RoundingMode) -> BigDecimal {
+        if other.is_zero() {
+            fail!("division by zero");
+        }
+        // the result's value is self.value * 10^(scale + other.scale - self.scale)
+        // divided by other.value
+        let (n, d) = if scale + other.scale >= self.scale {
+            (self.value * ten_pow(scale + other.scale - self.scale), other.value.clone())
+        } else {
+            (self.value.clone(), other.value * ten_pow(self.scale - scale - other.scale))
+        };
+        BigDecimal::new(div_rounded(&n, &d, mode), scale)
+    }
+
+    /// Returns the same number without trailing zeros after the point.
+    pub fn normalize(&self) -> BigDecimal {
+        let ten: BigInt = FromPrimitive::from_uint(10).unwrap();
+        let mut value = self.value.clone();
+        let mut scale = self.scale;
+        while scale > 0 && !value.is_zero() {
+            let (q, r) = value.div_rem(&ten);
+            if !r.is_zero() { break; }
+            value = q;
+            scale -= 1;
+        }
+        if value.is_zero() { scale = 0; }
+        BigDecimal::new(value, scale)
+    }
+}
+
+// 10^n
+fn ten_pow(n: uint) -> BigInt {
+    let ten: BigInt = FromPrimitive::from_uint(10).unwrap();
+    ten.pow(n)
+}
+
+// `n / d` rounded to an integer with the given mode. Fails if `d` is zero.
+fn div_rounded(n: &BigInt, d: &BigInt, mode: RoundingMode) -> BigInt {
+    if d.is_negative() {
+        return div_rounded(&-*n, &-*d, mode);
+    }
+    let one: BigInt = One::one();
+    let (q, r) = n.div_mod_floor(d);
+    if r.is_zero() {
+        return q;
+    }
+    match mode {
+        Floor => q,
+        Ceil => q + one,
+        HalfEven | HalfUp => match (r + r).cmp(d) {
+            Less => q,
+            Greater => q + one,
+            // exactly halfway between q and q + 1
+            Equal => match mode {
+                HalfUp if n.is_negative() => q,
+                HalfEven if q.is_even() => q,
+                _ => q + one
+            }
+        }
+    }
+}
+
+// The values of both numbers at the larger of their scales.
+fn align(a: &BigDecimal, b: &BigDecimal) -> (BigInt, BigInt, uint) {
+    let scale = cmp::max(a.scale, b.scale);
+    (a.value * ten_pow(scale - a.scale), b.value * ten_pow(scale - b.scale), scale)
+}
+
+/* Comparisons */
+impl Eq for BigDecimal {
+    #[inline]
+    fn eq(&self, other: &BigDecimal) -> bool { self.equals(other) }
+}
+
+impl TotalEq for BigDecimal {
+    /// Compares the numbers, so that `1.50` equals `1.5`.
+    #[inline]
+    fn equals(&self, other: &BigDecimal) -> bool {
+        self.cmp(other) == Equal
+    }
+}
+
+impl Ord for BigDecimal {
+    #[inline]
+    fn lt(&self, other: &BigDecimal) -> bool { self.cmp(other) == Less }
+}
+
+impl TotalOrd for BigDecimal {
+    fn cmp(&self, other: &BigDecimal) -> Ordering {
+        if self.scale == other.scale {
+            return self.value.cmp(&other.value);
+        }
+        let (a, b, _) = align(self, other);
+        a.cmp(&b)
+    }
+}
+
+impl Orderable for BigDecimal {
+    #[inline]
+    fn min(&self, other: &BigDecimal) -> BigDecimal {
+        if *self < *other { self.clone() } else { other.clone() }
+    }
+
+    #[inline]
+    fn max(&self, other: &BigDecimal) -> BigDecimal {
+        if *self > *other { self.clone() } else { other.clone() }
+    }
+
+    #[inline]
+    fn clamp(&self, mn: &BigDecimal, mx: &BigDecimal) -> BigDecimal {
+        if *self > *mx { mx.clone() } else
+        if *self < *mn { mn.clone() } else { self.clone() }
+    }
+}
+
+/* Arithmetic */
+impl Add<BigDecimal, BigDecimal> for BigDecimal {
+    fn add(&self, other: &BigDecimal) -> BigDecimal {
+        let (a, b, scale) = align(self, other);
+        BigDecimal::new(a + b, scale)
+    }
+}
+
+impl Sub<BigDecimal, BigDecimal> for BigDecimal {
+    fn sub(&self, other: &BigDecimal) -> BigDecimal {
+        let (a, b, scale) = align(self, other);
+        BigDecimal::new(a - b, scale)
+    }
+}
+
+impl Mul<BigDecimal, BigDecimal> for BigDecimal {
+    #[inline]
+    fn mul(&self, other: &BigDecimal) -> BigDecimal {
+        BigDecimal::new(self.value * other.value, self.scale + other.scale)
+    }
+}
+
+impl Div<BigDecimal, BigDecimal> for BigDecimal {
+    /// Divides, keeping as many digits after the point as the more precise
+    /// operand but at least `DIV_SCALE`, and rounding half-even.
+    fn div(&self, other: &BigDecimal) -> BigDecimal {
+        let scale = cmp::max(cmp::max(self.scale, other.scale), DIV_SCALE);
+        self.div_round(other, scale, HalfEven)
+    }
+}
+
+impl Rem<BigDecimal, BigDecimal> for BigDecimal {
+    /// The exact remainder of division truncated to an integer, which has
+    /// the sign of `self`.
+    fn rem(&self, other: &BigDecimal) -> BigDecimal {
+        let (a, b, scale) = align(self, other);
+        BigDecimal::new(a % b, scale)
+    }
+}
+
+impl Neg<BigDecimal> for BigDecimal {
+    #[inline]
+    fn neg(&self) -> BigDecimal {
+        BigDecimal::new(-self.value, self.scale)
+    }
+}
+
+/* Constants */
+impl Zero for BigDecimal {
+    #[inline]
+    fn zero() -> BigDecimal {
+        BigDecimal::new(Zero::zero(), 0)
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool { self.value.is_zero() }
+}
+
+impl One for BigDecimal {
+    #[inline]
+    fn one() -> BigDecimal {
+        BigDecimal::new(One::one(), 0)
+    }
+}
+
+impl Num for BigDecimal {}
+
+impl Signed for BigDecimal {
+    #[inline]
+    fn abs(&self) -> BigDecimal {
+        BigDecimal::new(self.value.abs(), self.scale)
+    }
+
+    #[inline]
+    fn abs_sub(&self, other: &BigDecimal) -> BigDecimal {
+        if *self <= *other { Zero::zero() } else { *self - *other }
+    }
+
+    #[inline]
+    fn signum(&self) -> BigDecimal {
+        BigDecimal::from_bigint(self.value.signum())
+    }
+
+    #[inline]
+    fn is_positive(&self) -> bool { self.value.is_positive() }
+
+    #[inline]
+    fn is_negative(&self) -> bool { self.value.is_negative() }
+}
+
+/* String conversions */
+impl ToStr for BigDecimal {
+    /// Renders with all `scale` digits after the point, as in `-0.50`.
+    fn to_str(&self) -> ~str {
+        let sign = if self.value.is_negative() { "-" } else { "" };
+        let digits = self.value.abs().to_str();
+        if self.scale == 0 {
+            return sign + digits;
+        }
+        let digits = if digits.len() <= self.scale {
+            "0".repeat(self.scale + 1 - digits.len()) + digits
+        } else {
+            digits
+        };
+        let point = digits.len() - self.scale;
+        format!("{}{}.{}", sign, digits.slice_to(point), digits.slice_from(point))
+    }
+}
+
+impl FromStr for BigDecimal {
+    /// Parses a number such as `-123.45`, keeping as many digits after the
+    /// point as it has.
+    fn from_str(s: &str) -> Option<BigDecimal> {
+        let (sign, s) = if s.starts_with("-") {
+            (Minus, s.slice_from(1))
+        } else if s.starts_with("+") {
+            (Plus, s.slice_from(1))
+        } else {
+            (Plus, s)
+        };
+        let (int_part, frac_part) = match s.find('.') {
+            Some(i) => (s.slice_to(i), s.slice_from(i + 1)),
+            None => (s, "")
+        };
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+        if !int_part.iter().all(|c| c.is_digit()) || !frac_part.iter().all(|c| c.is_digit()) {
+            return None;
+        }
+
+        let magnitude: Option<BigUint> = from_str(int_part.to_owned() + frac_part);
+        do magnitude.map |m| {
+            BigDecimal::new(BigInt::from_biguint(sign, m), frac_part.len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::num::{Zero, One};
+
+    fn dec(s: &str) -> BigDecimal {
+        from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_to_from_str() {
+        fn test(s: &str, ans: &str) {
+            assert_eq!(dec(s).to_str(), ans.to_owned());
+        }
+
+        test("0", "0");
+        test("-0.0", "0.0");
+        test("123.45", "123.45");
+        test("-123.450", "-123.450");
+        test("+7", "7");
+        test("0.05", "0.05");
+        test("-.5", "-0.5");
+        test("12.", "12");
+        test("00012.3400", "12.3400");
+        test("123456789012345678901234567890.000000000000000000001",
+             "123456789012345678901234567890.000000000000000000001");
+    }
+
+    #[test]
+    fn test_from_str_fail() {
+        fn test(s: &str) {
+            let d: Option<BigDecimal> = from_str(s);
+            assert_eq!(d, None);
+        }
+
+        test("");
+        test(".");
+        test("-");
+        test("1.2.3");
+        test("1e5");
+        test("- 1");
+        test("1,5");
+        test("--1");
+    }
+
+    #[test]
+    fn test_cmp() {
+        assert_eq!(dec("1.50"), dec("1.5"));
+        assert_eq!(dec("0.000"), Zero::zero());
+        assert_eq!(dec("1.0"), One::one());
+        assert!(dec("1.49") < dec("1.5"));
+        assert!(dec("-1.5") < dec("-1.49"));
+        assert!(dec("10") > dec("9.99999"));
+        assert_eq!(dec("2.5").max(&dec("2.50001")), dec("2.50001"));
+    }
+
+    #[test]
+    fn test_arith() {
+        assert_eq!((dec("0.1") + dec("0.2")).to_str(), ~"0.3");
+        assert_eq!((dec("1") - dec("0.01")).to_str(), ~"0.99");
+        assert_eq!((dec("1.5") * dec("-2.25")).to_str(), ~"-3.375");
+        assert_eq!((dec("7.5") % dec("2")).to_str(), ~"1.5");
+        assert_eq!((dec("-7.5") % dec("2")).to_str(), ~"-1.5");
+        assert_eq!((dec("1") / dec("4")), dec("0.25"));
+        assert_eq!((dec("1") / dec("3")).to_str(), ~"0." + "3".repeat(DIV_SCALE));
+        assert_eq!(-dec("1.5"), dec("-1.5"));
+        assert_eq!(dec("-1.5").abs(), dec("1.5"));
+        assert_eq!(dec("-1.5").signum(), dec("-1"));
+    }
+
+    #[test]
+    fn test_round() {
+        fn test(s: &str, scale: uint, mode: RoundingMode, ans: &str) {
+            assert_eq!(dec(s).round(scale, mode).to_str(), ans.to_owned());
+        }
+
+        test("1.005", 2, HalfEven, "1.00");
+        test("1.015", 2, HalfEven, "1.02");
+        test("-1.005", 2, HalfEven, "-1.00");
+        test("1.0051", 2, HalfEven, "1.01");
+        test("1.005", 2, HalfUp, "1.01");
+        test("-1.005", 2, HalfUp, "-1.01");
+        test("1.0049", 2, HalfUp, "1.00");
+        test("1.009", 2, Floor, "1.00");
+        test("-1.001", 2, Floor, "-1.01");
+        test("1.001", 2, Ceil, "1.01");
+        test("-1.009", 2, Ceil, "-1.00");
+        test("2.5", 0, HalfEven, "2");
+        test("3.5", 0, HalfEven, "4");
+        test("1.5", 3, Floor, "1.500");
+    }
+
+    #[test]
+    fn test_div_round() {
+        fn test(a: &str, b: &str, scale: uint, mode: RoundingMode, ans: &str) {
+            assert_eq!(dec(a).div_round(&dec(b), scale, mode).to_str(), ans.to_owned());
+        }
+
+        test("1", "3", 4, HalfEven, "0.3333");
+        test("2", "3", 4, HalfUp, "0.6667");
+        test("2", "3", 4, Floor, "0.6666");
+        test("-2", "3", 4, Floor, "-0.6667");
+        test("-2", "3", 4, Ceil, "-0.6666");
+        test("10", "4", 0, HalfEven, "2");
+        test("10", "4", 0, HalfUp, "3");
+        test("1", "-8", 2, HalfEven, "-0.12");
+        test("1", "-8", 2, HalfUp, "-0.13");
+        test("100.00", "0.3", 0, Floor, "333");
+        test("1.23456", "1", 2, HalfEven, "1.23");
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_div_zero() {
+        dec("1") / dec("0.00");
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(dec("1.500").normalize().to_str(), ~"1.5");
+        assert_eq!(dec("100").normalize().to_str(), ~"100");
+        assert_eq!(dec("-0.000").normalize().to_str(), ~"0");
+    }
+}
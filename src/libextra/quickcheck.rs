@@ -0,0 +1,601 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Property-based testing, in the style of Haskell's QuickCheck.
+ *
+ * A property is a function from some input to `bool` that should hold for
+ * every input. `quickcheck` calls it with randomly generated inputs, and if
+ * one makes it return `false`, shrinks that input to the smallest one it
+ * can find that still fails and then fails the task, reporting it:
+ *
+ * ```
+ * #[test]
+ * fn reverse_is_involutive() {
+ *     do quickcheck |v: ~[int]| {
+ *         let mut w = v.clone();
+ *         w.reverse();
+ *         w.reverse();
+ *         w == v
+ *     }
+ * }
+ * ```
+ *
+ * Inputs are produced by the `Arbitrary` trait, which is implemented for
+ * the primitive types, `~str`, vectors, `Option`, `Result`, tuples and the
+ * libextra containers, and can be derived with `#[deriving(Arbitrary)]` (the
+ * type must also be `Clone`). Properties of several values take a tuple.
+ */
+
+use dlist::DList;
+use priority_queue::PriorityQueue;
+use ringbuf::RingBuf;
+use treemap::{TreeMap, TreeSet};
+
+use std::rand::Rng;
+use std::rand;
+use std::str;
+use std::vec;
+
+/// A source of randomness for `Arbitrary`, which also bounds the size of
+/// what is generated: the length of vectors and strings, and the magnitude
+/// of numbers.
+pub trait Gen: Rng {
+    fn size(&self) -> uint;
+}
+
+/// The `Gen` made from any `Rng`.
+pub struct StdGen<R> {
+    priv rng: R,
+    priv size: uint
+}
+
+impl<R: Rng> StdGen<R> {
+    pub fn new(rng: R, size: uint) -> StdGen<R> {
+        StdGen { rng: rng, size: size }
+    }
+}
+
+impl<R: Rng> Rng for StdGen<R> {
+    fn next_u32(&mut self) -> u32 { self.rng.next_u32() }
+    fn next_u64(&mut self) -> u64 { self.rng.next_u64() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) { self.rng.fill_bytes(dest) }
+}
+
+impl<R: Rng> Gen for StdGen<R> {
+    fn size(&self) -> uint { self.size }
+}
+
+/// Types that random values can be generated for, and that know how to
+/// make a value "smaller" for when it turns out to be a counterexample.
+pub trait Arbitrary: Clone {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self;
+
+    /// Values simpler than `self`, simplest first. Every value reached by
+    /// shrinking repeatedly must eventually have no shrinks, or reducing a
+    /// counterexample would never end.
+    fn shrink(&self) -> ~[Self] { ~[] }
+}
+
+impl Arbitrary for () {
+    fn arbitrary<G: Gen>(_: &mut G) -> () { () }
+}
+
+impl Arbitrary for bool {
+    fn arbitrary<G: Gen>(g: &mut G) -> bool { g.gen() }
+
+    fn shrink(&self) -> ~[bool] {
+        if *self { ~[false] } else { ~[] }
+    }
+}
+
+impl Arbitrary for char {
+    fn arbitrary<G: Gen>(g: &mut G) -> char {
+        // Mostly printable ASCII, which is what string handling tends to
+        // get wrong first, with the odd arbitrary code point.
+        if g.gen_weighted_bool(10) {
+            g.gen()
+        } else {
+            g.gen_range(32u8, 127) as char
+        }
+    }
+
+    fn shrink(&self) -> ~[char] {
+        if *self == 'a' { ~[] } else { ~['a'] }
+    }
+}
+
+// Toward zero: 0 itself, then x with ever smaller halves of it taken off.
+fn shrink_signed(x: i64) -> ~[i64] {
+    if x == 0 {
+        return ~[];
+    }
+    let mut xs = ~[0];
+    if x < 0 {
+        xs.push(-x);
+    }
+    let mut i = x / 2;
+    while i != 0 {
+        xs.push(x - i);
+        i /= 2;
+    }
+    xs
+}
+
+fn shrink_unsigned(x: u64) -> ~[u64] {
+    if x == 0 {
+        return ~[];
+    }
+    let mut xs = ~[0];
+    let mut i = x / 2;
+    while i != 0 {
+        xs.push(x - i);
+        i /= 2;
+    }
+    xs
+}
+
+macro_rules! signed_arbitrary(
+    ($ty:ty) => (
+        impl Arbitrary for $ty {
+            fn arbitrary<G: Gen>(g: &mut G) -> $ty {
+                // In [-s, s], with s clamped to the type's maximum: then
+                // 2 * s + 1 is at most u64's maximum even for i64, and taking
+                // s off wraps values below it around to exactly the negative
+                // numbers the cast gives back. The minimum is never generated.
+                let max: $ty = Bounded::max_value();
+                let s = (g.size() as u64).min(&(max as u64));
+                (g.gen_range(0, 2 * s + 1) - s) as $ty
+            }
+
+            fn shrink(&self) -> ~[$ty] {
+                // Negating the minimum gives the minimum back, which must
+                // not count as a shrink of itself.
+                shrink_signed(*self as i64).move_iter()
+                    .map(|x| x as $ty)
+                    .filter(|&x| x != *self)
+                    .collect()
+            }
+        }
+    )
+)
+
+macro_rules! unsigned_arbitrary(
+    ($ty:ty) => (
+        impl Arbitrary for $ty {
+            fn arbitrary<G: Gen>(g: &mut G) -> $ty {
+                let max: $ty = Bounded::max_value();
+                let s = (g.size() as u64).min(&(max as u64));
+                if s == Bounded::max_value() {
+                    g.gen()
+                } else {
+                    g.gen_range(0, s + 1) as $ty
+                }
+            }
+
+            fn shrink(&self) -> ~[$ty] {
+                shrink_unsigned(*self as u64).move_iter().map(|x| x as $ty).collect()
+            }
+        }
+    )
+)
+
+signed_arbitrary!(int)
+signed_arbitrary!(i8)
+signed_arbitrary!(i16)
+signed_arbitrary!(i32)
+signed_arbitrary!(i64)
+unsigned_arbitrary!(uint)
+unsigned_arbitrary!(u8)
+unsigned_arbitrary!(u16)
+unsigned_arbitrary!(u32)
+unsigned_arbitrary!(u64)
+
+// Toward zero, and toward the nearest integer. Halving would take a
+// thousand steps to get anywhere, so floats shrink no further than that.
+fn shrink_float(x: f64) -> ~[f64] {
+    if x == 0.0 {
+        return ~[];
+    }
+    let mut xs = ~[0.0];
+    if x < 0.0 {
+        xs.push(-x);
+    }
+    let t = x.trunc();
+    if t != x && t != 0.0 {
+        xs.push(t);
+    }
+    xs
+}
+
+impl Arbitrary for f32 {
+    fn arbitrary<G: Gen>(g: &mut G) -> f32 {
+        let f: f64 = Arbitrary::arbitrary(g);
+        f as f32
+    }
+
+    fn shrink(&self) -> ~[f32] {
+        shrink_float(*self as f64).move_iter().map(|x| x as f32).collect()
+    }
+}
+
+impl Arbitrary for f64 {
+    fn arbitrary<G: Gen>(g: &mut G) -> f64 {
+        let s = g.size() as f64;
+        (g.gen::<f64>() * 2.0 - 1.0) * s
+    }
+
+    fn shrink(&self) -> ~[f64] {
+        shrink_float(*self)
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for ~[T] {
+    fn arbitrary<G: Gen>(g: &mut G) -> ~[T] {
+        let size = g.size();
+        let len = g.gen_range(0, size + 1);
+        let mut v = vec::with_capacity(len);
+        for _ in range(0, len) {
+            v.push(Arbitrary::arbitrary(g));
+        }
+        v
+    }
+
+    /// Removing ever smaller runs of elements, starting with all of them,
+    /// then shrinking each element in place.
+    fn shrink(&self) -> ~[~[T]] {
+        let mut shrunk = ~[];
+        let n = self.len();
+        let mut k = n;
+        while k > 0 {
+            let mut i = 0;
+            while i + k <= n {
+                let mut v = self.slice_to(i).to_owned();
+                v.push_all(self.slice_from(i + k));
+                shrunk.push(v);
+                i += k;
+            }
+            k /= 2;
+        }
+        for (i, x) in self.iter().enumerate() {
+            for s in x.shrink().move_iter() {
+                let mut v = self.clone();
+                v[i] = s;
+                shrunk.push(v);
+            }
+        }
+        shrunk
+    }
+}
+
+impl Arbitrary for ~str {
+    fn arbitrary<G: Gen>(g: &mut G) -> ~str {
+        let chars: ~[char] = Arbitrary::arbitrary(g);
+        str::from_chars(chars)
+    }
+
+    fn shrink(&self) -> ~[~str] {
+        let chars: ~[char] = self.iter().collect();
+        chars.shrink().move_iter().map(|cs| str::from_chars(cs)).collect()
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for ~T {
+    fn arbitrary<G: Gen>(g: &mut G) -> ~T {
+        ~Arbitrary::arbitrary(g)
+    }
+
+    fn shrink(&self) -> ~[~T] {
+        (**self).shrink().move_iter().map(|x| ~x).collect()
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Option<T> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Option<T> {
+        if g.gen_weighted_bool(4) {
+            None
+        } else {
+            Some(Arbitrary::arbitrary(g))
+        }
+    }
+
+    fn shrink(&self) -> ~[Option<T>] {
+        match *self {
+            None => ~[],
+            Some(ref x) => {
+                let mut shrunk = ~[None];
+                shrunk.extend(&mut x.shrink().move_iter().map(|y| Some(y)));
+                shrunk
+            }
+        }
+    }
+}
+
+impl<T: Arbitrary, E: Arbitrary> Arbitrary for Result<T, E> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Result<T, E> {
+        if g.gen() {
+            Ok(Arbitrary::arbitrary(g))
+        } else {
+            Err(Arbitrary::arbitrary(g))
+        }
+    }
+
+    fn shrink(&self) -> ~[Result<T, E>] {
+        match *self {
+            Ok(ref x) => x.shrink().move_iter().map(|y| Ok(y)).collect(),
+            Err(ref e) => e.shrink().move_iter().map(|y| Err(y)).collect()
+        }
+    }
+}
+
+impl<A: Arbitrary, B: Arbitrary> Arbitrary for (A, B) {
+    fn arbitrary<G: Gen>(g: &mut G) -> (A, B) {
+        let a = Arbitrary::arbitrary(g);
+        let b = Arbitrary::arbitrary(g);
+        (a, b)
+    }
+
+    fn shrink(&self) -> ~[(A, B)] {
+        let (ref a, ref b) = *self;
+        let mut shrunk: ~[(A, B)] = a.shrink().move_iter().map(|x| (x, b.clone())).collect();
+        shrunk.extend(&mut b.shrink().move_iter().map(|y| (a.clone(), y)));
+        shrunk
+    }
+}
+
+impl<A: Arbitrary, B: Arbitrary, C: Arbitrary> Arbitrary for (A, B, C) {
+    fn arbitrary<G: Gen>(g: &mut G) -> (A, B, C) {
+        let a = Arbitrary::arbitrary(g);
+        let b = Arbitrary::arbitrary(g);
+        let c = Arbitrary::arbitrary(g);
+        (a, b, c)
+    }
+
+    fn shrink(&self) -> ~[(A, B, C)] {
+        let (ref a, ref b, ref c) = *self;
+        let mut shrunk: ~[(A, B, C)] =
+            a.shrink().move_iter().map(|x| (x, b.clone(), c.clone())).collect();
+        shrunk.extend(&mut b.shrink().move_iter().map(|y| (a.clone(), y, c.clone())));
+        shrunk.extend(&mut c.shrink().move_iter().map(|z| (a.clone(), b.clone(), z)));
+        shrunk
+    }
+}
+
+// The containers are generated and shrunk as the vector of their elements.
+fn shrink_elements<T: Arbitrary, C: FromIterator<T>>(elements: ~[T]) -> ~[C] {
+    elements.shrink().move_iter().map(|v| v.move_iter().collect()).collect()
+}
+
+impl<K: TotalOrd + Arbitrary, V: Arbitrary> Arbitrary for TreeMap<K, V> {
+    fn arbitrary<G: Gen>(g: &mut G) -> TreeMap<K, V> {
+        let pairs: ~[(K, V)] = Arbitrary::arbitrary(g);
+        pairs.move_iter().collect()
+    }
+
+    fn shrink(&self) -> ~[TreeMap<K, V>] {
+        shrink_elements(self.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+    }
+}
+
+impl<T: TotalOrd + Arbitrary> Arbitrary for TreeSet<T> {
+    fn arbitrary<G: Gen>(g: &mut G) -> TreeSet<T> {
+        let elements: ~[T] = Arbitrary::arbitrary(g);
+        elements.move_iter().collect()
+    }
+
+    fn shrink(&self) -> ~[TreeSet<T>] {
+        shrink_elements(self.iter().map(|x| x.clone()).collect())
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for DList<T> {
+    fn arbitrary<G: Gen>(g: &mut G) -> DList<T> {
+        let elements: ~[T] = Arbitrary::arbitrary(g);
+        elements.move_iter().collect()
+    }
+
+    fn shrink(&self) -> ~[DList<T>] {
+        shrink_elements(self.iter().map(|x| x.clone()).collect())
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for RingBuf<T> {
+    fn arbitrary<G: Gen>(g: &mut G) -> RingBuf<T> {
+        let elements: ~[T] = Arbitrary::arbitrary(g);
+        elements.move_iter().collect()
+    }
+
+    fn shrink(&self) -> ~[RingBuf<T>] {
+        shrink_elements(self.iter().map(|x| x.clone()).collect())
+    }
+}
+
+impl<T: Ord + Arbitrary> Arbitrary for PriorityQueue<T> {
+    fn arbitrary<G: Gen>(g: &mut G) -> PriorityQueue<T> {
+        let elements: ~[T] = Arbitrary::arbitrary(g);
+        elements.move_iter().collect()
+    }
+
+    fn shrink(&self) -> ~[PriorityQueue<T>] {
+        shrink_elements(self.iter().map(|x| x.clone()).collect())
+    }
+}
+
+/// How hard `check` tries to falsify a property.
+pub struct Config {
+    /// How many inputs to try
+    tests: uint,
+    /// The size the last input is generated with. Earlier ones are smaller,
+    /// so that simple failures turn up first.
+    size: uint
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config { tests: 100, size: 100 }
+    }
+}
+
+/// An input that a property failed for.
+#[deriving(Clone, Eq)]
+pub struct Failure<A> {
+    /// The input as first generated
+    original: A,
+    /// The smallest input shrinking `original` led to that still fails
+    shrunk: A,
+    /// How many inputs were tried, including `original`
+    tests: uint,
+    /// How many times a smaller failing input was found
+    shrinks: uint
+}
+
+/// Tries `prop` on `config.tests` inputs generated from `rng`, returning
+/// how many passed, or the (shrunk) input it failed for.
+pub fn check<A: Arbitrary, R: Rng>(config: &Config, rng: R,
+                                   prop: &fn(A) -> bool) -> Result<uint, Failure<A>> {
+    let mut g = StdGen::new(rng, 0);
+    for i in range(0, config.tests) {
+        g.size = (i + 1) * config.size / config.tests;
+        let input: A = Arbitrary::arbitrary(&mut g);
+        if !prop(input.clone()) {
+            let (shrunk, shrinks) = shrink_failure(|a| prop(a), input.clone());
+            return Err(Failure {
+                original: input,
+                shrunk: shrunk,
+                tests: i + 1,
+                shrinks: shrinks
+            });
+        }
+    }
+    Ok(config.tests)
+}
+
+// Moves to the first failing shrink of the input until there are none.
+fn shrink_failure<A: Arbitrary>(prop: &fn(A) -> bool, input: A) -> (A, uint) {
+    let mut smallest = input;
+    let mut shrinks = 0;
+    loop {
+        match smallest.shrink().move_iter().find(|s| !prop(s.clone())) {
+            Some(s) => {
+                smallest = s;
+                shrinks += 1;
+            }
+            None => return (smallest, shrinks)
+        }
+    }
+}
+
+/// Checks `prop` with the default `Config`, failing the task with the
+/// smallest counterexample found if it does not hold.
+pub fn quickcheck<A: Arbitrary>(prop: &fn(A) -> bool) {
+    match check(&Config::new(), rand::rng(), prop) {
+        Ok(_) => {}
+        Err(failure) => {
+            fail!("property failed after {} tests and {} shrinks, counterexample: {:?}",
+                  failure.tests, failure.shrinks, failure.shrunk)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::rand::{SeedableRng, XorShiftRng};
+    use std::uint;
+
+    fn test_rng() -> XorShiftRng {
+        SeedableRng::from_seed([1, 2, 3, 4])
+    }
+
+    #[test]
+    fn test_shrink_integers() {
+        assert_eq!(10i.shrink(), ~[0, 5, 8, 9]);
+        assert_eq!((-4i).shrink(), ~[0, 4, -2, -3]);
+        assert_eq!(1u8.shrink(), ~[0]);
+        assert_eq!(0u.shrink(), ~[]);
+    }
+
+    #[test]
+    fn test_signed_minimum() {
+        let min: i8 = Bounded::min_value();
+        assert!(!min.shrink().contains(&min));
+        assert_eq!(super::shrink_failure(|x: i8| x.abs() >= 0, min), (min, 0));
+
+        // Sizes past the type's range are clamped rather than wrapping.
+        let mut g = StdGen::new(test_rng(), 1000);
+        do 1000.times {
+            let x: i8 = Arbitrary::arbitrary(&mut g);
+            assert!(x != min);
+        }
+        let mut g = StdGen::new(test_rng(), uint::max_value);
+        do 1000.times {
+            let x: i64 = Arbitrary::arbitrary(&mut g);
+            assert!(x != Bounded::min_value());
+        }
+    }
+
+    #[test]
+    fn test_shrink_vec() {
+        assert_eq!((~[1u, 2]).shrink(),
+                   ~[~[], ~[2], ~[1], ~[0, 2], ~[1, 0], ~[1, 1]]);
+        let empty: ~[uint] = ~[];
+        assert_eq!(empty.shrink(), ~[]);
+    }
+
+    #[test]
+    fn test_shrink_option_and_tuple() {
+        assert_eq!(Some(2u).shrink(), ~[None, Some(0), Some(1)]);
+        assert_eq!((true, 1u).shrink(), ~[(false, 1), (true, 0)]);
+    }
+
+    #[test]
+    fn test_generated_sizes() {
+        let mut g = StdGen::new(test_rng(), 5);
+        do 100.times {
+            let v: ~[int] = Arbitrary::arbitrary(&mut g);
+            assert!(v.len() <= 5);
+            assert!(v.iter().all(|&x| -5 <= x && x <= 5));
+        }
+    }
+
+    #[test]
+    fn test_property_holds() {
+        let result = do check(&Config::new(), test_rng()) |v: ~[int]| {
+            let mut w = v.clone();
+            w.reverse();
+            w.reverse();
+            w == v
+        };
+        assert_eq!(result, Ok(100));
+    }
+
+    #[test]
+    fn test_counterexample_is_minimal() {
+        match do check(&Config::new(), test_rng()) |v: ~[uint]| { v.len() < 3 } {
+            Ok(_) => fail!("property should not hold"),
+            Err(failure) => {
+                assert_eq!(failure.shrunk, ~[0, 0, 0]);
+                assert!(failure.original.len() >= 3);
+            }
+        }
+
+        match do check(&Config::new(), test_rng()) |x: int| { x < 10 } {
+            Ok(_) => fail!("property should not hold"),
+            Err(failure) => assert_eq!(failure.shrunk, 10)
+        }
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_quickcheck_fails() {
+        do quickcheck |s: ~str| { s.len() < 2 }
+    }
+}
@@ -0,0 +1,656 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * A rope: a string type for editing large texts.
+ *
+ * A `Rope` stores its text as a height-balanced binary tree whose leaves
+ * are small `~str` chunks. Each node caches the number of chars and
+ * newlines beneath it, so inserting, deleting and slicing by char index,
+ * and converting between char offsets and line numbers, are all
+ * `O(log n)` in the length of the text rather than `O(n)` as with `~str`.
+ *
+ * All indices taken and returned by this module count chars, not bytes.
+ *
+ * # Example
+ *
+ * ```rust
+ * use extra::rope::Rope;
+ *
+ * let mut r = Rope::from_slice("hello world");
+ * r.insert(5, ",");
+ * r.delete(6, 12);
+ * assert_eq!(r.to_str(), ~"hello,");
+ * ```
+ */
+
+use std::cmp;
+use std::str;
+use std::util;
+
+/// The largest number of chars stored in a single leaf
+static MAX_LEAF: uint = 512;
+
+#[deriving(Clone)]
+struct Node {
+    chars: uint,
+    newlines: uint,
+    height: uint,
+    kind: NodeKind
+}
+
+#[deriving(Clone)]
+enum NodeKind {
+    Leaf(~str),
+    Branch(~Node, ~Node)
+}
+
+fn leaf(s: ~str) -> ~Node {
+    let chars = s.char_len();
+    let newlines = s.iter().count(|c| c == '\n');
+    ~Node { chars: chars, newlines: newlines, height: 0, kind: Leaf(s) }
+}
+
+fn branch(l: ~Node, r: ~Node) -> ~Node {
+    let chars = l.chars + r.chars;
+    let newlines = l.newlines + r.newlines;
+    let height = cmp::max(l.height, r.height) + 1;
+    ~Node { chars: chars, newlines: newlines, height: height, kind: Branch(l, r) }
+}
+
+fn children(n: ~Node) -> (~Node, ~Node) {
+    let ~Node { kind, _ } = n;
+    match kind {
+        Branch(l, r) => (l, r),
+        Leaf(_) => fail!("rope: expected a branch node")
+    }
+}
+
+/// Builds a balanced tree from `s`, splitting it into leaves of at most
+/// `MAX_LEAF` chars.
+fn build(s: &str) -> ~Node {
+    let chars = s.char_len();
+    if chars <= MAX_LEAF {
+        return leaf(s.to_owned());
+    }
+    let mid = s.slice_chars(0, chars / 2).len();
+    branch(build(s.slice_to(mid)), build(s.slice_from(mid)))
+}
+
+/// Restores the height invariant for a node whose children differ in
+/// height by at most two.
+fn balance(l: ~Node, r: ~Node) -> ~Node {
+    if l.height > r.height + 1 {
+        let (ll, lr) = children(l);
+        if ll.height >= lr.height {
+            branch(ll, branch(lr, r))
+        } else {
+            let (lrl, lrr) = children(lr);
+            branch(branch(ll, lrl), branch(lrr, r))
+        }
+    } else if r.height > l.height + 1 {
+        let (rl, rr) = children(r);
+        if rr.height >= rl.height {
+            branch(branch(l, rl), rr)
+        } else {
+            let (rll, rlr) = children(rl);
+            branch(branch(l, rll), branch(rlr, rr))
+        }
+    } else {
+        branch(l, r)
+    }
+}
+
+/// Concatenates two trees, keeping the result balanced.
+fn join(l: ~Node, r: ~Node) -> ~Node {
+    if l.chars == 0 { return r; }
+    if r.chars == 0 { return l; }
+
+    if l.height == 0 && r.height == 0 && l.chars + r.chars <= MAX_LEAF {
+        let ~Node { kind: lk, _ } = l;
+        let ~Node { kind: rk, _ } = r;
+        return match (lk, rk) {
+            (Leaf(a), Leaf(b)) => leaf(a + b),
+            _ => fail!("rope: expected leaf nodes")
+        };
+    }
+
+    if l.height > r.height + 1 {
+        let (ll, lr) = children(l);
+        balance(ll, join(lr, r))
+    } else if r.height > l.height + 1 {
+        let (rl, rr) = children(r);
+        balance(join(l, rl), rr)
+    } else {
+        branch(l, r)
+    }
+}
+
+/// Splits a tree into the first `idx` chars and the rest.
+fn split(n: ~Node, idx: uint) -> (~Node, ~Node) {
+    if idx == 0 { return (leaf(~""), n); }
+    if idx >= n.chars { return (n, leaf(~"")); }
+
+    let ~Node { kind, _ } = n;
+    match kind {
+        Leaf(s) => {
+            let b = s.slice_chars(0, idx).len();
+            (leaf(s.slice_to(b).to_owned()), leaf(s.slice_from(b).to_owned()))
+        }
+        Branch(l, r) => {
+            let lchars = l.chars;
+            if idx <= lchars {
+                let (a, b) = split(l, idx);
+                (a, join(b, r))
+            } else {
+                let (a, b) = split(r, idx - lchars);
+                (join(l, a), b)
+            }
+        }
+    }
+}
+
+/// Copies the chars in `[start, end)` of `n` into a new tree.
+fn sub(n: &Node, start: uint, end: uint) -> ~Node {
+    if start == 0 && end == n.chars {
+        return ~n.clone();
+    }
+    match n.kind {
+        Leaf(ref s) => leaf(s.slice_chars(start, end).to_owned()),
+        Branch(ref l, ref r) => {
+            if end <= l.chars {
+                sub(*l, start, end)
+            } else if start >= l.chars {
+                sub(*r, start - l.chars, end - l.chars)
+            } else {
+                join(sub(*l, start, l.chars), sub(*r, 0, end - l.chars))
+            }
+        }
+    }
+}
+
+fn char_at(n: &Node, idx: uint) -> char {
+    match n.kind {
+        Leaf(ref s) => s.slice_chars(idx, idx + 1).char_at(0),
+        Branch(ref l, ref r) => {
+            if idx < l.chars { char_at(*l, idx) } else { char_at(*r, idx - l.chars) }
+        }
+    }
+}
+
+/// The number of newlines among the first `idx` chars of `n`.
+fn newlines_before(n: &Node, idx: uint) -> uint {
+    match n.kind {
+        Leaf(ref s) => s.slice_chars(0, idx).iter().count(|c| c == '\n'),
+        Branch(ref l, ref r) => {
+            if idx <= l.chars {
+                newlines_before(*l, idx)
+            } else {
+                l.newlines + newlines_before(*r, idx - l.chars)
+            }
+        }
+    }
+}
+
+/// The char offset of the `k`th newline in `n`, counting from 1.
+fn newline_pos(n: &Node, k: uint) -> uint {
+    match n.kind {
+        Leaf(ref s) => {
+            let mut seen = 0;
+            for (i, c) in s.iter().enumerate() {
+                if c == '\n' {
+                    seen += 1;
+                    if seen == k { return i; }
+                }
+            }
+            fail!("rope: newline index out of bounds")
+        }
+        Branch(ref l, ref r) => {
+            if k <= l.newlines {
+                newline_pos(*l, k)
+            } else {
+                l.chars + newline_pos(*r, k - l.newlines)
+            }
+        }
+    }
+}
+
+/// A string stored as a balanced tree of chunks
+#[deriving(Clone)]
+pub struct Rope {
+    priv root: ~Node
+}
+
+impl Rope {
+    /// Create an empty rope
+    pub fn new() -> Rope {
+        Rope { root: leaf(~"") }
+    }
+
+    /// Create a rope holding a copy of `s`
+    pub fn from_slice(s: &str) -> Rope {
+        Rope { root: build(s) }
+    }
+
+    fn take_root(&mut self) -> ~Node {
+        util::replace(&mut self.root, leaf(~""))
+    }
+
+    /// The length of the rope in bytes
+    pub fn byte_len(&self) -> uint {
+        self.chunk_iter().fold(0, |n, s| n + s.len())
+    }
+
+    /// Returns the char at char index `idx`
+    pub fn char_at(&self, idx: uint) -> char {
+        assert!(idx < self.root.chars);
+        char_at(self.root, idx)
+    }
+
+    /// Insert `s` so that its first char ends up at char index `idx`
+    pub fn insert(&mut self, idx: uint, s: &str) {
+        assert!(idx <= self.root.chars);
+        if s.is_empty() { return; }
+        let (a, b) = split(self.take_root(), idx);
+        self.root = join(join(a, build(s)), b);
+    }
+
+    /// Append `s` to the end of the rope
+    pub fn push_str(&mut self, s: &str) {
+        let len = self.root.chars;
+        self.insert(len, s);
+    }
+
+    /// Append the contents of `other` to the end of the rope
+    pub fn append(&mut self, other: Rope) {
+        let Rope { root } = other;
+        self.root = join(self.take_root(), root);
+    }
+
+    /// Remove the chars in the range `[start, end)`
+    pub fn delete(&mut self, start: uint, end: uint) {
+        assert!(start <= end && end <= self.root.chars);
+        if start == end { return; }
+        let (a, rest) = split(self.take_root(), start);
+        let (_, c) = split(rest, end - start);
+        self.root = join(a, c);
+    }
+
+    /// Returns a new rope holding the chars in the range `[start, end)`
+    pub fn slice(&self, start: uint, end: uint) -> Rope {
+        assert!(start <= end && end <= self.root.chars);
+        Rope { root: sub(self.root, start, end) }
+    }
+
+    /**
+     * Split the rope at char index `idx`, leaving the first `idx` chars in
+     * `self` and returning the rest.
+     */
+    pub fn split_off(&mut self, idx: uint) -> Rope {
+        assert!(idx <= self.root.chars);
+        let (a, b) = split(self.take_root(), idx);
+        self.root = a;
+        Rope { root: b }
+    }
+
+    /// The number of lines, which is one more than the number of newlines
+    pub fn line_count(&self) -> uint {
+        self.root.newlines + 1
+    }
+
+    /// Returns the char index of the first char of line `line`
+    pub fn line_to_char(&self, line: uint) -> uint {
+        assert!(line < self.line_count());
+        if line == 0 { 0 } else { newline_pos(self.root, line) + 1 }
+    }
+
+    /// Returns the line and column, both counted from 0, of char index `idx`
+    pub fn char_to_line(&self, idx: uint) -> (uint, uint) {
+        assert!(idx <= self.root.chars);
+        let line = newlines_before(self.root, idx);
+        (line, idx - self.line_to_char(line))
+    }
+
+    /// Returns the contents of line `line`, without its trailing newline
+    pub fn line(&self, line: uint) -> ~str {
+        let start = self.line_to_char(line);
+        let end = if line + 1 < self.line_count() {
+            self.line_to_char(line + 1) - 1
+        } else {
+            self.root.chars
+        };
+        self.slice(start, end).to_str()
+    }
+
+    /**
+     * An iterator over the chunks of the rope, in order.
+     *
+     * Each chunk is a `&str`, so `StrSlice` methods can be applied to
+     * the text without copying it.
+     */
+    pub fn chunk_iter<'a>(&'a self) -> RopeChunkIterator<'a> {
+        RopeChunkIterator { stack: ~[&*self.root] }
+    }
+
+    /// An iterator over the chars of the rope
+    pub fn char_iter<'a>(&'a self) -> RopeCharIterator<'a> {
+        RopeCharIterator { chunks: self.chunk_iter(), chars: None }
+    }
+
+    /**
+     * An iterator over the lines of the rope, without their trailing
+     * newlines. As with `StrSlice::line_iter`, a final newline does not
+     * start an extra, empty line.
+     */
+    pub fn line_iter<'a>(&'a self) -> RopeLineIterator<'a> {
+        RopeLineIterator { chars: self.char_iter(), finished: false }
+    }
+}
+
+impl Container for Rope {
+    /// The length of the rope in chars
+    fn len(&self) -> uint { self.root.chars }
+}
+
+impl Mutable for Rope {
+    fn clear(&mut self) { self.root = leaf(~""); }
+}
+
+impl Default for Rope {
+    fn default() -> Rope { Rope::new() }
+}
+
+impl FromStr for Rope {
+    fn from_str(s: &str) -> Option<Rope> {
+        Some(Rope::from_slice(s))
+    }
+}
+
+impl ToStr for Rope {
+    fn to_str(&self) -> ~str {
+        let mut s = str::with_capacity(self.byte_len());
+        for chunk in self.chunk_iter() {
+            s.push_str(chunk);
+        }
+        s
+    }
+}
+
+impl Eq for Rope {
+    fn eq(&self, other: &Rope) -> bool {
+        self.len() == other.len() &&
+            self.char_iter().zip(other.char_iter()).all(|(a, b)| a == b)
+    }
+}
+
+/// An iterator over the chunks of a `Rope`
+pub struct RopeChunkIterator<'self> {
+    priv stack: ~[&'self Node]
+}
+
+impl<'self> Iterator<&'self str> for RopeChunkIterator<'self> {
+    fn next(&mut self) -> Option<&'self str> {
+        loop {
+            match self.stack.pop_opt() {
+                None => return None,
+                Some(n) => match n.kind {
+                    Leaf(ref s) => if !s.is_empty() { return Some(s.as_slice()) },
+                    Branch(ref l, ref r) => {
+                        self.stack.push(&**r);
+                        self.stack.push(&**l);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the chars of a `Rope`
+pub struct RopeCharIterator<'self> {
+    priv chunks: RopeChunkIterator<'self>,
+    priv chars: Option<str::CharIterator<'self>>
+}
+
+impl<'self> Iterator<char> for RopeCharIterator<'self> {
+    fn next(&mut self) -> Option<char> {
+        loop {
+            match self.chars {
+                Some(ref mut it) => match it.next() {
+                    Some(c) => return Some(c),
+                    None => ()
+                },
+                None => ()
+            }
+            match self.chunks.next() {
+                Some(s) => self.chars = Some(s.iter()),
+                None => return None
+            }
+        }
+    }
+}
+
+/// An iterator over the lines of a `Rope`
+pub struct RopeLineIterator<'self> {
+    priv chars: RopeCharIterator<'self>,
+    priv finished: bool
+}
+
+impl<'self> Iterator<~str> for RopeLineIterator<'self> {
+    fn next(&mut self) -> Option<~str> {
+        if self.finished { return None; }
+        let mut line = ~"";
+        loop {
+            match self.chars.next() {
+                Some('\n') => return Some(line),
+                Some(c) => line.push_char(c),
+                None => {
+                    self.finished = true;
+                    return if line.is_empty() { None } else { Some(line) };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::MAX_LEAF;
+    use extra::test::BenchHarness;
+
+    use std::cmp;
+    use std::rand;
+    use std::rand::Rng;
+    use std::str;
+    use std::vec;
+
+    fn check_balanced(r: &Rope) {
+        fn check(n: &super::Node) -> uint {
+            match n.kind {
+                super::Leaf(ref s) => {
+                    assert_eq!(n.chars, s.char_len());
+                    0
+                }
+                super::Branch(ref l, ref r) => {
+                    let lh = check(*l);
+                    let rh = check(*r);
+                    assert!(lh <= rh + 1 && rh <= lh + 1);
+                    assert_eq!(n.chars, l.chars + r.chars);
+                    assert_eq!(n.newlines, l.newlines + r.newlines);
+                    assert_eq!(n.height, lh.max(&rh) + 1);
+                    n.height
+                }
+            }
+        }
+        check(r.root);
+    }
+
+    #[test]
+    fn test_empty() {
+        let r = Rope::new();
+        assert_eq!(r.len(), 0);
+        assert!(r.is_empty());
+        assert_eq!(r.to_str(), ~"");
+        assert_eq!(r.line_count(), 1);
+        assert_eq!(r.char_iter().next(), None);
+        assert_eq!(r.line_iter().next(), None);
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        let mut r = Rope::from_slice("hello world");
+        r.insert(5, ",");
+        assert_eq!(r.to_str(), ~"hello, world");
+        r.insert(0, ">> ");
+        r.push_str("!");
+        assert_eq!(r.to_str(), ~">> hello, world!");
+        r.delete(0, 3);
+        r.delete(5, 12);
+        assert_eq!(r.to_str(), ~"hello!");
+        assert_eq!(r.len(), 6);
+    }
+
+    #[test]
+    fn test_multibyte() {
+        let mut r = Rope::from_slice("ประเทศไทย中华Việt Nam");
+        assert_eq!(r.len(), 20);
+        assert_eq!(r.char_at(9), '中');
+        r.insert(9, "-");
+        assert_eq!(r.slice(6, 12).to_str(), ~"ไทย-中华");
+        r.delete(0, 10);
+        assert_eq!(r.to_str(), ~"中华Việt Nam");
+        assert_eq!(r.byte_len(), "中华Việt Nam".len());
+    }
+
+    #[test]
+    fn test_large_edits() {
+        let text = str::from_chars(vec::from_fn(10 * MAX_LEAF, |i| {
+            if i % 80 == 79 { '\n' } else { (('a' as uint) + i % 26) as char }
+        }));
+        let mut expected = text.clone();
+        let mut r = Rope::from_slice(text);
+        check_balanced(&r);
+
+        let mut rng = rand::task_rng();
+        for _ in range(0, 200) {
+            let len = expected.char_len();
+            let at = rng.gen_range(0, len + 1);
+            if rng.gen() {
+                r.insert(at, "xyz\n");
+                let b = expected.slice_chars(0, at).len();
+                expected = expected.slice_to(b) + "xyz\n" + expected.slice_from(b);
+            } else {
+                let end = cmp::min(len, at + rng.gen_range(0u, 100));
+                r.delete(at, end);
+                expected = expected.slice_chars(0, at) + expected.slice_chars(end, len);
+            }
+            check_balanced(&r);
+        }
+        assert_eq!(r.to_str(), expected);
+        assert_eq!(r.len(), expected.char_len());
+    }
+
+    #[test]
+    fn test_slice() {
+        let s = "0123456789".repeat(200);
+        let r = Rope::from_slice(s);
+        assert_eq!(r.slice(0, 0).to_str(), ~"");
+        assert_eq!(r.slice(5, 15).to_str(), s.slice(5, 15).to_owned());
+        assert_eq!(r.slice(500, 1700).to_str(), s.slice(500, 1700).to_owned());
+        assert_eq!(r.slice(0, 2000).to_str(), s);
+    }
+
+    #[test]
+    fn test_split_off_append() {
+        let mut r = Rope::from_slice("abcdef");
+        let tail = r.split_off(2);
+        assert_eq!(r.to_str(), ~"ab");
+        assert_eq!(tail.to_str(), ~"cdef");
+        r.append(tail);
+        assert_eq!(r.to_str(), ~"abcdef");
+    }
+
+    #[test]
+    fn test_lines() {
+        let r = Rope::from_slice("one\ntwo\n\nfour");
+        assert_eq!(r.line_count(), 4);
+        assert_eq!(r.line_to_char(0), 0);
+        assert_eq!(r.line_to_char(1), 4);
+        assert_eq!(r.line_to_char(2), 8);
+        assert_eq!(r.line_to_char(3), 9);
+        assert_eq!(r.char_to_line(0), (0, 0));
+        assert_eq!(r.char_to_line(3), (0, 3));
+        assert_eq!(r.char_to_line(5), (1, 1));
+        assert_eq!(r.char_to_line(8), (2, 0));
+        assert_eq!(r.char_to_line(13), (3, 4));
+        assert_eq!(r.line(1), ~"two");
+        assert_eq!(r.line(2), ~"");
+        assert_eq!(r.line(3), ~"four");
+
+        let lines: ~[~str] = r.line_iter().collect();
+        assert_eq!(lines, ~[~"one", ~"two", ~"", ~"four"]);
+        let lines: ~[~str] = Rope::from_slice("a\nb\n").line_iter().collect();
+        assert_eq!(lines, ~[~"a", ~"b"]);
+    }
+
+    #[test]
+    fn test_lines_large() {
+        let mut s = ~"";
+        for i in range(0u, 1000) {
+            s.push_str(i.to_str());
+            s.push_char('\n');
+        }
+        let r = Rope::from_slice(s);
+        assert_eq!(r.line_count(), 1001);
+        for i in range(0u, 1000) {
+            assert_eq!(r.line(i), i.to_str());
+            let start = r.line_to_char(i);
+            assert_eq!(r.char_to_line(start), (i, 0));
+        }
+    }
+
+    #[test]
+    fn test_iterators() {
+        let s = "αβγ\nδεζ".repeat(300);
+        let r = Rope::from_slice(s);
+        let chars: ~str = r.char_iter().collect();
+        assert_eq!(chars, s.clone());
+        assert!(r.chunk_iter().count(|_| true) > 1);
+        assert_eq!(r.chunk_iter().fold(0, |n, c| n + c.char_len()), r.len());
+        assert!(r.chunk_iter().all(|c| c.char_len() <= MAX_LEAF));
+    }
+
+    #[test]
+    fn test_eq() {
+        let mut a = Rope::from_slice("abc");
+        let b = Rope::from_slice("xabc");
+        assert!(a != b);
+        a.insert(0, "x");
+        assert!(a == b);
+        assert_eq!(from_str::<Rope>("xabc"), Some(b));
+    }
+
+    #[bench]
+    fn bench_insert_middle(bh: &mut BenchHarness) {
+        let mut r = Rope::from_slice("0123456789".repeat(100000));
+        do bh.iter {
+            let mid = r.len() / 2;
+            r.insert(mid, "abc");
+        }
+    }
+
+    #[bench]
+    fn bench_char_to_line(bh: &mut BenchHarness) {
+        let r = Rope::from_slice("0123456789\n".repeat(100000));
+        do bh.iter {
+            r.char_to_line(500000);
+        }
+    }
+}
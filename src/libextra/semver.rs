@@ -27,6 +27,10 @@
 //!
 //! An example version number with all five components is
 //! `0.8.1-rc.3.0+20130922.linux`.
+//!
+//! A `VersionReq` describes a range of acceptable versions, such as
+//! `>=1.2, <2.0` or `^0.4`, and can pick the newest matching version out of
+//! a list of candidates.
 
 use std::char;
 use std::cmp;
@@ -247,6 +251,260 @@ pub fn parse(s: &str) -> Option<Version> {
     }
 }
 
+/// A version requirement, such as `>=1.2, <2.0`, `~1.3` or `^0.4`.
+///
+/// A requirement is a comma-separated list of predicates, all of which must
+/// hold for a version to match. Each predicate is an operator followed by a
+/// version, in which the minor and patch numbers may be left out or given
+/// as a `*` (or `x`) wildcard:
+///
+///  * `=1.2.3` or `1.2.3` matches exactly that version; `=1.2` matches any
+///    `1.2.x` version and `1` or `1.*` any `1.x.y` version
+///  * `>`, `>=`, `<` and `<=` compare against the version, so `>1.2` means
+///    newer than any `1.2.x` and `<=1.2` means no newer than any `1.2.x`
+///  * `~1.2.3` allows patch level changes (`>=1.2.3, <1.3.0`), and `~1`
+///    minor level changes (`>=1.0.0, <2.0.0`)
+///  * `^1.2.3` allows changes that do not touch the leftmost non-zero
+///    number (`>=1.2.3, <2.0.0`), so `^0.4.1` means `>=0.4.1, <0.5.0`
+///  * `*` matches any version
+///
+/// Pre-release versions are ordered as the semver spec describes, but only
+/// match if one of the predicates names a pre-release of the same
+/// `major.minor.patch`. `>=1.0.0` does not match `1.1.0-beta`, while
+/// `>=1.1.0-alpha` matches `1.1.0-beta` but not `1.2.0-beta`.
+#[deriving(Clone, Eq)]
+pub struct VersionReq {
+    priv predicates: ~[Predicate]
+}
+
+#[deriving(Clone, Eq)]
+enum Op {
+    Ex,
+    Gt,
+    GtEq,
+    Lt,
+    LtEq,
+    Tilde,
+    Caret,
+    Wildcard
+}
+
+#[deriving(Clone, Eq)]
+struct Predicate {
+    op: Op,
+    major: uint,
+    minor: Option<uint>,
+    patch: Option<uint>,
+    pre: ~[Identifier]
+}
+
+impl VersionReq {
+    /// Parse a requirement such as `>=1.2, <2.0`. Returns `None` if the
+    /// requirement is malformed.
+    pub fn parse(s: &str) -> Option<VersionReq> {
+        if !s.is_ascii() {
+            return None;
+        }
+        let mut predicates = ~[];
+        for p in s.split_iter(',') {
+            match parse_predicate(p.trim()) {
+                Some(p) => predicates.push(p),
+                None => return None
+            }
+        }
+        Some(VersionReq { predicates: predicates })
+    }
+
+    /// A requirement matching any version that is not a pre-release.
+    pub fn any() -> VersionReq {
+        VersionReq {
+            predicates: ~[Predicate { op: Wildcard, major: 0, minor: None, patch: None, pre: ~[] }]
+        }
+    }
+
+    /// Return whether the given version satisfies this requirement.
+    pub fn matches(&self, version: &Version) -> bool {
+        self.predicates.iter().all(|p| p.matches(version)) &&
+            (version.pre.is_empty() || self.predicates.iter().any(|p| p.allows_pre(version)))
+    }
+
+    /// Return the highest of the given versions that satisfies this
+    /// requirement, or `None` if none of them do.
+    pub fn max_satisfying<'a>(&self, versions: &'a [Version]) -> Option<&'a Version> {
+        let mut best: Option<&'a Version> = None;
+        for version in versions.iter() {
+            if self.matches(version) && best.map_default(true, |b| *version > *b) {
+                best = Some(version);
+            }
+        }
+        best
+    }
+}
+
+impl ToStr for VersionReq {
+    fn to_str(&self) -> ~str {
+        self.predicates.map(|p| p.to_str()).connect(", ")
+    }
+}
+
+impl Predicate {
+    // The version named by the predicate, with any missing numbers as zero.
+    fn version(&self) -> Version {
+        Version {
+            major: self.major,
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+            pre: self.pre.clone(),
+            build: ~[]
+        }
+    }
+
+    // Compares the numbers given in the predicate against those of `v`,
+    // ignoring pre-release information.
+    fn cmp_given(&self, v: &Version) -> Ordering {
+        match v.major.cmp(&self.major) {
+            Equal => (),
+            o => return o
+        }
+        match self.minor {
+            None => return Equal,
+            Some(minor) => match v.minor.cmp(&minor) {
+                Equal => (),
+                o => return o
+            }
+        }
+        match self.patch {
+            None => Equal,
+            Some(patch) => v.patch.cmp(&patch)
+        }
+    }
+
+    fn matches(&self, v: &Version) -> bool {
+        match self.op {
+            Ex if self.patch.is_some() => *v >= self.version() && *v <= self.version(),
+            Ex => self.cmp_given(v) == Equal,
+            Gt if self.patch.is_some() => *v > self.version(),
+            Gt => self.cmp_given(v) == Greater,
+            GtEq => *v >= self.version(),
+            Lt => *v < self.version(),
+            LtEq if self.patch.is_some() => *v <= self.version(),
+            LtEq => self.cmp_given(v) != Greater,
+            Tilde => {
+                *v >= self.version() && v.major == self.major &&
+                    self.minor.map_default(true, |minor| v.minor == minor)
+            }
+            Caret => {
+                *v >= self.version() && v.major == self.major &&
+                    match (self.major, self.minor, self.patch) {
+                        (0, Some(0), Some(patch)) => v.minor == 0 && v.patch == patch,
+                        (0, Some(minor), _) => v.minor == minor,
+                        _ => true
+                    }
+            }
+            Wildcard => true
+        }
+    }
+
+    fn allows_pre(&self, v: &Version) -> bool {
+        !self.pre.is_empty() && self.major == v.major &&
+            self.minor == Some(v.minor) && self.patch == Some(v.patch)
+    }
+}
+
+impl ToStr for Predicate {
+    fn to_str(&self) -> ~str {
+        let op = match self.op {
+            Ex => "=",
+            Gt => ">",
+            GtEq => ">=",
+            Lt => "<",
+            LtEq => "<=",
+            Tilde => "~",
+            Caret => "^",
+            Wildcard => return ~"*"
+        };
+        let mut s = format!("{}{}", op, self.major);
+        for minor in self.minor.iter() {
+            s.push_str(format!(".{}", *minor));
+        }
+        for patch in self.patch.iter() {
+            s.push_str(format!(".{}", *patch));
+        }
+        if !self.pre.is_empty() {
+            s.push_str(format!("-{}", self.pre.map(|i| i.to_str()).connect(".")));
+        }
+        s
+    }
+}
+
+fn parse_predicate(s: &str) -> Option<Predicate> {
+    fn is_wildcard(p: &str) -> bool { p == "*" || p == "x" || p == "X" }
+
+    let ops = [(">=", GtEq), ("<=", LtEq), (">", Gt), ("<", Lt),
+               ("=", Ex), ("~", Tilde), ("^", Caret)];
+    let mut op = None;
+    let mut rest = s;
+    for &(prefix, o) in ops.iter() {
+        if s.starts_with(prefix) {
+            op = Some(o);
+            rest = s.slice_from(prefix.len()).trim_left();
+            break;
+        }
+    }
+
+    if is_wildcard(rest) {
+        // only a plain or `=` wildcard makes sense
+        return match op {
+            None | Some(Ex) => {
+                Some(Predicate { op: Wildcard, major: 0, minor: None, patch: None, pre: ~[] })
+            }
+            _ => None
+        };
+    }
+
+    let op = op.unwrap_or(Ex);
+    if rest.contains_char('-') || rest.contains_char('+') {
+        // only a full version may carry pre-release information
+        return parse(rest).map(|v| Predicate {
+            op: op,
+            major: v.major,
+            minor: Some(v.minor),
+            patch: Some(v.patch),
+            pre: v.pre
+        });
+    }
+
+    let parts = rest.split_iter('.').to_owned_vec();
+    if parts.len() > 3 {
+        return None;
+    }
+    let mut numbers = ~[];
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 && is_wildcard(*part) {
+            // anything after a wildcard has to be a wildcard too
+            if !parts.slice_from(i).iter().all(|p| is_wildcard(*p)) {
+                return None;
+            }
+            break;
+        }
+        if part.is_empty() || !part.iter().all(char::is_digit) {
+            return None;
+        }
+        match from_str::<uint>(*part) {
+            Some(n) => numbers.push(n),
+            None => return None
+        }
+    }
+
+    Some(Predicate {
+        op: op,
+        major: numbers[0],
+        minor: numbers.get_opt(1).map(|n| *n),
+        patch: numbers.get_opt(2).map(|n| *n),
+        pre: ~[]
+    })
+}
+
 #[test]
 fn test_parse() {
     assert_eq!(parse(""), None);
@@ -406,3 +664,110 @@ fn test_spec_order() {
         i += 1;
     }
 }
+
+#[test]
+fn test_req_parse() {
+    let reqs = ["*", "=1.2.3", ">=1.2, <2.0", "~1.3", "^0.4", ">1.2.3-alpha.1", "<=1.*"];
+    let expected = ["*", "=1.2.3", ">=1.2, <2.0", "~1.3", "^0.4", ">1.2.3-alpha.1", "<=1"];
+    for (r, e) in reqs.iter().zip(expected.iter()) {
+        assert_eq!(VersionReq::parse(*r).unwrap().to_str(), e.to_owned());
+    }
+    assert_eq!(VersionReq::parse("1.2.3"), VersionReq::parse("=1.2.3"));
+    assert_eq!(VersionReq::parse(" >= 1.2 ,<2.0 "), VersionReq::parse(">=1.2, <2.0"));
+    assert_eq!(VersionReq::parse("1.x.X"), VersionReq::parse("1"));
+    assert_eq!(VersionReq::parse("*"), Some(VersionReq::any()));
+
+    let bad = ["", "1.2.3.4", "a.b", "1.*.3", ">*", "1.2.3-", "1.2.3,", "1.-1", ">= ",
+               "1.2-alpha"];
+    for b in bad.iter() {
+        assert_eq!(VersionReq::parse(*b), None);
+    }
+}
+
+#[test]
+fn test_req_matches() {
+    let check = |req: &str, version: &str, expected: bool| {
+        let req = VersionReq::parse(req).unwrap();
+        assert_eq!(req.matches(&parse(version).unwrap()), expected);
+    };
+
+    check("1.2.3", "1.2.3", true);
+    check("1.2.3", "1.2.3+build", true);
+    check("1.2.3", "1.2.4", false);
+    check("=1.2", "1.2.9", true);
+    check("=1.2", "1.3.0", false);
+    check("1.*", "1.9.9", true);
+    check("1.*", "2.0.0", false);
+
+    check(">=1.2, <2.0", "1.2.0", true);
+    check(">=1.2, <2.0", "1.9.9", true);
+    check(">=1.2, <2.0", "1.1.9", false);
+    check(">=1.2, <2.0", "2.0.0", false);
+    check(">1.2", "1.2.9", false);
+    check(">1.2", "1.3.0", true);
+    check(">1.2.3", "1.2.3", false);
+    check(">1.2.3", "1.2.4", true);
+    check("<=1.2", "1.2.9", true);
+    check("<=1.2", "1.3.0", false);
+    check("<1.2", "1.1.9", true);
+    check("<1.2", "1.2.0", false);
+    check("> 1.2.3, <= 1.2.5", "1.2.5", true);
+    check("> 1.2.3, <= 1.2.5", "1.2.6", false);
+
+    check("~1.2.3", "1.2.9", true);
+    check("~1.2.3", "1.2.2", false);
+    check("~1.2.3", "1.3.0", false);
+    check("~1", "1.9.0", true);
+    check("~1", "2.0.0", false);
+
+    check("^1.2.3", "1.9.0", true);
+    check("^1.2.3", "1.2.2", false);
+    check("^1.2.3", "2.0.0", false);
+    check("^0.4.1", "0.4.9", true);
+    check("^0.4.1", "0.5.0", false);
+    check("^0.0.3", "0.0.3", true);
+    check("^0.0.3", "0.0.4", false);
+    check("^0", "0.9.9", true);
+    check("^0", "1.0.0", false);
+
+    check("*", "0.0.0", true);
+    check("*", "99.1.2", true);
+}
+
+#[test]
+fn test_req_pre_release() {
+    let check = |req: &str, version: &str, expected: bool| {
+        let req = VersionReq::parse(req).unwrap();
+        assert_eq!(req.matches(&parse(version).unwrap()), expected);
+    };
+
+    // pre-releases only match when the requirement asks for them
+    check("*", "1.0.0-rc.1", false);
+    check(">=1.2, <2.0", "1.5.0-beta", false);
+    check(">=1.2, <2.0", "2.0.0-alpha", false);
+    check("<1.2", "1.2.0-alpha", false);
+
+    check(">=1.1.0-alpha", "1.1.0-alpha", true);
+    check(">=1.1.0-alpha", "1.1.0-beta", true);
+    check(">=1.1.0-alpha", "1.1.0", true);
+    check(">=1.1.0-alpha", "1.1.0-0", false);
+    check(">=1.1.0-alpha", "1.2.0-beta", false);
+    check("^1.0.0-beta.2", "1.0.0-beta.11", true);
+    check("^1.0.0-beta.2", "1.0.0-beta", false);
+    check("^1.0.0-beta.2", "1.5.0", true);
+    check("^1.0.0-beta.2", "1.5.0-rc.1", false);
+}
+
+#[test]
+fn test_req_max_satisfying() {
+    let versions = ["0.9.0", "1.0.0", "1.4.2", "1.5.0-beta", "2.0.0"].map(|v| parse(*v).unwrap());
+    let best = |req: &str| {
+        VersionReq::parse(req).unwrap().max_satisfying(versions).map(|v| v.to_str())
+    };
+
+    assert_eq!(best(">=1.0, <2.0"), Some(~"1.4.2"));
+    assert_eq!(best("~0.9"), Some(~"0.9.0"));
+    assert_eq!(best("^1.5.0-alpha"), Some(~"1.5.0-beta"));
+    assert_eq!(best("*"), Some(~"2.0.0"));
+    assert_eq!(best("^3"), None);
+}
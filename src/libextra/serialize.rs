@@ -174,6 +174,95 @@ pub trait Decodable<D:Decoder> {
     fn decode(d: &mut D) -> Self;
 }
 
+/// The kinds of error a `Decoder` reports through `decode_error`
+#[deriving(Clone, Eq)]
+pub enum DecoderError {
+    /// A value of the wrong type was found; holds descriptions of the
+    /// expected and the found value.
+    ExpectedError(~str, ~str),
+    /// A struct field was not present in the input.
+    MissingFieldError(~str),
+    /// An enum variant name or index was not recognized.
+    UnknownVariantError(~str),
+    /// The input was malformed or ended unexpectedly.
+    SyntaxError(~str),
+}
+
+/// An error found while decoding, along with where it occurred
+#[deriving(Clone, Eq)]
+pub struct DecodeError {
+    /// The location of the failing value. Decoders that know the names of
+    /// the values being read (such as `json::Decoder`) report a path like
+    /// `inner[0].b`; empty for the top-level value.
+    path: ~str,
+    /// What went wrong.
+    kind: DecoderError,
+}
+
+impl ToStr for DecoderError {
+    fn to_str(&self) -> ~str {
+        match *self {
+            ExpectedError(ref expected, ref found) =>
+                format!("expected {}, found {}", *expected, *found),
+            MissingFieldError(ref name) => format!("missing field `{}`", *name),
+            UnknownVariantError(ref name) => format!("unknown variant `{}`", *name),
+            SyntaxError(ref msg) => msg.clone(),
+        }
+    }
+}
+
+impl ToStr for DecodeError {
+    fn to_str(&self) -> ~str {
+        if self.path.is_empty() {
+            self.kind.to_str()
+        } else {
+            format!("{}: {}", self.path, self.kind.to_str())
+        }
+    }
+}
+
+/**
+ * Raised by decoders when the input does not match the type being decoded.
+ *
+ * If no handler is installed, the task fails, as it always has. If the
+ * handler returns, the decoder substitutes a placeholder (zero, the empty
+ * string, the first enum variant, ...) and carries on, so a handler can
+ * collect every error in a document. `try_decode` uses this to turn the
+ * first error into an `Err`.
+ */
+condition! {
+    pub decode_error: DecodeError -> ();
+}
+
+/**
+ * Decodes a value, returning the first error raised on `decode_error`
+ * instead of failing the task.
+ *
+ * # Example
+ *
+ * ```rust
+ * let mut decoder = json::Decoder(json::from_str(input).unwrap());
+ * match serialize::try_decode::<Config, json::Decoder>(&mut decoder) {
+ *     Ok(config) => run(config),
+ *     Err(e) => println!("bad config: {}", e.to_str())
+ * }
+ * ```
+ */
+pub fn try_decode<T: Decodable<D>, D: Decoder>(d: &mut D) -> Result<T, DecodeError> {
+    let mut error = None;
+    let value = do decode_error::cond.trap(|e| {
+        if error.is_none() {
+            error = Some(e);
+        }
+    }).inside {
+        Decodable::decode(d)
+    };
+    match error {
+        None => Ok(value),
+        Some(e) => Err(e)
+    }
+}
+
 impl<S:Encoder> Encodable<S> for uint {
     fn encode(&self, s: &mut S) {
         s.emit_uint(*self)
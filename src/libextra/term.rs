@@ -9,6 +9,11 @@
 // except according to those terms.
 
 //! Simple ANSI color library
+//!
+//! Besides colors and attributes, `Terminal` can move the cursor and clear
+//! lines using the terminal's terminfo entry, and report the size of the
+//! terminal. `RawMode` switches the terminal on stdin into raw mode for as
+//! long as it is alive.
 
 #[allow(missing_doc)];
 
@@ -19,7 +24,8 @@ use std::io;
 #[cfg(not(target_os = "win32"))] use terminfo::*;
 #[cfg(not(target_os = "win32"))] use terminfo::searcher::open;
 #[cfg(not(target_os = "win32"))] use terminfo::parser::compiled::parse;
-#[cfg(not(target_os = "win32"))] use terminfo::parm::{expand, Number, Variables};
+#[cfg(not(target_os = "win32"))] use terminfo::parm::{expand, Number, Param, Variables};
+#[cfg(not(target_os = "win32"))] use std::libc::{STDIN_FILENO, STDOUT_FILENO};
 
 // FIXME (#2807): Windows support.
 
@@ -43,6 +49,34 @@ pub mod color {
     pub static BRIGHT_MAGENTA: Color = 13u16;
     pub static BRIGHT_CYAN:    Color = 14u16;
     pub static BRIGHT_WHITE:   Color = 15u16;
+
+    /// Returns the color in the 256 color palette closest to the given
+    /// 24-bit color, out of the 6x6x6 color cube (16-231) and the grayscale
+    /// ramp (232-255).
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> Color {
+        static LEVELS: [int, ..6] = [0, 95, 135, 175, 215, 255];
+        fn cube_index(v: int) -> int {
+            if v < 48 { 0 } else if v < 115 { 1 } else { (v - 35) / 40 }
+        }
+        fn distance((r1, g1, b1): (int, int, int), (r2, g2, b2): (int, int, int)) -> int {
+            (r1 - r2) * (r1 - r2) + (g1 - g2) * (g1 - g2) + (b1 - b2) * (b1 - b2)
+        }
+
+        let rgb = (r as int, g as int, b as int);
+        let (ri, gi, bi) = (cube_index(r as int), cube_index(g as int), cube_index(b as int));
+        let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+
+        // the grays run from 8 to 238 in steps of 10
+        let average = (r as int + g as int + b as int) / 3;
+        let gray_index = if average < 3 { 0 } else { ((average - 3) / 10).min(&23) };
+        let gray = 8 + 10 * gray_index;
+
+        if distance(rgb, (gray, gray, gray)) < distance(rgb, cube) {
+            (232 + gray_index) as Color
+        } else {
+            (16 + 36 * ri + 6 * gi + bi) as Color
+        }
+    }
 }
 
 pub mod attr {
@@ -96,6 +130,7 @@ fn cap_for_attr(attr: attr::Attr) -> &'static str {
 #[cfg(not(target_os = "win32"))]
 pub struct Terminal {
     priv num_colors: u16,
+    priv truecolor: bool,
     priv out: @io::Writer,
     priv ti: ~TermInfo
 }
@@ -130,8 +165,26 @@ impl Terminal {
                      inf.numbers.find_equiv(&("colors")).map_default(0, |&n| n)
                  } else { 0 };
 
-        return Ok(Terminal {out: out, ti: inf, num_colors: nc});
+        // terminfo has no standard way to describe 24-bit color support, so
+        // go by what terminals that have it commonly advertise
+        let truecolor = nc > 0 && match os::getenv("COLORTERM") {
+            Some(~"truecolor") | Some(~"24bit") => true,
+            _ => inf.supports_truecolor()
+        };
+
+        return Ok(Terminal {out: out, ti: inf, num_colors: nc, truecolor: truecolor});
     }
+
+    /// Returns the number of colors the terminal supports.
+    pub fn num_colors(&self) -> u16 {
+        self.num_colors
+    }
+
+    /// Returns whether the terminal supports 24-bit color.
+    pub fn supports_truecolor(&self) -> bool {
+        self.truecolor
+    }
+
     /// Sets the foreground color to the given color.
     ///
     /// If the color is a bright color, but the terminal only supports 8 colors,
@@ -173,26 +226,47 @@ impl Terminal {
         false
     }
 
+    /// Sets the foreground color to the given 24-bit color.
+    ///
+    /// If the terminal does not support 24-bit color but has 256 colors, the
+    /// closest of those will be used instead.
+    ///
+    /// Returns true if the color was set, false otherwise.
+    pub fn fg_rgb(&self, r: u8, g: u8, b: u8) -> bool {
+        if self.truecolor {
+            self.out.write(format!("\x1b[38;2;{};{};{}m", r, g, b).as_bytes());
+            true
+        } else if self.num_colors >= 256 {
+            self.fg(color::from_rgb(r, g, b))
+        } else {
+            false
+        }
+    }
+
+    /// Sets the background color to the given 24-bit color.
+    ///
+    /// If the terminal does not support 24-bit color but has 256 colors, the
+    /// closest of those will be used instead.
+    ///
+    /// Returns true if the color was set, false otherwise.
+    pub fn bg_rgb(&self, r: u8, g: u8, b: u8) -> bool {
+        if self.truecolor {
+            self.out.write(format!("\x1b[48;2;{};{};{}m", r, g, b).as_bytes());
+            true
+        } else if self.num_colors >= 256 {
+            self.bg(color::from_rgb(r, g, b))
+        } else {
+            false
+        }
+    }
+
     /// Sets the given terminal attribute, if supported.
     /// Returns true if the attribute was supported, false otherwise.
     pub fn attr(&self, attr: attr::Attr) -> bool {
         match attr {
             attr::ForegroundColor(c) => self.fg(c),
             attr::BackgroundColor(c) => self.bg(c),
-            _ => {
-                let cap = cap_for_attr(attr);
-                let parm = self.ti.strings.find_equiv(&cap);
-                if parm.is_some() {
-                    let s = expand(*parm.unwrap(), [], &mut Variables::new());
-                    if s.is_ok() {
-                        self.out.write(s.unwrap());
-                        return true
-                    } else {
-                        warn!("{}", s.unwrap_err());
-                    }
-                }
-                false
-            }
+            _ => self.apply_cap(cap_for_attr(attr), [])
         }
     }
 
@@ -234,6 +308,84 @@ impl Terminal {
         }
     }
 
+    /// Moves the cursor to the given row and column, counting from 0 at the
+    /// top left. Returns true if the terminal supports this, false otherwise.
+    pub fn cursor_to(&self, row: uint, col: uint) -> bool {
+        self.apply_cap("cup", [Number(row as int), Number(col as int)])
+    }
+
+    /// Moves the cursor up by `n` lines.
+    /// Returns true if the terminal supports this, false otherwise.
+    pub fn cursor_up(&self, n: uint) -> bool {
+        n == 0 || self.apply_cap("cuu", [Number(n as int)])
+    }
+
+    /// Moves the cursor down by `n` lines.
+    /// Returns true if the terminal supports this, false otherwise.
+    pub fn cursor_down(&self, n: uint) -> bool {
+        n == 0 || self.apply_cap("cud", [Number(n as int)])
+    }
+
+    /// Makes the cursor invisible.
+    /// Returns true if the terminal supports this, false otherwise.
+    pub fn hide_cursor(&self) -> bool {
+        self.apply_cap("civis", [])
+    }
+
+    /// Makes the cursor visible again after `hide_cursor`.
+    /// Returns true if the terminal supports this, false otherwise.
+    pub fn show_cursor(&self) -> bool {
+        self.apply_cap("cnorm", [])
+    }
+
+    /// Clears the current line from the cursor to the end.
+    /// Returns true if the terminal supports this, false otherwise.
+    pub fn clear_line(&self) -> bool {
+        self.apply_cap("el", [])
+    }
+
+    /// Returns the size of the terminal as `(columns, rows)`.
+    ///
+    /// The size is asked of the terminal driver if stdout is a terminal, and
+    /// otherwise taken from the `COLUMNS` and `LINES` environment variables
+    /// or, failing those, the terminfo entry.
+    ///
+    /// This is always the size of the terminal on stdout, even for a
+    /// `Terminal` that writes somewhere else: an `io::Writer` doesn't give
+    /// away the file descriptor it writes to.
+    pub fn size(&self) -> Option<(uint, uint)> {
+        match tty::window_size(STDOUT_FILENO) {
+            Some(size) => return Some(size),
+            None => ()
+        }
+        let env = |var| os::getenv(var).and_then(|s| from_str::<uint>(s));
+        match (env("COLUMNS"), env("LINES")) {
+            (Some(cols), Some(lines)) => return Some((cols, lines)),
+            _ => ()
+        }
+        match (self.ti.numbers.find_equiv(&("cols")), self.ti.numbers.find_equiv(&("lines"))) {
+            (Some(&cols), Some(&lines)) => Some((cols as uint, lines as uint)),
+            _ => None
+        }
+    }
+
+    // Expands the given capability and writes it out, if the terminal has it.
+    fn apply_cap(&self, cap: &str, params: &[Param]) -> bool {
+        match self.ti.strings.find_equiv(&cap) {
+            Some(s) => match expand(*s, params, &mut Variables::new()) {
+                Ok(s) => {
+                    self.out.write(s);
+                    true
+                }
+                Err(e) => {
+                    warn!("{}", e);
+                    false
+                }
+            },
+            None => false
+        }
+    }
+
     fn dim_if_necessary(&self, color: color::Color) -> color::Color {
         if color >= self.num_colors && color >= 8 && color < 16 {
             color-8
@@ -241,6 +393,116 @@ impl Terminal {
     }
 }
 
+/// Keeps the terminal on stdin in raw mode, in which input is available a
+/// byte at a time without being echoed or interpreted, and output is not
+/// post-processed. The terminal's previous settings are restored when this
+/// value is dropped.
+#[cfg(not(target_os = "win32"))]
+pub struct RawMode {
+    priv saved: tty::Termios
+}
+
+#[cfg(not(target_os = "win32"))]
+impl RawMode {
+    /// Switches the terminal on stdin into raw mode.
+    pub fn enter() -> Result<RawMode, ~str> {
+        let saved = match tty::get_attr(STDIN_FILENO) {
+            Ok(t) => t,
+            Err(e) => return Err(e)
+        };
+        let mut raw = saved;
+        tty::make_raw(&mut raw);
+        match tty::set_attr(STDIN_FILENO, &raw) {
+            Ok(()) => Ok(RawMode { saved: saved }),
+            Err(e) => Err(e)
+        }
+    }
+}
+
+#[cfg(not(target_os = "win32"))]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        match tty::set_attr(STDIN_FILENO, &self.saved) {
+            Ok(()) => (),
+            Err(e) => warn!("couldn't leave raw mode: {}", e)
+        }
+    }
+}
+
+#[cfg(not(target_os = "win32"))]
+mod tty {
+    use std::libc::{c_int, c_ulong, c_ushort};
+    use std::os;
+
+    // The layout of `struct termios` differs from platform to platform, but
+    // it is only ever touched by libc, so a buffer that is large and aligned
+    // enough for all of them will do.
+    pub struct Termios {
+        priv buf: [u64, ..32]
+    }
+
+    struct WinSize {
+        ws_row: c_ushort,
+        ws_col: c_ushort,
+        ws_xpixel: c_ushort,
+        ws_ypixel: c_ushort
+    }
+
+    #[cfg(target_os = "linux")]
+    #[cfg(target_os = "android")]
+    static TIOCGWINSZ: c_ulong = 0x5413;
+    #[cfg(target_os = "macos")]
+    #[cfg(target_os = "freebsd")]
+    static TIOCGWINSZ: c_ulong = 0x40087468;
+
+    static TCSANOW: c_int = 0;
+
+    extern {
+        fn ioctl(fd: c_int, request: c_ulong, size: *mut WinSize) -> c_int;
+        fn tcgetattr(fd: c_int, termios: *mut Termios) -> c_int;
+        fn tcsetattr(fd: c_int, optional_actions: c_int, termios: *Termios) -> c_int;
+        fn cfmakeraw(termios: *mut Termios);
+    }
+
+    pub fn window_size(fd: c_int) -> Option<(uint, uint)> {
+        #[fixed_stack_segment]; #[inline(never)];
+
+        let mut size = WinSize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+        if unsafe { ioctl(fd, TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 {
+            Some((size.ws_col as uint, size.ws_row as uint))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_attr(fd: c_int) -> Result<Termios, ~str> {
+        #[fixed_stack_segment]; #[inline(never)];
+
+        let mut termios = Termios { buf: [0, ..32] };
+        if unsafe { tcgetattr(fd, &mut termios) } == 0 {
+            Ok(termios)
+        } else {
+            Err(os::last_os_error())
+        }
+    }
+
+    pub fn set_attr(fd: c_int, termios: &Termios) -> Result<(), ~str> {
+        #[fixed_stack_segment]; #[inline(never)];
+
+        if unsafe { tcsetattr(fd, TCSANOW, termios) } == 0 {
+            Ok(())
+        } else {
+            Err(os::last_os_error())
+        }
+    }
+
+    pub fn make_raw(termios: &mut Termios) {
+        #[fixed_stack_segment]; #[inline(never)];
+
+        unsafe { cfmakeraw(termios) }
+    }
+}
+
 #[cfg(target_os = "win32")]
 impl Terminal {
     pub fn new(out: @io::Writer) -> Result<Terminal, ~str> {
@@ -265,4 +527,74 @@ impl Terminal {
 
     pub fn reset(&self) {
     }
+
+    pub fn num_colors(&self) -> u16 {
+        0
+    }
+
+    pub fn supports_truecolor(&self) -> bool {
+        false
+    }
+
+    pub fn fg_rgb(&self, _r: u8, _g: u8, _b: u8) -> bool {
+        false
+    }
+
+    pub fn bg_rgb(&self, _r: u8, _g: u8, _b: u8) -> bool {
+        false
+    }
+
+    pub fn cursor_to(&self, _row: uint, _col: uint) -> bool {
+        false
+    }
+
+    pub fn cursor_up(&self, _n: uint) -> bool {
+        false
+    }
+
+    pub fn cursor_down(&self, _n: uint) -> bool {
+        false
+    }
+
+    pub fn hide_cursor(&self) -> bool {
+        false
+    }
+
+    pub fn show_cursor(&self) -> bool {
+        false
+    }
+
+    pub fn clear_line(&self) -> bool {
+        false
+    }
+
+    pub fn size(&self) -> Option<(uint, uint)> {
+        None
+    }
+}
+
+#[cfg(target_os = "win32")]
+pub struct RawMode;
+
+#[cfg(target_os = "win32")]
+impl RawMode {
+    pub fn enter() -> Result<RawMode, ~str> {
+        Err(~"raw mode is not supported on this platform")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::color;
+
+    #[test]
+    fn test_from_rgb() {
+        assert_eq!(color::from_rgb(0, 0, 0), 16);
+        assert_eq!(color::from_rgb(255, 255, 255), 231);
+        assert_eq!(color::from_rgb(255, 0, 0), 196);
+        assert_eq!(color::from_rgb(95, 135, 175), 67);
+        assert_eq!(color::from_rgb(100, 140, 170), 67);
+        assert_eq!(color::from_rgb(128, 128, 128), 244);
+        assert_eq!(color::from_rgb(20, 20, 22), 233);
+    }
 }
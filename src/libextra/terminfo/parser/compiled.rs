@@ -309,10 +309,130 @@ pub fn parse(file: @Reader, longnames: bool) -> Result<~TermInfo, ~str> {
         }
     }
 
+    // ncurses appends user-defined ("extended") capabilities, such as `Tc` and `RGB`, after
+    // the standard ones. Files without any just end here.
+    if string_offsets_count != 0 || string_table_bytes == 0 {
+        if string_table_bytes % 2 == 1 {
+            file.read_byte(); // compensate for padding
+        }
+        let header = file.read_bytes(10);
+        if header.len() == 10 {
+            match parse_extended(file, header, &mut bools_map, &mut numbers_map,
+                                 &mut string_map) {
+                Ok(()) => {}
+                Err(e) => return Err(e)
+            }
+        }
+    }
+
     // And that's all there is to it
     Ok(~TermInfo {names: term_names, bools: bools_map, numbers: numbers_map, strings: string_map })
 }
 
+/// The NUL-terminated string starting at `offset` in a string table
+fn nul_terminated<'a>(table: &'a [u8], offset: uint) -> Option<&'a [u8]> {
+    if offset > table.len() {
+        return None;
+    }
+    let rest = table.slice_from(offset);
+    rest.iter().position(|&b| b == 0).map(|len| rest.slice_to(len))
+}
+
+/// Parse the extended capabilities that follow the standard ones, given the 5-word header
+/// that starts them. Unlike the standard capabilities, their names are stored in the file:
+/// the string table holds the string values followed by the names of every capability.
+fn parse_extended(file: @Reader, header: &[u8],
+                  bools_map: &mut HashMap<~str, bool>,
+                  numbers_map: &mut HashMap<~str, u16>,
+                  string_map: &mut HashMap<~str, ~[u8]>) -> Result<(), ~str> {
+    let field = |i: uint| (header[2 * i] as u16 | header[2 * i + 1] as u16 << 8) as i16 as int;
+    let bools_count   = field(0);
+    let numbers_count = field(1);
+    let strings_count = field(2);
+    // field(3) is the number of entries in the string table, values and names together
+    let table_bytes   = field(4);
+
+    debug!("extended: {} bools, {} numbers, {} strings, {} bytes of strings",
+           bools_count, numbers_count, strings_count, table_bytes);
+
+    if bools_count < 0 || numbers_count < 0 || strings_count < 0 || table_bytes < 0 {
+        return Err(~"invalid file: negative count in extended header");
+    }
+
+    let bools = file.read_bytes(bools_count as uint);
+    if bools.len() != bools_count as uint {
+        return Err(~"error: expected more extended bools but hit EOF");
+    }
+    if bools_count % 2 == 1 {
+        file.read_byte(); // compensate for padding
+    }
+    let numbers = vec::from_fn(numbers_count as uint, |_| file.read_le_u16());
+    let string_offsets = vec::from_fn(strings_count as uint, |_| file.read_le_u16());
+    let names_count = (bools_count + numbers_count + strings_count) as uint;
+    let name_offsets = vec::from_fn(names_count, |_| file.read_le_u16());
+
+    let table = file.read_bytes(table_bytes as uint);
+    if table.len() != table_bytes as uint {
+        error!("EOF reading extended string table after {} bytes, wanted {}", table.len(),
+               table_bytes);
+        return Err(~"error: hit EOF before end of extended string table");
+    }
+
+    // The names come right after the last string value
+    let mut values = ~[];
+    let mut names_start = 0;
+    for &offset in string_offsets.iter() {
+        if offset == 0xFFFF { // non-entry
+            values.push(None);
+            continue;
+        }
+        if offset == 0xFFFE { // cancelled, as for the standard strings
+            values.push(Some(~[]));
+            continue;
+        }
+        match nul_terminated(table, offset as uint) {
+            Some(v) => {
+                names_start = names_start.max(&(offset as uint + v.len() + 1));
+                values.push(Some(v.to_owned()));
+            }
+            None => return Err(~"invalid file: missing NUL in extended string table")
+        }
+    }
+
+    let mut names = ~[];
+    for &offset in name_offsets.iter() {
+        match nul_terminated(table, names_start + offset as uint) {
+            Some(name) => names.push(str::from_utf8(name)),
+            None => return Err(~"invalid file: missing NUL in extended capability names")
+        }
+    }
+
+    debug!("extended names: {:?}", names);
+
+    let (bool_names, rest) = (names.slice_to(bools_count as uint),
+                              names.slice_from(bools_count as uint));
+    let (number_names, string_names) = (rest.slice_to(numbers_count as uint),
+                                        rest.slice_from(numbers_count as uint));
+
+    for (name, &b) in bool_names.iter().zip(bools.iter()) {
+        if b == 1 {
+            bools_map.insert(name.clone(), true);
+        }
+    }
+    for (name, &n) in number_names.iter().zip(numbers.iter()) {
+        if n != 0xFFFF {
+            numbers_map.insert(name.clone(), n);
+        }
+    }
+    for (name, value) in string_names.iter().zip(values.move_iter()) {
+        match value {
+            Some(v) => { string_map.insert(name.clone(), v); }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -324,6 +444,35 @@ mod test {
         assert_eq!(stringfnames.len(), stringnames.len());
     }
 
+    #[test]
+    fn test_parse_extended() {
+        use std::io;
+
+        let mut bytes = ~[
+            0x1A, 0x01, // magic
+            2, 0,       // names_bytes
+            0, 0,       // bools_bytes
+            0, 0,       // numbers_count
+            0, 0,       // string_offsets_count
+            0, 0,       // string_table_bytes
+            'x' as u8, 0,
+            // extended header: 1 bool, 1 number, 1 string, 4 table entries, 13 bytes of table
+            1, 0, 1, 0, 1, 0, 4, 0, 13, 0,
+            1, 0,       // Tc, padding
+            8, 0,       // RGB#8
+            0, 0,       // offset of Xs's value
+            0, 0, 3, 0, 7, 0, // offsets of the names
+        ];
+        bytes.push_all(bytes!("ab", 0, "Tc", 0, "RGB", 0, "Xs", 0));
+
+        let ti = do io::with_bytes_reader(bytes) |rdr| { parse(rdr, false) };
+        let ti = ti.unwrap();
+        assert_eq!(ti.names, ~[~"x"]);
+        assert_eq!(ti.bools.find_equiv(&("Tc")), Some(&true));
+        assert_eq!(ti.numbers.find_equiv(&("RGB")), Some(&8));
+        assert_eq!(ti.strings.find_equiv(&("Xs")), Some(&bytes!("ab").to_owned()));
+    }
+
     #[test]
     #[ignore(reason = "no ncurses on buildbots, needs a bundled terminfo file to test against")]
     fn test_parse() {
@@ -24,8 +24,49 @@ pub struct TermInfo {
     strings: HashMap<~str, ~[u8]>
 }
 
+impl TermInfo {
+    /// Whether the entry advertises 24-bit color. There is no standard capability for it, so
+    /// this goes by the extended ones terminals commonly use: `Tc` (tmux) and `RGB` (ncurses),
+    /// which may be either a boolean or a number.
+    pub fn supports_truecolor(&self) -> bool {
+        self.bools.contains_key_equiv(&("Tc")) ||
+            self.bools.contains_key_equiv(&("RGB")) ||
+            self.numbers.contains_key_equiv(&("RGB"))
+    }
+}
+
 pub mod searcher;
 pub mod parser {
     pub mod compiled;
 }
 pub mod parm;
+
+#[cfg(test)]
+mod test {
+    use super::TermInfo;
+    use std::hashmap::HashMap;
+
+    fn terminfo(bools: &[&str], numbers: &[(&str, u16)]) -> TermInfo {
+        let mut ti = TermInfo {
+            names: ~[~"test"],
+            bools: HashMap::new(),
+            numbers: HashMap::new(),
+            strings: HashMap::new()
+        };
+        for name in bools.iter() {
+            ti.bools.insert(name.to_owned(), true);
+        }
+        for &(name, n) in numbers.iter() {
+            ti.numbers.insert(name.to_owned(), n);
+        }
+        ti
+    }
+
+    #[test]
+    fn test_supports_truecolor() {
+        assert!(terminfo(["Tc"], []).supports_truecolor());
+        assert!(terminfo(["RGB"], []).supports_truecolor());
+        assert!(terminfo([], [("RGB", 8)]).supports_truecolor());
+        assert!(!terminfo(["am"], [("colors", 256)]).supports_truecolor());
+    }
+}
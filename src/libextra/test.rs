@@ -18,9 +18,10 @@
 
 use getopts;
 use getopts::groups;
+use glob::Pattern;
 use json::ToJson;
 use json;
-use serialize::Decodable;
+use serialize::{Decodable, try_decode};
 use sort;
 use stats::Stats;
 use stats;
@@ -29,9 +30,16 @@ use time::precise_time_ns;
 use treemap::TreeMap;
 
 use std::clone::Clone;
-use std::comm::{stream, SharedChan, GenericPort, GenericChan};
+use std::comm::{stream, Port, SharedChan, GenericPort, GenericChan};
+use std::comm::Peekable;
+use std::fmt;
 use std::io;
+use std::rt::io::stdio;
+use std::rt::io::timer;
+use std::rt::logging;
+use std::str;
 use std::result;
+use std::send_str::SendStr;
 use std::task;
 use std::to_str::ToStr;
 use std::f64;
@@ -114,7 +122,11 @@ pub struct BenchHarness {
 pub struct TestDesc {
     name: TestName,
     ignore: bool,
-    should_fail: bool
+    should_fail: bool,
+    // Text the failure message of a should_fail test has to contain.
+    expected_failure: Option<&'static str>,
+    // Milliseconds the test may run before it is reported as failed.
+    timeout: Option<u64>
 }
 
 pub struct TestDescAndFn {
@@ -149,6 +161,11 @@ pub enum MetricChange {
 
 pub type MetricDiff = TreeMap<~str,MetricChange>;
 
+/// The per-iteration timings of a run's benchmarks, in nanoseconds, kept
+/// whole so that two runs can be compared by their distributions.
+#[deriving(Clone, Eq)]
+pub struct BenchBaseline(TreeMap<~str,~[f64]>);
+
 // The default console test runner. It accepts the command line
 // arguments and a vector of test_descs.
 pub fn test_main(args: &[~str], tests: ~[TestDescAndFn]) {
@@ -185,16 +202,42 @@ pub fn test_main_static(args: &[~str], tests: &[TestDescAndFn]) {
     test_main(args, owned_tests)
 }
 
+/// How `run_tests_console` reports results on stdout.
+#[deriving(Clone, Eq)]
+pub enum OutputFormat {
+    /// Human-readable lines, colored when running one test at a time.
+    FmtPretty,
+    /// One JSON object per line for every event of the run.
+    FmtJson,
+    /// A JUnit XML report, written when the run finishes.
+    FmtJunit,
+}
+
 pub struct TestOpts {
-    filter: Option<~str>,
+    filters: ~[~str],
+    filter_exact: bool,
+    skip: ~[~str],
     run_ignored: bool,
     run_tests: bool,
     run_benchmarks: bool,
     ratchet_metrics: Option<Path>,
     ratchet_noise_percent: Option<f64>,
     save_metrics: Option<Path>,
+    // The file benchmark timings are saved to, or compared with if it exists.
+    bench_baseline: Option<Path>,
     test_shard: Option<(uint,uint)>,
-    logfile: Option<Path>
+    logfile: Option<Path>,
+    format: OutputFormat,
+    // Report how long each test took, flagging those slower than this many
+    // milliseconds.
+    report_time: Option<u64>,
+    // Let tests print straight to the console instead of capturing what they
+    // write for the report of failed tests.
+    nocapture: bool,
+    // Milliseconds any test without a timeout of its own may run.
+    test_timeout: Option<u64>,
+    // Print the selected tests instead of running them.
+    list: bool
 }
 
 type OptRes = Result<TestOpts, ~str>;
@@ -213,26 +256,63 @@ fn optgroups() -> ~[getopts::groups::OptGroup] {
       groups::optopt("", "ratchet-noise-percent",
                      "Tests within N% of the recorded metrics will be \
                       considered as passing", "PERCENTAGE"),
+      groups::optopt("", "bench-baseline",
+                     "Save benchmark timings as the baseline NAME, or if it \
+                      already exists, compare them with it", "NAME"),
       groups::optopt("", "logfile", "Write logs to the specified file instead \
                           of stdout", "PATH"),
       groups::optopt("", "test-shard", "run shard A, of B shards, worth of the testsuite",
-                     "A.B")]
+                     "A.B"),
+      groups::optopt("", "format", "Output format: pretty (the default), json for one \
+                                   event per line, or junit for an XML report",
+                     "pretty|json|junit"),
+      groups::optflagopt("", "report-time", "Show how long each test took, flagging \
+                                             tests slower than MS milliseconds \
+                                             (default 1000)", "MS"),
+      groups::optflag("", "nocapture", "Don't capture stdout, stderr and logging \
+                                        of each test task"),
+      groups::optopt("", "test-timeout", "Fail tests that run for longer than SECS \
+                                          seconds", "SECS"),
+      groups::optflag("", "exact", "Match filters against whole test names"),
+      groups::optmulti("", "skip", "Skip tests matching PATTERN (may be repeated)",
+                       "PATTERN"),
+      groups::optflag("", "list", "List the selected tests and benchmarks \
+                                   instead of running them")]
 }
 
 fn usage(binary: &str, helpstr: &str) {
     #[fixed_stack_segment]; #[inline(never)];
 
-    let message = format!("Usage: {} [OPTIONS] [FILTER]", binary);
+    let message = format!("Usage: {} [OPTIONS] [FILTER...]", binary);
     println(groups::usage(message, optgroups()));
     println("");
     if helpstr == "help" {
         println("\
-The FILTER is matched against the name of all tests to run, and if any tests
-have a substring match, only those tests are run.
+Each FILTER is matched against the name of all tests to run, and if any are
+given, only the tests matching at least one of them are run. A filter matches
+names it is a substring of, or with --exact only the name equal to it. A
+filter containing any of `*?[{` is instead a glob pattern (as in extra::glob)
+that has to match the whole name, e.g. `sync::*::test_{send,recv}*`. Tests
+matching a --skip pattern, which works the same way, are left out.
+
+--bench-baseline NAME saves every sample each benchmark took to NAME.json,
+in the directory named by RUST_BENCH_BASELINE_DIR or else the current one.
+Once that file exists, later runs are compared with it instead: a benchmark
+has improved or regressed only if a Mann-Whitney U test finds its timings
+differ (p < 0.05) and, with --ratchet-noise-percent, its median moved by
+more than that. Regressions make the run fail. Runs without benchmarks
+leave the file alone. Remove the file to save a new baseline.
+
+--list prints the name of each selected test as `NAME: test` (or `bench` or
+`metric`), one per line, without running anything.
 
 By default, all tests are run in parallel. This can be altered with the
 RUST_TEST_TASKS environment variable when running tests (set it to 1).
 
+What a test task prints or logs is captured and only shown if the test
+fails. Pass --nocapture, or set the RUST_TEST_NOCAPTURE environment
+variable, to let tests write to the console directly.
+
 Test Attributes:
 
     #[test]        - Indicates a function is a test to be run. This function
@@ -240,7 +320,11 @@ Test Attributes:
     #[bench]       - Indicates a function is a benchmark to be run. This
                      function takes one argument (extra::test::BenchHarness).
     #[should_fail] - This function (also labeled with #[test]) will only pass if
-                     the code causes a failure (an assertion failure or fail!)
+                     the code causes a failure (an assertion failure or fail!).
+                     Written as #[should_fail(expected = "text")], the failure
+                     message must also contain the given text.
+    #[timeout = N] - The test fails if it runs for longer than N seconds. This
+                     overrides --test-timeout.
     #[ignore]      - When applied to a function which is already attributed as a
                      test, then the test runner will ignore these tests during
                      normal test runs. Running with --ignored will run these
@@ -261,12 +345,10 @@ pub fn parse_opts(args: &[~str]) -> Option<OptRes> {
     if matches.opt_present("h") { usage(args[0], "h"); return None; }
     if matches.opt_present("help") { usage(args[0], "help"); return None; }
 
-    let filter =
-        if matches.free.len() > 0 {
-            Some((matches).free[0].clone())
-        } else {
-            None
-        };
+    let filters = matches.free.clone();
+    let filter_exact = matches.opt_present("exact");
+    let skip = matches.opt_strs("skip");
+    let list = matches.opt_present("list");
 
     let run_ignored = matches.opt_present("ignored");
 
@@ -280,25 +362,72 @@ pub fn parse_opts(args: &[~str]) -> Option<OptRes> {
     let ratchet_metrics = matches.opt_str("ratchet-metrics");
     let ratchet_metrics = ratchet_metrics.map(|s| Path::new(s));
 
-    let ratchet_noise_percent = matches.opt_str("ratchet-noise-percent");
-    let ratchet_noise_percent = ratchet_noise_percent.map(|s| from_str::<f64>(s).unwrap());
+    let ratchet_noise_percent = match matches.opt_parse::<f64>("ratchet-noise-percent") {
+        Ok(p) => p,
+        Err(f) => return Some(Err(f.to_err_msg()))
+    };
 
     let save_metrics = matches.opt_str("save-metrics");
     let save_metrics = save_metrics.map(|s| Path::new(s));
 
+    let bench_baseline = matches.opt_str("bench-baseline").map(|name| {
+        let dir = os::getenv("RUST_BENCH_BASELINE_DIR").unwrap_or(~".");
+        Path::new(dir).join(name + ".json")
+    });
+
     let test_shard = matches.opt_str("test-shard");
     let test_shard = opt_shard(test_shard);
 
+    let format = match matches.opt_str("format") {
+        None => FmtPretty,
+        Some(s) => match s.as_slice() {
+            "pretty" => FmtPretty,
+            "json" => FmtJson,
+            "junit" => FmtJunit,
+            _ => return Some(Err(format!("argument for --format must be pretty, json \
+                                          or junit (was {})", s)))
+        }
+    };
+
+    let report_time = match matches.opt_default("report-time", "1000") {
+        None => None,
+        Some(s) => match from_str::<u64>(s) {
+            Some(ms) => Some(ms),
+            None => return Some(Err(format!("argument for --report-time must be a \
+                                             number of milliseconds (was {})", s)))
+        }
+    };
+
+    let nocapture = matches.opt_present("nocapture") ||
+        os::getenv("RUST_TEST_NOCAPTURE").is_some();
+
+    let test_timeout = match matches.opt_str("test-timeout") {
+        None => None,
+        Some(s) => match from_str::<u64>(s) {
+            Some(secs) if secs > 0 => Some(secs * 1000),
+            _ => return Some(Err(format!("argument for --test-timeout must be a \
+                                          positive number of seconds (was {})", s)))
+        }
+    };
+
     let test_opts = TestOpts {
-        filter: filter,
+        filters: filters,
+        filter_exact: filter_exact,
+        skip: skip,
         run_ignored: run_ignored,
         run_tests: run_tests,
         run_benchmarks: run_benchmarks,
         ratchet_metrics: ratchet_metrics,
         ratchet_noise_percent: ratchet_noise_percent,
         save_metrics: save_metrics,
+        bench_baseline: bench_baseline,
         test_shard: test_shard,
-        logfile: logfile
+        logfile: logfile,
+        format: format,
+        report_time: report_time,
+        nocapture: nocapture,
+        test_timeout: test_timeout,
+        list: list
     };
 
     Some(Ok(test_opts))
@@ -323,13 +452,27 @@ pub fn opt_shard(maybestr: Option<~str>) -> Option<(uint,uint)> {
 #[deriving(Clone, Eq)]
 pub struct BenchSamples {
     priv ns_iter_summ: stats::Summary,
-    priv mb_s: uint
+    priv mb_s: uint,
+    priv ns_iter_samples: ~[f64],
+    priv bytes: u64 // processed by each iteration
+}
+
+impl BenchSamples {
+    /// Throughput in MB/s over the samples, if the benchmark set `bytes`.
+    pub fn mb_s_summary(&self) -> Option<stats::Summary> {
+        if self.bytes == 0 || self.ns_iter_samples.is_empty() {
+            return None;
+        }
+        let mb_s = self.ns_iter_samples.map(|&ns| self.bytes as f64 * 1000.0 / ns.max(&1.0));
+        Some(stats::Summary::new(mb_s))
+    }
 }
 
 #[deriving(Clone, Eq)]
 pub enum TestResult {
     TrOk,
     TrFailed,
+    TrFailedMsg(~str),
     TrIgnored,
     TrMetrics(MetricMap),
     TrBench(BenchSamples),
@@ -340,13 +483,19 @@ struct ConsoleTestState {
     log_out: Option<@io::Writer>,
     term: Option<term::Terminal>,
     use_color: bool,
+    format: OutputFormat,
+    report_time: Option<u64>, // tests slower than this many ns are flagged
     total: uint,
     passed: uint,
     failed: uint,
     ignored: uint,
     measured: uint,
     metrics: MetricMap,
-    failures: ~[TestDesc],
+    baseline: BenchBaseline,
+    failures: ~[(TestDesc, ~[u8])], // with what each test printed
+    slow: ~[(TestDesc, u64)],
+    results: ~[(TestDesc, TestResult, u64, ~[u8])], // kept for the junit report
+    start_ns: u64,
     max_name_len: uint, // number of columns to fill when aligning names
 }
 
@@ -373,13 +522,19 @@ impl ConsoleTestState {
             log_out: log_out,
             use_color: use_color(),
             term: term,
+            format: opts.format,
+            report_time: opts.report_time.map(|ms| ms * 1_000_000),
             total: 0u,
             passed: 0u,
             failed: 0u,
             ignored: 0u,
             measured: 0u,
             metrics: MetricMap::new(),
+            baseline: BenchBaseline::new(),
             failures: ~[],
+            slow: ~[],
+            results: ~[],
+            start_ns: 0u64,
             max_name_len: 0u,
         }
     }
@@ -437,32 +592,176 @@ impl ConsoleTestState {
         }
     }
 
+    // Writes one event as a single line of JSON.
+    fn write_json(&self, fields: ~[(&str, json::Json)]) {
+        let mut obj = ~TreeMap::new();
+        for (k, v) in fields.move_iter() {
+            obj.insert(k.to_owned(), v);
+        }
+        self.out.write_line(json::Object(obj).to_str());
+    }
+
     pub fn write_run_start(&mut self, len: uint) {
         self.total = len;
-        let noun = if len != 1 { &"tests" } else { &"test" };
-        self.out.write_line(format!("\nrunning {} {}", len, noun));
+        self.start_ns = precise_time_ns();
+        match self.format {
+            FmtPretty => {
+                let noun = if len != 1 { &"tests" } else { &"test" };
+                self.out.write_line(format!("\nrunning {} {}", len, noun));
+            }
+            FmtJson => {
+                self.write_json(~[("type", json::String(~"suite")),
+                                  ("event", json::String(~"started")),
+                                  ("test_count", json::Number(len as f64))]);
+            }
+            FmtJunit => {}
+        }
     }
 
     pub fn write_test_start(&self, test: &TestDesc, align: NamePadding) {
-        let name = test.padded_name(self.max_name_len, align);
-        self.out.write_str(format!("test {} ... ", name));
+        match self.format {
+            FmtPretty => {
+                let name = test.padded_name(self.max_name_len, align);
+                self.out.write_str(format!("test {} ... ", name));
+            }
+            FmtJson => {
+                self.write_json(~[("type", json::String(~"test")),
+                                  ("event", json::String(~"started")),
+                                  ("name", json::String(test.name.to_str()))]);
+            }
+            FmtJunit => {}
+        }
+    }
+
+    // Whether a test should be flagged by --report-time. Only tests that
+    // ran count; benchmarks choose their own running time.
+    pub fn is_slow(&self, result: &TestResult, elapsed_ns: u64) -> bool {
+        match (self.report_time, result) {
+            (Some(limit), &TrOk) | (Some(limit), &TrFailed) |
+            (Some(limit), &TrFailedMsg(_)) => elapsed_ns > limit,
+            _ => false
+        }
+    }
+
+    pub fn write_result(&mut self, test: &TestDesc, result: &TestResult, elapsed_ns: u64,
+                        stdout: &[u8]) {
+        match self.format {
+            FmtPretty => {
+                match *result {
+                    TrOk => self.write_ok(),
+                    TrFailed | TrFailedMsg(_) => self.write_failed(),
+                    TrIgnored => self.write_ignored(),
+                    TrMetrics(ref mm) => {
+                        self.write_metric();
+                        self.out.write_str(": " + fmt_metrics(mm));
+                    }
+                    TrBench(ref bs) => {
+                        self.write_bench();
+                        self.out.write_str(": " + fmt_bench_samples(bs))
+                    }
+                }
+                match (self.report_time, result) {
+                    (Some(_), &TrOk) | (Some(_), &TrFailed) | (Some(_), &TrFailedMsg(_)) => {
+                        self.out.write_str(format!(" <{:.3f}s>", secs(elapsed_ns)));
+                        if self.is_slow(result, elapsed_ns) {
+                            self.out.write_str(" ");
+                            self.write_pretty("slow", term::color::YELLOW);
+                        }
+                    }
+                    _ => {}
+                }
+                self.out.write_str(&"\n");
+            }
+            FmtJson => self.write_json_result(test, result, elapsed_ns, stdout),
+            FmtJunit => self.results.push((test.clone(), result.clone(), elapsed_ns,
+                                           stdout.to_owned())),
+        }
     }
 
-    pub fn write_result(&self, result: &TestResult) {
+    fn write_json_result(&self, test: &TestDesc, result: &TestResult, elapsed_ns: u64,
+                         stdout: &[u8]) {
+        let (kind, event) = match *result {
+            TrOk => ("test", "ok"),
+            TrFailed | TrFailedMsg(_) => ("test", "failed"),
+            TrIgnored => ("test", "ignored"),
+            TrMetrics(_) => ("metric", "ok"),
+            TrBench(_) => ("bench", "ok"),
+        };
+        let mut fields = ~[("type", json::String(kind.to_owned())),
+                           ("event", json::String(event.to_owned())),
+                           ("name", json::String(test.name.to_str()))];
+        match *result {
+            TrIgnored => {}
+            _ => fields.push(("exec_time", json::Number(secs(elapsed_ns))))
+        }
+        if self.is_slow(result, elapsed_ns) {
+            fields.push(("slow", json::Boolean(true)));
+        }
+        match failure_message(test, result) {
+            Some(msg) => fields.push(("message", json::String(msg))),
+            None => {}
+        }
         match *result {
-            TrOk => self.write_ok(),
-            TrFailed => self.write_failed(),
-            TrIgnored => self.write_ignored(),
-            TrMetrics(ref mm) => {
-                self.write_metric();
-                self.out.write_str(": " + fmt_metrics(mm));
+            TrFailed | TrFailedMsg(_) if !stdout.is_empty() => {
+                fields.push(("stdout", json::String(output_str(stdout))));
             }
-            TrBench(ref bs) => {
-                self.write_bench();
-                self.out.write_str(": " + fmt_bench_samples(bs))
+            _ => {}
+        }
+        match *result {
+            TrMetrics(ref mm) => fields.push(("metrics", mm.to_json())),
+            TrBench(ref bs) => fields.push(("samples", bs.to_json())),
+            _ => {}
+        }
+        self.write_json(fields);
+    }
+
+    pub fn write_junit(&self) {
+        let elapsed = precise_time_ns() - self.start_ns;
+        self.out.write_line("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        self.out.write_line("<testsuites>");
+        self.out.write_line(format!("  <testsuite name=\"test\" tests=\"{}\" failures=\"{}\" \
+                                     skipped=\"{}\" time=\"{:.3f}\">",
+                                    self.results.len(), self.failed, self.ignored,
+                                    secs(elapsed)));
+        for &(ref test, ref result, elapsed_ns, ref stdout) in self.results.iter() {
+            // Test names are paths; the module becomes the class name.
+            let name = test.name.to_str();
+            let (class, leaf) = match name.rfind(':') {
+                Some(i) if i > 0 && name[i - 1] == ':' as u8 => {
+                    (name.slice_to(i - 1), name.slice_from(i + 1))
+                }
+                _ => ("", name.as_slice())
+            };
+            let open = format!("    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3f}\"",
+                               xml_escape(class), xml_escape(leaf), secs(elapsed_ns));
+            match *result {
+                TrOk => self.out.write_line(open + "/>"),
+                TrFailed | TrFailedMsg(_) => {
+                    let msg = failure_message(test, result).unwrap_or(~"test failed");
+                    self.out.write_line(open + ">");
+                    self.out.write_line(format!("      <failure message=\"{}\"/>",
+                                                xml_escape(msg)));
+                    if !stdout.is_empty() {
+                        self.out.write_line("      <system-out>" +
+                                            xml_escape(output_str(*stdout)) +
+                                            "</system-out>");
+                    }
+                    self.out.write_line("    </testcase>");
+                }
+                TrIgnored => self.out.write_line(open + "><skipped/></testcase>"),
+                TrMetrics(ref mm) => {
+                    self.out.write_line(open + "><system-out>" + xml_escape(fmt_metrics(mm)) +
+                                        "</system-out></testcase>");
+                }
+                TrBench(ref bs) => {
+                    self.out.write_line(open + "><system-out>" +
+                                        xml_escape(fmt_bench_samples(bs)) +
+                                        "</system-out></testcase>");
+                }
             }
         }
-        self.out.write_str(&"\n");
+        self.out.write_line("  </testsuite>");
+        self.out.write_line("</testsuites>");
     }
 
     pub fn write_log(&self, test: &TestDesc, result: &TestResult) {
@@ -472,7 +771,7 @@ impl ConsoleTestState {
                 out.write_line(format!("{} {}",
                                     match *result {
                                         TrOk => ~"ok",
-                                        TrFailed => ~"failed",
+                                        TrFailed | TrFailedMsg(_) => ~"failed",
                                         TrIgnored => ~"ignored",
                                         TrMetrics(ref mm) => fmt_metrics(mm),
                                         TrBench(ref bs) => fmt_bench_samples(bs)
@@ -484,16 +783,55 @@ impl ConsoleTestState {
     pub fn write_failures(&self) {
         self.out.write_line("\nfailures:");
         let mut failures = ~[];
-        for f in self.failures.iter() {
-            failures.push(f.name.to_str());
+        for &(ref f, ref stdout) in self.failures.iter() {
+            failures.push((f.name.to_str(), stdout.as_slice()));
+        }
+        sort::quick_sort(failures, |&(ref a, _), &(ref b, _)| *a <= *b);
+
+        let mut printed_output = false;
+        for &(ref name, stdout) in failures.iter() {
+            if !stdout.is_empty() {
+                self.out.write_line(format!("\n---- {} stdout ----", *name));
+                self.out.write(stdout);
+                if stdout.last() != &('\n' as u8) {
+                    self.out.write_str("\n");
+                }
+                printed_output = true;
+            }
+        }
+        if printed_output {
+            self.out.write_line("\nfailures:");
         }
-        sort::tim_sort(failures);
-        for name in failures.iter() {
+
+        for &(ref name, _) in failures.iter() {
             self.out.write_line(format!("    {}", name.to_str()));
         }
     }
 
+    pub fn write_slow(&self) {
+        let limit = self.report_time.unwrap_or(0) / 1_000_000;
+        self.out.write_line(format!("\nslow tests (over {}ms):", limit));
+        for &(ref test, elapsed_ns) in self.slow.iter() {
+            self.out.write_line(format!("    {} ({:.3f}s)", test.name.to_str(),
+                                        secs(elapsed_ns)));
+        }
+    }
+
     pub fn write_metric_diff(&self, diff: &MetricDiff) {
+        let (added, removed, improved, regressed, noise) = self.write_metric_changes(diff);
+        self.out.write_line(format!("result of ratchet: {} matrics added, {} removed, \
+                                  {} improved, {} regressed, {} noise",
+                                 added, removed, improved, regressed, noise));
+        if regressed == 0 {
+            self.out.write_line("updated ratchet file")
+        } else {
+            self.out.write_line("left ratchet file untouched")
+        }
+    }
+
+    // Writes a line for each metric that isn't just noise, and returns how
+    // many were added, removed, improved, regressed and noise.
+    fn write_metric_changes(&self, diff: &MetricDiff) -> (uint, uint, uint, uint, uint) {
         let mut noise = 0;
         let mut improved = 0;
         let mut regressed = 0;
@@ -529,57 +867,171 @@ impl ConsoleTestState {
                 }
             }
         }
-        self.out.write_line(format!("result of ratchet: {} matrics added, {} removed, \
-                                  {} improved, {} regressed, {} noise",
-                                 added, removed, improved, regressed, noise));
-        if regressed == 0 {
-            self.out.write_line("updated ratchet file")
+        (added, removed, improved, regressed, noise)
+    }
+
+    // Compares the benchmarks of this run with the baseline at `pth`, or
+    // saves them there if there is none yet. Returns false if any regressed.
+    fn check_bench_baseline(&self, pth: &Path, noise_pct: Option<f64>) -> bool {
+        let pretty = self.format == FmtPretty;
+        if self.baseline.is_empty() {
+            // Saving this would leave nothing for later runs to compare with.
+            if pretty {
+                self.out.write_str(format!("\nno benchmarks were run, left bench \
+                                            baseline untouched: {}\n", pth.display()));
+            }
+            return true;
+        }
+
+        let old = if os::path_exists(pth) {
+            match BenchBaseline::load(pth) {
+                Ok(old) => old,
+                Err(e) => {
+                    io::stderr().write_line(format!("warning: can't read bench baseline \
+                                                     {}, left it untouched: {}",
+                                                    pth.display(), e));
+                    return true;
+                }
+            }
         } else {
-            self.out.write_line("left ratchet file untouched")
+            BenchBaseline::new()
+        };
+
+        if old.is_empty() {
+            self.baseline.save(pth);
+            if pretty {
+                self.out.write_str(format!("\nsaved bench baseline: {}\n", pth.display()));
+            }
+            return true;
+        }
+
+        if pretty {
+            self.out.write_str(format!("\ncomparing with bench baseline: {}\n",
+                                       pth.display()));
+        }
+        let diff = self.baseline.compare_to_old(&old, noise_pct);
+        if pretty {
+            let (added, removed, improved, regressed, noise) = self.write_metric_changes(&diff);
+            self.out.write_line(format!("result of comparison: {} benchmarks added, \
+                                         {} removed, {} improved, {} regressed, {} noise",
+                                        added, removed, improved, regressed, noise));
+        }
+        do diff.iter().all |(_, v)| {
+            match *v {
+                Regression(_) => false,
+                _ => true
+            }
         }
     }
 
     pub fn write_run_finish(&self,
                             ratchet_metrics: &Option<Path>,
-                            ratchet_pct: Option<f64>) -> bool {
+                            ratchet_pct: Option<f64>,
+                            bench_baseline: &Option<Path>) -> bool {
         assert!(self.passed + self.failed + self.ignored + self.measured == self.total);
 
+        // Only the pretty format has room for the ratchet's commentary.
+        let pretty = self.format == FmtPretty;
         let ratchet_success = match *ratchet_metrics {
             None => true,
             Some(ref pth) => {
-                self.out.write_str(format!("\nusing metrics ratchet: {}\n", pth.display()));
-                match ratchet_pct {
-                    None => (),
-                    Some(pct) =>
-                    self.out.write_str(format!("with noise-tolerance forced to: {}%%\n",
-                                            pct as f64))
+                if pretty {
+                    self.out.write_str(format!("\nusing metrics ratchet: {}\n",
+                                               pth.display()));
+                    match ratchet_pct {
+                        None => (),
+                        Some(pct) =>
+                        self.out.write_str(format!("with noise-tolerance forced to: {}%%\n",
+                                                pct as f64))
+                    }
                 }
                 let (diff, ok) = self.metrics.ratchet(pth, ratchet_pct);
-                self.write_metric_diff(&diff);
+                if pretty {
+                    self.write_metric_diff(&diff);
+                }
                 ok
             }
         };
 
+        let baseline_success = match *bench_baseline {
+            None => true,
+            Some(ref pth) => self.check_bench_baseline(pth, ratchet_pct)
+        };
+
         let test_success = self.failed == 0u;
-        if !test_success {
-            self.write_failures();
-        }
+        let success = ratchet_success && baseline_success && test_success;
 
-        let success = ratchet_success && test_success;
+        match self.format {
+            FmtPretty => {
+                if !test_success {
+                    self.write_failures();
+                }
+                if !self.slow.is_empty() {
+                    self.write_slow();
+                }
 
-        self.out.write_str("\ntest result: ");
-        if success {
-            // There's no parallelism at this point so it's safe to use color
-            self.write_ok();
-        } else {
-            self.write_failed();
+                self.out.write_str("\ntest result: ");
+                if success {
+                    // There's no parallelism at this point so it's safe to use color
+                    self.write_ok();
+                } else {
+                    self.write_failed();
+                }
+                self.out.write_str(format!(". {} passed; {} failed; {} ignored; {} measured\n\n",
+                                        self.passed, self.failed, self.ignored, self.measured));
+            }
+            FmtJson => {
+                let elapsed = precise_time_ns() - self.start_ns;
+                let event = if success { ~"ok" } else { ~"failed" };
+                self.write_json(~[("type", json::String(~"suite")),
+                                  ("event", json::String(event)),
+                                  ("passed", json::Number(self.passed as f64)),
+                                  ("failed", json::Number(self.failed as f64)),
+                                  ("ignored", json::Number(self.ignored as f64)),
+                                  ("measured", json::Number(self.measured as f64)),
+                                  ("exec_time", json::Number(secs(elapsed)))]);
+            }
+            FmtJunit => self.write_junit(),
         }
-        self.out.write_str(format!(". {} passed; {} failed; {} ignored; {} measured\n\n",
-                                self.passed, self.failed, self.ignored, self.measured));
         return success;
     }
 }
 
+fn secs(ns: u64) -> f64 { ns as f64 / 1e9 }
+
+// Captured output as text; bytes that aren't UTF-8 are shown one per char.
+fn output_str(output: &[u8]) -> ~str {
+    match str::from_utf8_opt(output) {
+        Some(s) => s,
+        None => output.iter().map(|&b| b as char).collect()
+    }
+}
+
+// Escapes text for use in XML attributes and character data.
+fn xml_escape(s: &str) -> ~str {
+    let mut escaped = ~"";
+    for c in s.iter() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push_char(c)
+        }
+    }
+    escaped
+}
+
+// What the harness knows about why a test failed.
+fn failure_message(test: &TestDesc, result: &TestResult) -> Option<~str> {
+    match *result {
+        TrFailed if test.should_fail => Some(~"test did not fail as expected"),
+        TrFailedMsg(ref msg) => Some(msg.clone()),
+        _ => None
+    }
+}
+
 pub fn fmt_metrics(mm: &MetricMap) -> ~str {
     let v : ~[~str] = mm.iter()
         .map(|(k,v)| format!("{}: {} (+/- {})",
@@ -592,10 +1044,17 @@ pub fn fmt_metrics(mm: &MetricMap) -> ~str {
 
 pub fn fmt_bench_samples(bs: &BenchSamples) -> ~str {
     if bs.mb_s != 0 {
-        format!("{:>9} ns/iter (+/- {}) = {} MB/s",
+        // The throughput's spread comes from the samples themselves, so
+        // results loaded without them just show the median.
+        let mb_s_spread = match bs.mb_s_summary() {
+            Some(summ) => format!(" (+/- {})", (summ.max - summ.min) as uint),
+            None => ~""
+        };
+        format!("{:>9} ns/iter (+/- {}) = {} MB/s{}",
              bs.ns_iter_summ.median as uint,
              (bs.ns_iter_summ.max - bs.ns_iter_summ.min) as uint,
-             bs.mb_s)
+             bs.mb_s,
+             mb_s_spread)
     } else {
         format!("{:>9} ns/iter (+/- {})",
              bs.ns_iter_summ.median as uint,
@@ -603,17 +1062,41 @@ pub fn fmt_bench_samples(bs: &BenchSamples) -> ~str {
     }
 }
 
+// Writes the name and kind of each test the options select, one per line.
+// Benchmarks are listed even without --bench, so that one listing shows
+// everything; --bench on its own leaves the tests out.
+pub fn list_tests(out: @io::Writer, opts: &TestOpts, tests: ~[TestDescAndFn]) {
+    for test in filter_tests(opts, tests).iter() {
+        let kind = match test.testfn {
+            StaticTestFn(_) | DynTestFn(_) if opts.run_tests => "test",
+            StaticTestFn(_) | DynTestFn(_) => continue,
+            StaticBenchFn(_) | DynBenchFn(_) => "bench",
+            StaticMetricFn(_) | DynMetricFn(_) => "metric",
+        };
+        out.write_line(format!("{}: {}", test.desc.name.to_str(), kind));
+    }
+}
+
 // A simple console test runner
 pub fn run_tests_console(opts: &TestOpts,
                          tests: ~[TestDescAndFn]) -> bool {
+    if opts.list {
+        list_tests(io::stdout(), opts, tests);
+        return true;
+    }
+
     fn callback(event: &TestEvent, st: &mut ConsoleTestState) {
         debug!("callback(event={:?})", event);
         match (*event).clone() {
             TeFiltered(ref filtered_tests) => st.write_run_start(filtered_tests.len()),
             TeWait(ref test, padding) => st.write_test_start(test, padding),
-            TeResult(test, result) => {
+            TeResult(test, result, elapsed_ns, stdout) => {
                 st.write_log(&test, &result);
-                st.write_result(&result);
+                st.write_result(&test, &result, elapsed_ns, stdout);
+                if st.is_slow(&result, elapsed_ns) {
+                    st.slow.push((test.clone(), elapsed_ns));
+                }
+                let note = failure_message(&test, &result);
                 match result {
                     TrOk => st.passed += 1,
                     TrIgnored => st.ignored += 1,
@@ -629,11 +1112,17 @@ pub fn run_tests_console(opts: &TestOpts,
                         st.metrics.insert_metric(test.name.to_str(),
                                                  bs.ns_iter_summ.median,
                                                  bs.ns_iter_summ.max - bs.ns_iter_summ.min);
+                        st.baseline.insert(test.name.to_str(), bs.ns_iter_samples);
                         st.measured += 1
                     }
-                    TrFailed => {
+                    TrFailed | TrFailedMsg(_) => {
+                        let mut stdout = stdout;
+                        match note {
+                            Some(msg) => stdout.push_all(format!("note: {}\n", msg).as_bytes()),
+                            None => {}
+                        }
                         st.failed += 1;
-                        st.failures.push(test);
+                        st.failures.push((test, stdout));
                     }
                 }
             }
@@ -659,10 +1148,13 @@ pub fn run_tests_console(opts: &TestOpts,
         None => (),
         Some(ref pth) => {
             st.metrics.save(pth);
-            st.out.write_str(format!("\nmetrics saved to: {}", pth.display()));
+            if st.format == FmtPretty {
+                st.out.write_str(format!("\nmetrics saved to: {}", pth.display()));
+            }
         }
     }
-    return st.write_run_finish(&opts.ratchet_metrics, opts.ratchet_noise_percent);
+    return st.write_run_finish(&opts.ratchet_metrics, opts.ratchet_noise_percent,
+                               &opts.bench_baseline);
 }
 
 #[test]
@@ -673,13 +1165,17 @@ fn should_sort_failures_before_printing_them() {
         let test_a = TestDesc {
             name: StaticTestName("a"),
             ignore: false,
-            should_fail: false
+            should_fail: false,
+            expected_failure: None,
+            timeout: None
         };
 
         let test_b = TestDesc {
             name: StaticTestName("b"),
             ignore: false,
-            should_fail: false
+            should_fail: false,
+            expected_failure: None,
+            timeout: None
         };
 
         let st = @ConsoleTestState {
@@ -687,13 +1183,19 @@ fn should_sort_failures_before_printing_them() {
             log_out: None,
             term: None,
             use_color: false,
+            format: FmtPretty,
+            report_time: None,
             total: 0u,
             passed: 0u,
             failed: 0u,
             ignored: 0u,
             measured: 0u,
             metrics: MetricMap::new(),
-            failures: ~[test_b, test_a],
+            baseline: BenchBaseline::new(),
+            failures: ~[(test_b, ~[]), (test_a, ~[])],
+            slow: ~[],
+            results: ~[],
+            start_ns: 0u64,
             max_name_len: 0u,
         };
 
@@ -705,16 +1207,158 @@ fn should_sort_failures_before_printing_them() {
     assert!(apos < bpos);
 }
 
+#[test]
+fn should_print_captured_output_of_failures() {
+    let test = desc_for_test("noisy");
+    let quiet = desc_for_test("quiet");
+    let s = do io::with_str_writer |wr| {
+        let mut st = console_state_for_test(wr, FmtPretty);
+        st.failures = ~[(test.clone(), bytes!("said something").to_owned()),
+                        (quiet.clone(), ~[])];
+        st.write_failures();
+    };
+    assert_eq!(s, ~"\nfailures:\n\n---- noisy stdout ----\nsaid something\n\n\
+                    failures:\n    noisy\n    quiet\n");
+}
+
+#[cfg(test)]
+fn desc_for_test(name: &'static str) -> TestDesc {
+    TestDesc {
+        name: StaticTestName(name),
+        ignore: false,
+        should_fail: false,
+        expected_failure: None,
+        timeout: None
+    }
+}
+
+#[cfg(test)]
+fn console_state_for_test(out: @io::Writer, format: OutputFormat) -> ConsoleTestState {
+    ConsoleTestState {
+        out: out,
+        log_out: None,
+        term: None,
+        use_color: false,
+        format: format,
+        report_time: Some(1_000_000),
+        total: 0u,
+        passed: 0u,
+        failed: 0u,
+        ignored: 0u,
+        measured: 0u,
+        metrics: MetricMap::new(),
+        baseline: BenchBaseline::new(),
+        failures: ~[],
+        slow: ~[],
+        results: ~[],
+        start_ns: 0u64,
+        max_name_len: 0u,
+    }
+}
+
+#[test]
+fn should_write_one_json_event_per_line() {
+    let test = TestDesc {
+        name: StaticTestName("m::slow \"one\""),
+        ignore: false,
+        should_fail: true,
+        expected_failure: None,
+        timeout: None
+    };
+    let s = do io::with_str_writer |wr| {
+        let mut st = console_state_for_test(wr, FmtJson);
+        st.write_test_start(&test, PadNone);
+        st.write_result(&test, &TrFailed, 2_500_000, bytes!("out\n"));
+        st.write_result(&test, &TrIgnored, 0, []);
+    };
+    let lines: ~[&str] = s.line_iter().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], r#"{"event":"started","name":"m::slow \"one\"","type":"test"}"#);
+    assert_eq!(lines[1].to_owned(),
+               r#"{"event":"failed","exec_time":0.0025,"#.to_owned() +
+               r#""message":"test did not fail as expected","# +
+               r#""name":"m::slow \"one\"","slow":true,"stdout":"out\n","type":"test"}"#);
+    assert_eq!(lines[2], r#"{"event":"ignored","name":"m::slow \"one\"","type":"test"}"#);
+}
+
+#[test]
+fn should_write_junit_report() {
+    let ok = desc_for_test("a::b::ok");
+    let failed = desc_for_test("<top>");
+    let s = do io::with_str_writer |wr| {
+        let mut st = console_state_for_test(wr, FmtJunit);
+        st.write_result(&ok, &TrOk, 1_000_000, bytes!("quiet"));
+        st.write_result(&failed, &TrFailed, 0, bytes!("x < y"));
+        st.failed = 1;
+        st.write_junit();
+    };
+    assert!(s.starts_with("<?xml"));
+    assert!(s.contains(r#"tests="2" failures="1" skipped="0""#));
+    assert!(s.contains(r#"<testcase classname="a::b" name="ok" time="0.001"/>"#));
+    assert!(s.contains(r#"<testcase classname="" name="&lt;top&gt;" time="0.000">"#));
+    assert!(s.contains(r#"<failure message="test failed"/>"#));
+    assert!(s.contains("<system-out>x &lt; y</system-out>"));
+    assert!(!s.contains("quiet"));
+    assert!(s.ends_with("</testsuites>\n"));
+}
+
+#[test]
+fn should_not_save_empty_bench_baseline() {
+    let dir = ::tempfile::TempDir::new("test-empty-baseline").expect("missing temp dir");
+    let pth = dir.path().join("base.json");
+    let finish = |baseline: BenchBaseline| {
+        do io::with_str_writer |wr| {
+            let mut st = console_state_for_test(wr, FmtPretty);
+            st.baseline = baseline.clone();
+            assert!(st.write_run_finish(&None, None, &Some(pth.clone())));
+        };
+    };
+
+    finish(BenchBaseline::new());
+    assert!(!os::path_exists(&pth));
+
+    let mut saved = BenchBaseline::new();
+    saved.insert(~"b", ~[1.0, 2.0]);
+    finish(saved.clone());
+    assert_eq!(BenchBaseline::load(&pth), Ok(saved.clone()));
+
+    // A later run without benchmarks leaves the baseline alone
+    finish(BenchBaseline::new());
+    assert_eq!(BenchBaseline::load(&pth), Ok(saved));
+}
+
+#[test]
+fn should_report_message_of_ordinary_failure() {
+    let test = TestDesc { name: StaticTestName("fails"), ignore: false, should_fail: false };
+    let result = calc_result(&test, Err(Some(SendStrOwned(~"assertion failed: x < y"))));
+    assert_eq!(result, TrFailedMsg(~"assertion failed: x < y"));
+
+    let json = do io::with_str_writer |wr| {
+        let mut st = console_state_for_test(wr, FmtJson);
+        st.write_result(&test, &result, 0, []);
+    };
+    assert!(json.contains(r#""message":"assertion failed: x < y""#));
+
+    let junit = do io::with_str_writer |wr| {
+        let mut st = console_state_for_test(wr, FmtJunit);
+        st.write_result(&test, &result, 0, []);
+        st.failed = 1;
+        st.write_junit();
+    };
+    assert!(junit.contains(r#"<failure message="assertion failed: x &lt; y"/>"#));
+}
+
 fn use_color() -> bool { return get_concurrency() == 1; }
 
 #[deriving(Clone)]
 enum TestEvent {
     TeFiltered(~[TestDesc]),
     TeWait(TestDesc, NamePadding),
-    TeResult(TestDesc, TestResult),
+    TeResult(TestDesc, TestResult, u64, ~[u8]),
 }
 
-type MonitorMsg = (TestDesc, TestResult);
+// A finished test, with how long it took in nanoseconds and what it printed.
+type MonitorMsg = (TestDesc, TestResult, u64, ~[u8]);
 
 fn run_tests(opts: &TestOpts,
              tests: ~[TestDescAndFn],
@@ -747,22 +1391,25 @@ fn run_tests(opts: &TestOpts,
 
     while pending > 0 || !remaining.is_empty() {
         while pending < concurrency && !remaining.is_empty() {
-            let test = remaining.pop();
+            let mut test = remaining.pop();
+            if test.desc.timeout.is_none() {
+                test.desc.timeout = opts.test_timeout;
+            }
             if concurrency == 1 {
                 // We are doing one test at a time so we can print the name
                 // of the test before we run it. Useful for debugging tests
                 // that hang forever.
                 callback(TeWait(test.desc.clone(), test.testfn.padding()));
             }
-            run_test(!opts.run_tests, test, ch.clone());
+            run_test(!opts.run_tests, opts.nocapture, test, ch.clone());
             pending += 1;
         }
 
-        let (desc, result) = p.recv();
+        let (desc, result, elapsed_ns, stdout) = p.recv();
         if concurrency != 1 {
             callback(TeWait(desc.clone(), PadNone));
         }
-        callback(TeResult(desc, result));
+        callback(TeResult(desc, result, elapsed_ns, stdout));
         pending -= 1;
     }
 
@@ -770,9 +1417,9 @@ fn run_tests(opts: &TestOpts,
     // (this includes metric fns)
     for b in filtered_benchs_and_metrics.move_iter() {
         callback(TeWait(b.desc.clone(), b.testfn.padding()));
-        run_test(!opts.run_benchmarks, b, ch.clone());
-        let (test, result) = p.recv();
-        callback(TeResult(test, result));
+        run_test(!opts.run_benchmarks, opts.nocapture, b, ch.clone());
+        let (test, result, elapsed_ns, stdout) = p.recv();
+        callback(TeResult(test, result, elapsed_ns, stdout));
     }
 }
 
@@ -792,31 +1439,59 @@ fn get_concurrency() -> uint {
     }
 }
 
+// A test filter or skip pattern, ready to be matched against test names.
+enum NameMatcher {
+    MatchSubstring(~str),
+    MatchExact(~str),
+    MatchGlob(Pattern),
+}
+
+impl NameMatcher {
+    fn new(pattern: &str, exact: bool) -> NameMatcher {
+        if pattern.iter().any(|c| "*?[{".contains_char(c)) {
+            MatchGlob(Pattern::new(pattern))
+        } else if exact {
+            MatchExact(pattern.to_owned())
+        } else {
+            MatchSubstring(pattern.to_owned())
+        }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        match *self {
+            MatchSubstring(ref s) => name.contains(*s),
+            MatchExact(ref s) => name == s.as_slice(),
+            MatchGlob(ref p) => p.matches(name),
+        }
+    }
+}
+
 pub fn filter_tests(
     opts: &TestOpts,
     tests: ~[TestDescAndFn]) -> ~[TestDescAndFn]
 {
     let mut filtered = tests;
 
-    // Remove tests that don't match the test filter
-    filtered = if opts.filter.is_none() {
+    // Remove tests that don't match any of the test filters
+    filtered = if opts.filters.is_empty() {
         filtered
     } else {
-        let filter_str = match opts.filter {
-          Some(ref f) => (*f).clone(),
-          None => ~""
-        };
-
-        fn filter_fn(test: TestDescAndFn, filter_str: &str) ->
-            Option<TestDescAndFn> {
-            if test.desc.name.to_str().contains(filter_str) {
-                return Some(test);
-            } else {
-                return None;
-            }
-        }
+        let filters = opts.filters.map(|f| NameMatcher::new(*f, opts.filter_exact));
+        filtered.move_iter().filter(|test| {
+            let name = test.desc.name.to_str();
+            filters.iter().any(|f| f.matches(name))
+        }).collect()
+    };
 
-        filtered.move_iter().filter_map(|x| filter_fn(x, filter_str)).collect()
+    // Remove tests that match a skip pattern
+    filtered = if opts.skip.is_empty() {
+        filtered
+    } else {
+        let skip = opts.skip.map(|s| NameMatcher::new(*s, opts.filter_exact));
+        filtered.move_iter().filter(|test| {
+            let name = test.desc.name.to_str();
+            !skip.iter().any(|s| s.matches(name))
+        }).collect()
     };
 
     // Maybe pull out the ignored test and unignore them
@@ -854,69 +1529,192 @@ pub fn filter_tests(
     }
 }
 
+// Forwards what a test task prints or logs to the task supervising it.
+struct OutputCapture {
+    priv chan: SharedChan<~[u8]>
+}
+
+impl ::std::rt::io::Writer for OutputCapture {
+    fn write(&mut self, buf: &[u8]) {
+        self.chan.send(buf.to_owned());
+    }
+
+    fn flush(&mut self) {}
+}
+
+impl logging::Logger for OutputCapture {
+    fn log(&mut self, args: &fmt::Arguments) {
+        let mut line = fmt::format(args);
+        line.push_char('\n');
+        self.chan.send(line.into_bytes());
+    }
+}
+
 pub fn run_test(force_ignore: bool,
+                nocapture: bool,
                 test: TestDescAndFn,
                 monitor_ch: SharedChan<MonitorMsg>) {
 
     let TestDescAndFn {desc, testfn} = test;
 
     if force_ignore || desc.ignore {
-        monitor_ch.send((desc, TrIgnored));
+        monitor_ch.send((desc, TrIgnored, 0, ~[]));
         return;
     }
 
     fn run_test_inner(desc: TestDesc,
+                      nocapture: bool,
                       monitor_ch: SharedChan<MonitorMsg>,
                       testfn: ~fn()) {
         let testfn_cell = ::std::cell::Cell::new(testfn);
         do task::spawn {
-            let mut task = task::task();
-            task.unlinked();
-            let result_future = task.future_result();
-            task.spawn(testfn_cell.take());
-
-            let task_result = result_future.recv();
-            let test_result = calc_result(&desc,
-                                          task_result == task::Success);
-            monitor_ch.send((desc.clone(), test_result));
+            let (out_port, out_chan) = stream();
+            let out_chan = SharedChan::new(out_chan);
+
+            let start = precise_time_ns();
+            let testfn = testfn_cell.take();
+            let test_result = match desc.timeout {
+                None => calc_result(&desc, run_in_task(nocapture, out_chan.clone(), testfn)),
+                Some(ms) => match run_with_timeout(nocapture, out_chan.clone(), testfn, ms) {
+                    Some(task_result) => calc_result(&desc, task_result),
+                    None => TrFailedMsg(format!("test timed out after {}ms", ms))
+                }
+            };
+            let elapsed = precise_time_ns() - start;
+
+            // Unless it timed out, the test task has exited and everything it
+            // wrote is already queued; don't wait on `out_chan`, which this
+            // task still holds.
+            let mut stdout = ~[];
+            while out_port.peek() {
+                stdout.push_all_move(out_port.recv());
+            }
+            monitor_ch.send((desc.clone(), test_result, elapsed, stdout));
         }
     }
 
+    let start = precise_time_ns();
     match testfn {
         DynBenchFn(benchfn) => {
             let bs = ::test::bench::benchmark(benchfn);
-            monitor_ch.send((desc, TrBench(bs)));
+            monitor_ch.send((desc, TrBench(bs), precise_time_ns() - start, ~[]));
             return;
         }
         StaticBenchFn(benchfn) => {
             let bs = ::test::bench::benchmark(benchfn);
-            monitor_ch.send((desc, TrBench(bs)));
+            monitor_ch.send((desc, TrBench(bs), precise_time_ns() - start, ~[]));
             return;
         }
         DynMetricFn(f) => {
             let mut mm = MetricMap::new();
             f(&mut mm);
-            monitor_ch.send((desc, TrMetrics(mm)));
+            monitor_ch.send((desc, TrMetrics(mm), precise_time_ns() - start, ~[]));
             return;
         }
         StaticMetricFn(f) => {
             let mut mm = MetricMap::new();
             f(&mut mm);
-            monitor_ch.send((desc, TrMetrics(mm)));
+            monitor_ch.send((desc, TrMetrics(mm), precise_time_ns() - start, ~[]));
             return;
         }
-        DynTestFn(f) => run_test_inner(desc, monitor_ch, f),
-        StaticTestFn(f) => run_test_inner(desc, monitor_ch, || f())
+        DynTestFn(f) => run_test_inner(desc, nocapture, monitor_ch, f),
+        StaticTestFn(f) => run_test_inner(desc, nocapture, monitor_ch, || f())
     }
 }
 
-fn calc_result(desc: &TestDesc, task_succeeded: bool) -> TestResult {
-    if task_succeeded {
-        if desc.should_fail { TrFailed }
-        else { TrOk }
+// Runs `testfn` in a task of its own, supervised by the calling task so that
+// it goes down with it, and returns how the test task ended.
+fn run_in_task(nocapture: bool,
+               out_chan: SharedChan<~[u8]>,
+               testfn: ~fn()) -> Result<(), Option<SendStr>> {
+    let mut task = task::task();
+    task.supervised();
+    if nocapture {
+        task.try_with_cause(testfn)
     } else {
-        if desc.should_fail { TrOk }
-        else { TrFailed }
+        do task.try_with_cause {
+            stdio::set_stdout(~OutputCapture { chan: out_chan.clone() }
+                              as ~::std::rt::io::Writer);
+            stdio::set_stderr(~OutputCapture { chan: out_chan.clone() }
+                              as ~::std::rt::io::Writer);
+            logging::set_logger(~OutputCapture { chan: out_chan.clone() }
+                                as ~logging::Logger);
+            testfn();
+        }
+    }
+}
+
+// Like `run_in_task`, but gives up on the test after `timeout_ms` and returns
+// `None`. The test task is then killed along with the task watching it, so it
+// can't keep running next to the tests that come after it.
+fn run_with_timeout(nocapture: bool,
+                    out_chan: SharedChan<~[u8]>,
+                    testfn: ~fn(),
+                    timeout_ms: u64) -> Option<Result<(), Option<SendStr>>> {
+    let (result_port, result_chan) = stream();
+    let args = ::std::cell::Cell::new((out_chan, testfn, result_chan));
+    do task::spawn_supervised {
+        let (out_chan, testfn, result_chan) = args.take();
+        let (port, chan) = stream();
+        let args = ::std::cell::Cell::new((out_chan, testfn, chan));
+        do task::spawn_supervised {
+            let (out_chan, testfn, chan) = args.take();
+            chan.send(run_in_task(nocapture, out_chan, testfn));
+        }
+        match recv_with_timeout(&port, timeout_ms) {
+            Some(task_result) => result_chan.send(Some(task_result)),
+            None => {
+                result_chan.send(None);
+                fail!("test timed out after {}ms", timeout_ms);
+            }
+        }
+    }
+    result_port.recv()
+}
+
+// Waits up to `timeout_ms` for a value on `port`, looking at it every few
+// milliseconds rather than keeping another task around to wake this one.
+fn recv_with_timeout<T: Send>(port: &Port<T>, timeout_ms: u64) -> Option<T> {
+    let start = precise_time_ns();
+    loop {
+        if port.peek() {
+            return Some(port.recv());
+        }
+        let waited_ms = (precise_time_ns() - start) / 1_000_000;
+        if waited_ms >= timeout_ms {
+            return None;
+        }
+        timer::sleep(::std::cmp::min(10, timeout_ms - waited_ms));
+    }
+}
+
+fn calc_result(desc: &TestDesc, task_result: Result<(), Option<SendStr>>) -> TestResult {
+    match task_result {
+        Ok(()) => {
+            if desc.should_fail { TrFailed }
+            else { TrOk }
+        }
+        Err(cause) => {
+            if !desc.should_fail {
+                // Keep the message the test failed with, for the reports.
+                return match cause {
+                    Some(msg) => TrFailedMsg(msg.into_owned()),
+                    None => TrFailed
+                };
+            }
+            match (desc.expected_failure, cause) {
+                (None, _) => TrOk,
+                (Some(expected), Some(ref msg)) if msg.as_slice().contains(expected) => TrOk,
+                (Some(expected), Some(msg)) => {
+                    TrFailedMsg(format!("failure message '{}' did not contain '{}'",
+                                        msg.as_slice(), expected))
+                }
+                (Some(expected), None) => {
+                    TrFailedMsg(format!("test failed without a message, expected '{}'",
+                                        expected))
+                }
+            }
+        }
     }
 }
 
@@ -930,6 +1728,20 @@ impl ToJson for Metric {
     }
 }
 
+impl ToJson for BenchSamples {
+    fn to_json(&self) -> json::Json {
+        let mut map = ~TreeMap::new();
+        map.insert(~"median", json::Number(self.ns_iter_summ.median));
+        map.insert(~"min", json::Number(self.ns_iter_summ.min));
+        map.insert(~"max", json::Number(self.ns_iter_summ.max));
+        map.insert(~"median_abs_dev", json::Number(self.ns_iter_summ.median_abs_dev));
+        map.insert(~"mb_s", json::Number(self.mb_s as f64));
+        map.insert(~"bytes", json::Number(self.bytes as f64));
+        map.insert(~"ns_iter_samples", self.ns_iter_samples.to_json());
+        json::Object(map)
+    }
+}
+
 impl MetricMap {
 
     pub fn new() -> MetricMap {
@@ -1054,6 +1866,77 @@ impl MetricMap {
     }
 }
 
+impl BenchBaseline {
+
+    pub fn new() -> BenchBaseline {
+        BenchBaseline(TreeMap::new())
+    }
+
+    /// Load a BenchBaseline from a file, or say why it can't be read.
+    pub fn load(p: &Path) -> Result<BenchBaseline, ~str> {
+        let f = match io::file_reader(p) {
+            Ok(f) => f,
+            Err(e) => return Err(e)
+        };
+        let json = match json::from_reader(f) {
+            Ok(json) => json,
+            Err(e) => return Err(e.to_str())
+        };
+        let mut decoder = json::Decoder(json);
+        match try_decode::<TreeMap<~str,~[f64]>, json::Decoder>(&mut decoder) {
+            Ok(map) => Ok(BenchBaseline(map)),
+            Err(e) => Err(e.to_str())
+        }
+    }
+
+    /// Write a BenchBaseline to a file.
+    pub fn save(&self, p: &Path) {
+        let f = io::file_writer(p, [io::Create, io::Truncate]).unwrap();
+        self.to_json().to_pretty_writer(f);
+    }
+
+    /// Compare against the benchmarks of an older run. A benchmark counts
+    /// as changed only when a Mann-Whitney U test says its two sets of
+    /// samples are unlikely (p < 0.05) to come from the same distribution,
+    /// and, given `noise_pct`, its median also moved by more than that
+    /// percentage; the change is reported as the median's.
+    pub fn compare_to_old(&self, old: &BenchBaseline,
+                          noise_pct: Option<f64>) -> MetricDiff {
+        let mut diff : MetricDiff = TreeMap::new();
+        for (k, vold) in old.iter() {
+            let r = match self.find(k) {
+                None => MetricRemoved,
+                Some(v) => {
+                    let (new, old) = (v.as_slice(), vold.as_slice());
+                    let (_, p) = stats::mann_whitney_u(new, old);
+                    let median = new.median();
+                    let old_median = old.median();
+                    let delta = median - old_median;
+                    let pct = delta.abs() / old_median.max(&f64::epsilon) * 100.0;
+                    let within_noise = match noise_pct {
+                        None => false,
+                        Some(noise) => pct <= noise
+                    };
+                    if p >= 0.05 || delta == 0.0 || within_noise {
+                        LikelyNoise
+                    } else if delta < 0.0 {
+                        Improvement(pct)
+                    } else {
+                        Regression(pct)
+                    }
+                }
+            };
+            diff.insert((*k).clone(), r);
+        }
+        for (k, _) in self.iter() {
+            if !diff.contains_key(k) {
+                diff.insert((*k).clone(), MetricAdded);
+            }
+        }
+        diff
+    }
+}
+
 
 // Benchmarking
 
@@ -1093,6 +1976,12 @@ impl BenchHarness {
 
     // This is a more statistics-driven benchmark algorithm
     pub fn auto_bench(&mut self, f: &fn(&mut BenchHarness)) -> stats::Summary {
+        stats::Summary::new(self.auto_bench_samples(f))
+    }
+
+    // Runs `auto_bench`, returning the (winsorized) per-iteration times of
+    // its final round rather than just their summary.
+    fn auto_bench_samples(&mut self, f: &fn(&mut BenchHarness)) -> ~[f64] {
 
         // Initial bench run to get ballpark figure.
         let mut n = 1_u64;
@@ -1141,13 +2030,13 @@ impl BenchHarness {
             if loop_run > 100_000_000 &&
                 summ.median_abs_dev_pct < 1.0 &&
                 summ.median - summ5.median < summ5.median_abs_dev {
-                return summ5;
+                return samples.to_owned();
             }
 
             total_run += loop_run;
             // Longest we ever run for is 3s.
             if total_run > 3_000_000_000 {
-                return summ5;
+                return samples.to_owned();
             }
 
             n *= 2;
@@ -1161,6 +2050,7 @@ impl BenchHarness {
 
 pub mod bench {
     use test::{BenchHarness, BenchSamples};
+    use stats;
 
     pub fn benchmark(f: &fn(&mut BenchHarness)) -> BenchSamples {
 
@@ -1171,7 +2061,8 @@ pub mod bench {
             bytes: 0
         };
 
-        let ns_iter_summ = bs.auto_bench(f);
+        let ns_iter_samples = bs.auto_bench_samples(f);
+        let ns_iter_summ = stats::Summary::new(ns_iter_samples);
 
         let ns_iter = (ns_iter_summ.median as u64).max(&1);
         let iter_s = 1_000_000_000 / ns_iter;
@@ -1179,21 +2070,27 @@ pub mod bench {
 
         BenchSamples {
             ns_iter_summ: ns_iter_summ,
-            mb_s: mb_s as uint
+            mb_s: mb_s as uint,
+            ns_iter_samples: ns_iter_samples,
+            bytes: bs.bytes
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use test::{TrFailed, TrIgnored, TrOk, filter_tests, parse_opts,
+    use test::{TrFailed, TrFailedMsg, TrIgnored, TrOk, filter_tests, parse_opts,
                TestDesc, TestDescAndFn,
-               Metric, MetricMap, MetricAdded, MetricRemoved,
+               Metric, MetricMap, MetricAdded, MetricRemoved, BenchBaseline,
                Improvement, Regression, LikelyNoise,
                StaticTestName, DynTestName, DynTestFn};
-    use test::{TestOpts, run_test};
+    use test::{TestOpts, run_test, list_tests, FmtPretty, FmtJunit};
+    use test::{BenchHarness, StaticBenchFn};
 
     use std::comm::{stream, SharedChan};
+    use std::io;
+    use std::rt::io::timer;
+    use std::str;
     use tempfile::TempDir;
 
     #[test]
@@ -1203,14 +2100,16 @@ mod tests {
             desc: TestDesc {
                 name: StaticTestName("whatever"),
                 ignore: true,
-                should_fail: false
+                should_fail: false,
+                expected_failure: None,
+                timeout: None
             },
             testfn: DynTestFn(|| f()),
         };
         let (p, ch) = stream();
         let ch = SharedChan::new(ch);
-        run_test(false, desc, ch);
-        let (_, res) = p.recv();
+        run_test(false, false, desc, ch);
+        let (_, res, _, _) = p.recv();
         assert!(res != TrOk);
     }
 
@@ -1221,14 +2120,16 @@ mod tests {
             desc: TestDesc {
                 name: StaticTestName("whatever"),
                 ignore: true,
-                should_fail: false
+                should_fail: false,
+                expected_failure: None,
+                timeout: None
             },
             testfn: DynTestFn(|| f()),
         };
         let (p, ch) = stream();
         let ch = SharedChan::new(ch);
-        run_test(false, desc, ch);
-        let (_, res) = p.recv();
+        run_test(false, false, desc, ch);
+        let (_, res, _, _) = p.recv();
         assert_eq!(res, TrIgnored);
     }
 
@@ -1239,14 +2140,16 @@ mod tests {
             desc: TestDesc {
                 name: StaticTestName("whatever"),
                 ignore: false,
-                should_fail: true
+                should_fail: true,
+                expected_failure: None,
+                timeout: None
             },
             testfn: DynTestFn(|| f()),
         };
         let (p, ch) = stream();
         let ch = SharedChan::new(ch);
-        run_test(false, desc, ch);
-        let (_, res) = p.recv();
+        run_test(false, false, desc, ch);
+        let (_, res, _, _) = p.recv();
         assert_eq!(res, TrOk);
     }
 
@@ -1257,17 +2160,103 @@ mod tests {
             desc: TestDesc {
                 name: StaticTestName("whatever"),
                 ignore: false,
-                should_fail: true
+                should_fail: true,
+                expected_failure: None,
+                timeout: None
             },
             testfn: DynTestFn(|| f()),
         };
         let (p, ch) = stream();
         let ch = SharedChan::new(ch);
-        run_test(false, desc, ch);
-        let (_, res) = p.recv();
+        run_test(false, false, desc, ch);
+        let (_, res, _, _) = p.recv();
         assert_eq!(res, TrFailed);
     }
 
+    #[test]
+    fn test_should_fail_with_expected_message() {
+        fn f() { fail!("index out of bounds: the len is 3"); }
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_fail: true,
+                expected_failure: Some("out of bounds"),
+                timeout: None
+            },
+            testfn: DynTestFn(|| f()),
+        };
+        let (p, ch) = stream();
+        let ch = SharedChan::new(ch);
+        run_test(false, false, desc, ch);
+        let (_, res, _, _) = p.recv();
+        assert_eq!(res, TrOk);
+    }
+
+    #[test]
+    fn test_should_fail_with_other_message() {
+        fn f() { fail!("boom"); }
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_fail: true,
+                expected_failure: Some("out of bounds"),
+                timeout: None
+            },
+            testfn: DynTestFn(|| f()),
+        };
+        let (p, ch) = stream();
+        let ch = SharedChan::new(ch);
+        run_test(false, false, desc, ch);
+        let (_, res, _, _) = p.recv();
+        assert_eq!(res, TrFailedMsg(~"failure message 'boom' did not contain 'out of bounds'"));
+    }
+
+    #[test]
+    fn test_that_runs_too_long_fails() {
+        fn f() { timer::sleep(3000); }
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_fail: false,
+                expected_failure: None,
+                timeout: Some(20)
+            },
+            testfn: DynTestFn(|| f()),
+        };
+        let (p, ch) = stream();
+        let ch = SharedChan::new(ch);
+        run_test(false, false, desc, ch);
+        let (_, res, elapsed_ns, _) = p.recv();
+        assert_eq!(res, TrFailedMsg(~"test timed out after 20ms"));
+        assert!(elapsed_ns < 1_000_000_000);
+    }
+
+    #[test]
+    fn captures_output_of_test_task() {
+        fn f() { println!("from {}", "the test"); fail!("boom") }
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_fail: false,
+                expected_failure: None,
+                timeout: None
+            },
+            testfn: DynTestFn(|| f()),
+        };
+        let (p, ch) = stream();
+        let ch = SharedChan::new(ch);
+        run_test(false, false, desc, ch);
+        let (_, res, _, stdout) = p.recv();
+        assert_eq!(res, TrFailedMsg(~"boom"));
+        let stdout = str::from_utf8(stdout);
+        assert!(stdout.starts_with("from the test\n"));
+        assert!(stdout.contains("failed at 'boom'"));
+    }
+
     #[test]
     fn first_free_arg_should_be_a_filter() {
         let args = ~[~"progname", ~"filter"];
@@ -1275,7 +2264,116 @@ mod tests {
             Some(Ok(o)) => o,
             _ => fail!("Malformed arg in first_free_arg_should_be_a_filter")
         };
-        assert!("filter" == opts.filter.clone().unwrap());
+        assert_eq!(opts.filters, ~[~"filter"]);
+    }
+
+    #[test]
+    fn parse_filters_skip_and_list() {
+        let args = ~[~"progname", ~"a", ~"--skip", ~"x", ~"b", ~"--exact", ~"--skip=y",
+                     ~"--list"];
+        let opts = match parse_opts(args) {
+            Some(Ok(o)) => o,
+            _ => fail!("Malformed arg in parse_filters_skip_and_list")
+        };
+        assert_eq!(opts.filters, ~[~"a", ~"b"]);
+        assert_eq!(opts.skip, ~[~"x", ~"y"]);
+        assert!(opts.filter_exact);
+        assert!(opts.list);
+    }
+
+    fn selected_names(args: ~[~str]) -> ~[~str] {
+        let opts = match parse_opts(~[~"progname"] + args) {
+            Some(Ok(o)) => o,
+            _ => fail!("Malformed arg in selected_names")
+        };
+        let names = ~[~"io::test_read", ~"io::test_write", ~"io::net::test_read",
+                      ~"sync::test_send", ~"sync::test_recv_timeout"];
+        let tests = do names.map |name| {
+            TestDescAndFn {
+                desc: TestDesc {
+                    name: DynTestName(name.clone()),
+                    ignore: false,
+                    should_fail: false,
+                    expected_failure: None,
+                    timeout: None
+                },
+                testfn: DynTestFn(|| {}),
+            }
+        };
+        filter_tests(&opts, tests).map(|t| t.desc.name.to_str())
+    }
+
+    #[test]
+    fn filter_tests_by_name() {
+        assert_eq!(selected_names(~[~"read", ~"send"]),
+                   ~[~"io::net::test_read", ~"io::test_read", ~"sync::test_send"]);
+        assert_eq!(selected_names(~[~"--exact", ~"read", ~"io::test_read"]),
+                   ~[~"io::test_read"]);
+        assert_eq!(selected_names(~[~"io", ~"--skip", ~"net", ~"--skip", ~"write"]),
+                   ~[~"io::test_read"]);
+        assert_eq!(selected_names(~[~"sync::test_{send,recv}*"]),
+                   ~[~"sync::test_recv_timeout", ~"sync::test_send"]);
+        assert_eq!(selected_names(~[~"--skip", ~"*_re[a-z]d"]),
+                   ~[~"io::test_write", ~"sync::test_recv_timeout", ~"sync::test_send"]);
+    }
+
+    #[test]
+    fn list_selected_tests_and_benches() {
+        fn bench(_: &mut BenchHarness) {}
+        let args = ~[~"progname", ~"--list", ~"--skip", ~"skipped"];
+        let opts = match parse_opts(args) {
+            Some(Ok(o)) => o,
+            _ => fail!("Malformed arg in list_selected_tests_and_benches")
+        };
+        let desc = |name: &'static str| TestDesc {
+            name: StaticTestName(name),
+            ignore: false,
+            should_fail: false,
+            expected_failure: None,
+            timeout: None
+        };
+        let tests = ~[TestDescAndFn { desc: desc("b::bench"), testfn: StaticBenchFn(bench) },
+                      TestDescAndFn { desc: desc("a::test"), testfn: DynTestFn(|| {}) },
+                      TestDescAndFn { desc: desc("skipped"), testfn: DynTestFn(|| {}) }];
+        let s = do io::with_str_writer |wr| {
+            list_tests(wr, &opts, tests);
+        };
+        assert_eq!(s, ~"a::test: test\nb::bench: bench\n");
+    }
+
+    #[test]
+    fn parse_format_and_report_time() {
+        let args = ~[~"progname", ~"--format", ~"junit", ~"--report-time"];
+        let opts = match parse_opts(args) {
+            Some(Ok(o)) => o,
+            _ => fail!("Malformed arg in parse_format_and_report_time")
+        };
+        assert!(opts.format == FmtJunit);
+        assert_eq!(opts.report_time, Some(1000));
+
+        let args = ~[~"progname", ~"--report-time=250"];
+        let opts = match parse_opts(args) {
+            Some(Ok(o)) => o,
+            _ => fail!("Malformed arg in parse_format_and_report_time")
+        };
+        assert!(opts.format == FmtPretty);
+        assert_eq!(opts.report_time, Some(250));
+
+        let args = ~[~"progname", ~"--format", ~"xml"];
+        assert!(parse_opts(args).unwrap().is_err());
+    }
+
+    #[test]
+    fn parse_test_timeout() {
+        let args = ~[~"progname", ~"--test-timeout", ~"30"];
+        let opts = match parse_opts(args) {
+            Some(Ok(o)) => o,
+            _ => fail!("Malformed arg in parse_test_timeout")
+        };
+        assert_eq!(opts.test_timeout, Some(30_000));
+
+        let args = ~[~"progname", ~"--test-timeout", ~"0"];
+        assert!(parse_opts(args).unwrap().is_err());
     }
 
     #[test]
@@ -1296,7 +2394,9 @@ mod tests {
         // unignored tests and flip the ignore flag on the rest to false
 
         let opts = TestOpts {
-            filter: None,
+            filters: ~[],
+            filter_exact: false,
+            skip: ~[],
             run_ignored: true,
             logfile: None,
             run_tests: true,
@@ -1304,7 +2404,13 @@ mod tests {
             ratchet_noise_percent: None,
             ratchet_metrics: None,
             save_metrics: None,
-            test_shard: None
+            bench_baseline: None,
+            test_shard: None,
+            format: FmtPretty,
+            report_time: None,
+            nocapture: false,
+            test_timeout: None,
+            list: false
         };
 
         let tests = ~[
@@ -1313,6 +2419,8 @@ mod tests {
                     name: StaticTestName("1"),
                     ignore: true,
                     should_fail: false,
+                    expected_failure: None,
+                    timeout: None
                 },
                 testfn: DynTestFn(|| {}),
             },
@@ -1320,7 +2428,9 @@ mod tests {
                 desc: TestDesc {
                     name: StaticTestName("2"),
                     ignore: false,
-                    should_fail: false
+                    should_fail: false,
+                    expected_failure: None,
+                    timeout: None
                 },
                 testfn: DynTestFn(|| {}),
             },
@@ -1335,7 +2445,9 @@ mod tests {
     #[test]
     pub fn sort_tests() {
         let opts = TestOpts {
-            filter: None,
+            filters: ~[],
+            filter_exact: false,
+            skip: ~[],
             run_ignored: false,
             logfile: None,
             run_tests: true,
@@ -1343,7 +2455,13 @@ mod tests {
             ratchet_noise_percent: None,
             ratchet_metrics: None,
             save_metrics: None,
-            test_shard: None
+            bench_baseline: None,
+            test_shard: None,
+            format: FmtPretty,
+            report_time: None,
+            nocapture: false,
+            test_timeout: None,
+            list: false
         };
 
         let names =
@@ -1362,7 +2480,9 @@ mod tests {
                     desc: TestDesc {
                         name: DynTestName((*name).clone()),
                         ignore: false,
-                        should_fail: false
+                        should_fail: false,
+                        expected_failure: None,
+                        timeout: None
                     },
                     testfn: DynTestFn(testfn),
                 };
@@ -1477,4 +2597,62 @@ mod tests {
         assert_eq!(*(m4.find(&~"runtime").unwrap()), Metric { value: 1100.0, noise: 2.0 });
         assert_eq!(*(m4.find(&~"throughput").unwrap()), Metric { value: 50.0, noise: 2.0 });
     }
+
+    #[test]
+    pub fn bench_baseline_compare() {
+        let fast = ~[100.0, 101.0, 99.0, 100.0, 102.0, 98.0, 100.0, 101.0];
+        let slow = ~[110.0, 111.0, 109.0, 110.0, 112.0, 108.0, 110.0, 111.0];
+        let jittery = ~[100.0, 99.0, 101.0, 100.0, 103.0, 97.0, 100.0, 100.0];
+
+        let mut old = BenchBaseline::new();
+        old.insert(~"faster", slow.clone());
+        old.insert(~"slower", fast.clone());
+        old.insert(~"same", fast.clone());
+        old.insert(~"gone", fast.clone());
+
+        let mut new = BenchBaseline::new();
+        new.insert(~"faster", fast.clone());
+        new.insert(~"slower", slow.clone());
+        new.insert(~"same", jittery.clone());
+        new.insert(~"new", fast.clone());
+
+        let diff = new.compare_to_old(&old, None);
+        assert_eq!(diff.len(), 5);
+        match *diff.find(&~"faster").unwrap() {
+            Improvement(pct) => assert!(pct > 9.0 && pct < 10.0),
+            ref other => fail!("expected an improvement, got {:?}", other)
+        }
+        assert_eq!(*diff.find(&~"slower").unwrap(), Regression(10.0));
+        assert_eq!(*diff.find(&~"same").unwrap(), LikelyNoise);
+        assert_eq!(*diff.find(&~"gone").unwrap(), MetricRemoved);
+        assert_eq!(*diff.find(&~"new").unwrap(), MetricAdded);
+
+        // A 10% move is within a 15% noise allowance.
+        let diff2 = new.compare_to_old(&old, Some(15.0));
+        assert_eq!(*diff2.find(&~"slower").unwrap(), LikelyNoise);
+    }
+
+    #[test]
+    pub fn bench_baseline_save_and_load() {
+        let dpth = TempDir::new("test-bench-baseline").expect("missing test for baseline");
+        let pth = dpth.path().join("baseline.json");
+
+        let mut b = BenchBaseline::new();
+        b.insert(~"bench_one", ~[10.0, 12.5, 11.0]);
+        b.insert(~"bench_two", ~[2000.0]);
+        b.save(&pth);
+
+        assert_eq!(BenchBaseline::load(&pth), Ok(b));
+    }
+
+    #[test]
+    pub fn bench_baseline_load_errors() {
+        let dpth = TempDir::new("test-bench-baseline").expect("missing test for baseline");
+        let pth = dpth.path().join("baseline.json");
+        assert!(BenchBaseline::load(&pth).is_err());
+
+        let f = io::file_writer(&pth, [io::Create, io::Truncate]).unwrap();
+        f.write_str("{\"bench_one\": \"fast\"}");
+        assert!(BenchBaseline::load(&pth).is_err());
+    }
 }
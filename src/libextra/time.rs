@@ -10,6 +10,8 @@
 
 #[allow(missing_doc)];
 
+use std::i32;
+use std::i64;
 use std::io;
 use std::num;
 use std::str;
@@ -57,6 +59,193 @@ impl Ord for Timespec {
     }
 }
 
+impl Add<Duration, Timespec> for Timespec {
+    fn add(&self, other: &Duration) -> Timespec {
+        self.checked_add(other).expect("Timespec + Duration overflowed")
+    }
+}
+
+impl Sub<Timespec, Duration> for Timespec {
+    /// The time elapsed between two instants, negative if `other` is later.
+    fn sub(&self, other: &Timespec) -> Duration {
+        let sec = self.sec.checked_sub(&other.sec).expect("Timespec - Timespec overflowed");
+        Duration::new(sec, 0) + Duration::nanoseconds((self.nsec - other.nsec) as i64)
+    }
+}
+
+impl Timespec {
+    /// Returns the instant `d` after this one, or `None` on overflow.
+    pub fn checked_add(&self, d: &Duration) -> Option<Timespec> {
+        let mut nsec = self.nsec + d.nsec;
+        let mut sec = self.sec.checked_add(&d.sec);
+        if nsec >= NSEC_PER_SEC {
+            nsec -= NSEC_PER_SEC;
+            sec = sec.and_then(|s| s.checked_add(&1));
+        }
+        sec.map(|s| Timespec::new(s, nsec))
+    }
+
+    /// Returns the instant `d` before this one, or `None` on overflow.
+    pub fn checked_sub(&self, d: &Duration) -> Option<Timespec> {
+        d.checked_neg().and_then(|d| self.checked_add(&d))
+    }
+}
+
+/**
+ * A span of time in seconds and nanoseconds, which may be negative.
+ *
+ * Like `Timespec`, the nanoseconds are always in `[0, 1_000_000_000)`, so
+ * minus 1.2 seconds is `Duration { sec: -2, nsec: 800_000_000 }`.
+ */
+#[deriving(Clone, DeepClone, Eq, Ord, Encodable, Decodable)]
+pub struct Duration { sec: i64, nsec: i32 }
+
+static SEC_PER_MIN: i64 = 60;
+static SEC_PER_HOUR: i64 = 3600;
+static SEC_PER_DAY: i64 = 86400;
+static SEC_PER_WEEK: i64 = 604800;
+
+// Multiplies a count of some unit by the number of seconds in the unit.
+fn seconds_of(n: i64, unit: i64, what: &str) -> Duration {
+    match n.checked_mul(&unit) {
+        Some(sec) => Duration::new(sec, 0),
+        None => fail!("Duration::{} out of bounds", what)
+    }
+}
+
+impl Duration {
+    pub fn new(sec: i64, nsec: i32) -> Duration {
+        assert!(nsec >= 0 && nsec < NSEC_PER_SEC);
+        Duration { sec: sec, nsec: nsec }
+    }
+
+    /// The empty duration.
+    pub fn zero() -> Duration { Duration::new(0, 0) }
+
+    /// Makes a duration of `n` weeks. Fails on overflow.
+    pub fn weeks(n: i64) -> Duration { seconds_of(n, SEC_PER_WEEK, "weeks") }
+
+    /// Makes a duration of `n` days. Fails on overflow.
+    pub fn days(n: i64) -> Duration { seconds_of(n, SEC_PER_DAY, "days") }
+
+    /// Makes a duration of `n` hours. Fails on overflow.
+    pub fn hours(n: i64) -> Duration { seconds_of(n, SEC_PER_HOUR, "hours") }
+
+    /// Makes a duration of `n` minutes. Fails on overflow.
+    pub fn minutes(n: i64) -> Duration { seconds_of(n, SEC_PER_MIN, "minutes") }
+
+    /// Makes a duration of `n` seconds.
+    pub fn seconds(n: i64) -> Duration { Duration::new(n, 0) }
+
+    /// Makes a duration of `n` milliseconds.
+    pub fn milliseconds(n: i64) -> Duration {
+        let (sec, ms) = n.div_mod_floor(&1_000);
+        Duration::new(sec, (ms * 1_000_000) as i32)
+    }
+
+    /// Makes a duration of `n` microseconds.
+    pub fn microseconds(n: i64) -> Duration {
+        let (sec, us) = n.div_mod_floor(&1_000_000);
+        Duration::new(sec, (us * 1_000) as i32)
+    }
+
+    /// Makes a duration of `n` nanoseconds.
+    pub fn nanoseconds(n: i64) -> Duration {
+        let (sec, ns) = n.div_mod_floor(&(NSEC_PER_SEC as i64));
+        Duration::new(sec, ns as i32)
+    }
+
+    /// The number of whole weeks, rounded towards zero.
+    pub fn num_weeks(&self) -> i64 { self.num_seconds() / SEC_PER_WEEK }
+
+    /// The number of whole days, rounded towards zero.
+    pub fn num_days(&self) -> i64 { self.num_seconds() / SEC_PER_DAY }
+
+    /// The number of whole hours, rounded towards zero.
+    pub fn num_hours(&self) -> i64 { self.num_seconds() / SEC_PER_HOUR }
+
+    /// The number of whole minutes, rounded towards zero.
+    pub fn num_minutes(&self) -> i64 { self.num_seconds() / SEC_PER_MIN }
+
+    /// The number of whole seconds, rounded towards zero.
+    pub fn num_seconds(&self) -> i64 {
+        if self.sec < 0 && self.nsec > 0 { self.sec + 1 } else { self.sec }
+    }
+
+    /// The number of whole milliseconds, rounded towards zero, or `None`
+    /// if it does not fit in an i64.
+    pub fn num_milliseconds(&self) -> Option<i64> {
+        self.num_units(1_000_000)
+    }
+
+    /// The number of nanoseconds, or `None` if it does not fit in an i64.
+    pub fn num_nanoseconds(&self) -> Option<i64> {
+        self.num_units(1)
+    }
+
+    fn num_units(&self, nsec_per_unit: i64) -> Option<i64> {
+        let units_per_sec = NSEC_PER_SEC as i64 / nsec_per_unit;
+        // Split off the last second of a negative duration, so that the
+        // fractional part rounds towards zero.
+        let (sec, nsec) = if self.sec < 0 && self.nsec > 0 {
+            (self.sec + 1, self.nsec as i64 - NSEC_PER_SEC as i64)
+        } else {
+            (self.sec, self.nsec as i64)
+        };
+        sec.checked_mul(&units_per_sec).and_then(|n| {
+            n.checked_add(&(nsec / nsec_per_unit))
+        })
+    }
+
+    /// Adds two durations, returning `None` on overflow.
+    pub fn checked_add(&self, other: &Duration) -> Option<Duration> {
+        let mut nsec = self.nsec + other.nsec;
+        let mut sec = self.sec.checked_add(&other.sec);
+        if nsec >= NSEC_PER_SEC {
+            nsec -= NSEC_PER_SEC;
+            sec = sec.and_then(|s| s.checked_add(&1));
+        }
+        sec.map(|s| Duration::new(s, nsec))
+    }
+
+    /// Subtracts two durations, returning `None` on overflow.
+    pub fn checked_sub(&self, other: &Duration) -> Option<Duration> {
+        other.checked_neg().and_then(|d| self.checked_add(&d))
+    }
+
+    /// Negates the duration, returning `None` on overflow.
+    pub fn checked_neg(&self) -> Option<Duration> {
+        if self.nsec == 0 {
+            if self.sec == i64::min_value {
+                None
+            } else {
+                Some(Duration::new(-self.sec, 0))
+            }
+        } else {
+            // -(s + n) = -(s + 1) + (1 - n), and -(s + 1) cannot overflow
+            Some(Duration::new(-self.sec - 1, NSEC_PER_SEC - self.nsec))
+        }
+    }
+}
+
+impl Neg<Duration> for Duration {
+    fn neg(&self) -> Duration {
+        self.checked_neg().expect("Duration negation overflowed")
+    }
+}
+
+impl Add<Duration, Duration> for Duration {
+    fn add(&self, other: &Duration) -> Duration {
+        self.checked_add(other).expect("Duration + Duration overflowed")
+    }
+}
+
+impl Sub<Duration, Duration> for Duration {
+    fn sub(&self, other: &Duration) -> Duration {
+        self.checked_sub(other).expect("Duration - Duration overflowed")
+    }
+}
+
 /**
  * Returns the current time as a `timespec` containing the seconds and
  * nanoseconds since 1970-01-01T00:00:00Z.
@@ -174,6 +363,55 @@ pub fn now() -> Tm {
     at(get_time())
 }
 
+/// Returns true if `year` is a leap year in the proleptic Gregorian calendar.
+pub fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Returns the number of days in a month (`month` in `[0, 11]`, as in
+/// `tm_mon`) of the proleptic Gregorian calendar.
+pub fn days_in_month(year: i64, month: i32) -> i32 {
+    match month {
+        1 => if is_leap_year(year) { 29 } else { 28 },
+        3 | 5 | 8 | 10 => 30,
+        0 | 2 | 4 | 6 | 7 | 9 | 11 => 31,
+        _ => fail!("invalid month: {}", month)
+    }
+}
+
+// Days since 1970-01-01 of a date in the proleptic Gregorian calendar, with
+// `month` in [1, 12]. The calendar repeats every 400 years (146097 days);
+// counting years from March puts the leap day at the end of the year.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_floor(&400);
+    let yoe = y - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// The inverse of `days_from_civil`. Returns (year, month in [1, 12], day).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_floor(&146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+// Bounds on the years and day counts fed to the two functions above, far
+// beyond what fits in `tm_year` but small enough to keep them from
+// overflowing.
+static MAX_YEARS: i64 = 1 << 34;
+static MAX_DAYS: i64 = 1 << 40;
+
 
 impl Tm {
     /// Convert time to the seconds from January 1, 1970
@@ -253,6 +491,120 @@ impl Tm {
             s + format!("{}{:02d}:{:02d}", sign, h as int, m as int)
         }
     }
+
+    /**
+     * Returns the same time with every field brought into its usual range,
+     * carrying overflow from `tm_nsec` up to `tm_year`, and with `tm_wday`
+     * and `tm_yday` recomputed from the date. For example the 32nd of
+     * December 2012 becomes the 1st of January 2013.
+     *
+     * This is pure date arithmetic in the proleptic Gregorian calendar, so it
+     * neither consults nor changes the process's timezone: `tm_gmtoff`,
+     * `tm_isdst` and `tm_zone` are kept as they are. A leap second
+     * (`tm_sec == 60`) is carried into the next minute.
+     *
+     * Returns `None` if the year does not fit in `tm_year`.
+     */
+    pub fn normalize(&self) -> Option<Tm> {
+        self.with_fields(1900 + self.tm_year as i64, self.tm_mon as i64, self.tm_mday as i64,
+                         self.sec_of_day(), self.tm_nsec as i64)
+    }
+
+    /**
+     * Returns the same time of day `days` days later (or earlier, if `days`
+     * is negative), normalized as by `normalize`. Returns `None` if the year
+     * does not fit in `tm_year`.
+     */
+    pub fn add_days(&self, days: i64) -> Option<Tm> {
+        if days < -MAX_DAYS || days > MAX_DAYS {
+            return None;
+        }
+        self.with_fields(1900 + self.tm_year as i64, self.tm_mon as i64,
+                         self.tm_mday as i64 + days, self.sec_of_day(), self.tm_nsec as i64)
+    }
+
+    /**
+     * Returns the same day of the month and time of day `months` months
+     * later (or earlier), normalized as by `normalize`. If the day does not
+     * exist in the resulting month the last day of that month is used, so
+     * one month after the 31st of January is the 28th or 29th of February.
+     * Returns `None` if the year does not fit in `tm_year`.
+     */
+    pub fn add_months(&self, months: i64) -> Option<Tm> {
+        let tm = match self.normalize() {
+            Some(tm) => tm,
+            None => return None
+        };
+        let total = match (tm.tm_year as i64 * 12 + tm.tm_mon as i64).checked_add(&months) {
+            Some(total) => total,
+            None => return None
+        };
+        let (year, mon) = total.div_mod_floor(&12);
+        let year = year + 1900;
+        if year < -MAX_YEARS || year > MAX_YEARS {
+            return None;
+        }
+        let mday = num::min(tm.tm_mday, days_in_month(year, mon as i32));
+        tm.with_fields(year, mon, mday as i64, tm.sec_of_day(), tm.tm_nsec as i64)
+    }
+
+    /**
+     * Returns the wall-clock time `d` later (or earlier, if `d` is
+     * negative), normalized as by `normalize`. Days are always 86400
+     * seconds long; no timezone transitions are taken into account. Returns
+     * `None` if the year does not fit in `tm_year`.
+     */
+    pub fn add_duration(&self, d: &Duration) -> Option<Tm> {
+        match self.sec_of_day().checked_add(&d.sec) {
+            Some(sec) => self.with_fields(1900 + self.tm_year as i64, self.tm_mon as i64,
+                                          self.tm_mday as i64, sec,
+                                          self.tm_nsec as i64 + d.nsec as i64),
+            None => None
+        }
+    }
+
+    // Seconds since midnight, not necessarily in [0, 86400).
+    fn sec_of_day(&self) -> i64 {
+        self.tm_hour as i64 * SEC_PER_HOUR + self.tm_min as i64 * SEC_PER_MIN + self.tm_sec as i64
+    }
+
+    // Builds a normalized copy of this Tm from fields that may be out of
+    // range. `mon` counts from 0, `mday` from 1 and `sec` from midnight.
+    fn with_fields(&self, year: i64, mon: i64, mday: i64, sec: i64, nsec: i64) -> Option<Tm> {
+        let (sec_carry, nsec) = nsec.div_mod_floor(&(NSEC_PER_SEC as i64));
+        let sec = match sec.checked_add(&sec_carry) {
+            Some(sec) => sec,
+            None => return None
+        };
+        let (day_carry, sec) = sec.div_mod_floor(&SEC_PER_DAY);
+        let (year_carry, mon) = mon.div_mod_floor(&12);
+        let year = year + year_carry;
+        if year < -MAX_YEARS || year > MAX_YEARS ||
+           mday < -MAX_DAYS || mday > MAX_DAYS ||
+           day_carry < -MAX_DAYS || day_carry > MAX_DAYS {
+            return None;
+        }
+
+        let days = days_from_civil(year, mon + 1, 1) + mday - 1 + day_carry;
+        let (year, month, day) = civil_from_days(days);
+        let tm_year = year - 1900;
+        if tm_year < i32::min_value as i64 || tm_year > i32::max_value as i64 {
+            return None;
+        }
+
+        let mut tm = self.clone();
+        tm.tm_nsec = nsec as i32;
+        tm.tm_sec = (sec % SEC_PER_MIN) as i32;
+        tm.tm_min = (sec / SEC_PER_MIN % 60) as i32;
+        tm.tm_hour = (sec / SEC_PER_HOUR) as i32;
+        tm.tm_mday = day as i32;
+        tm.tm_mon = (month - 1) as i32;
+        tm.tm_year = tm_year as i32;
+        // 1970-01-01 was a Thursday
+        tm.tm_wday = (days + 4).mod_floor(&7) as i32;
+        tm.tm_yday = (days - days_from_civil(year, 1, 1)) as i32;
+        Some(tm)
+    }
 }
 
 /// Parses the time from the string according to the format string.
@@ -943,11 +1295,627 @@ pub fn strftime(format: &str, tm: &Tm) -> ~str {
     buf
 }
 
+macro_rules! try_tz(
+    ($e:expr) => (match $e { Ok(v) => v, Err(e) => return Err(e) })
+)
+
+/// The directory time zones are loaded from by `TimeZone::load`
+pub static ZONEINFO_DIR: &'static str = "/usr/share/zoneinfo";
+
+// A local time type: an offset from UTC, whether it is daylight saving time,
+// and its abbreviation, such as "EST".
+#[deriving(Clone, Eq)]
+struct LocalTimeType {
+    utoff: i32,
+    isdst: bool,
+    abbr: ~str
+}
+
+// The day of the year a POSIX TZ rule switches on
+#[deriving(Clone, Eq)]
+enum RuleDay {
+    // Jn: day n in [1, 365], never counting February 29
+    JulianNoLeap(i64),
+    // n: day n in [0, 365], counting February 29
+    JulianLeap(i64),
+    // Mm.w.d: day d (0 is Sunday) of week w (5 is the last) of month m
+    MonthWeekDay(i64, i64, i64)
+}
+
+// A daylight saving time rule: DST starts on `start` at `start_time`
+// seconds after midnight standard time, and ends on `end` at `end_time`
+// seconds after midnight daylight saving time.
+#[deriving(Clone, Eq)]
+struct DstRule {
+    dst: LocalTimeType,
+    start: RuleDay,
+    start_time: i64,
+    end: RuleDay,
+    end_time: i64
+}
+
+// A POSIX TZ string such as "EST5EDT,M3.2.0,M11.1.0", which a TZif file
+// uses for the times after its last transition
+#[deriving(Clone, Eq)]
+struct PosixTz {
+    std: LocalTimeType,
+    dst: Option<DstRule>
+}
+
+/**
+ * A time zone from the IANA time zone database, such as "America/New_York".
+ *
+ * Unlike `at` and `Tm::to_local`, a `TimeZone` does not depend on the
+ * process-global `TZ` setting, so any number of zones can be used at once
+ * from any task. Leap seconds are not taken into account.
+ */
+#[deriving(Clone, Eq)]
+pub struct TimeZone {
+    priv name: ~str,
+    // Instants at which the local time type changes, in ascending order
+    priv transitions: ~[i64],
+    // For each transition, the index into `types` in effect after it
+    priv transition_types: ~[uint],
+    priv types: ~[LocalTimeType],
+    priv rule: Option<PosixTz>
+}
+
+/// The result of looking up a wall-clock time in a `TimeZone`
+#[deriving(Clone, Eq)]
+pub enum LocalResult {
+    /// The wall-clock time occurs exactly once.
+    Unique(Timespec),
+    /// The wall-clock time occurs twice, because the clocks were turned back
+    /// around it. The earlier instant comes first.
+    Ambiguous(Timespec, Timespec),
+    /// The wall-clock time never occurs, because the clocks were turned
+    /// forward over it.
+    Skipped
+}
+
+impl TimeZone {
+    /// Returns the UTC time zone.
+    pub fn utc() -> TimeZone {
+        TimeZone {
+            name: ~"UTC",
+            transitions: ~[],
+            transition_types: ~[],
+            types: ~[LocalTimeType { utoff: 0, isdst: false, abbr: ~"UTC" }],
+            rule: None
+        }
+    }
+
+    /**
+     * Loads a time zone, such as "Europe/Paris", from the system's time zone
+     * database in `ZONEINFO_DIR`.
+     */
+    pub fn load(name: &str) -> Result<TimeZone, ~str> {
+        TimeZone::load_from(&Path::new(ZONEINFO_DIR), name)
+    }
+
+    /**
+     * Loads a time zone from a directory laid out like the system's time
+     * zone database.
+     */
+    pub fn load_from(dir: &Path, name: &str) -> Result<TimeZone, ~str> {
+        if name.is_empty() || name.starts_with("/") ||
+           name.split_iter('/').any(|c| c == "." || c == "..") {
+            return Err(format!("invalid time zone name: {}", name));
+        }
+        match io::read_whole_file(&dir.join(name)) {
+            Ok(data) => TimeZone::from_tzif(name, data),
+            Err(e) => Err(format!("{}: {}", name, e))
+        }
+    }
+
+    /**
+     * Parses a time zone from the contents of a TZif file, as described in
+     * RFC 8536. Versions 1 to 4 are supported.
+     */
+    pub fn from_tzif(name: &str, data: &[u8]) -> Result<TimeZone, ~str> {
+        let mut rdr = TzifReader { data: data, pos: 0 };
+        let header = try_tz!(rdr.header());
+        if header.version == 0 {
+            return TimeZone::from_tzif_block(name, &mut rdr, &header, 4);
+        }
+
+        // Skip the version 1 data block, which only has 32 bit times.
+        try_tz!(rdr.bytes(header.block_len(4)));
+        let header = try_tz!(rdr.header());
+        let mut tz = try_tz!(TimeZone::from_tzif_block(name, &mut rdr, &header, 8));
+
+        // The footer holds the rule for the times after the last transition.
+        if try_tz!(rdr.byte()) != '\n' as u8 {
+            return Err(~"missing TZif footer");
+        }
+        let start = rdr.pos;
+        while try_tz!(rdr.byte()) != '\n' as u8 {}
+        let footer = data.slice(start, rdr.pos - 1);
+        if !footer.is_empty() {
+            match str::from_utf8_opt(footer).and_then(|s| parse_posix_tz(s.as_slice())) {
+                Some(rule) => tz.rule = Some(rule),
+                None => return Err(~"invalid TZ string in TZif footer")
+            }
+        }
+        Ok(tz)
+    }
+
+    fn from_tzif_block(name: &str, rdr: &mut TzifReader, header: &TzifHeader,
+                       time_size: uint) -> Result<TimeZone, ~str> {
+        if header.typecnt == 0 || header.charcnt == 0 {
+            return Err(~"TZif data has no local time types");
+        }
+
+        let mut transitions = vec::with_capacity(header.timecnt);
+        for _ in range(0, header.timecnt) {
+            let t = try_tz!(rdr.int(time_size));
+            if !transitions.is_empty() && t <= *transitions.last() {
+                return Err(~"TZif transition times are not in ascending order");
+            }
+            transitions.push(t);
+        }
+        let mut transition_types = vec::with_capacity(header.timecnt);
+        for _ in range(0, header.timecnt) {
+            let idx = try_tz!(rdr.byte()) as uint;
+            if idx >= header.typecnt {
+                return Err(~"TZif transition has an invalid local time type");
+            }
+            transition_types.push(idx);
+        }
+        let mut raw_types = vec::with_capacity(header.typecnt);
+        for _ in range(0, header.typecnt) {
+            let utoff = try_tz!(rdr.int(4)) as i32;
+            let isdst = try_tz!(rdr.byte());
+            let desigidx = try_tz!(rdr.byte()) as uint;
+            if utoff == i32::min_value || isdst > 1 || desigidx >= header.charcnt {
+                return Err(~"TZif data has an invalid local time type");
+            }
+            raw_types.push((utoff, isdst == 1, desigidx));
+        }
+        let chars = try_tz!(rdr.bytes(header.charcnt));
+        let mut types = vec::with_capacity(header.typecnt);
+        for &(utoff, isdst, desigidx) in raw_types.iter() {
+            let abbr = chars.slice_from(desigidx);
+            let abbr = match abbr.iter().position(|&b| b == 0) {
+                Some(len) => abbr.slice_to(len),
+                None => return Err(~"TZif time zone abbreviation is not terminated")
+            };
+            let abbr = match str::from_utf8_opt(abbr) {
+                Some(abbr) => abbr,
+                None => return Err(~"TZif time zone abbreviation is not valid UTF-8")
+            };
+            types.push(LocalTimeType { utoff: utoff, isdst: isdst, abbr: abbr });
+        }
+
+        // Leap second records and the standard/wall and UT/local indicators
+        // are not needed for converting times.
+        try_tz!(rdr.bytes(header.leapcnt * (time_size + 4) + header.isstdcnt + header.isutcnt));
+
+        Ok(TimeZone {
+            name: name.to_owned(),
+            transitions: transitions,
+            transition_types: transition_types,
+            types: types,
+            rule: None
+        })
+    }
+
+    /// Returns the name the time zone was loaded with.
+    pub fn name<'a>(&'a self) -> &'a str {
+        self.name.as_slice()
+    }
+
+    // Returns the local time type in effect at `sec` seconds after the epoch.
+    fn type_at<'a>(&'a self, sec: i64) -> &'a LocalTimeType {
+        if self.transitions.is_empty() || sec >= *self.transitions.last() {
+            match self.rule {
+                Some(ref rule) => return rule.type_at(sec),
+                None => {}
+            }
+        }
+        if self.transitions.is_empty() || sec < self.transitions[0] {
+            return &self.types[0];
+        }
+        // The last transition at or before `sec`
+        let mut lo = 0;
+        let mut hi = self.transitions.len();
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if self.transitions[mid] <= sec { lo = mid; } else { hi = mid; }
+        }
+        &self.types[self.transition_types[lo]]
+    }
+
+    /**
+     * Returns the time in this time zone. `tm_gmtoff`, `tm_isdst` and
+     * `tm_zone` describe the local time type in effect, so `%Z` in
+     * `strftime` gives its abbreviation.
+     *
+     * Fails if the year does not fit in `tm_year`.
+     */
+    pub fn to_local(&self, clock: Timespec) -> Tm {
+        let ty = self.type_at(clock.sec);
+        let mut tm = empty_tm();
+        tm.tm_gmtoff = ty.utoff;
+        tm.tm_isdst = ty.isdst as i32;
+        tm.tm_zone = ty.abbr.clone();
+        let tm = clock.sec.checked_add(&(ty.utoff as i64)).and_then(|sec| {
+            tm.with_fields(1970, 0, 1, sec, clock.nsec as i64)
+        });
+        match tm {
+            Some(tm) => tm,
+            None => fail!("time out of range: {:?}", clock)
+        }
+    }
+
+    /**
+     * Finds the instants at which the wall clocks in this time zone show the
+     * date and time in `tm`. Only the date and time fields of `tm` are used;
+     * they need not be normalized.
+     */
+    pub fn resolve_local(&self, tm: &Tm) -> LocalResult {
+        let (wall, nsec) = match local_seconds(tm) {
+            Some(local) => local,
+            None => return Skipped
+        };
+
+        // The offset in effect at an instant is within a day of the wall
+        // time, and so is one of the offsets seen around it.
+        let mut found: ~[i64] = ~[];
+        for i in range(-2, 3) {
+            let utoff = self.type_at(wall + i * SEC_PER_DAY).utoff as i64;
+            let sec = wall - utoff;
+            if self.type_at(sec).utoff as i64 == utoff && !found.contains(&sec) {
+                let pos = found.iter().position(|&t| t > sec).unwrap_or(found.len());
+                found.insert(pos, sec);
+            }
+        }
+
+        match found.len() {
+            0 => Skipped,
+            1 => Unique(Timespec::new(found[0], nsec)),
+            n => Ambiguous(Timespec::new(found[0], nsec), Timespec::new(found[n - 1], nsec))
+        }
+    }
+
+    /**
+     * Returns the instant at which the wall clocks in this time zone show
+     * the date and time in `tm`, resolving the edge cases like `mktime`
+     * does:
+     *
+     * * When the time occurs twice, a positive `tm_isdst` picks the daylight
+     *   saving time instant and zero picks the standard time one; a negative
+     *   `tm_isdst` picks the earlier instant.
+     * * When the time is skipped, it is read with the offset in effect
+     *   before the clocks were turned forward, so half past two in a gap
+     *   from two to three o'clock becomes half past three.
+     *
+     * Fails if the time is too far out of range to be represented.
+     */
+    pub fn from_local(&self, tm: &Tm) -> Timespec {
+        match self.resolve_local(tm) {
+            Unique(t) => t,
+            Ambiguous(a, b) => {
+                let want_dst = tm.tm_isdst > 0;
+                if tm.tm_isdst >= 0 && self.type_at(a.sec).isdst != want_dst &&
+                   self.type_at(b.sec).isdst == want_dst {
+                    b
+                } else {
+                    a
+                }
+            }
+            Skipped => {
+                let (wall, nsec) = match local_seconds(tm) {
+                    Some(local) => local,
+                    None => fail!("time out of range")
+                };
+                let before = self.type_at(wall - SEC_PER_DAY).utoff as i64;
+                Timespec::new(wall - before, nsec)
+            }
+        }
+    }
+}
+
+// The date and time in `tm` as seconds since 1970-01-01 00:00 on the same
+// wall clock, and nanoseconds.
+fn local_seconds(tm: &Tm) -> Option<(i64, i32)> {
+    do tm.normalize().and_then |tm| {
+        let days = days_from_civil(1900 + tm.tm_year as i64, tm.tm_mon as i64 + 1,
+                                   tm.tm_mday as i64);
+        Some((days * SEC_PER_DAY + tm.sec_of_day(), tm.tm_nsec))
+    }
+}
+
+// The day of `year` a POSIX TZ rule switches on, as days since 1970-01-01
+fn rule_day(year: i64, day: &RuleDay) -> i64 {
+    let jan1 = days_from_civil(year, 1, 1);
+    match *day {
+        JulianNoLeap(n) => jan1 + n - 1 + if is_leap_year(year) && n >= 60 { 1 } else { 0 },
+        JulianLeap(n) => jan1 + n,
+        MonthWeekDay(month, week, wday) => {
+            let first = days_from_civil(year, month, 1);
+            let first_wday = (first + 4).mod_floor(&7);
+            let mut day = first + (wday - first_wday).mod_floor(&7) + (week - 1) * 7;
+            let days = days_in_month(year, (month - 1) as i32) as i64;
+            while day >= first + days {
+                day -= 7;
+            }
+            day
+        }
+    }
+}
+
+impl PosixTz {
+    fn type_at<'a>(&'a self, sec: i64) -> &'a LocalTimeType {
+        let rule = match self.dst {
+            Some(ref rule) => rule,
+            None => return &self.std
+        };
+        let std_off = self.std.utoff as i64;
+        let (year, _, _) = civil_from_days((sec + std_off).div_floor(&SEC_PER_DAY));
+        let start = rule_day(year, &rule.start) * SEC_PER_DAY + rule.start_time - std_off;
+        let end = rule_day(year, &rule.end) * SEC_PER_DAY + rule.end_time -
+            rule.dst.utoff as i64;
+        let is_dst = if start < end {
+            start <= sec && sec < end
+        } else {
+            // Southern hemisphere: DST spans the new year
+            !(end <= sec && sec < start)
+        };
+        if is_dst { &rule.dst } else { &self.std }
+    }
+}
+
+// A parser for POSIX TZ strings, as extended by RFC 8536
+struct PosixTzParser<'a> {
+    s: &'a [u8],
+    pos: uint
+}
+
+impl<'a> PosixTzParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        if self.pos < self.s.len() { Some(self.s[self.pos]) } else { None }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c as u8) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // A zone abbreviation: three or more letters, or anything but '>'
+    // between angle brackets
+    fn abbr(&mut self) -> Option<~str> {
+        let quoted = self.eat('<');
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                Some(c) if quoted && c != '>' as u8 => self.pos += 1,
+                Some(c) if !quoted && ((c >= 'a' as u8 && c <= 'z' as u8) ||
+                                       (c >= 'A' as u8 && c <= 'Z' as u8)) => self.pos += 1,
+                _ => break
+            }
+        }
+        let abbr = self.s.slice(start, self.pos);
+        if abbr.len() < 3 || (quoted && !self.eat('>')) {
+            return None;
+        }
+        str::from_utf8_opt(abbr)
+    }
+
+    fn number(&mut self) -> Option<i64> {
+        let start = self.pos;
+        let mut n = 0i64;
+        loop {
+            match self.peek() {
+                Some(c) if c >= '0' as u8 && c <= '9' as u8 && self.pos - start < 9 => {
+                    n = n * 10 + (c - '0' as u8) as i64;
+                    self.pos += 1;
+                }
+                _ => break
+            }
+        }
+        if self.pos == start { None } else { Some(n) }
+    }
+
+    // [+-]hh[:mm[:ss]] in seconds, with hours up to `max_hours`
+    fn time(&mut self, max_hours: i64) -> Option<i64> {
+        let sign = if self.eat('-') { -1 } else { self.eat('+'); 1 };
+        let mut secs = match self.number() {
+            Some(h) if h <= max_hours => h * SEC_PER_HOUR,
+            _ => return None
+        };
+        for &scale in [SEC_PER_MIN, 1].iter() {
+            if !self.eat(':') {
+                break;
+            }
+            match self.number() {
+                Some(n) if n < 60 => secs += n * scale,
+                _ => return None
+            }
+        }
+        Some(sign * secs)
+    }
+
+    fn rule_day(&mut self) -> Option<RuleDay> {
+        if self.eat('J') {
+            match self.number() {
+                Some(n) if n >= 1 && n <= 365 => Some(JulianNoLeap(n)),
+                _ => None
+            }
+        } else if self.eat('M') {
+            let m = self.number();
+            if !self.eat('.') { return None; }
+            let w = self.number();
+            if !self.eat('.') { return None; }
+            let d = self.number();
+            match (m, w, d) {
+                (Some(m), Some(w), Some(d)) if m >= 1 && m <= 12 && w >= 1 && w <= 5 && d <= 6 =>
+                    Some(MonthWeekDay(m, w, d)),
+                _ => None
+            }
+        } else {
+            match self.number() {
+                Some(n) if n <= 365 => Some(JulianLeap(n)),
+                _ => None
+            }
+        }
+    }
+
+    // ,date[/time]
+    fn rule_part(&mut self) -> Option<(RuleDay, i64)> {
+        if !self.eat(',') {
+            return None;
+        }
+        let day = self.rule_day();
+        let time = if self.eat('/') { self.time(167) } else { Some(2 * SEC_PER_HOUR) };
+        match (day, time) {
+            (Some(day), Some(time)) => Some((day, time)),
+            _ => None
+        }
+    }
+}
+
+// Parses a POSIX TZ string such as "CET-1CEST,M3.5.0,M10.5.0/3"
+fn parse_posix_tz(s: &str) -> Option<PosixTz> {
+    let mut p = PosixTzParser { s: s.as_bytes(), pos: 0 };
+
+    // POSIX offsets count hours west of Greenwich.
+    let std = match (p.abbr(), p.time(24)) {
+        (Some(abbr), Some(off)) => LocalTimeType { utoff: -off as i32, isdst: false, abbr: abbr },
+        _ => return None
+    };
+    if p.peek().is_none() {
+        return Some(PosixTz { std: std, dst: None });
+    }
+
+    let dst_abbr = match p.abbr() {
+        Some(abbr) => abbr,
+        None => return None
+    };
+    let dst_off = match p.peek() {
+        Some(c) if c != ',' as u8 => match p.time(24) {
+            Some(off) => -off,
+            None => return None
+        },
+        _ => std.utoff as i64 + SEC_PER_HOUR
+    };
+    let dst = LocalTimeType { utoff: dst_off as i32, isdst: true, abbr: dst_abbr };
+
+    // Without a rule, the zone follows the United States' rules.
+    let (start, end) = if p.peek().is_none() {
+        ((MonthWeekDay(3, 2, 0), 2 * SEC_PER_HOUR), (MonthWeekDay(11, 1, 0), 2 * SEC_PER_HOUR))
+    } else {
+        match (p.rule_part(), p.rule_part()) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return None
+        }
+    };
+    if p.peek().is_some() {
+        return None;
+    }
+
+    let (start, start_time) = start;
+    let (end, end_time) = end;
+    Some(PosixTz {
+        std: std,
+        dst: Some(DstRule {
+            dst: dst,
+            start: start,
+            start_time: start_time,
+            end: end,
+            end_time: end_time
+        })
+    })
+}
+
+struct TzifHeader {
+    version: u8,
+    isutcnt: uint,
+    isstdcnt: uint,
+    leapcnt: uint,
+    timecnt: uint,
+    typecnt: uint,
+    charcnt: uint
+}
+
+impl TzifHeader {
+    // The length of the data block following the header
+    fn block_len(&self, time_size: uint) -> uint {
+        self.timecnt * (time_size + 1) + self.typecnt * 6 + self.charcnt +
+            self.leapcnt * (time_size + 4) + self.isstdcnt + self.isutcnt
+    }
+}
+
+struct TzifReader<'a> {
+    data: &'a [u8],
+    pos: uint
+}
+
+impl<'a> TzifReader<'a> {
+    fn bytes(&mut self, n: uint) -> Result<&'a [u8], ~str> {
+        if self.data.len() - self.pos < n {
+            return Err(~"truncated TZif data");
+        }
+        self.pos += n;
+        Ok(self.data.slice(self.pos - n, self.pos))
+    }
+
+    fn byte(&mut self) -> Result<u8, ~str> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    // A big-endian two's complement integer of `size` bytes
+    fn int(&mut self, size: uint) -> Result<i64, ~str> {
+        let bytes = try_tz!(self.bytes(size));
+        let mut n = if bytes[0] & 0x80 != 0 { -1i64 } else { 0 };
+        for &b in bytes.iter() {
+            n = (n << 8) | b as i64;
+        }
+        Ok(n)
+    }
+
+    fn header(&mut self) -> Result<TzifHeader, ~str> {
+        if try_tz!(self.bytes(4)) != bytes!("TZif") {
+            return Err(~"not a TZif file");
+        }
+        let version = match try_tz!(self.byte()) {
+            0 => 0,
+            v if v >= '2' as u8 && v <= '4' as u8 => v - '0' as u8,
+            _ => return Err(~"unsupported TZif version")
+        };
+        try_tz!(self.bytes(15));
+        let mut counts = [0u, ..6];
+        for count in counts.mut_iter() {
+            let n = try_tz!(self.int(4));
+            if n < 0 || n as uint > self.data.len() {
+                return Err(~"truncated TZif data");
+            }
+            *count = n as uint;
+        }
+        Ok(TzifHeader {
+            version: version,
+            isutcnt: counts[0],
+            isstdcnt: counts[1],
+            leapcnt: counts[2],
+            timecnt: counts[3],
+            typecnt: counts[4],
+            charcnt: counts[5]
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::{LocalTimeType, MonthWeekDay, parse_posix_tz};
 
     use std::f64;
+    use std::i32;
+    use std::i64;
     use std::os;
     use std::result::{Err, Ok};
 
@@ -1350,6 +2318,352 @@ mod tests {
         assert!(d.gt(c));
     }
 
+    fn utc_tm(year: i32, mon: i32, mday: i32, hour: i32, min: i32, sec: i32) -> Tm {
+        let mut tm = empty_tm();
+        tm.tm_year = year - 1900;
+        tm.tm_mon = mon;
+        tm.tm_mday = mday;
+        tm.tm_hour = hour;
+        tm.tm_min = min;
+        tm.tm_sec = sec;
+        tm.tm_zone = ~"UTC";
+        tm
+    }
+
+    fn ymd(tm: Option<Tm>) -> (i32, i32, i32) {
+        let tm = tm.unwrap();
+        (tm.tm_year + 1900, tm.tm_mon, tm.tm_mday)
+    }
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(Duration::weeks(1), Duration::days(7));
+        assert_eq!(Duration::days(1), Duration::hours(24));
+        assert_eq!(Duration::hours(1), Duration::minutes(60));
+        assert_eq!(Duration::minutes(1), Duration::seconds(60));
+        assert_eq!(Duration::seconds(1), Duration::milliseconds(1000));
+        assert_eq!(Duration::milliseconds(1), Duration::microseconds(1000));
+        assert_eq!(Duration::microseconds(1), Duration::nanoseconds(1000));
+        assert_eq!(Duration::milliseconds(-1), Duration::new(-1, 999_000_000));
+
+        let d = Duration::milliseconds(-1200);
+        assert_eq!(d.num_seconds(), -1);
+        assert_eq!(d.num_milliseconds(), Some(-1200));
+        assert_eq!(d.num_nanoseconds(), Some(-1_200_000_000));
+        assert_eq!(-d, Duration::milliseconds(1200));
+        assert_eq!(Duration::days(-9).num_weeks(), -1);
+        assert_eq!(Duration::hours(49).num_days(), 2);
+        assert_eq!(Duration::seconds(i64::max_value).num_milliseconds(), None);
+
+        assert_eq!(Duration::milliseconds(700) + Duration::milliseconds(800),
+                   Duration::milliseconds(1500));
+        assert_eq!(Duration::milliseconds(700) - Duration::milliseconds(800),
+                   Duration::milliseconds(-100));
+        assert!(Duration::milliseconds(-1) < Duration::zero());
+        assert!(Duration::zero() < Duration::nanoseconds(1));
+    }
+
+    #[test]
+    fn test_duration_overflow() {
+        let max = Duration::new(i64::max_value, 999_999_999);
+        let min = Duration::seconds(i64::min_value);
+        assert_eq!(max.checked_add(&Duration::nanoseconds(1)), None);
+        assert_eq!(min.checked_sub(&Duration::nanoseconds(1)), None);
+        assert_eq!(min.checked_neg(), None);
+        assert_eq!(max.checked_neg(), Some(Duration::new(i64::min_value, 1)));
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_duration_constructor_overflow() {
+        Duration::weeks(i64::max_value / 1000);
+    }
+
+    #[test]
+    fn test_timespec_arithmetic() {
+        let a = Timespec::new(1, 500_000_000);
+        let b = Timespec::new(2, 200_000_000);
+
+        assert_eq!(a + Duration::milliseconds(700), b);
+        assert_eq!(b - a, Duration::milliseconds(700));
+        assert_eq!(a - b, Duration::milliseconds(-700));
+        assert_eq!(b.checked_sub(&Duration::milliseconds(700)), Some(a));
+        assert_eq!(Timespec::new(i64::max_value, 0).checked_add(&Duration::seconds(1)), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let tm = utc_tm(2012, 11, 32, 0, 0, 0).normalize().unwrap();
+        assert_eq!((tm.tm_year, tm.tm_mon, tm.tm_mday), (113, 0, 1));
+        assert_eq!(tm.tm_wday, 2);
+        assert_eq!(tm.tm_yday, 0);
+
+        let tm = utc_tm(2013, 0, 1, 0, 0, -1).normalize().unwrap();
+        assert_eq!(tm.strftime("%Y-%m-%d %H:%M:%S"), ~"2012-12-31 23:59:59");
+        assert_eq!(tm.tm_wday, 1);
+        assert_eq!(tm.tm_yday, 365);
+        assert_eq!(tm.tm_zone, ~"UTC");
+
+        let tm = utc_tm(2013, 14, 0, 25, 61, 60).normalize().unwrap();
+        assert_eq!(tm.strftime("%Y-%m-%d %H:%M:%S"), ~"2014-03-01 02:02:00");
+
+        let mut tm = utc_tm(2013, 0, 1, 0, 0, 0);
+        tm.tm_year = i32::max_value;
+        tm.tm_mon = 12;
+        assert!(tm.normalize().is_none());
+    }
+
+    #[test]
+    fn test_add_days() {
+        assert_eq!(ymd(utc_tm(2012, 1, 28, 0, 0, 0).add_days(1)), (2012, 1, 29));
+        assert_eq!(ymd(utc_tm(2012, 1, 28, 0, 0, 0).add_days(2)), (2012, 2, 1));
+        assert_eq!(ymd(utc_tm(1900, 1, 28, 0, 0, 0).add_days(1)), (1900, 2, 1));
+        assert_eq!(ymd(utc_tm(2000, 1, 28, 0, 0, 0).add_days(1)), (2000, 1, 29));
+        assert_eq!(ymd(utc_tm(2013, 5, 15, 0, 0, 0).add_days(-365)), (2012, 5, 15));
+        assert_eq!(ymd(utc_tm(1970, 0, 1, 0, 0, 0).add_days(-1)), (1969, 11, 31));
+        assert_eq!(utc_tm(1970, 0, 1, 0, 0, 0).add_days(-1).unwrap().tm_wday, 3);
+        assert!(utc_tm(2013, 0, 1, 0, 0, 0).add_days(i64::max_value).is_none());
+        assert!(utc_tm(2013, 0, 1, 0, 0, 0).add_days(i64::min_value).is_none());
+    }
+
+    #[test]
+    fn test_add_months() {
+        assert_eq!(ymd(utc_tm(2013, 0, 31, 0, 0, 0).add_months(1)), (2013, 1, 28));
+        assert_eq!(ymd(utc_tm(2012, 0, 31, 0, 0, 0).add_months(1)), (2012, 1, 29));
+        assert_eq!(ymd(utc_tm(2013, 2, 31, 0, 0, 0).add_months(-1)), (2013, 1, 28));
+        assert_eq!(ymd(utc_tm(2013, 2, 31, 0, 0, 0).add_months(12)), (2014, 2, 31));
+        assert_eq!(ymd(utc_tm(2013, 2, 15, 0, 0, 0).add_months(-15)), (2011, 11, 15));
+        assert!(utc_tm(2013, 0, 1, 0, 0, 0).add_months(i64::max_value).is_none());
+    }
+
+    #[test]
+    fn test_add_duration() {
+        let mut tm = utc_tm(2013, 11, 31, 23, 59, 59);
+        tm.tm_nsec = 500_000_000;
+        let tm = tm.add_duration(&Duration::milliseconds(500)).unwrap();
+        assert_eq!(tm.strftime("%Y-%m-%d %H:%M:%S"), ~"2014-01-01 00:00:00");
+        assert_eq!(tm.tm_nsec, 0);
+
+        let tm = tm.add_duration(&Duration::weeks(-1)).unwrap();
+        assert_eq!(tm.strftime("%Y-%m-%d %H:%M:%S"), ~"2013-12-25 00:00:00");
+        assert_eq!(tm.tm_wday, 3);
+    }
+
+    #[test]
+    fn test_date_math_matches_gmtime() {
+        // Pure Rust arithmetic from the epoch agrees with the C library.
+        let epoch = at_utc(Timespec::new(0, 0));
+        let mut sec = -2_000_000_000i64;
+        while sec < 4_000_000_000 {
+            let expected = at_utc(Timespec::new(sec, 0));
+            assert_eq!(epoch.add_duration(&Duration::seconds(sec)), Some(expected));
+            sec += 12_345_679;
+        }
+    }
+
+    #[test]
+    fn test_leap_years() {
+        assert!(is_leap_year(2000));
+        assert!(is_leap_year(2012));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2013));
+        assert!(is_leap_year(-4));
+        assert_eq!(days_in_month(2012, 1), 29);
+        assert_eq!(days_in_month(2013, 1), 28);
+        assert_eq!(days_in_month(2013, 8), 30);
+        assert_eq!(days_in_month(2013, 11), 31);
+    }
+
+    // Builds a version 2 TZif file with the same data in both blocks.
+    fn tzif(transitions: &[(i64, u8)], types: &[(i32, bool, &str)], footer: &str) -> ~[u8] {
+        fn push_int(buf: &mut ~[u8], n: i64, size: uint) {
+            for i in range(0, size).invert() {
+                buf.push((n >> (8 * i)) as u8);
+            }
+        }
+
+        let mut chars = ~[];
+        let mut buf = ~[];
+        for &time_size in [4u, 8].iter() {
+            buf.push_all(bytes!("TZif2"));
+            buf.grow(15, &0u8);
+            for &n in [0, 0, 0, transitions.len(), types.len()].iter() {
+                push_int(&mut buf, n as i64, 4);
+            }
+            chars.clear();
+            for &(_, _, abbr) in types.iter() {
+                chars.push_all(abbr.as_bytes());
+                chars.push(0);
+            }
+            push_int(&mut buf, chars.len() as i64, 4);
+
+            for &(t, _) in transitions.iter() {
+                push_int(&mut buf, t, time_size);
+            }
+            for &(_, idx) in transitions.iter() {
+                buf.push(idx);
+            }
+            let mut desigidx = 0;
+            for &(utoff, isdst, abbr) in types.iter() {
+                push_int(&mut buf, utoff as i64, 4);
+                buf.push(isdst as u8);
+                buf.push(desigidx as u8);
+                desigidx += abbr.len() + 1;
+            }
+            buf.push_all(chars);
+        }
+        buf.push('\n' as u8);
+        buf.push_all(footer.as_bytes());
+        buf.push('\n' as u8);
+        buf
+    }
+
+    fn new_york() -> TimeZone {
+        let data = tzif([], [(-18000, false, "EST")], "EST5EDT,M3.2.0,M11.1.0");
+        TimeZone::from_tzif("America/New_York", data).unwrap()
+    }
+
+    #[test]
+    fn test_timezone_utc() {
+        let time = Timespec::new(1234567890, 54321);
+        assert_eq!(TimeZone::utc().to_local(time), at_utc(time));
+        assert_eq!(TimeZone::utc().from_local(&at_utc(time)), time);
+    }
+
+    #[test]
+    fn test_timezone_rule() {
+        let tz = new_york();
+        assert_eq!(tz.name(), "America/New_York");
+
+        // 2013-07-01 12:00 UTC
+        let summer = tz.to_local(Timespec::new(1372680000, 0));
+        assert_eq!(summer.strftime("%Y-%m-%d %H:%M %Z %z"), ~"2013-07-01 08:00 EDT -0400");
+        assert_eq!(summer.tm_isdst, 1);
+        assert_eq!(summer.tm_gmtoff, -14400);
+        assert_eq!(summer.tm_wday, 1);
+        assert_eq!(tz.from_local(&summer), Timespec::new(1372680000, 0));
+
+        // 2013-01-15 12:00 UTC
+        let winter = tz.to_local(Timespec::new(1358251200, 0));
+        assert_eq!(winter.strftime("%Y-%m-%d %H:%M %Z %z"), ~"2013-01-15 07:00 EST -0500");
+        assert_eq!(winter.tm_isdst, 0);
+
+        // 2040-07-01 12:00 UTC, past the range of 32 bit times
+        let later = tz.to_local(Timespec::new(2224756800, 0));
+        assert_eq!(later.strftime("%H:%M %Z"), ~"08:00 EDT");
+    }
+
+    #[test]
+    fn test_timezone_gap_and_overlap() {
+        let tz = new_york();
+        let mut tm = utc_tm(2013, 2, 10, 2, 30, 0);
+
+        // The clocks went from 02:00 EST to 03:00 EDT.
+        assert_eq!(tz.resolve_local(&tm), Skipped);
+        let t = tz.from_local(&tm);
+        assert_eq!(t, Timespec::new(1362900600, 0));
+        assert_eq!(tz.to_local(t).strftime("%H:%M %Z"), ~"03:30 EDT");
+
+        // The clocks went back from 02:00 EDT to 01:00 EST.
+        tm = utc_tm(2013, 10, 3, 1, 30, 0);
+        let first = Timespec::new(1383456600, 0);
+        let second = Timespec::new(1383460200, 0);
+        assert_eq!(tz.resolve_local(&tm), Ambiguous(first, second));
+        tm.tm_isdst = -1;
+        assert_eq!(tz.from_local(&tm), first);
+        tm.tm_isdst = 1;
+        assert_eq!(tz.from_local(&tm), first);
+        tm.tm_isdst = 0;
+        assert_eq!(tz.from_local(&tm), second);
+
+        tm = utc_tm(2013, 6, 1, 12, 0, 0);
+        tm.tm_nsec = 5;
+        assert_eq!(tz.resolve_local(&tm), Unique(Timespec::new(1372694400, 5)));
+    }
+
+    #[test]
+    fn test_timezone_southern_hemisphere() {
+        let data = tzif([], [(36000, false, "AEST")], "AEST-10AEDT,M10.1.0,M4.1.0/3");
+        let tz = TimeZone::from_tzif("Australia/Sydney", data).unwrap();
+
+        // 2013-01-15 and 2013-07-01 00:00 UTC
+        assert_eq!(tz.to_local(Timespec::new(1358208000, 0)).strftime("%H:%M %Z"), ~"11:00 AEDT");
+        assert_eq!(tz.to_local(Timespec::new(1372636800, 0)).strftime("%H:%M %Z"), ~"10:00 AEST");
+
+        let tm = utc_tm(2013, 3, 7, 2, 30, 0);
+        assert_eq!(tz.resolve_local(&tm), Ambiguous(Timespec::new(1365262200, 0),
+                                                    Timespec::new(1365265800, 0)));
+        assert_eq!(tz.resolve_local(&utc_tm(2013, 9, 6, 2, 30, 0)), Skipped);
+    }
+
+    #[test]
+    fn test_timezone_transitions() {
+        let data = tzif([(1000000, 1), (2000000, 0)],
+                        [(3600, false, "AAA"), (7200, true, "BBB")], "");
+        let tz = TimeZone::from_tzif("Test/Zone", data).unwrap();
+
+        assert_eq!(tz.to_local(Timespec::new(999999, 0)).tm_zone, ~"AAA");
+        assert_eq!(tz.to_local(Timespec::new(1000000, 0)).tm_zone, ~"BBB");
+        assert_eq!(tz.to_local(Timespec::new(1999999, 0)).tm_gmtoff, 7200);
+        assert_eq!(tz.to_local(Timespec::new(2000000, 0)).tm_zone, ~"AAA");
+        assert_eq!(tz.to_local(Timespec::new(-1000000000, 0)).tm_zone, ~"AAA");
+    }
+
+    #[test]
+    fn test_posix_tz() {
+        let tz = parse_posix_tz("<+0330>-3:30").unwrap();
+        assert_eq!(tz.std, LocalTimeType { utoff: 12600, isdst: false, abbr: ~"+0330" });
+        assert!(tz.dst.is_none());
+
+        let tz = parse_posix_tz("<-03>3<-02>,M3.5.0/-2,M10.5.0/-1").unwrap();
+        let rule = tz.dst.unwrap();
+        assert_eq!(rule.dst.utoff, -7200);
+        assert_eq!(rule.start, MonthWeekDay(3, 5, 0));
+        assert_eq!(rule.start_time, -7200);
+        assert_eq!(rule.end_time, -3600);
+
+        assert_eq!(parse_posix_tz("EST5EDT"), parse_posix_tz("EST5EDT4,M3.2.0/2,M11.1.0/02:00"));
+        assert_eq!(parse_posix_tz("JST-9").unwrap().std.utoff, 32400);
+        assert!(parse_posix_tz("").is_none());
+        assert!(parse_posix_tz("ES5").is_none());
+        assert!(parse_posix_tz("EST").is_none());
+        assert!(parse_posix_tz("EST5EDT,M3.2.0").is_none());
+        assert!(parse_posix_tz("EST5EDT,M13.2.0,M11.1.0").is_none());
+        assert!(parse_posix_tz("EST5EDT,M3.2.0,M11.1.0x").is_none());
+    }
+
+    #[test]
+    fn test_invalid_tzif() {
+        let data = tzif([(1000000, 1)], [(3600, false, "AAA"), (7200, true, "BBB")], "");
+        assert!(TimeZone::from_tzif("x", data.slice_to(data.len() - 10)).is_err());
+        assert!(TimeZone::from_tzif("x", bytes!("TZjf")).is_err());
+        assert!(TimeZone::from_tzif("x", []).is_err());
+
+        // A transition to a local time type that does not exist
+        let data = tzif([(1000000, 2)], [(3600, false, "AAA"), (7200, true, "BBB")], "");
+        assert!(TimeZone::from_tzif("x", data).is_err());
+
+        let data = tzif([], [(3600, false, "AAA")], "AAA");
+        assert!(TimeZone::from_tzif("x", data).is_err());
+    }
+
+    #[test]
+    fn test_load_timezone() {
+        assert!(TimeZone::load("../../etc/passwd").is_err());
+        assert!(TimeZone::load("/etc/passwd").is_err());
+        assert!(TimeZone::load("No/Such_Zone").is_err());
+
+        // The system's time zone database is not always installed.
+        if !os::path_exists(&Path::new(ZONEINFO_DIR).join("America/New_York")) {
+            return;
+        }
+        let tz = TimeZone::load("America/New_York").unwrap();
+        assert_eq!(tz.to_local(Timespec::new(1372680000, 0)).strftime("%H:%M %Z"), ~"08:00 EDT");
+        assert_eq!(tz.to_local(Timespec::new(1358251200, 0)).strftime("%H:%M %Z"), ~"07:00 EST");
+        // 1883-11-18 16:00 UTC, before standard time
+        assert_eq!(tz.to_local(Timespec::new(-2717654400, 0)).strftime("%H:%M %Z"), ~"11:03 LMT");
+    }
+
     #[test]
     fn run_tests() {
         // The tests race on tzset. So instead of having many independent
@@ -400,6 +400,7 @@ impl<'self, T> Iterator<&'self T> for TreeSetRevIterator<'self, T> {
 /// A implementation of the `Set` trait on top of the `TreeMap` container. The
 /// only requirement is that the type of the elements contained ascribes to the
 /// `TotalOrd` trait.
+#[deriving(Clone)]
 pub struct TreeSet<T> {
     priv map: TreeMap<T, ()>
 }
@@ -17,6 +17,8 @@ use std::cmp::Eq;
 use std::io::{Reader, ReaderUtil};
 use std::io;
 use std::hashmap::HashMap;
+use std::rt::io::net::ip::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::StrAsciiExt;
 use std::to_bytes;
 use std::uint;
 
@@ -39,6 +41,27 @@ pub struct UserInfo {
 
 pub type Query = ~[(~str, ~str)];
 
+/// The host of a URL, as found by `Url::parse_host`.
+#[deriving(Clone, Eq)]
+pub enum Host {
+    /// A registered name, such as `rust-lang.org`
+    Domain(~str),
+    /// An IPv4 address, such as `127.0.0.1`
+    Ipv4(IpAddr),
+    /// An IPv6 address, which URLs write in brackets: `[::1]`
+    Ipv6(IpAddr)
+}
+
+impl ToStr for Host {
+    fn to_str(&self) -> ~str {
+        match *self {
+            Domain(ref name) => name.clone(),
+            Ipv4(ref addr) => addr.to_str(),
+            Ipv6(ref addr) => format!("[{}]", addr.to_str())
+        }
+    }
+}
+
 impl Url {
     pub fn new(scheme: ~str,
                user: Option<UserInfo>,
@@ -58,6 +81,129 @@ impl Url {
             fragment: fragment,
         }
     }
+
+    /**
+     * Resolve a URL reference against this URL, as described in section 5.2
+     * of RFC 3986.
+     *
+     * # Arguments
+     *
+     * `relative` - a URL reference, which may be relative (such as
+     * `../a?b#c`) or a full url.
+     *
+     * # Returns
+     *
+     * the url `relative` refers to when found in a document at this url,
+     * or a parsing error.
+     */
+    pub fn join(&self, relative: &str) -> Result<Url, ~str> {
+        // a reference with a scheme of its own is a full url
+        if get_scheme(relative).is_ok() {
+            return match from_str(relative) {
+                Ok(url) => Ok(Url { path: remove_dot_segments(url.path), .. url }),
+                Err(e) => Err(e)
+            };
+        }
+
+        let (userinfo, host, port, rest) = match get_authority(relative) {
+            Ok(val) => val,
+            Err(e) => return Err(e),
+        };
+        let has_authority = relative.starts_with("//");
+        let (path, rest) = match get_path(rest, has_authority) {
+            Ok(val) => val,
+            Err(e) => return Err(e),
+        };
+        let has_query = rest.starts_with("?");
+        let (query, fragment) = match get_query_fragment(rest) {
+            Ok(val) => val,
+            Err(e) => return Err(e),
+        };
+
+        let mut url = self.clone();
+        if has_authority {
+            url.user = userinfo;
+            url.host = host;
+            url.port = port;
+            url.path = remove_dot_segments(path);
+            url.query = query;
+        } else if path.is_empty() {
+            if has_query {
+                url.query = query;
+            }
+        } else {
+            url.path = if path.starts_with("/") {
+                remove_dot_segments(path)
+            } else {
+                remove_dot_segments(merge_paths(self, path))
+            };
+            url.query = query;
+        }
+        url.fragment = fragment;
+        Ok(url)
+    }
+
+    /**
+     * Normalize a url so that equivalent urls compare equal, following
+     * section 6.2.2 of RFC 3986: the scheme and host are lowercased, dot
+     * segments are removed from the path, an empty path after a host
+     * becomes "/", and a port that is empty or the scheme's default is
+     * dropped.
+     */
+    pub fn normalize(&self) -> Url {
+        let scheme = self.scheme.to_ascii_lower();
+        let host = self.host.to_ascii_lower();
+
+        let port = match self.port {
+            Some(ref port) if port.is_empty() => None,
+            Some(ref port) if Some(port.as_slice()) == default_port(scheme) => None,
+            ref port => port.clone()
+        };
+
+        let path = if !host.is_empty() && self.path.is_empty() {
+            ~"/"
+        } else {
+            remove_dot_segments(self.path)
+        };
+
+        Url {
+            scheme: scheme,
+            user: self.user.clone(),
+            host: host,
+            port: port,
+            path: path,
+            query: self.query.clone(),
+            fragment: self.fragment.clone()
+        }
+    }
+
+    /**
+     * Parse the host of a url into a domain name or an IP address.
+     *
+     * # Returns
+     *
+     * `None` if the url has no host, or if a bracketed host is not a valid
+     * IPv6 address.
+     */
+    pub fn parse_host(&self) -> Option<Host> {
+        let host = self.host.as_slice();
+        if host.is_empty() {
+            return None;
+        }
+        if host.starts_with("[") && host.ends_with("]") {
+            let addr: Option<IpAddr> = FromStr::from_str(host.slice(1, host.len() - 1));
+            return match addr {
+                Some(addr @ Ipv6Addr(*)) => Some(Ipv6(addr)),
+                _ => None
+            };
+        }
+        let addr: Option<IpAddr> = FromStr::from_str(host);
+        match addr {
+            Some(addr @ Ipv4Addr(*)) => Some(Ipv4(addr)),
+            Some(addr @ Ipv6Addr(*)) => Some(Ipv6(addr)),
+            None => Some(Domain(host.to_owned()))
+        }
+    }
 }
 
 impl UserInfo {
@@ -403,6 +549,7 @@ fn get_authority(rawurl: &str) ->
         Ip6Port, // either in ipv6 host or port
         Ip6Host, // are in an ipv6 host
         InHost, // are in a host - may be ipv6, but don't know yet
+        Ip6Literal, // just after a bracketed ipv6 host
         InPort // are in port
     }
 
@@ -418,9 +565,34 @@ fn get_authority(rawurl: &str) ->
     let mut pos = 0;
     let mut begin = 2;
     let mut end = len;
+    let mut skip = 2; // ignore the leading //
 
     for (i,c) in rawurl.iter().enumerate() {
-        if i < 2 { continue; } // ignore the leading //
+        if i < skip { continue; }
+
+        // a bracketed ipv6 address makes up the whole host
+        if c == '[' && i == begin {
+            let close = match rawurl.slice_from(i).find(']') {
+                Some(j) => i + j,
+                None => return Err(~"Unterminated IPv6 address.")
+            };
+            let addr: Option<IpAddr> = FromStr::from_str(rawurl.slice(i+1, close));
+            match addr {
+                Some(Ipv6Addr(*)) => (),
+                _ => return Err(~"Invalid IPv6 address.")
+            }
+            host = rawurl.slice(i, close+1).to_owned();
+            skip = close+1;
+            st = Ip6Literal;
+            continue;
+        }
+
+        match st {
+          Ip6Literal if c != ':' && c != '@' && c != '?' && c != '#' && c != '/' => {
+            return Err(~"Illegal characters after IPv6 address.");
+          }
+          _ => ()
+        }
 
         // deal with input class first
         match c {
@@ -483,6 +655,10 @@ fn get_authority(rawurl: &str) ->
                     st = InPort;
                 }
               }
+              Ip6Literal => {
+                pos = i;
+                st = InPort;
+              }
               _ => {
                 return Err(~"Invalid ':' in authority.");
               }
@@ -535,6 +711,7 @@ fn get_authority(rawurl: &str) ->
       Ip6Host | InHost => {
         host = rawurl.slice(begin, end).to_owned();
       }
+      Ip6Literal => (),
       InPort => {
         if input != Digit {
             return Err(~"Non-digit characters in port.");
@@ -579,6 +756,67 @@ fn get_path(rawurl: &str, authority: bool) ->
                     rawurl.slice(end, len).to_owned()));
 }
 
+// removes "." and ".." segments from a path (RFC 3986 section 5.2.4)
+fn remove_dot_segments(path: &str) -> ~str {
+    // drops the last segment of the output, along with its leading '/'
+    fn pop_segment(output: &mut ~str) {
+        let len = output.rfind('/').unwrap_or(0);
+        output.truncate(len);
+    }
+
+    let mut input = path;
+    let mut output = ~"";
+    while !input.is_empty() {
+        if input.starts_with("../") {
+            input = input.slice_from(3);
+        } else if input.starts_with("./") || input.starts_with("/./") {
+            input = input.slice_from(2);
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") {
+            input = input.slice_from(3);
+            pop_segment(&mut output);
+        } else if input == "/.." {
+            input = "/";
+            pop_segment(&mut output);
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            // move the first segment, with its leading '/', to the output
+            let start = if input.starts_with("/") { 1 } else { 0 };
+            let end = match input.slice_from(start).find('/') {
+                Some(i) => start + i,
+                None => input.len()
+            };
+            output.push_str(input.slice_to(end));
+            input = input.slice_from(end);
+        }
+    }
+    output
+}
+
+// appends a relative path to the directory of the base url's path
+// (RFC 3986 section 5.2.3)
+fn merge_paths(base: &Url, path: &str) -> ~str {
+    if !base.host.is_empty() && base.path.is_empty() {
+        return ~"/" + path;
+    }
+    match base.path.rfind('/') {
+        Some(i) => base.path.slice_to(i+1).to_owned() + path,
+        None => path.to_owned()
+    }
+}
+
+// the port a scheme uses when none is given
+fn default_port(scheme: &str) -> Option<&'static str> {
+    match scheme {
+        "http" | "ws" => Some("80"),
+        "https" | "wss" => Some("443"),
+        "ftp" => Some("21"),
+        _ => None
+    }
+}
+
 // returns the parsed query and the fragment, if present
 fn get_query_fragment(rawurl: &str) ->
     Result<(Query, Option<~str>), ~str> {
@@ -791,6 +1029,16 @@ fn test_get_path() {
     assert!(get_path("something?q", true).is_err());
 }
 
+#[test]
+fn test_remove_dot_segments() {
+    assert_eq!(remove_dot_segments("/a/b/c/./../../g"), ~"/a/g");
+    assert_eq!(remove_dot_segments("mid/content=5/../6"), ~"mid/6");
+    assert_eq!(remove_dot_segments("/../a"), ~"/a");
+    assert_eq!(remove_dot_segments("a/.."), ~"");
+    assert_eq!(remove_dot_segments("/a/b/."), ~"/a/b/");
+    assert_eq!(remove_dot_segments("/a/..b/.c"), ~"/a/..b/.c");
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -931,6 +1179,105 @@ mod tests {
         assert!(u.query == ~[(~"ba%d ", ~"#&+")]);
     }
 
+    #[test]
+    fn test_url_join() {
+        // the examples from section 5.4 of RFC 3986
+        let base = from_str("http://a/b/c/d;p?q").unwrap();
+        let examples = [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            ("g?y#s", "http://a/b/c/g?y#s"),
+            (";x", "http://a/b/c/;x"),
+            ("g;x", "http://a/b/c/g;x"),
+            ("g;x?y#s", "http://a/b/c/g;x?y#s"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../", "http://a/"),
+            ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"),
+            ("../../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("/../g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            (".g", "http://a/b/c/.g"),
+            ("g..", "http://a/b/c/g.."),
+            ("..g", "http://a/b/c/..g"),
+            ("./../g", "http://a/b/g"),
+            ("./g/.", "http://a/b/c/g/"),
+            ("g/./h", "http://a/b/c/g/h"),
+            ("g/../h", "http://a/b/c/h"),
+            ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+            ("g;x=1/../y", "http://a/b/c/y"),
+            ("g?y/./x", "http://a/b/c/g?y/./x"),
+            ("g?y/../x", "http://a/b/c/g?y/../x"),
+            ("g#s/./x", "http://a/b/c/g#s/./x"),
+            ("g#s/../x", "http://a/b/c/g#s/../x"),
+            ("http:g", "http:g"),
+        ];
+        for &(relative, expected) in examples.iter() {
+            assert_eq!(base.join(relative), from_str(expected));
+        }
+
+        let base = from_str("http://rust-lang.org").unwrap();
+        assert_eq!(base.join("doc").unwrap().to_str(), ~"http://rust-lang.org/doc");
+        assert!(base.join("/doc^").is_err());
+    }
+
+    #[test]
+    fn test_url_normalize() {
+        let url = from_str("HTTP://Rust-Lang.ORG:80/a/./b/../c?q=v#f").unwrap();
+        assert_eq!(url.normalize().to_str(), ~"http://rust-lang.org/a/c?q=v#f");
+
+        let url = from_str("https://rust-lang.org:8443").unwrap();
+        assert_eq!(url.normalize().to_str(), ~"https://rust-lang.org:8443/");
+
+        let url = from_str("mailto:Test@Email.com").unwrap();
+        assert_eq!(url.normalize().to_str(), ~"mailto:Test@Email.com");
+    }
+
+    #[test]
+    fn test_url_ipv6_host() {
+        let url = from_str("http://[2001:db8::1]:8080/doc").unwrap();
+        assert_eq!(&url.host, &~"[2001:db8::1]");
+        assert_eq!(&url.port, &Some(~"8080"));
+        assert_eq!(&url.path, &~"/doc");
+        assert_eq!(url.to_str(), ~"http://[2001:db8::1]:8080/doc");
+
+        let url = from_str("http://user@[::1]").unwrap();
+        assert_eq!(&url.host, &~"[::1]");
+        assert_eq!(&url.user, &Some(UserInfo::new(~"user", None)));
+
+        assert!(from_str("http://[::1").is_err());
+        assert!(from_str("http://[rust-lang.org]/").is_err());
+        assert!(from_str("http://[::1]x/").is_err());
+    }
+
+    #[test]
+    fn test_url_parse_host() {
+        use std::rt::io::net::ip::{Ipv4Addr, Ipv6Addr};
+
+        let host = |s| from_str(s).unwrap().parse_host();
+        assert_eq!(host("http://rust-lang.org/"), Some(Domain(~"rust-lang.org")));
+        assert_eq!(host("http://127.0.0.1:80/"), Some(Ipv4(Ipv4Addr(127, 0, 0, 1))));
+        assert_eq!(host("http://[::1]/"), Some(Ipv6(Ipv6Addr(0, 0, 0, 0, 0, 0, 0, 1))));
+        assert_eq!(host("mailto:test@email.com"), None);
+        assert_eq!(Domain(~"rust-lang.org").to_str(), ~"rust-lang.org");
+        assert_eq!(Ipv4(Ipv4Addr(127, 0, 0, 1)).to_str(), ~"127.0.0.1");
+    }
+
     #[test]
     fn test_url_without_authority() {
         let url = ~"mailto:test@email.com";
@@ -38,6 +38,19 @@ fn main() {
 }
  ```
 
+To create the name-based (V5) UUID of a domain name, which is the same
+every time:
+
+```rust
+extern mod extra;
+use extra::uuid::{Uuid, NAMESPACE_DNS};
+
+fn main() {
+    let uuid1 = Uuid::new_v5(&NAMESPACE_DNS, "www.rust-lang.org");
+    println(uuid1.to_hyphenated_str());
+}
+ ```
+
 # Strings
 
 Examples of string representations:
@@ -65,8 +78,13 @@ use std::rand;
 use std::rand::Rng;
 use std::cmp::Eq;
 use std::cast::{transmute,transmute_copy};
+use std::local_data;
 
+use digest::Digest;
+use md5::Md5;
 use serialize::{Encoder, Encodable, Decoder, Decodable};
+use sha1::Sha1;
+use time;
 
 /// A 128-bit (16 byte) buffer containing the ID
 pub type UuidBytes = [u8, ..16];
@@ -150,6 +168,39 @@ impl ToStr for ParseError {
 // Length of each hyphenated group in hex digits
 static UuidGroupLens: [uint, ..5] = [8u, 4u, 4u, 4u, 12u];
 
+/// The namespace for fully-qualified domain names, for use with `new_v3` and `new_v5`
+pub static NAMESPACE_DNS: Uuid = Uuid { bytes: [
+    0x6b, 0xa7, 0xb8, 0x10, 0x9d, 0xad, 0x11, 0xd1,
+    0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+
+/// The namespace for URLs, for use with `new_v3` and `new_v5`
+pub static NAMESPACE_URL: Uuid = Uuid { bytes: [
+    0x6b, 0xa7, 0xb8, 0x11, 0x9d, 0xad, 0x11, 0xd1,
+    0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+
+/// The namespace for ISO object identifiers, for use with `new_v3` and `new_v5`
+pub static NAMESPACE_OID: Uuid = Uuid { bytes: [
+    0x6b, 0xa7, 0xb8, 0x12, 0x9d, 0xad, 0x11, 0xd1,
+    0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+
+/// The namespace for X.500 distinguished names, for use with `new_v3` and `new_v5`
+pub static NAMESPACE_X500: Uuid = Uuid { bytes: [
+    0x6b, 0xa7, 0xb8, 0x14, 0x9d, 0xad, 0x11, 0xd1,
+    0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+
+// The number of 100ns intervals between the start of the Gregorian calendar
+// (1582-10-15) and the Unix epoch, which V1 timestamps count from
+static GregorianEpochOffset: u64 = 0x01B21DD213814000;
+
+// Per-task state of the V1 generator
+struct V1Context {
+    node: [u8, ..6],
+    clock_seq: u16,
+    last_timestamp: u64
+}
+
+local_data_key!(v1_context: V1Context)
+
 /// UUID support
 impl Uuid {
 
@@ -160,13 +211,135 @@ impl Uuid {
     }
 
     /// Create a new UUID of the specified version
+    ///
+    /// Only the time-based (V1) and random (V4) versions can be created
+    /// this way; the name-based versions need a namespace and a name, see
+    /// `new_v3` and `new_v5`.
     pub fn new(v: UuidVersion) -> Option<Uuid> {
         match v {
+            Version1Mac => Some(Uuid::new_v1()),
             Version4Random => Some(Uuid::new_v4()),
             _ => None
         }
     }
 
+    /// Creates a new time-based UUID
+    ///
+    /// The timestamp is taken from `time::get_time`. Rather than a MAC
+    /// address, the node id is a random number (with the multicast bit set,
+    /// as RFC4122 requires) chosen once per task, so the UUID does not reveal
+    /// which machine created it. UUIDs created by the same task are unique
+    /// and ordered by their timestamp, even when the clock stands still or
+    /// goes backwards.
+    pub fn new_v1() -> Uuid {
+        let now = time::get_time();
+        let timestamp = (now.sec as u64) * 10_000_000 + (now.nsec as u64) / 100 +
+            GregorianEpochOffset;
+
+        let mut result = Uuid::new_nil();
+        do local_data::modify(v1_context) |ctx| {
+            let mut ctx = match ctx {
+                Some(ctx) => ctx,
+                None => {
+                    let mut rng = rand::task_rng();
+                    let mut node = [0u8, ..6];
+                    vec::bytes::copy_memory(node, rng.gen_vec(6), 6);
+                    node[0] |= 0x01;
+                    V1Context {
+                        node: node,
+                        clock_seq: rng.gen::<u16>() & 0x3fff,
+                        last_timestamp: 0
+                    }
+                }
+            };
+            // Within one clock tick, borrow timestamps from the future
+            if timestamp <= ctx.last_timestamp {
+                ctx.last_timestamp += 1;
+            } else {
+                ctx.last_timestamp = timestamp;
+            }
+            result = Uuid::from_v1_parts(ctx.last_timestamp, ctx.clock_seq, ctx.node);
+            Some(ctx)
+        }
+        result
+    }
+
+    /// Creates a time-based UUID from its parts
+    ///
+    /// # Arguments
+    /// * `timestamp` The number of 100ns intervals since 1582-10-15 00:00 UTC;
+    ///   only the low 60 bits are used
+    /// * `clock_seq` The clock sequence; only the low 14 bits are used
+    /// * `node` The node id, 6 octets
+    pub fn from_v1_parts(timestamp: u64, clock_seq: u16, node: &[u8]) -> Uuid {
+        let mut d4 = [0u8, ..8];
+        d4[0] = (clock_seq >> 8) as u8;
+        d4[1] = clock_seq as u8;
+        vec::bytes::copy_memory(d4.mut_slice_from(2), node, 6);
+
+        let mut uuid = Uuid::from_fields(timestamp as u32,
+                                         (timestamp >> 32) as u16,
+                                         (timestamp >> 48) as u16,
+                                         d4);
+        uuid.set_variant(VariantRFC4122);
+        uuid.set_version(Version1Mac);
+        uuid
+    }
+
+    /// Returns the timestamp of a time-based (V1) UUID
+    ///
+    /// The timestamp counts 100ns intervals since 1582-10-15 00:00 UTC.
+    /// Returns `None` for other versions.
+    pub fn get_timestamp(&self) -> Option<u64> {
+        if self.get_version_num() != 1 {
+            return None;
+        }
+        let b = &self.bytes;
+        let time_low = (b[0] as u64 << 24) | (b[1] as u64 << 16) |
+                       (b[2] as u64 << 8) | b[3] as u64;
+        let time_mid = (b[4] as u64 << 8) | b[5] as u64;
+        let time_hi = ((b[6] & 0x0f) as u64 << 8) | b[7] as u64;
+        Some((time_hi << 48) | (time_mid << 32) | time_low)
+    }
+
+    /// Creates a name-based UUID using MD5
+    ///
+    /// The same namespace and name always give the same UUID. Prefer
+    /// `new_v5` unless compatibility requires V3.
+    ///
+    /// # Arguments
+    /// * `namespace` The namespace the name belongs to, such as `NAMESPACE_DNS`
+    /// * `name` The name
+    pub fn new_v3(namespace: &Uuid, name: &str) -> Uuid {
+        Uuid::from_hash(Md5::new(), namespace, name, Version3Md5)
+    }
+
+    /// Creates a name-based UUID using SHA-1
+    ///
+    /// The same namespace and name always give the same UUID.
+    ///
+    /// # Arguments
+    /// * `namespace` The namespace the name belongs to, such as `NAMESPACE_DNS`
+    /// * `name` The name
+    pub fn new_v5(namespace: &Uuid, name: &str) -> Uuid {
+        Uuid::from_hash(Sha1::new(), namespace, name, Version5Sha1)
+    }
+
+    // Builds a name-based UUID from the first 16 octets of the hash of the
+    // namespace followed by the name
+    fn from_hash<D: Digest>(digest: D, namespace: &Uuid, name: &str, v: UuidVersion) -> Uuid {
+        let mut digest = digest;
+        digest.input(namespace.bytes);
+        digest.input(name.as_bytes());
+        let hash = digest.result_bytes();
+
+        let mut uuid = Uuid{ bytes: [0, .. 16] };
+        vec::bytes::copy_memory(uuid.bytes, hash, 16);
+        uuid.set_variant(VariantRFC4122);
+        uuid.set_version(v);
+        uuid
+    }
+
     /// Creates a new random UUID
     ///
     /// Uses the `rand` module's default RNG task as the source
@@ -269,9 +442,10 @@ impl Uuid {
     ///
     /// This represents the algorithm used to generate the contents.
     ///
-    /// Currently only the Random (V4) algorithm is supported by this
-    /// module.  There are security and privacy implications for using
-    /// older versions - see [Wikipedia: Universally Unique Identifier](
+    /// The time-based (V1), name-based (V3 and V5) and random (V4)
+    /// algorithms are supported by this module.  There are security and
+    /// privacy implications for using older versions - see
+    /// [Wikipedia: Universally Unique Identifier](
     /// http://en.wikipedia.org/wiki/Universally_unique_identifier) for
     /// details.
     ///
@@ -549,8 +723,10 @@ mod test {
         assert!(s.len() == 32);
         assert!(uuid1.get_version().unwrap() == Version4Random);
 
+        let uuid2 = Uuid::new(Version1Mac).unwrap();
+        assert!(uuid2.get_version().unwrap() == Version1Mac);
+
         // Test unsupported versions
-        assert!(Uuid::new(Version1Mac) == None);
         assert!(Uuid::new(Version2Dce) == None);
         assert!(Uuid::new(Version3Md5) == None);
         assert!(Uuid::new(Version5Sha1) == None);
@@ -564,6 +740,68 @@ mod test {
         assert!(uuid1.get_variant().unwrap() == VariantRFC4122);
     }
 
+    #[test]
+    fn test_new_v1() {
+        let uuid1 = Uuid::new_v1();
+        let uuid2 = Uuid::new_v1();
+
+        assert!(uuid1.get_version().unwrap() == Version1Mac);
+        assert!(uuid1.get_variant().unwrap() == VariantRFC4122);
+        assert!(uuid1 != uuid2);
+        assert!(uuid2.get_timestamp().unwrap() > uuid1.get_timestamp().unwrap());
+
+        // The clock sequence and node id stay the same within a task
+        assert_eq!(uuid1.to_bytes().slice_from(8), uuid2.to_bytes().slice_from(8));
+        // The random node id has the multicast bit set
+        assert!(uuid1.to_bytes()[10] & 0x01 == 0x01);
+
+        // 2013-01-01 00:00 UTC is 1356998400 seconds after the epoch
+        let ts = uuid1.get_timestamp().unwrap();
+        assert!(ts > 0x01B21DD213814000 + 1356998400 * 10_000_000);
+        assert!(Uuid::new_v4().get_timestamp().is_none());
+    }
+
+    #[test]
+    fn test_from_v1_parts() {
+        let node = [0x01u8, 0x23, 0x45, 0x67, 0x89, 0xab];
+        let u = Uuid::from_v1_parts(0x1d19dad8a2f6b3e, 0x2a5, node);
+
+        assert_eq!(u.to_hyphenated_str(), ~"8a2f6b3e-9dad-11d1-82a5-0123456789ab");
+        assert_eq!(u.get_timestamp(), Some(0x1d19dad8a2f6b3e));
+    }
+
+    #[test]
+    fn test_new_v3() {
+        let u = Uuid::new_v3(&NAMESPACE_DNS, "www.example.com");
+        assert!(u.get_version().unwrap() == Version3Md5);
+        assert!(u.get_variant().unwrap() == VariantRFC4122);
+        assert_eq!(u.to_hyphenated_str(), ~"5df41881-3aed-3515-88a7-2f4a814cf09e");
+
+        let u = Uuid::new_v3(&NAMESPACE_URL, "http://rust-lang.org/");
+        assert_eq!(u.to_hyphenated_str(), ~"110a30b5-1f2a-3534-8a77-863d0e4ea6d1");
+    }
+
+    #[test]
+    fn test_new_v5() {
+        let u = Uuid::new_v5(&NAMESPACE_DNS, "www.example.com");
+        assert!(u.get_version().unwrap() == Version5Sha1);
+        assert!(u.get_variant().unwrap() == VariantRFC4122);
+        assert_eq!(u.to_hyphenated_str(), ~"2ed6657d-e927-568b-95e1-2665a8aea6a2");
+
+        let u = Uuid::new_v5(&NAMESPACE_URL, "http://rust-lang.org/");
+        assert_eq!(u.to_hyphenated_str(), ~"91e0633a-eff5-5ab4-8e35-adaca6246e04");
+        assert!(Uuid::new_v5(&NAMESPACE_OID, "1.3.6.1") !=
+                Uuid::new_v5(&NAMESPACE_X500, "1.3.6.1"));
+    }
+
+    #[test]
+    fn test_namespaces() {
+        assert_eq!(NAMESPACE_DNS.to_hyphenated_str(), ~"6ba7b810-9dad-11d1-80b4-00c04fd430c8");
+        assert_eq!(NAMESPACE_URL.to_hyphenated_str(), ~"6ba7b811-9dad-11d1-80b4-00c04fd430c8");
+        assert_eq!(NAMESPACE_OID.to_hyphenated_str(), ~"6ba7b812-9dad-11d1-80b4-00c04fd430c8");
+        assert_eq!(NAMESPACE_X500.to_hyphenated_str(), ~"6ba7b814-9dad-11d1-80b4-00c04fd430c8");
+    }
+
     #[test]
     fn test_get_version() {
         let uuid1 = Uuid::new_v4();
@@ -815,6 +1053,20 @@ mod bench {
         }
     }
 
+    #[bench]
+    pub fn create_uuids_v1(bh: &mut BenchHarness) {
+        do bh.iter {
+            Uuid::new_v1();
+        }
+    }
+
+    #[bench]
+    pub fn create_uuids_v5(bh: &mut BenchHarness) {
+        do bh.iter {
+            Uuid::new_v5(&NAMESPACE_DNS, "www.example.com");
+        }
+    }
+
     #[bench]
     pub fn uuid_to_str(bh: &mut BenchHarness) {
         let u = Uuid::new_v4();
@@ -32,7 +32,9 @@ struct Test {
     path: ~[ast::Ident],
     bench: bool,
     ignore: bool,
-    should_fail: bool
+    should_fail: bool,
+    expected_failure: Option<@str>,
+    timeout: Option<u64>
 }
 
 struct TestCtxt {
@@ -97,7 +99,9 @@ impl fold::ast_fold for TestHarnessGenerator {
                         path: self.cx.path.clone(),
                         bench: is_bench_fn(i),
                         ignore: is_ignored(self.cx, i),
-                        should_fail: should_fail(i)
+                        should_fail: should_fail(i),
+                        expected_failure: expected_failure(self.cx, i),
+                        timeout: timeout(self.cx, i)
                     };
                     self.cx.testfns.push(test);
                     // debug!("have {} test/bench functions",
@@ -245,6 +249,47 @@ fn should_fail(i: @ast::item) -> bool {
     attr::contains_name(i.attrs, "should_fail")
 }
 
+// The text given by #[should_fail(expected = "...")], which the failure
+// message has to contain.
+fn expected_failure(cx: @mut TestCtxt, i: @ast::item) -> Option<@str> {
+    let mut expected = None;
+    for attr in i.attrs.iter().filter(|attr| "should_fail" == attr.name()) {
+        for items in attr.meta_item_list().iter() {
+            for item in items.iter() {
+                match item.value_str() {
+                    Some(s) if "expected" == item.name() => expected = Some(s),
+                    _ => cx.sess.span_err(item.span,
+                                          "malformed should_fail attribute, expected \
+                                           #[should_fail(expected = \"...\")]")
+                }
+            }
+        }
+    }
+    expected
+}
+
+// The number of seconds given by #[timeout = N].
+fn timeout(cx: @mut TestCtxt, i: @ast::item) -> Option<u64> {
+    let mut timeout = None;
+    for attr in i.attrs.iter().filter(|attr| "timeout" == attr.name()) {
+        let secs = match attr.node.value.node {
+            ast::MetaNameValue(_, ref lit) => match lit.node {
+                ast::lit_int_unsuffixed(n) if n > 0 => Some(n as u64),
+                ast::lit_uint(n, _) if n > 0 => Some(n),
+                _ => None
+            },
+            _ => None
+        };
+        match secs {
+            Some(_) => timeout = secs,
+            None => cx.sess.span_err(attr.span,
+                                     "malformed timeout attribute, expected #[timeout = N] \
+                                      with N a positive number of seconds")
+        }
+    }
+    timeout
+}
+
 fn add_test_module(cx: &TestCtxt, m: &ast::_mod) -> ast::_mod {
     let testmod = mk_test_module(cx);
     ast::_mod {
@@ -441,12 +486,39 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> @ast::Expr {
         quote_expr!(cx.ext_cx, false )
     };
 
+    let expected_expr = match test.expected_failure {
+        Some(expected) => {
+            let expected_expr = @ast::Expr {
+                id: ast::DUMMY_NODE_ID,
+                node: ast::ExprLit(@nospan(ast::lit_str(expected, ast::CookedStr))),
+                span: span
+            };
+            quote_expr!(cx.ext_cx, ::std::option::Some($expected_expr) )
+        }
+        None => quote_expr!(cx.ext_cx, ::std::option::None )
+    };
+
+    // The attribute counts seconds, `TestDesc` milliseconds.
+    let timeout_expr = match test.timeout {
+        Some(secs) => {
+            let secs_expr = @ast::Expr {
+                id: ast::DUMMY_NODE_ID,
+                node: ast::ExprLit(@nospan(ast::lit_uint(secs * 1000, ast::ty_u64))),
+                span: span
+            };
+            quote_expr!(cx.ext_cx, ::std::option::Some($secs_expr) )
+        }
+        None => quote_expr!(cx.ext_cx, ::std::option::None )
+    };
+
     let e = quote_expr!(cx.ext_cx,
         self::extra::test::TestDescAndFn {
             desc: self::extra::test::TestDesc {
                 name: self::extra::test::StaticTestName($name_expr),
                 ignore: $ignore_expr,
-                should_fail: $fail_expr
+                should_fail: $fail_expr,
+                expected_failure: $expected_expr,
+                timeout: $timeout_expr
             },
             testfn: $t_expr,
         }
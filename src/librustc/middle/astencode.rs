@@ -1026,12 +1026,12 @@ fn encode_side_tables_for_id(ecx: &e::EncodeContext,
 
 trait doc_decoder_helpers {
     fn as_int(&self) -> int;
-    fn opt_child(&self, tag: c::astencode_tag) -> Option<ebml::Doc>;
+    fn opt_child(&self, tag: c::astencode_tag) -> Option<Self>;
 }
 
-impl doc_decoder_helpers for ebml::Doc {
+impl<'self> doc_decoder_helpers for ebml::Doc<'self> {
     fn as_int(&self) -> int { reader::doc_as_u64(*self) as int }
-    fn opt_child(&self, tag: c::astencode_tag) -> Option<ebml::Doc> {
+    fn opt_child(&self, tag: c::astencode_tag) -> Option<ebml::Doc<'self>> {
         reader::maybe_get_doc(*self, tag as uint)
     }
 }
@@ -1063,7 +1063,7 @@ impl ebml_decoder_decoder_helpers for reader::Decoder {
                      tcx: ty::ctxt, cdata: @cstore::crate_metadata) -> ty::t {
         do self.read_opaque |_, doc| {
             tydecode::parse_ty_data(
-                *doc.data,
+                doc.data,
                 cdata.cnum,
                 doc.start,
                 tcx,
@@ -1085,7 +1085,7 @@ impl ebml_decoder_decoder_helpers for reader::Decoder {
 
         return do self.read_opaque |this, doc| {
             let ty = tydecode::parse_ty_data(
-                *doc.data,
+                doc.data,
                 xcx.dcx.cdata.cnum,
                 doc.start,
                 xcx.dcx.tcx,
@@ -1115,7 +1115,7 @@ impl ebml_decoder_decoder_helpers for reader::Decoder {
                            -> ty::TypeParameterDef {
         do self.read_opaque |this, doc| {
             tydecode::parse_type_param_def_data(
-                *doc.data,
+                doc.data,
                 doc.start,
                 xcx.dcx.cdata.cnum,
                 xcx.dcx.tcx,
@@ -10,6 +10,7 @@
 
 use fmt;
 use libc;
+use local_data;
 use option::{Option, Some, None};
 use result::{Ok, Err};
 use rt::local::Local;
@@ -27,18 +28,24 @@ pub fn stdin() -> StdReader {
     StdReader { inner: stream }
 }
 
+local_data_key!(local_stdout: ~Writer)
+local_data_key!(local_stderr: ~Writer)
+
 /// Creates a new non-blocking handle to the stdout of the current process.
 ///
 /// Note that this is a fairly expensive operation in that at least one memory
 /// allocation is performed. Additionally, this must be called from a runtime
 /// task context because the stream returned will be a non-blocking object using
 /// the local scheduler to perform the I/O.
+///
+/// Output written by a task that has called `set_stdout` goes to the writer
+/// it installed instead.
 pub fn stdout() -> StdWriter {
     let stream = unsafe {
         let io: *mut IoFactoryObject = Local::unsafe_borrow();
         (*io).fs_from_raw_fd(libc::STDOUT_FILENO, false)
     };
-    StdWriter { inner: stream }
+    StdWriter { inner: stream, redirect: local_stdout }
 }
 
 /// Creates a new non-blocking handle to the stderr of the current process.
@@ -49,7 +56,26 @@ pub fn stderr() -> StdWriter {
         let io: *mut IoFactoryObject = Local::unsafe_borrow();
         (*io).fs_from_raw_fd(libc::STDERR_FILENO, false)
     };
-    StdWriter { inner: stream }
+    StdWriter { inner: stream, redirect: local_stderr }
+}
+
+/// Redirects the current task's stdout, as written through `stdout()`,
+/// `print` and `println`, to `stdout`. Returns the writer previously
+/// installed, if any.
+pub fn set_stdout(stdout: ~Writer) -> Option<~Writer> {
+    let prev = local_data::pop(local_stdout);
+    local_data::set(local_stdout, stdout);
+    prev
+}
+
+/// Redirects the current task's stderr, as written through `stderr()`, to
+/// `stderr`. Returns the writer previously installed, if any.
+///
+/// Log messages are not affected; see `rt::logging::set_logger`.
+pub fn set_stderr(stderr: ~Writer) -> Option<~Writer> {
+    let prev = local_data::pop(local_stderr);
+    local_data::set(local_stderr, stderr);
+    prev
 }
 
 /// Prints a string to the stdout of the current process. No newline is emitted
@@ -106,21 +132,37 @@ impl Reader for StdReader {
 
 /// Representation of a writer to a standard output stream
 pub struct StdWriter {
-    priv inner: ~RtioFileStream
+    priv inner: ~RtioFileStream,
+    // The task-local writer that replaces this stream, if one is set.
+    priv redirect: local_data::Key<~Writer>,
 }
 
 impl Writer for StdWriter {
     fn write(&mut self, buf: &[u8]) {
-        match self.inner.write(buf) {
-            Ok(()) => {}
-            Err(e) => io_error::cond.raise(e)
+        // The redirection is taken out while it is written to, so that a
+        // writer which itself prints ends up on the real stream.
+        match local_data::pop(self.redirect) {
+            Some(mut w) => {
+                w.write(buf);
+                local_data::set(self.redirect, w);
+            }
+            None => match self.inner.write(buf) {
+                Ok(()) => {}
+                Err(e) => io_error::cond.raise(e)
+            }
         }
     }
 
     fn flush(&mut self) {
-        match self.inner.flush() {
-            Ok(()) => {}
-            Err(e) => io_error::cond.raise(e)
+        match local_data::pop(self.redirect) {
+            Some(mut w) => {
+                w.flush();
+                local_data::set(self.redirect, w);
+            }
+            None => match self.inner.flush() {
+                Ok(()) => {}
+                Err(e) => io_error::cond.raise(e)
+            }
         }
     }
 }
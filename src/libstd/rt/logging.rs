@@ -177,6 +177,46 @@ impl Logger for StdErrLogger {
     }
 }
 
+/// A task's logger. Messages go to stderr unless the task has installed
+/// a logger of its own with `set_logger`.
+pub struct TaskLogger {
+    priv logger: Option<~Logger>,
+}
+
+impl TaskLogger {
+    pub fn new() -> TaskLogger {
+        TaskLogger { logger: None }
+    }
+}
+
+impl Logger for TaskLogger {
+    fn log(&mut self, args: &fmt::Arguments) {
+        match self.logger {
+            Some(ref mut logger) => logger.log(args),
+            None => {
+                let mut logger = StdErrLogger;
+                logger.log(args);
+            }
+        }
+    }
+}
+
+/// Sends the current task's log messages, including the message it prints
+/// when it fails, to `logger` instead of stderr. Returns the logger that
+/// was installed before, if any.
+///
+/// A stack overflow is still reported on stderr.
+pub fn set_logger(logger: ~Logger) -> Option<~Logger> {
+    use rt::local::Local;
+    use rt::task::Task;
+    use util;
+
+    unsafe {
+        let task: *mut Task = Local::unsafe_borrow();
+        util::replace(&mut (*task).logger.logger, Some(logger))
+    }
+}
+
 /// Configure logging by traversing the crate map and setting the
 /// per-module global logging flags based on the logging spec
 pub fn init() {
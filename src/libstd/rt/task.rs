@@ -25,7 +25,7 @@ use rt::borrowck::BorrowRecord;
 use rt::env;
 use rt::kill::Death;
 use rt::local::Local;
-use rt::logging::StdErrLogger;
+use rt::logging::TaskLogger;
 use super::local_heap::LocalHeap;
 use rt::sched::{Scheduler, SchedHandle};
 use rt::stack::{StackSegment, StackPool};
@@ -46,7 +46,7 @@ pub struct Task {
     heap: LocalHeap,
     priv gc: GarbageCollector,
     storage: LocalStorage,
-    logger: StdErrLogger,
+    logger: TaskLogger,
     unwinder: Unwinder,
     taskgroup: Option<Taskgroup>,
     death: Death,
@@ -85,6 +85,9 @@ pub struct LocalStorage(Option<local_data::Map>);
 
 pub struct Unwinder {
     unwinding: bool,
+    // The message the task failed with, if it failed through
+    // `begin_unwind_with_cause`.
+    cause: Option<SendStr>,
 }
 
 impl Task {
@@ -132,8 +135,8 @@ impl Task {
             heap: LocalHeap::new(),
             gc: GarbageCollector,
             storage: LocalStorage(None),
-            logger: StdErrLogger,
-            unwinder: Unwinder { unwinding: false },
+            logger: TaskLogger::new(),
+            unwinder: Unwinder { unwinding: false, cause: None },
             taskgroup: None,
             death: Death::new(),
             destroyed: false,
@@ -166,8 +169,8 @@ impl Task {
             heap: LocalHeap::new(),
             gc: GarbageCollector,
             storage: LocalStorage(None),
-            logger: StdErrLogger,
-            unwinder: Unwinder { unwinding: false },
+            logger: TaskLogger::new(),
+            unwinder: Unwinder { unwinding: false, cause: None },
             taskgroup: None,
             death: Death::new(),
             destroyed: false,
@@ -188,8 +191,8 @@ impl Task {
             heap: LocalHeap::new(),
             gc: GarbageCollector,
             storage: LocalStorage(None),
-            logger: StdErrLogger,
-            unwinder: Unwinder { unwinding: false },
+            logger: TaskLogger::new(),
+            unwinder: Unwinder { unwinding: false, cause: None },
             taskgroup: None,
             // FIXME(#7544) make watching optional
             death: self.death.new_child(),
@@ -479,7 +482,7 @@ pub extern "C" fn rust_stack_exhausted() {
     use rt::in_green_task_context;
     use rt::task::Task;
     use rt::local::Local;
-    use rt::logging::Logger;
+    use rt::logging::{Logger, StdErrLogger};
     use unstable::intrinsics;
 
     unsafe {
@@ -529,7 +532,10 @@ pub extern "C" fn rust_stack_exhausted() {
             do Local::borrow |task: &mut Task| {
                 let n = task.name.as_ref().map(|n| n.as_slice()).unwrap_or("<unnamed>");
 
-                format_args!(|args| { task.logger.log(args) },
+                // This goes straight to stderr, not to a logger the task may
+                // have installed, which could need more stack than is left.
+                let mut logger = StdErrLogger;
+                format_args!(|args| { logger.log(args) },
                              "task '{}' has overflowed its stack", n);
             }
         } else {
@@ -558,21 +564,25 @@ pub fn begin_unwind(msg: *c_char, file: *c_char, line: size_t) -> ! {
         };
 
         if in_green_task_context() {
-            // Be careful not to allocate in this block, if we're failing we may
-            // have been failing due to a lack of memory in the first place...
-            do Local::borrow |task: &mut Task| {
-                let n = task.name.as_ref().map(|n| n.as_slice()).unwrap_or("<unnamed>");
+            // Running out of memory aborts rather than failing, and a stack
+            // overflow is reported by `rust_stack_exhausted`, so this is an
+            // ordinary failure and the task's own logger, which may allocate,
+            // can be used.
+            //
+            // The task is left in TLS while logging, because a task's own
+            // logger may need the runtime (to send the message elsewhere).
+            let task: *mut Task = Local::unsafe_borrow();
+            let n = (*task).name.as_ref().map(|n| n.as_slice()).unwrap_or("<unnamed>");
 
-                match file.as_str() {
-                    Some(file) => {
-                        format_args!(|args| { task.logger.log(args) },
-                                     "task '{}' failed at '{}', {}:{}",
-                                     n, msg, file, line);
-                    }
-                    None => {
-                        format_args!(|args| { task.logger.log(args) },
-                                     "task '{}' failed at '{}'", n, msg);
-                    }
+            match file.as_str() {
+                Some(file) => {
+                    format_args!(|args| { (*task).logger.log(args) },
+                                 "task '{}' failed at '{}', {}:{}",
+                                 n, msg, file, line);
+                }
+                None => {
+                    format_args!(|args| { (*task).logger.log(args) },
+                                 "task '{}' failed at '{}'", n, msg);
                 }
             }
         } else {
@@ -593,6 +603,28 @@ pub fn begin_unwind(msg: *c_char, file: *c_char, line: size_t) -> ! {
     }
 }
 
+/// Like `begin_unwind`, but also keeps `cause` in the task as the message it
+/// failed with, for `task::try_with_cause`. The string is moved into the
+/// task rather than copied, so keeping it doesn't allocate.
+pub fn begin_unwind_with_cause(cause: SendStr, file: &'static str, line: size_t) -> ! {
+    use rt::in_green_task_context;
+
+    unsafe {
+        let msg = if in_green_task_context() {
+            let task: *mut Task = Local::unsafe_borrow();
+            (*task).unwinder.cause = Some(cause);
+            (*task).unwinder.cause.get_ref().as_slice()
+        } else {
+            cause.as_slice()
+        };
+        do msg.with_c_str |msg_buf| {
+            do file.with_c_str |file_buf| {
+                begin_unwind(msg_buf, file_buf, line)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use rt::test::*;
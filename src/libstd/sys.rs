@@ -12,10 +12,10 @@
 
 #[allow(missing_doc)];
 
-use c_str::ToCStr;
 use libc::size_t;
 use libc;
 use rt::task;
+use send_str::{SendStrOwned, SendStrStatic};
 
 /// Trait for initiating task failure.
 pub trait FailWithCause {
@@ -25,21 +25,13 @@ pub trait FailWithCause {
 
 impl FailWithCause for ~str {
     fn fail_with(cause: ~str, file: &'static str, line: uint) -> ! {
-        do cause.with_c_str |msg_buf| {
-            do file.with_c_str |file_buf| {
-                task::begin_unwind(msg_buf, file_buf, line as libc::size_t)
-            }
-        }
+        task::begin_unwind_with_cause(SendStrOwned(cause), file, line as libc::size_t)
     }
 }
 
 impl FailWithCause for &'static str {
     fn fail_with(cause: &'static str, file: &'static str, line: uint) -> ! {
-        do cause.with_c_str |msg_buf| {
-            do file.with_c_str |file_buf| {
-                task::begin_unwind(msg_buf, file_buf, line as libc::size_t)
-            }
-        }
+        task::begin_unwind_with_cause(SendStrStatic(cause), file, line as libc::size_t)
     }
 }
 
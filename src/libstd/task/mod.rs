@@ -408,6 +408,32 @@ impl TaskBuilder {
             Failure => result::Err(())
         }
     }
+
+    /**
+     * Like `try`, but on failure the error holds the message the task
+     * failed with. It is `None` if the task didn't fail with a message of
+     * its own, for instance if it was killed because a linked task failed.
+     */
+    pub fn try_with_cause<T:Send>(&mut self, f: ~fn() -> T)
+                                  -> Result<T, Option<SendStr>> {
+        let (po, ch) = stream::<T>();
+        let (cause_po, cause_ch) = stream::<Option<SendStr>>();
+
+        let result = self.future_result();
+
+        do self.spawn {
+            do (|| ch.send(f())).finally {
+                if failing() {
+                    cause_ch.send(take_failure_cause());
+                }
+            }
+        }
+
+        match result.recv() {
+            Success => result::Ok(po.recv()),
+            Failure => result::Err(cause_po.try_recv().unwrap_or(None))
+        }
+    }
 }
 
 
@@ -525,6 +551,19 @@ pub fn try<T:Send>(f: ~fn() -> T) -> Result<T,()> {
     task.try(f)
 }
 
+pub fn try_with_cause<T:Send>(f: ~fn() -> T) -> Result<T, Option<SendStr>> {
+    /*!
+     * Execute a function in another task and return either the return value
+     * of the function or the message the task failed with.
+     *
+     * This is equivalent to task().supervised().try_with_cause.
+     */
+
+    let mut task = task();
+    task.supervised();
+    task.try_with_cause(f)
+}
+
 
 /* Lifecycle functions */
 
@@ -565,6 +604,15 @@ pub fn failing() -> bool {
     }
 }
 
+// Takes the message the running task failed with out of the task.
+fn take_failure_cause() -> Option<SendStr> {
+    use rt::task::Task;
+
+    do Local::borrow |local: &mut Task| {
+        local.unwinder.cause.take()
+    }
+}
+
 /**
  * Temporarily make the task unkillable
  *
@@ -1062,6 +1110,19 @@ fn test_try_fail() {
     }
 }
 
+#[test]
+fn test_try_with_cause_success() {
+    assert_eq!(try_with_cause(|| 10), result::Ok(10));
+}
+
+#[test]
+fn test_try_with_cause_fail() {
+    let r: Result<(), Option<SendStr>> = do try_with_cause { fail!("static") };
+    assert_eq!(r, result::Err(Some(SendStrStatic("static"))));
+    let r: Result<(), Option<SendStr>> = do try_with_cause { fail!("owned {}", 1) };
+    assert_eq!(r, result::Err(Some(SendStrOwned(~"owned 1"))));
+}
+
 #[cfg(test)]
 fn get_sched_id() -> int {
     do Local::borrow |sched: &mut ::rt::shouldnt_be_public::Scheduler| {
@@ -0,0 +1,248 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+The compiler code necessary for `#[deriving(Arbitrary)]`, which implements
+`extra::quickcheck::Arbitrary`.
+
+`arbitrary` picks a variant (for enums) like `deriving(Rand)` does, and
+fills each field with an arbitrary value. `shrink` offers, for every field
+in turn, the value with that field replaced by each of its own shrinks and
+all the other fields cloned; so a struct like
+
+```
+#[deriving(Clone, Arbitrary)]
+struct A { x: uint, y: ~str }
+```
+
+shrinks `A { x: 4, y: ~"ab" }` to the `A`s with `x` shrunk and `y ==
+~"ab"`, followed by those with `x == 4` and `y` shrunk. The type must also
+be `Clone`, as `Arbitrary` requires.
+*/
+
+use ast;
+use ast::{MetaItem, item, Expr, Ident};
+use codemap::Span;
+use ext::base::ExtCtxt;
+use ext::build::{AstBuilder};
+use ext::deriving::generic::*;
+
+use std::vec;
+
+pub fn expand_deriving_arbitrary(cx: @ExtCtxt,
+                                 span: Span,
+                                 mitem: @MetaItem,
+                                 in_items: ~[@item])
+    -> ~[@item] {
+    let trait_def = TraitDef {
+        path: Path::new(~["extra", "quickcheck", "Arbitrary"]),
+        additional_bounds: ~[],
+        generics: LifetimeBounds::empty(),
+        methods: ~[
+            MethodDef {
+                name: "arbitrary",
+                generics: LifetimeBounds {
+                    lifetimes: ~[],
+                    bounds: ~[("G",
+                               ~[ Path::new(~["extra", "quickcheck", "Gen"]) ])]
+                },
+                explicit_self: None,
+                args: ~[
+                    Ptr(~Literal(Path::new_local("G")),
+                        Borrowed(None, ast::MutMutable))
+                ],
+                ret_ty: Self,
+                const_nonmatching: false,
+                combine_substructure: arbitrary_substructure
+            },
+            MethodDef {
+                name: "shrink",
+                generics: LifetimeBounds::empty(),
+                explicit_self: borrowed_explicit_self(),
+                args: ~[],
+                ret_ty: Ptr(~Vec(~Self), Send),
+                const_nonmatching: false,
+                combine_substructure: shrink_substructure
+            }
+        ]
+    };
+    trait_def.expand(cx, span, mitem, in_items)
+}
+
+fn arbitrary_substructure(cx: @ExtCtxt, span: Span, substr: &Substructure) -> @Expr {
+    let gen = match substr.nonself_args {
+        [gen] => gen,
+        _ => cx.bug("Incorrect number of arguments to `arbitrary` in `deriving(Arbitrary)`")
+    };
+    let arbitrary_ident = ~[
+        cx.ident_of("extra"),
+        cx.ident_of("quickcheck"),
+        cx.ident_of("Arbitrary"),
+        cx.ident_of("arbitrary")
+    ];
+    let arbitrary_call = || {
+        cx.expr_call_global(span, arbitrary_ident.clone(), ~[ gen ])
+    };
+
+    return match *substr.fields {
+        StaticStruct(_, ref summary) => {
+            arbitrary_thing(cx, span, substr.type_ident, summary, arbitrary_call)
+        }
+        StaticEnum(_, ref variants) => {
+            if variants.is_empty() {
+                cx.span_fatal(span, "`Arbitrary` cannot be derived for enums with no variants");
+            }
+
+            let variant_count = cx.expr_uint(span, variants.len());
+
+            // ::std::rand::Rand::rand(gen), as a uint
+            let rand_ident = ~[
+                cx.ident_of("std"),
+                cx.ident_of("rand"),
+                cx.ident_of("Rand"),
+                cx.ident_of("rand")
+            ];
+            let rv_call = cx.expr_call_global(span, rand_ident, ~[ gen ]);
+            let uint_ty = cx.ty_ident(span, cx.ident_of("uint"));
+            let value_ident = cx.ident_of("__value");
+            let let_statement = cx.stmt_let_typed(span,
+                                                  false,
+                                                  value_ident,
+                                                  uint_ty,
+                                                  rv_call);
+
+            // rand() % variants.len()
+            let value_ref = cx.expr_ident(span, value_ident);
+            let arbitrary_variant = cx.expr_binary(span,
+                                                   ast::BiRem,
+                                                   value_ref,
+                                                   variant_count);
+
+            let mut arms = do variants.iter().enumerate().map |(i, id_sum)| {
+                let i_expr = cx.expr_uint(span, i);
+                let pat = cx.pat_lit(span, i_expr);
+
+                match *id_sum {
+                    (ident, ref summary) => {
+                        cx.arm(span,
+                               ~[ pat ],
+                               arbitrary_thing(cx, span, ident, summary,
+                                               || arbitrary_call()))
+                    }
+                }
+            }.collect::<~[ast::Arm]>();
+
+            // _ => {} at the end. Should never occur
+            arms.push(cx.arm_unreachable(span));
+
+            let match_expr = cx.expr_match(span, arbitrary_variant, arms);
+
+            let block = cx.block(span, ~[ let_statement ], Some(match_expr));
+            cx.expr_block(block)
+        }
+        _ => cx.bug("Non-static method in `deriving(Arbitrary)`")
+    };
+
+    fn arbitrary_thing(cx: @ExtCtxt, span: Span,
+                       ctor_ident: Ident,
+                       summary: &Either<uint, ~[Ident]>,
+                       arbitrary_call: &fn() -> @Expr) -> @Expr {
+        match *summary {
+            Left(count) => {
+                if count == 0 {
+                    cx.expr_ident(span, ctor_ident)
+                } else {
+                    let exprs = vec::from_fn(count, |_| arbitrary_call());
+                    cx.expr_call_ident(span, ctor_ident, exprs)
+                }
+            }
+            Right(ref fields) => {
+                let arbitrary_fields = do fields.map |ident| {
+                    cx.field_imm(span, *ident, arbitrary_call())
+                };
+                cx.expr_struct_ident(span, ctor_ident, arbitrary_fields)
+            }
+        }
+    }
+}
+
+fn shrink_substructure(cx: @ExtCtxt, span: Span, substr: &Substructure) -> @Expr {
+    let (ctor_ident, all_fields) = match *substr.fields {
+        Struct(ref af) => (substr.type_ident, af),
+        EnumMatching(_, variant, ref af) => (variant.node.name, af),
+        EnumNonMatching(*) => {
+            cx.span_bug(span, "Non-matching enum variants in `deriving(Arbitrary)`")
+        }
+        StaticEnum(*) | StaticStruct(*) => {
+            cx.span_bug(span, "Static method in `deriving(Arbitrary)`")
+        }
+    };
+
+    if all_fields.is_empty() {
+        // nothing to shrink
+        return cx.expr_vec_uniq(span, ~[]);
+    }
+
+    let shrunk_ident = cx.ident_of("__shrunk");
+    let value_ident = cx.ident_of("__value");
+    let clone_ident = cx.ident_of("clone");
+
+    // The value with field `i` replaced by `__value` and the rest cloned.
+    let rebuild = |i: uint| {
+        let field_expr = |j: uint, self_f: @Expr| {
+            if i == j {
+                cx.expr_ident(span, value_ident)
+            } else {
+                cx.expr_method_call(span, self_f, clone_ident, ~[])
+            }
+        };
+
+        match *all_fields {
+            [(None, _, _), .. _] => {
+                // enum-like
+                let args = do all_fields.iter().enumerate().map |(j, &(_, self_f, _))| {
+                    field_expr(j, self_f)
+                }.collect();
+                cx.expr_call_ident(span, ctor_ident, args)
+            }
+            _ => {
+                // struct-like
+                let fields = do all_fields.iter().enumerate().map |(j, &(o_id, self_f, _))| {
+                    let ident = match o_id {
+                        Some(i) => i,
+                        None => cx.span_bug(span, "unnamed field in normal struct in \
+                                                   `deriving(Arbitrary)`")
+                    };
+                    cx.field_imm(span, ident, field_expr(j, self_f))
+                }.collect();
+                cx.expr_struct_ident(span, ctor_ident, fields)
+            }
+        }
+    };
+
+    // __shrunk.push_all_move(field.shrink().move_iter()
+    //                             .map(|__value| <rebuilt>).collect());
+    let mut stmts = ~[cx.stmt_let(span, true, shrunk_ident, cx.expr_vec_uniq(span, ~[]))];
+    for (i, &(_, self_f, _)) in all_fields.iter().enumerate() {
+        let shrinks = cx.expr_method_call(span, self_f, cx.ident_of("shrink"), ~[]);
+        let shrinks = cx.expr_method_call(span, shrinks, cx.ident_of("move_iter"), ~[]);
+        let rebuilt = cx.lambda_expr_1(span, rebuild(i), value_ident);
+        let rebuilt = cx.expr_method_call(span, shrinks, cx.ident_of("map"), ~[rebuilt]);
+        let rebuilt = cx.expr_method_call(span, rebuilt, cx.ident_of("collect"), ~[]);
+        let push = cx.expr_method_call(span,
+                                       cx.expr_ident(span, shrunk_ident),
+                                       cx.ident_of("push_all_move"),
+                                       ~[rebuilt]);
+        stmts.push(cx.stmt_expr(push));
+    }
+
+    let block = cx.block(span, stmts, Some(cx.expr_ident(span, shrunk_ident)));
+    cx.expr_block(block)
+}
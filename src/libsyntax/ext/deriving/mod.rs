@@ -29,6 +29,7 @@ pub mod iter_bytes;
 pub mod encodable;
 pub mod decodable;
 pub mod rand;
+pub mod arbitrary;
 pub mod to_str;
 pub mod zero;
 pub mod default;
@@ -96,6 +97,7 @@ pub fn expand_meta_deriving(cx: @ExtCtxt,
                             "TotalOrd" => expand!(totalord::expand_deriving_totalord),
 
                             "Rand" => expand!(rand::expand_deriving_rand),
+                            "Arbitrary" => expand!(arbitrary::expand_deriving_arbitrary),
 
                             "ToStr" => expand!(to_str::expand_deriving_to_str),
 
@@ -83,6 +83,8 @@ pub enum Ty<'self> {
     Self,
     // &/~/@ Ty
     Ptr(~Ty<'self>, PtrTy<'self>),
+    // [Ty], usually behind a Ptr, e.g. ~[Self]
+    Vec(~Ty<'self>),
     // mod::mod::Type<[lifetime], [Params...]>, including a plain type
     // parameter, and things like `int`
     Literal(Path<'self>),
@@ -139,6 +141,10 @@ impl<'self> Ty<'self> {
                     }
                 }
             }
+            Vec(ref ty) => {
+                let raw_ty = ty.to_ty(cx, span, self_ty, self_generics);
+                cx.ty(span, ast::ty_vec(cx.ty_mt(raw_ty, ast::MutImmutable)))
+            }
             Literal(ref p) => { p.to_ty(cx, span, self_ty, self_generics) }
             Self  => {
                 cx.ty_path(self.to_path(cx, span, self_ty, self_generics), None)
@@ -179,6 +185,7 @@ impl<'self> Ty<'self> {
                 p.to_path(cx, span, self_ty, self_generics)
             }
             Ptr(*) => { cx.span_bug(span, "Pointer in a path in generic `deriving`") }
+            Vec(*) => { cx.span_bug(span, "Vector in a path in generic `deriving`") }
             Tuple(*) => { cx.span_bug(span, "Tuple in a path in generic `deriving`") }
         }
     }
@@ -0,0 +1,56 @@
+// xfail-fast #6330
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[feature(struct_variant)];
+
+extern mod extra;
+
+use extra::quickcheck::{Arbitrary, StdGen};
+use std::rand;
+
+#[deriving(Clone, Eq, Arbitrary)]
+struct A;
+
+#[deriving(Clone, Eq, Arbitrary)]
+struct B(uint, bool);
+
+#[deriving(Clone, Eq, Arbitrary)]
+struct C {
+    x: uint,
+    y: ~str
+}
+
+#[deriving(Clone, Eq, Arbitrary)]
+enum D {
+    D0,
+    D1(uint),
+    D2 { x: bool, y: () }
+}
+
+fn main() {
+    // check there's no segfaults
+    let mut g = StdGen::new(rand::rng(), 10);
+    do 20.times {
+        let _: A = Arbitrary::arbitrary(&mut g);
+        let _: B = Arbitrary::arbitrary(&mut g);
+        let _: C = Arbitrary::arbitrary(&mut g);
+        let _: D = Arbitrary::arbitrary(&mut g);
+    }
+
+    // each field is shrunk in turn, with the others left alone
+    assert_eq!(A.shrink(), ~[]);
+    assert_eq!(B(2, true).shrink(), ~[B(0, true), B(1, true), B(2, false)]);
+    assert_eq!(C { x: 1, y: ~"b" }.shrink(),
+               ~[C { x: 0, y: ~"b" }, C { x: 1, y: ~"" }, C { x: 1, y: ~"a" }]);
+    assert_eq!(D0.shrink(), ~[]);
+    assert_eq!(D1(1).shrink(), ~[D1(0)]);
+    assert_eq!(D2 { x: true, y: () }.shrink(), ~[D2 { x: false, y: () }]);
+}
@@ -0,0 +1,70 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Reading EBML in place from buffers that aren't `@~[u8]`.
+
+// xfail-fast
+
+extern mod extra;
+
+use std::io;
+use extra::arc::Arc;
+use extra::serialize::{Encodable, Decodable};
+use extra::ebml::Doc;
+use extra::ebml::reader;
+use extra::ebml::writer::Encoder;
+use extra::ebml::reader::Decoder;
+
+#[deriving(Encodable, Decodable, Eq)]
+struct Entry {
+    name: ~str,
+    sizes: ~[uint],
+}
+
+// Finds the string in the first element with tag `tag` directly inside `doc`.
+fn find_str<'a>(doc: Doc<'a>, tag: uint) -> Option<&'a str> {
+    reader::maybe_get_doc(doc, tag).map(|d| d.as_str_slice())
+}
+
+pub fn main() {
+    let entry = Entry { name: ~"libfoo", sizes: ~[1, 2, 3] };
+    let bytes = do io::with_bytes_writer |w| {
+        let mut e = Encoder(w);
+        e.wr_tagged_str(0x30, "header");
+        e.start_tag(0x31);
+        entry.encode(&mut e);
+        e.end_tag();
+    };
+
+    // A `Doc` can start partway into a larger buffer.
+    let mut buf = ~[0u8, 0, 0, 0];
+    buf.push_all(bytes);
+    let doc = Doc::new(buf.slice_from(4));
+    assert_eq!(find_str(doc, 0x30), Some("header"));
+    assert_eq!(find_str(doc, 0x32), None);
+
+    // A decoder keeps its own copy of the document, so it can outlive the buffer.
+    let mut dec = {
+        let copy = buf.clone();
+        Decoder(Doc::new(copy.slice_from(4)).get(0x31))
+    };
+    let entry2: Entry = Decodable::decode(&mut dec);
+    assert!(entry == entry2);
+
+    // Tasks sharing one buffer read it in place.
+    let shared = Arc::new(buf);
+    let local = shared.clone();
+    let (port, chan) = stream();
+    do spawn {
+        let doc = Doc::new(local.get().slice_from(4));
+        chan.send(find_str(doc, 0x30).map(|s| s.to_owned()));
+    }
+    assert_eq!(port.recv(), Some(~"header"));
+}